@@ -26,14 +26,80 @@
 use log::debug;
 use num_traits::bounds::Bounded;
 use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Condvar, Mutex};
 use std::{fmt, thread, time};
 
+/// Callback function, which is called with every new best solution (and its score), found during the branch and bound
+/// execution.
+pub type NewBestCallback<Solution, Score> = Box<dyn Fn(&Solution, Score) + Send + Sync>;
+
+/// Callback function, which is called for every solved branch and bound node with the node's subproblem, the type of
+/// its result and the result's score (if any), e.g. to write a trace of the branch and bound execution.
+pub type NodeTraceCallback<SubProblem, Score> =
+    Box<dyn Fn(&SubProblem, NodeResultType, Option<Score>) + Send + Sync>;
+
+/// Callback function, which is called for every solved branch and bound node with the node's full result, e.g. to
+/// collect custom analytics of the branch and bound execution. See [solve] for the locking contract.
+pub type NodeResultCallback<SubProblem, Solution, Score> =
+    Box<dyn Fn(&NodeResult<SubProblem, Solution, Score>) + Send + Sync>;
+
+/// Shared flag for stopping the branch and bound execution early from any other thread. See [solve].
+pub type CancellationToken = Arc<AtomicBool>;
+
+/// Lock-free readable copy of the current best solution's score (converted to `u32`). See [solve_with_handle].
+pub type BestScoreHandle = Arc<AtomicU32>;
+
+/// Atomic best score together with the function to convert the score for storing it. See [solve_with_handle].
+type ScoreUpdater<Score> = (BestScoreHandle, fn(Score) -> u32);
+
+/// Optional settings of a branch and bound execution. See [solve] for the meaning of each option.
+/// All options are disabled by default.
+pub struct BabOptions<SubProblem, Solution, Score> {
+    /// Callback to be called for each new best solution
+    pub new_best_callback: Option<NewBestCallback<Solution, Score>>,
+    /// Callback to be called for each solved subproblem
+    pub node_trace_callback: Option<NodeTraceCallback<SubProblem, Score>>,
+    /// Callback to be called with each subproblem's result
+    pub node_result_callback: Option<NodeResultCallback<SubProblem, Solution, Score>>,
+    /// If true, collect statistics of each worker thread in `Statistics::per_thread`
+    pub collect_thread_statistics: bool,
+    /// Flag to stop the execution early
+    pub cancellation_token: Option<CancellationToken>,
+    /// Maximum duration of the execution
+    pub timeout: Option<time::Duration>,
+}
+
+impl<SubProblem, Solution, Score> Default for BabOptions<SubProblem, Solution, Score> {
+    fn default() -> Self {
+        BabOptions {
+            new_best_callback: None,
+            node_trace_callback: None,
+            node_result_callback: None,
+            collect_thread_statistics: false,
+            cancellation_token: None,
+            timeout: None,
+        }
+    }
+}
+
 /// Struct to hold the synchronization information for the parallel execution. It contains a mutex-ed SharedState object
 /// And a Candvar to allow worker threads to sleep-wait for new subproblems to solve.
 struct BranchAndBound<SubProblem: Ord + Send, Solution: Send, Score: Ord> {
     shared_state: Mutex<SharedState<SubProblem, Solution, Score>>,
     condvar: Condvar,
+    /// Optional callback to be called for each new best solution
+    new_best_callback: Option<NewBestCallback<Solution, Score>>,
+    /// Optional callback to be called for each solved subproblem
+    node_trace_callback: Option<NodeTraceCallback<SubProblem, Score>>,
+    /// Optional callback to be called with each subproblem's result
+    node_result_callback: Option<NodeResultCallback<SubProblem, Solution, Score>>,
+    /// Optional flag to stop the execution early
+    cancellation_token: Option<CancellationToken>,
+    /// Optional point in time to stop the execution early
+    deadline: Option<time::Instant>,
+    /// Optional lock-free copy of the best score (and the function to convert the score for it)
+    best_score_handle: Option<ScoreUpdater<Score>>,
 }
 
 /// The shared state of the worker threads of the parallel branch and bound execution
@@ -43,6 +109,12 @@ struct SharedState<SubProblem: Ord, Solution, Score: Ord> {
     /// The number of currently busy worker threads. It is used to determine the end of execution (no pending problems
     /// and no busy workers left)
     busy_threads: u32,
+    /// The number of worker threads, currently waiting for new subproblems
+    idle_threads: u32,
+    /// The number of notifications of idle threads, which have not yet been received by the woken
+    /// threads. Together with `idle_threads`, it is used to avoid needless wakeups, when no idle
+    /// thread is left to take the new subproblems.
+    pending_wakeups: u32,
     /// The best solution, found so far
     best_result: Option<Solution>,
     /// The score of the best solution, found so far
@@ -77,6 +149,48 @@ pub struct Statistics {
     /// Cummulated exeuction time of the subproblem solver function
     /// Heads up! Due to parallelism this will be multiple times `total_time`.
     pub total_subproblem_time: time::Duration,
+    /// Statistics of each single worker thread (indexed by thread number), if collecting them has
+    /// been requested. Otherwise, it's empty.
+    pub per_thread: Vec<ThreadStatistics>,
+    /// True, if the execution has been stopped by the timeout before all branches have been
+    /// evaluated. In this case, the result is not necessarily optimal and a missing result does not
+    /// prove that there is no solution.
+    pub aborted_by_timeout: bool,
+}
+
+/// Statistics of a single worker thread of the branch and bound execution, e.g. to diagnose an
+/// imbalanced distribution of the work between the threads.
+#[derive(Default, Clone)]
+pub struct ThreadStatistics {
+    /// Number of calls to the subproblem solver function by this thread
+    pub num_executed_subproblems: u32,
+    /// Cummulated execution time of the subproblem solver function in this thread
+    pub busy_time: time::Duration,
+}
+
+impl Statistics {
+    /// Add the statistics of another branch and bound execution (e.g. of the same problem with
+    /// different constraints) to these statistics. The per-thread statistics are added by thread
+    /// number.
+    pub fn accumulate(&mut self, other: &Statistics) {
+        self.num_executed_subproblems += other.num_executed_subproblems;
+        self.num_no_solution += other.num_no_solution;
+        self.num_infeasible += other.num_infeasible;
+        self.num_feasible += other.num_feasible;
+        self.num_new_best += other.num_new_best;
+        self.num_bound_subproblems += other.num_bound_subproblems;
+        self.total_time += other.total_time;
+        self.total_subproblem_time += other.total_subproblem_time;
+        if self.per_thread.len() < other.per_thread.len() {
+            self.per_thread
+                .resize(other.per_thread.len(), ThreadStatistics::default());
+        }
+        for (own, other) in self.per_thread.iter_mut().zip(other.per_thread.iter()) {
+            own.num_executed_subproblems += other.num_executed_subproblems;
+            own.busy_time += other.busy_time;
+        }
+        self.aborted_by_timeout |= other.aborted_by_timeout;
+    }
 }
 
 impl fmt::Display for Statistics {
@@ -100,9 +214,25 @@ Average subproblem solver time: {:.3}s\n",
             self.num_new_best,
             self.num_bound_subproblems,
             self.total_time.as_millis() as f32 / 1000f32,
-            (self.total_subproblem_time / self.num_executed_subproblems).as_millis() as f32
+            self.total_subproblem_time
+                .checked_div(self.num_executed_subproblems)
+                .unwrap_or_default()
+                .as_millis() as f32
                 / 1000f32
-        )
+        )?;
+        if self.aborted_by_timeout {
+            writeln!(f, "Aborted by timeout before evaluating all branches.")?;
+        }
+        for (i, thread_statistics) in self.per_thread.iter().enumerate() {
+            writeln!(
+                f,
+                "Worker {: >3}: {: >6} subproblems, busy {:.3}s",
+                i,
+                thread_statistics.num_executed_subproblems,
+                thread_statistics.busy_time.as_millis() as f32 / 1000f32
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -118,6 +248,15 @@ pub enum NodeResult<SubProblem, Solution, Score> {
     Feasible(Solution, Score),
 }
 
+/// Type of a single branch and bound node's result, as reported to the `NodeTraceCallback` (i.e. the `NodeResult`
+/// variant without the data)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeResultType {
+    NoSolution,
+    Infeasible,
+    Feasible,
+}
+
 /// Main function of this module to solve a generic problem by doing pseudo-depth-first parallel branch and bound
 /// optimization.
 ///
@@ -132,22 +271,136 @@ pub enum NodeResult<SubProblem, Solution, Score> {
 /// When all branches of the branch and bound tree are evaluated (or bound), the best result is
 /// returned. It may be possible, that no result is found at all.
 ///
+/// The execution can be customized with the optional settings in `options` (see [BabOptions]):
+///
+/// If a `new_best_callback` is given, it is called for each new best solution, as soon as it is found. The callback is
+/// executed by the worker thread, which found the solution, while holding the lock on the shared state. Thus, calls
+/// are never executed concurrently and always happen in the order of improving scores, but all other worker threads
+/// may be blocked for the duration of the call.
+///
+/// If a `node_trace_callback` is given, it is called for each solved subproblem with a copy of the subproblem and
+/// the type and score of its result. Like the `new_best_callback`, it is executed while holding the lock on the shared
+/// state, so calls are never executed concurrently. If no callback is given, subproblems are not copied at all.
+///
+/// If a `node_result_callback` is given, it is called for each solved subproblem with a reference to the full
+/// `NodeResult` (including the solution or the branches). In contrast to the other callbacks, it is executed by the
+/// worker thread, which solved the subproblem, *without* holding the lock on the shared state, directly after solving
+/// the subproblem. Thus, calls may be executed concurrently by multiple worker threads and in any order, and the
+/// callback must synchronize any shared data itself (e.g. with atomics or its own mutex). In turn, a slow callback
+/// only delays its own worker thread. The number of calls equals `Statistics::num_executed_subproblems`.
+///
+/// If `collect_thread_statistics` is true, the number of solved subproblems and the busy time of each worker thread
+/// are collected in `Statistics::per_thread`.
+///
+/// If a `cancellation_token` is given, it may be set (to true) from any other thread at any time to stop the execution
+/// early. The worker threads check the token before taking the next pending subproblem, so subproblems, which are
+/// already being solved, are finished and their results are still considered. Afterwards, the best solution found so
+/// far (if any) is returned, which is not necessarily the optimal solution.
+///
+/// If a `timeout` is given, the execution is stopped in the same way, as soon as the timeout has elapsed, and
+/// `Statistics::aborted_by_timeout` is set. Thus, callers can distinguish a proven absence of any solution (no result
+/// and no timeout) from a search, which has been stopped before finding one.
+///
 /// # Result
 ///
 /// Returns the best solution and its score (if one has been found) and some statistics about the solving process.
 pub fn solve<
-    SubProblem: 'static + Ord + Send + fmt::Debug,
+    SubProblem: 'static + Ord + Send + Clone + fmt::Debug,
     Solution: 'static + Send,
     Score: 'static + Ord + Bounded + Send + Copy + fmt::Display,
-    F: 'static,
+    F,
 >(
     node_solver: F,
     base_problem: SubProblem,
     num_threads: u32,
+    options: BabOptions<SubProblem, Solution, Score>,
 ) -> (Option<(Solution, Score)>, Statistics)
 where
-    F: (Fn(SubProblem) -> NodeResult<SubProblem, Solution, Score>) + Send + Sync,
+    F: 'static + (Fn(SubProblem) -> NodeResult<SubProblem, Solution, Score>) + Send + Sync,
 {
+    solve_internal(node_solver, base_problem, num_threads, options, None)
+}
+
+/// Handle of a branch and bound execution in the background, as returned by [solve_with_handle].
+pub struct SolveHandle<Solution, Score> {
+    thread: thread::JoinHandle<(Option<(Solution, Score)>, Statistics)>,
+}
+
+impl<Solution, Score> SolveHandle<Solution, Score> {
+    /// Wait for the branch and bound execution to finish and get its result, like returned by [solve].
+    pub fn join(self) -> (Option<(Solution, Score)>, Statistics) {
+        self.thread.join().unwrap()
+    }
+}
+
+/// Start the branch and bound algorithm (see [solve]) in a background thread and return a handle to it. Meanwhile, the
+/// score of the best solution found so far can be read from `best_score` without locking the worker threads' shared
+/// state, e.g. for a progress display.
+///
+/// The score is converted to `u32` with the given `score_to_u32` function for storing it in the atomic integer
+/// `best_score`. It should be initialized with 0 (or the best score of a previous execution, e.g. with random
+/// restarts). The atomic is updated with `fetch_max()` and `Ordering::Relaxed` by
+/// the worker thread, which found a new best solution (while holding the lock on the shared state). Thus, the read
+/// values never decrease and reading it gives the latest or a slightly outdated best score, but it must not be used to
+/// synchronize any other data (like the solution itself). After [SolveHandle::join], the final best score is always
+/// visible.
+pub fn solve_with_handle<
+    SubProblem: 'static + Ord + Send + Clone + fmt::Debug,
+    Solution: 'static + Send,
+    Score: 'static + Ord + Bounded + Send + Copy + fmt::Display,
+    F,
+>(
+    node_solver: F,
+    base_problem: SubProblem,
+    num_threads: u32,
+    options: BabOptions<SubProblem, Solution, Score>,
+    best_score: BestScoreHandle,
+    score_to_u32: fn(Score) -> u32,
+) -> SolveHandle<Solution, Score>
+where
+    F: 'static + (Fn(SubProblem) -> NodeResult<SubProblem, Solution, Score>) + Send + Sync,
+{
+    let thread = thread::Builder::new()
+        .name("BaB Main".to_owned())
+        .spawn(move || {
+            solve_internal(
+                node_solver,
+                base_problem,
+                num_threads,
+                options,
+                Some((best_score, score_to_u32)),
+            )
+        })
+        .unwrap();
+    SolveHandle { thread }
+}
+
+/// Implementation of [solve] and [solve_with_handle]
+fn solve_internal<
+    SubProblem: 'static + Ord + Send + Clone + fmt::Debug,
+    Solution: 'static + Send,
+    Score: 'static + Ord + Bounded + Send + Copy + fmt::Display,
+    F,
+>(
+    node_solver: F,
+    base_problem: SubProblem,
+    num_threads: u32,
+    options: BabOptions<SubProblem, Solution, Score>,
+    best_score_handle: Option<ScoreUpdater<Score>>,
+) -> (Option<(Solution, Score)>, Statistics)
+where
+    F: 'static + (Fn(SubProblem) -> NodeResult<SubProblem, Solution, Score>) + Send + Sync,
+{
+    let BabOptions {
+        new_best_callback,
+        node_trace_callback,
+        node_result_callback,
+        collect_thread_statistics,
+        cancellation_token,
+        timeout,
+    } = options;
+    let tic = time::Instant::now();
+
     // Create shared data structure with base problem
     let mut pending_nodes = BinaryHeap::new();
     pending_nodes.push(PendingProblem(base_problem, Score::max_value()));
@@ -155,15 +408,28 @@ where
         shared_state: Mutex::new(SharedState {
             pending_nodes,
             busy_threads: 0,
+            idle_threads: 0,
+            pending_wakeups: 0,
             best_result: None,
             best_score: Score::min_value(),
-            statistics: Statistics::default(),
+            statistics: Statistics {
+                per_thread: if collect_thread_statistics {
+                    vec![ThreadStatistics::default(); num_threads as usize]
+                } else {
+                    Vec::new()
+                },
+                ..Statistics::default()
+            },
         }),
         condvar: Condvar::new(),
+        new_best_callback,
+        node_trace_callback,
+        node_result_callback,
+        cancellation_token,
+        deadline: timeout.map(|timeout| tic + timeout),
+        best_score_handle,
     });
 
-    let tic = time::Instant::now();
-
     // Spawn worker threads
     let mut workers = Vec::<thread::JoinHandle<()>>::new();
     let node_solver = Arc::new(node_solver);
@@ -172,7 +438,7 @@ where
         let node_solver_clone = node_solver.clone();
         let thread = thread::Builder::new()
             .name(format!("BaB Worker {}", i))
-            .spawn(move || worker(bab_clone, node_solver_clone))
+            .spawn(move || worker(bab_clone, node_solver_clone, i as usize))
             .unwrap();
         workers.push(thread);
     }
@@ -203,12 +469,38 @@ where
 }
 
 /// Worker thread entry point for the parallel branch and bound solving
-fn worker<SubProblem: Ord + Send + fmt::Debug, Solution: Send, Score: Ord + Copy + fmt::Display>(
+fn worker<
+    SubProblem: Ord + Send + Clone + fmt::Debug,
+    Solution: Send,
+    Score: Ord + Copy + fmt::Display,
+>(
     bab: Arc<BranchAndBound<SubProblem, Solution, Score>>,
     node_solver: Arc<dyn Fn(SubProblem) -> NodeResult<SubProblem, Solution, Score>>,
+    thread_index: usize,
 ) {
     let mut shared_state = bab.shared_state.lock().unwrap();
     loop {
+        // If the execution has been cancelled, wake up the other threads to let them exit, too
+        if bab
+            .cancellation_token
+            .as_ref()
+            .is_some_and(|token| token.load(Ordering::Relaxed))
+        {
+            debug!("Stopping worker thread, since the execution has been cancelled.");
+            bab.condvar.notify_all();
+            break;
+        }
+        // Same for the timeout
+        if bab
+            .deadline
+            .is_some_and(|deadline| time::Instant::now() >= deadline)
+        {
+            debug!("Stopping worker thread, since the timeout has elapsed.");
+            shared_state.statistics.aborted_by_timeout = true;
+            bab.condvar.notify_all();
+            break;
+        }
+
         // In case of pending subproblems, get one and solve it
         if let Some(PendingProblem(subproblem, parent_score)) = shared_state.pending_nodes.pop() {
             // Only consider this subproblem, if the parent node's solution was better then best solution known so
@@ -220,15 +512,38 @@ fn worker<SubProblem: Ord + Send + fmt::Debug, Solution: Send, Score: Ord + Copy
                 std::mem::drop(shared_state);
                 let subproblem_formatted = format!("{:?}", subproblem);
                 debug!("Solving subproblem: {}", subproblem_formatted);
+                let traced_subproblem =
+                    bab.node_trace_callback.as_ref().map(|_| subproblem.clone());
                 let tic = time::Instant::now();
                 let result = node_solver(subproblem);
                 let consumed_time = tic.elapsed();
+                if let Some(ref callback) = bab.node_result_callback {
+                    callback(&result);
+                }
 
                 // Reacquire shared_state lock and interpret subproblem result
                 shared_state = bab.shared_state.lock().unwrap();
                 shared_state.busy_threads -= 1;
                 shared_state.statistics.num_executed_subproblems += 1;
                 shared_state.statistics.total_subproblem_time += consumed_time;
+                if let Some(thread_statistics) =
+                    shared_state.statistics.per_thread.get_mut(thread_index)
+                {
+                    thread_statistics.num_executed_subproblems += 1;
+                    thread_statistics.busy_time += consumed_time;
+                }
+                if let (Some(callback), Some(traced_subproblem)) =
+                    (&bab.node_trace_callback, &traced_subproblem)
+                {
+                    let (result_type, score) = match &result {
+                        NodeResult::NoSolution => (NodeResultType::NoSolution, None),
+                        NodeResult::Infeasible(_, score) => {
+                            (NodeResultType::Infeasible, Some(*score))
+                        }
+                        NodeResult::Feasible(_, score) => (NodeResultType::Feasible, Some(*score)),
+                    };
+                    callback(traced_subproblem, result_type, score);
+                }
                 match result {
                     NodeResult::NoSolution => {
                         shared_state.statistics.num_no_solution += 1;
@@ -245,8 +560,14 @@ fn worker<SubProblem: Ord + Send + fmt::Debug, Solution: Send, Score: Ord + Copy
                                 "Wow, this is the best solution, we found so far. Let's store it."
                             );
                             shared_state.statistics.num_new_best += 1;
+                            if let Some(ref callback) = bab.new_best_callback {
+                                callback(&solution, score);
+                            }
                             shared_state.best_result = Some(solution);
                             shared_state.best_score = score;
+                            if let Some((ref handle, score_to_u32)) = bab.best_score_handle {
+                                handle.fetch_max(score_to_u32(score), Ordering::Relaxed);
+                            }
                         }
                     }
 
@@ -257,15 +578,24 @@ fn worker<SubProblem: Ord + Send + fmt::Debug, Solution: Send, Score: Ord + Copy
                             score, subproblem_formatted
                         );
                         // Add new subproblems to queue
-                        for (i, new_problem) in new_problems.into_iter().enumerate() {
+                        let num_new_problems = new_problems.len() as u32;
+                        for new_problem in new_problems.into_iter() {
                             shared_state
                                 .pending_nodes
                                 .push(PendingProblem(new_problem, score));
-                            // Wake up n-1 other threads to solve the new subproblems
-                            if i != 0 {
-                                bab.condvar.notify_one();
-                            }
                         }
+                        // Wake up n-1 other threads to solve the new subproblems (this thread takes
+                        // one of them), but only as many as are idle and not notified yet.
+                        let num_wakeups = std::cmp::min(
+                            num_new_problems.saturating_sub(1),
+                            shared_state
+                                .idle_threads
+                                .saturating_sub(shared_state.pending_wakeups),
+                        );
+                        for _ in 0..num_wakeups {
+                            bab.condvar.notify_one();
+                        }
+                        shared_state.pending_wakeups += num_wakeups;
                     }
                 }
             } else {
@@ -287,7 +617,13 @@ fn worker<SubProblem: Ord + Send + fmt::Debug, Solution: Send, Score: Ord + Copy
         } else if shared_state.busy_threads > 0 {
             // Wait for notification by other threads. CondVar.wait() automatically handels the mutex unlock and re-lock
             // for us.
+            shared_state.idle_threads += 1;
             shared_state = bab.condvar.wait(shared_state).unwrap();
+            shared_state.idle_threads -= 1;
+            // We don't know, if this is the thread that has been notified (or if this is a spurious
+            // wakeup or a wakeup by notify_all()), so we might underestimate the pending wakeups.
+            // This only results in additional notifications, but never in missing ones.
+            shared_state.pending_wakeups = shared_state.pending_wakeups.saturating_sub(1);
 
         // If no work is left to do, exit
         } else {
@@ -300,9 +636,11 @@ fn worker<SubProblem: Ord + Send + fmt::Debug, Solution: Send, Score: Ord + Copy
 // Tests
 #[cfg(test)]
 mod tests {
-    use super::NodeResult;
+    use super::{NodeResult, NodeResultType};
     use ordered_float::NotNan;
     use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::sync::Arc;
 
     #[test]
     fn test_bab_rounding() {
@@ -362,10 +700,19 @@ mod tests {
             }
         }
 
+        let num_callback_calls = Arc::new(AtomicU32::new(0));
+        let num_callback_calls_clone = num_callback_calls.clone();
         let (result, statistics) = super::solve(
             move |node| solver(node, ndarray::arr1(&[0.51, 0.46, 3.7, 0.56, 0.6])),
             SubProblem(BTreeMap::new()),
             1,
+            super::BabOptions {
+                new_best_callback: Some(Box::new(move |_solution, _score| {
+                    num_callback_calls_clone.fetch_add(1, Ordering::SeqCst);
+                })),
+                collect_thread_statistics: true,
+                ..Default::default()
+            },
         );
         match result {
             None => panic!("Expected to get a solution"),
@@ -377,16 +724,221 @@ mod tests {
             "Number of executed subproblems should be < 2^6-1, due to bounding."
         );
         assert!(statistics.num_bound_subproblems > 0);
+        assert!(statistics.num_new_best > 0);
+        assert_eq!(
+            num_callback_calls.load(Ordering::SeqCst),
+            statistics.num_new_best
+        );
+        assert_eq!(statistics.per_thread.len(), 1);
+        assert_eq!(
+            statistics.per_thread[0].num_executed_subproblems,
+            statistics.num_executed_subproblems
+        );
+        assert_eq!(
+            statistics.per_thread[0].busy_time,
+            statistics.total_subproblem_time
+        );
 
         // Unfortunately, there's no good (platform independent) check, if parallelism works. :(
-        let (result, _statistics) = super::solve(
+        let num_traced_nodes = Arc::new(AtomicU32::new(0));
+        let num_traced_feasible = Arc::new(AtomicU32::new(0));
+        let num_traced_nodes_clone = num_traced_nodes.clone();
+        let num_traced_feasible_clone = num_traced_feasible.clone();
+        let num_node_results = Arc::new(AtomicU32::new(0));
+        let num_node_results_clone = num_node_results.clone();
+        let (result, statistics) = super::solve(
             move |node| solver(node, ndarray::arr1(&[0.51, 6.46, 0.7, 0.56, 0.6])),
             SubProblem(BTreeMap::new()),
             4,
+            super::BabOptions {
+                node_trace_callback: Some(Box::new(move |_node, result_type, score| {
+                    num_traced_nodes_clone.fetch_add(1, Ordering::SeqCst);
+                    if result_type == NodeResultType::Feasible {
+                        num_traced_feasible_clone.fetch_add(1, Ordering::SeqCst);
+                    }
+                    assert_eq!(score.is_some(), result_type != NodeResultType::NoSolution);
+                })),
+                node_result_callback: Some(Box::new(
+                    move |result: &NodeResult<_, ndarray::Array1<i32>, _>| {
+                        num_node_results_clone.fetch_add(1, Ordering::SeqCst);
+                        if let NodeResult::Feasible(solution, _score) = result {
+                            assert_eq!(solution.len(), 5);
+                        }
+                    },
+                )),
+                ..Default::default()
+            },
         );
         match result {
             None => panic!("Expected to get a solution"),
             Some((solution, _)) => assert_eq!(solution, ndarray::arr1(&[1, 6, 1, 1, 1])),
         }
+        assert_eq!(
+            num_traced_nodes.load(Ordering::SeqCst),
+            statistics.num_executed_subproblems
+        );
+        assert_eq!(
+            num_traced_feasible.load(Ordering::SeqCst),
+            statistics.num_feasible
+        );
+        assert_eq!(
+            num_node_results.load(Ordering::SeqCst),
+            statistics.num_executed_subproblems
+        );
+        assert!(statistics.per_thread.is_empty());
+    }
+
+    #[test]
+    fn test_bab_cancellation() {
+        // An infinitely deep branch and bound tree: Each node has a leaf child (with a feasible
+        // solution, scored by its depth) and a deeper (infeasible) child.
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct SubProblem {
+            depth: u32,
+            leaf: bool,
+        }
+
+        let token = Arc::new(AtomicBool::new(false));
+        let token_clone = token.clone();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            token_clone.store(true, Ordering::Relaxed);
+        });
+        let (result, statistics) = super::solve(
+            |node: SubProblem| {
+                if node.leaf {
+                    NodeResult::Feasible(node.depth, node.depth)
+                } else {
+                    let child = |leaf| SubProblem {
+                        depth: node.depth + 1,
+                        leaf,
+                    };
+                    NodeResult::Infeasible(vec![child(true), child(false)], u32::MAX)
+                }
+            },
+            SubProblem {
+                depth: 0,
+                leaf: false,
+            },
+            4,
+            super::BabOptions {
+                cancellation_token: Some(token),
+                ..Default::default()
+            },
+        );
+        canceller.join().unwrap();
+
+        // The best solution, found before cancelling, is returned
+        match result {
+            None => panic!("Expected to get a solution"),
+            Some((solution, score)) => {
+                assert_eq!(solution, score);
+                assert!(score > 0);
+            }
+        }
+        assert!(statistics.num_feasible > 0);
+        assert!(!statistics.aborted_by_timeout);
+    }
+
+    #[test]
+    fn test_bab_timeout() {
+        // An infinitely deep branch and bound tree (like in test_bab_cancellation)
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct SubProblem {
+            depth: u32,
+            leaf: bool,
+        }
+        let solver = |node: SubProblem| {
+            if node.leaf {
+                NodeResult::Feasible(node.depth, node.depth)
+            } else {
+                let child = |leaf| SubProblem {
+                    depth: node.depth + 1,
+                    leaf,
+                };
+                NodeResult::Infeasible(vec![child(true), child(false)], u32::MAX)
+            }
+        };
+        let root = SubProblem {
+            depth: 0,
+            leaf: false,
+        };
+
+        let (result, statistics) = super::solve(
+            solver,
+            root.clone(),
+            4,
+            super::BabOptions {
+                timeout: Some(std::time::Duration::from_millis(50)),
+                ..Default::default()
+            },
+        );
+        assert!(result.is_some_and(|(_, score)| score > 0));
+        assert!(statistics.aborted_by_timeout);
+
+        // With an elapsed timeout, not even the root node is solved
+        let (result, statistics) = super::solve(
+            solver,
+            root,
+            4,
+            super::BabOptions {
+                timeout: Some(std::time::Duration::ZERO),
+                ..Default::default()
+            },
+        );
+        assert!(result.is_none());
+        assert!(statistics.aborted_by_timeout);
+        assert_eq!(statistics.num_executed_subproblems, 0);
+
+        // A proven absence of any solution is not reported as timeout
+        let (result, statistics) = super::solve(
+            |_node: SubProblem| NodeResult::<SubProblem, u32, u32>::NoSolution,
+            SubProblem {
+                depth: 0,
+                leaf: false,
+            },
+            2,
+            super::BabOptions {
+                timeout: Some(std::time::Duration::from_secs(60)),
+                ..Default::default()
+            },
+        );
+        assert!(result.is_none());
+        assert!(!statistics.aborted_by_timeout);
+        assert_eq!(statistics.num_executed_subproblems, 1);
+    }
+
+    #[test]
+    fn test_bab_solve_with_handle() {
+        // A binary tree of depth 4: Each leaf's solution is scored by its path (as binary number)
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct SubProblem {
+            depth: u32,
+            path: u32,
+        }
+
+        let best_score = super::BestScoreHandle::default();
+        let handle = super::solve_with_handle(
+            |node: SubProblem| {
+                if node.depth == 4 {
+                    NodeResult::Feasible(node.path, node.path)
+                } else {
+                    let child = |bit| SubProblem {
+                        depth: node.depth + 1,
+                        path: node.path * 2 + bit,
+                    };
+                    NodeResult::Infeasible(vec![child(0), child(1)], u32::MAX)
+                }
+            },
+            SubProblem { depth: 0, path: 0 },
+            2,
+            super::BabOptions::default(),
+            best_score.clone(),
+            |score| score,
+        );
+        let (result, _statistics) = handle.join();
+
+        assert_eq!(result, Some((15, 15)));
+        assert_eq!(best_score.load(Ordering::Relaxed), 15);
     }
 }
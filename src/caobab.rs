@@ -17,13 +17,16 @@
 
 use crate::bab::NodeResult::{Feasible, Infeasible, NoSolution};
 use crate::hungarian::{EdgeWeight, Score};
-use crate::util::{binom, IterSelections};
+use crate::util::{binom, binom_checked, IterSelections, SplitMix64};
 use crate::{bab, Choice};
 use crate::{Assignment, Course, Participant};
-use log::{debug, info};
+use log::{debug, error, info, log_enabled, trace, Level};
+use num_traits::bounds::Bounded;
 use std::cmp::min;
-use std::fmt::Debug;
-use std::sync::Arc;
+use std::fmt::{self, Debug};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 pub mod solution_score;
 
@@ -32,6 +35,9 @@ pub mod solution_score;
 ///
 /// It takes a list of Courses, a list of Participants and a list of available rooms sizes to create
 /// an optimal assignment of courses to participants.
+///
+/// This is a shortcut for [solve_with_config] with the default options and the given `rooms`, `report_no_solution` and
+/// `num_threads`. All further options are only available via [SolveConfig].
 pub fn solve(
     courses: Arc<Vec<Course>>,
     participants: Arc<Vec<Participant>>,
@@ -39,35 +45,1473 @@ pub fn solve(
     report_no_solution: bool,
     num_threads: u32,
 ) -> (Option<(Assignment, u32)>, bab::Statistics) {
-    let pre_computed_problem = Arc::new(precompute_problem(&courses, &participants, rooms));
-
-    bab::solve(
-        move |sub_problem| -> bab::NodeResult<BABNode, Assignment, Score> {
-            run_bab_node(
-                &courses,
-                &participants,
-                &pre_computed_problem,
-                sub_problem,
-                report_no_solution,
-            )
-        },
-        BABNode {
-            cancelled_courses: Vec::new(),
-            enforced_courses: Vec::new(),
-            shrinked_courses: Vec::new(),
-        },
+    let mut config = SolveConfig::new()
+        .report_no_solution(report_no_solution)
+        .num_threads(num_threads);
+    if let Some(rooms) = rooms {
+        config = config.rooms(rooms.clone());
+    }
+    solve_with_config(courses, participants, config)
+}
+
+/// Configuration of the course assignment solver, i.e. all options of [solve_with_config].
+///
+/// The configuration is created with [SolveConfig::new] (or `default()`) and customized with the builder-style
+/// setters, e.g.
+/// ```
+/// # use cdecao::caobab::SolveConfig;
+/// let config = SolveConfig::new().rooms(vec![20, 15, 10]).num_threads(4);
+/// ```
+///
+/// The soft penalties (`soft_room_penalty`, `group_balance_penalty` and `cancel_cost_penalty`) are given in score
+/// units: For comparison, assigning a participant to their next worse course choice reduces the score by 1. They are
+/// only applied to the solutions found by the branch and bound algorithm and do not create additional branches. Thus,
+/// they select the best one among the explored solutions, but solutions, which would avoid the penalty, are only found
+/// if they are explored for other reasons.
+pub struct SolveConfig {
+    rooms: Option<Vec<usize>>,
+    report_no_solution: bool,
+    min_first_choice_count: Option<usize>,
+    require_full_assignment: bool,
+    num_threads: u32,
+    new_best_callback: Option<NewBestCallback>,
+    trace_writer: Option<TraceWriter>,
+    random_restarts: Option<RandomRestarts>,
+    collect_thread_statistics: bool,
+    soft_room_penalty: Option<Score>,
+    prefer_flexible_cancellation: bool,
+    objective: SolveObjective,
+    cancellation_token: Option<CancellationToken>,
+    timeout: Option<Duration>,
+    max_scored_instructors: Option<usize>,
+    count_instructors_in_score: bool,
+    group_balance_penalty: Option<Score>,
+    even_fill_tie_break: bool,
+    max_penalty_tie_break: bool,
+    best_score_handle: Option<BestScoreHandle>,
+    stop_at_first_feasible: bool,
+    cancel_cost_penalty: Option<Score>,
+}
+
+impl SolveConfig {
+    /// Create a new configuration with the default options: No rooms, no additional constraints, full assignment of
+    /// all participants required and one worker thread per CPU core.
+    pub fn new() -> Self {
+        SolveConfig {
+            rooms: None,
+            report_no_solution: false,
+            min_first_choice_count: None,
+            require_full_assignment: true,
+            num_threads: num_cpus::get() as u32,
+            new_best_callback: None,
+            trace_writer: None,
+            random_restarts: None,
+            collect_thread_statistics: false,
+            soft_room_penalty: None,
+            prefer_flexible_cancellation: false,
+            objective: SolveObjective::ChoiceQuality,
+            cancellation_token: None,
+            timeout: None,
+            max_scored_instructors: None,
+            count_instructors_in_score: true,
+            group_balance_penalty: None,
+            even_fill_tie_break: false,
+            max_penalty_tie_break: false,
+            best_score_handle: None,
+            stop_at_first_feasible: false,
+            cancel_cost_penalty: None,
+        }
+    }
+
+    /// Set the sizes of the available rooms. If given, each course, which takes place, must fit into one of the
+    /// rooms.
+    pub fn rooms(mut self, rooms: Vec<usize>) -> Self {
+        self.rooms = Some(rooms);
+        self
+    }
+
+    /// If true, subproblems without any solution are reported by the branch and bound algorithm (at debug log level).
+    pub fn report_no_solution(mut self, report_no_solution: bool) -> Self {
+        self.report_no_solution = report_no_solution;
+        self
+    }
+
+    /// Only accept solutions which assign at least this number of participants to their first choice.
+    ///
+    /// This is a best-effort constraint: To find other solutions, the branch and bound algorithm only tries cancelling
+    /// courses with participants, who did not get their first choice. Thus, the solver may report no solution, even if
+    /// a solution fulfilling the constraint exists.
+    pub fn min_first_choice_count(mut self, count: usize) -> Self {
+        self.min_first_choice_count = Some(count);
+        self
+    }
+
+    /// If false, any participant may stay unassigned (e.g. for optional courses), although assigning them to one of
+    /// their choices is still preferred. Otherwise (default), only participants with a maximum acceptable choice
+    /// rank may stay unassigned (see [Participant::may_stay_unassigned]) and assignments leaving any other
+    /// participant unassigned are infeasible.
+    pub fn require_full_assignment(mut self, require_full_assignment: bool) -> Self {
+        self.require_full_assignment = require_full_assignment;
+        self
+    }
+
+    /// Set the number of worker threads for the branch and bound algorithm. Defaults to the number of CPU cores.
+    pub fn num_threads(mut self, num_threads: u32) -> Self {
+        self.num_threads = num_threads;
+        self
+    }
+
+    /// Set a callback function, which is called with each new best assignment (and its score) during the solving
+    /// process, e.g. to save intermediate results. See `bab::solve()` for details.
+    pub fn new_best_callback(mut self, callback: NewBestCallback) -> Self {
+        self.new_best_callback = Some(callback);
+        self
+    }
+
+    /// Write a trace of the branch and bound execution to the given writer: One JSON object per line for each solved
+    /// subproblem, containing the subproblem's cancelled, enforced and shrinked courses, the type of the result
+    /// (`no_solution`, `infeasible` or `feasible`) and its score and tie-break score (if any).
+    pub fn trace_writer(mut self, writer: TraceWriter) -> Self {
+        self.trace_writer = Some(writer);
+        self
+    }
+
+    /// Execute the branch and bound algorithm multiple times, each with a different (seeded) random ordering of the
+    /// branches, emitted by each subproblem. This may help to escape heuristic traps, where the deterministic ordering
+    /// explores a bad part of the branch and bound tree first. The best result is returned; among equally good
+    /// results, the one of the run with the fewest executed subproblems is chosen. The `new_best_callback` is only
+    /// called for results, which are better than the best result of all previous runs. For a fixed seed, the results
+    /// are reproducible (at least with a single thread).
+    pub fn random_restarts(mut self, random_restarts: RandomRestarts) -> Self {
+        self.random_restarts = Some(random_restarts);
+        self
+    }
+
+    /// If true, the returned statistics include statistics of each worker thread. See `bab::solve()`. With
+    /// `random_restarts`, the statistics of the run with the returned result are returned.
+    pub fn collect_thread_statistics(mut self, collect_thread_statistics: bool) -> Self {
+        self.collect_thread_statistics = collect_thread_statistics;
+        self
+    }
+
+    /// Use soft room constraints: Instead of shrinking or cancelling courses, which don't fit into the available rooms,
+    /// each place of a course's room-effective size exceeding its room's size (e.g. using an overflow space) reduces
+    /// the score by the given `penalty_per_place`. Only relevant, if `rooms` are given.
+    ///
+    /// Courses are still shrinked or cancelled for other reasons (e.g. their minimum size), but not for room
+    /// violations. With a high penalty, the result may even be worse than with hard room constraints.
+    pub fn soft_room_penalty(mut self, penalty_per_place: Score) -> Self {
+        self.soft_room_penalty = Some(penalty_per_place);
+        self
+    }
+
+    /// If true, among the courses with the same shortfall of attendees, the branch and bound algorithm first
+    /// constrains (i.e. enforces or cancels) the course with the fewest choosers, who have no alternative course choice
+    /// left. This way, solutions cancelling courses, which displace fewer participants, tend to be found earlier.
+    pub fn prefer_flexible_cancellation(mut self, prefer_flexible_cancellation: bool) -> Self {
+        self.prefer_flexible_cancellation = prefer_flexible_cancellation;
+        self
+    }
+
+    /// Set the primary optimization objective. Defaults to [SolveObjective::ChoiceQuality].
+    pub fn objective(mut self, objective: SolveObjective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Set a token for cancelling the solving process from another thread (e.g. on user request): As soon as the token
+    /// is set to true, the worker threads finish their current subproblems and stop. Then, the best assignment found so
+    /// far (if any) is returned. The token may be set at any time from any thread. With `random_restarts`, no further
+    /// runs are started after cancelling. See `bab::solve()` for details.
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Stop the solving process after the given time (like with the `cancellation_token`) and return the best assignment
+    /// found so far (if any). With `random_restarts`, the timeout applies to all runs together. If the timeout has
+    /// elapsed, the returned statistics' `aborted_by_timeout` flag is set, so a missing result can be distinguished from
+    /// a proven infeasible problem.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Count at most this number of instructors per course in the score. By default, each instructor of a course taking
+    /// place adds the same score as a participant getting their first choice, so courses with many instructors get a
+    /// large score boost, which effectively soft-enforces them to take place. With this option, only the first
+    /// `max_scored_instructors` instructors (in the order of the course's instructors list) are scored.
+    pub fn max_scored_instructors(mut self, max_scored_instructors: usize) -> Self {
+        self.max_scored_instructors = Some(max_scored_instructors);
+        self
+    }
+
+    /// If false, instructors are not considered in the score at all (like instructor-only participants): They are
+    /// still assigned to their course, if it takes place, but courses are not soft-enforced to take place by their
+    /// instructors. This results in a pure optimization of the participants' choices, where courses are cancelled
+    /// more readily. Defaults to true. Overrides `max_scored_instructors`.
+    pub fn count_instructors_in_score(mut self, count_instructors_in_score: bool) -> Self {
+        self.count_instructors_in_score = count_instructors_in_score;
+        self
+    }
+
+    /// Balance the participant groups (see `Participant::group`) across the courses: For each course taking place,
+    /// the number of attendees of each group is compared to the number, which would be expected from the distribution
+    /// of the groups among all participants. The total deviation (in places) of all groups in all courses, multiplied
+    /// by the given `penalty_per_place`, reduces the score. Participants without a group and instructors are not
+    /// considered.
+    pub fn group_balance_penalty(mut self, penalty_per_place: Score) -> Self {
+        self.group_balance_penalty = Some(penalty_per_place);
+        self
+    }
+
+    /// Among solutions with equal score (and equal tie-break score), prefer the one with the most even distribution
+    /// of participants to the courses, i.e. the lowest variance of the courses' fill ratios (number of attendees
+    /// divided by the maximum size). Instructors and cancelled courses are not considered. Defaults to false.
+    ///
+    /// This is only a secondary comparison of the found solutions and never outweighs the choice quality.
+    pub fn even_fill_tie_break(mut self, even_fill_tie_break: bool) -> Self {
+        self.even_fill_tie_break = even_fill_tie_break;
+        self
+    }
+
+    /// Among solutions with equal score, prefer the one with the lowest maximum penalty of all assigned course choices,
+    /// i.e. the best result for the worst-off participant (minimax fairness). Defaults to false.
+    ///
+    /// This is the first tie-break criterion, so it takes precedence over all other tie-break
+    /// criteria, but never outweighs the choice quality. For a primary minimax objective, see
+    /// [SolveObjective::MinimizeWorstChoice].
+    pub fn max_penalty_tie_break(mut self, max_penalty_tie_break: bool) -> Self {
+        self.max_penalty_tie_break = max_penalty_tie_break;
+        self
+    }
+
+    /// Set an atomic integer, which is updated with the score of each new best assignment during the solving process.
+    /// In contrast to the `new_best_callback`, it can be polled cheaply from any other thread (e.g. for a progress
+    /// display), without blocking the worker threads. It only increases, so with
+    /// [SolveObjective::MaximizeCoursesRunning], it is the highest score of all best assignments found so far. See
+    /// `bab::solve_with_handle()` for details on the memory ordering.
+    pub fn best_score_handle(mut self, handle: BestScoreHandle) -> Self {
+        self.best_score_handle = Some(handle);
+        self
+    }
+
+    /// If true, stop the solving process as soon as the first feasible assignment has been found (like with the
+    /// `cancellation_token`), without optimizing it any further. Subproblems, which are already being solved by other
+    /// worker threads, are finished, so the returned assignment may be slightly better than the first one. The
+    /// returned statistics show the number of subproblems, which have been required to get there. This is useful as a
+    /// fast probe of the problem's hardness. The returned assignment is valid, but not necessarily optimal.
+    ///
+    /// If a `cancellation_token` is given, it is set when the first feasible assignment is found. With
+    /// `random_restarts`, no further runs are started afterwards. Defaults to false.
+    pub fn stop_at_first_feasible(mut self, stop_at_first_feasible: bool) -> Self {
+        self.stop_at_first_feasible = stop_at_first_feasible;
+        self
+    }
+
+    /// Weight the cancel costs of the courses (see `Course::cancel_cost`) in the score: The sum of the cancel costs of
+    /// all cancelled courses, multiplied by the given `penalty_per_cost`, reduces the score. By default, the cancel
+    /// costs are only used as a tie-break between solutions of equal score, so they never outweigh the choice
+    /// quality.
+    pub fn cancel_cost_penalty(mut self, penalty_per_cost: Score) -> Self {
+        self.cancel_cost_penalty = Some(penalty_per_cost);
+        self
+    }
+
+    /// Maximum number of scored instructors per course, w.r.t. `max_scored_instructors` and
+    /// `count_instructors_in_score`. See `num_scored_instructors()`.
+    fn effective_max_scored_instructors(&self) -> Option<usize> {
+        if self.count_instructors_in_score {
+            self.max_scored_instructors
+        } else {
+            Some(0)
+        }
+    }
+}
+
+/// Primary optimization objective of the course assignment. See [SolveConfig::objective].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SolveObjective {
+    /// Maximize the quality of the assigned course choices (sum of the choices' scores). This is the default.
+    #[default]
+    ChoiceQuality,
+    /// Maximize the number of courses taking place. The quality of the assigned course choices is only optimized
+    /// secondarily, among the assignments with the maximum number of courses taking place. This favours the variety of
+    /// courses over the satisfaction of individual participants.
+    MaximizeCoursesRunning,
+    /// Minimize the worst (highest) penalty of all assigned course choices, i.e. optimize the result
+    /// of the worst-off participant (minimax fairness). The quality of the assigned course choices
+    /// (sum of the choices' scores) is only optimized secondarily, among the assignments with the
+    /// best result for the worst-off participant. Participants, who are left unassigned or assigned
+    /// to a course they did not choose, are considered worse off than any assigned choice.
+    ///
+    /// The problem is solved repeatedly, each time without the choices, which are as bad as the
+    /// previous result's worst assigned choice, until the worst-off participant's result does not
+    /// improve anymore.
+    MinimizeWorstChoice,
+}
+
+impl Default for SolveConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Solve a course assignement problem using the branch and bound method together with the hungarian method, with the
+/// options given by `config`.
+///
+/// It takes a list of Courses and a list of Participants to create an optimal assignment of courses to participants.
+/// Returns the best assignment found (if any) with its score and the statistics of the branch and bound execution.
+/// Assignments without any course taking place are never considered a solution, so there is no result for an empty
+/// list of courses.
+pub fn solve_with_config(
+    courses: Arc<Vec<Course>>,
+    participants: Arc<Vec<Participant>>,
+    config: SolveConfig,
+) -> (Option<(Assignment, u32)>, bab::Statistics) {
+    let max_scored_instructors = config.effective_max_scored_instructors();
+    let SolveConfig {
+        rooms,
+        report_no_solution,
+        min_first_choice_count,
+        require_full_assignment,
         num_threads,
+        new_best_callback,
+        trace_writer,
+        random_restarts,
+        collect_thread_statistics,
+        soft_room_penalty,
+        prefer_flexible_cancellation,
+        objective,
+        cancellation_token,
+        timeout,
+        max_scored_instructors: _,
+        count_instructors_in_score: _,
+        group_balance_penalty,
+        even_fill_tie_break,
+        max_penalty_tie_break,
+        best_score_handle,
+        stop_at_first_feasible,
+        cancel_cost_penalty,
+    } = config;
+    if courses.is_empty() {
+        error!("Calculating course assignments is only possible with 1 or more courses.");
+        return (None, bab::Statistics::default());
+    }
+    let new_best_callback = new_best_callback.map(Arc::new);
+    // With stop_at_first_feasible, the execution is cancelled with the first feasible assignment
+    let cancellation_token = if stop_at_first_feasible {
+        Some(cancellation_token.unwrap_or_default())
+    } else {
+        cancellation_token
+    };
+    let trace_writer = trace_writer.map(|writer| Arc::new(Mutex::new(writer)));
+
+    // Each run gets its own seed for randomizing the branch ordering (or None for the deterministic ordering)
+    let branch_seeds: Vec<Option<u64>> = match random_restarts {
+        None => vec![None],
+        Some(restarts) => {
+            let mut rng = SplitMix64::new(restarts.seed);
+            (0..std::cmp::max(restarts.num_runs, 1))
+                .map(|_| Some(rng.next_u64()))
+                .collect()
+        }
+    };
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+    // Courses, which must run, are enforced from the root node on
+    let must_run_courses: Vec<usize> = courses
+        .iter()
+        .filter(|c| c.must_run)
+        .map(|c| c.index)
+        .collect();
+
+    // Execute all runs of the branch and bound algorithm for the given courses (with selected
+    // instructors, see below) and participants (whose choices may be restricted, see below) and
+    // return the best result of all runs. New best results are only reported, if they are better
+    // than `best_score`.
+    let solve_runs = |courses: &Arc<Vec<Course>>,
+                      participants: Arc<Vec<Participant>>,
+                      best_score: Arc<Mutex<Option<NodeScore>>>| {
+        let pre_computed_problem = Arc::new(precompute_problem(
+            courses,
+            &participants,
+            rooms.as_ref(),
+            require_full_assignment,
+            soft_room_penalty,
+            objective,
+            max_scored_instructors,
+            group_balance_penalty,
+            even_fill_tie_break,
+            cancel_cost_penalty,
+            max_penalty_tie_break,
+        ));
+        if log_enabled!(Level::Trace) {
+            if let Ok(matrix) = debug_format_matrix(&pre_computed_problem, courses, &participants) {
+                trace!("Adjacency matrix:\n{}", matrix);
+            }
+        }
+        let mut best_run: Option<(Option<(Assignment, NodeScore)>, bab::Statistics)> = None;
+        let mut aborted_by_timeout = false;
+
+        for (run, branch_seed) in branch_seeds.iter().enumerate() {
+            let stop_token = cancellation_token
+                .clone()
+                .filter(|_| stop_at_first_feasible);
+            let run_new_best_callback = if new_best_callback.is_some() || stop_token.is_some() {
+                let callback = new_best_callback.clone();
+                let best_score = best_score.clone();
+                Some(
+                    Box::new(move |assignment: &Assignment, node_score: NodeScore| {
+                        if let Some(ref token) = stop_token {
+                            token.store(true, std::sync::atomic::Ordering::Relaxed);
+                        }
+                        let mut best_score = best_score.lock().unwrap();
+                        if best_score.is_none_or(|best| node_score > best) {
+                            *best_score = Some(node_score);
+                            if let Some(ref callback) = callback {
+                                callback(assignment, node_score.score)
+                            }
+                        }
+                    }) as bab::NewBestCallback<Assignment, NodeScore>,
+                )
+            } else {
+                None
+            };
+            let node_trace_callback = trace_writer.clone().map(|writer| {
+                Box::new(
+                    move |node: &BABNode,
+                          result_type: bab::NodeResultType,
+                          node_score: Option<NodeScore>| {
+                        let line = serde_json::json!({
+                            "cancelled_courses": node.cancelled_courses,
+                            "enforced_courses": node.enforced_courses,
+                            "shrinked_courses": node.shrinked_courses,
+                            "result": match result_type {
+                                bab::NodeResultType::NoSolution => "no_solution",
+                                bab::NodeResultType::Infeasible => "infeasible",
+                                bab::NodeResultType::Feasible => "feasible",
+                            },
+                            "score": node_score.map(|s| s.score),
+                            "tie_break": node_score.map(|s| s.tie_break),
+                            "fill_balance": node_score.map(|s| s.fill_balance),
+                        });
+                        if let Err(e) = writeln!(writer.lock().unwrap(), "{}", line) {
+                            error!("Could not write branch and bound trace: {}", e);
+                        }
+                    },
+                ) as bab::NodeTraceCallback<BABNode, NodeScore>
+            });
+
+            let courses = courses.clone();
+            let participants = participants.clone();
+            let pre_computed_problem = pre_computed_problem.clone();
+            let branch_seed = *branch_seed;
+            let node_solver =
+                move |sub_problem| -> bab::NodeResult<BABNode, Assignment, NodeScore> {
+                    let node_seed = branch_seed.map(|seed| node_branch_seed(seed, &sub_problem));
+                    let mut result = run_bab_node(
+                        &courses,
+                        &participants,
+                        &pre_computed_problem,
+                        sub_problem,
+                        report_no_solution,
+                        min_first_choice_count,
+                        prefer_flexible_cancellation,
+                    );
+                    if let (Some(seed), Infeasible(branches, _)) = (node_seed, &mut result) {
+                        SplitMix64::new(seed).shuffle(branches);
+                    }
+                    result
+                };
+            let root_node = BABNode {
+                cancelled_courses: Vec::new(),
+                enforced_courses: must_run_courses.clone(),
+                shrinked_courses: Vec::new(),
+            };
+            let run_timeout =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            let (result, statistics) = match best_score_handle {
+                Some(ref handle) => bab::solve_with_handle(
+                    node_solver,
+                    root_node,
+                    num_threads,
+                    bab::BabOptions {
+                        new_best_callback: run_new_best_callback,
+                        node_trace_callback,
+                        collect_thread_statistics,
+                        cancellation_token: cancellation_token.clone(),
+                        timeout: run_timeout,
+                        ..Default::default()
+                    },
+                    handle.clone(),
+                    |node_score| node_score.score,
+                )
+                .join(),
+                None => bab::solve(
+                    node_solver,
+                    root_node,
+                    num_threads,
+                    bab::BabOptions {
+                        new_best_callback: run_new_best_callback,
+                        node_trace_callback,
+                        collect_thread_statistics,
+                        cancellation_token: cancellation_token.clone(),
+                        timeout: run_timeout,
+                        ..Default::default()
+                    },
+                ),
+            };
+
+            if random_restarts.is_some() {
+                info!(
+                    "Finished run {} of {}: {} after {} subproblems.",
+                    run + 1,
+                    branch_seeds.len(),
+                    match result {
+                        Some((_, node_score)) =>
+                            format!("Found solution with score {}", node_score.score),
+                        None => "No solution found".to_owned(),
+                    },
+                    statistics.num_executed_subproblems
+                );
+            }
+            let is_better = match (&best_run, &result) {
+                (None, _) => true,
+                (Some((None, _)), Some(_)) => true,
+                (Some((Some((_, best_score)), best_statistics)), Some((_, node_score))) => {
+                    node_score > best_score
+                        || (node_score == best_score
+                            && statistics.num_executed_subproblems
+                                < best_statistics.num_executed_subproblems)
+                }
+                (Some(_), None) => false,
+            };
+            aborted_by_timeout |= statistics.aborted_by_timeout;
+            if is_better {
+                best_run = Some((result, statistics));
+            }
+            if aborted_by_timeout {
+                break;
+            }
+        }
+
+        let (result, mut statistics) = best_run.unwrap();
+        statistics.aborted_by_timeout = aborted_by_timeout;
+        (result, statistics)
+    };
+    // Best score of all runs, to only report results, that are better than the previous runs' results
+    let reported_best_score: Arc<Mutex<Option<NodeScore>>> = Arc::new(Mutex::new(None));
+    let solve_problem = |courses: &Arc<Vec<Course>>, participants: &Arc<Vec<Participant>>| {
+        let (mut result, mut statistics) =
+            solve_runs(courses, participants.clone(), reported_best_score.clone());
+
+        // With MinimizeWorstChoice, solve the problem again without the participants' choices,
+        // which are as bad as the worst assigned choice, as long as this improves the worst-off
+        // participant's result. The last improved result is the best assignment among those with
+        // the best result for the worst-off participant.
+        if objective == SolveObjective::MinimizeWorstChoice {
+            while let Some((ref assignment, _)) = result {
+                let worst = worst_choice(courses, participants, assignment);
+                if worst.1 == 0 || statistics.aborted_by_timeout {
+                    break;
+                }
+                debug!(
+                    "Worst assigned choice penalty is {}. Solving again without these choices.",
+                    worst.1
+                );
+                let restricted_participants = Arc::new(restrict_choices(participants, worst.1 - 1));
+                let (new_result, new_statistics) =
+                    solve_runs(courses, restricted_participants, Arc::new(Mutex::new(None)));
+                let improved = new_result.as_ref().is_some_and(|(assignment, _)| {
+                    worst_choice(courses, participants, assignment) < worst
+                });
+                if !improved {
+                    statistics.aborted_by_timeout |= new_statistics.aborted_by_timeout;
+                    break;
+                }
+                result = new_result;
+                statistics = new_statistics;
+            }
+        }
+        (result, statistics)
+    };
+
+    // If instructors are to be selected from the courses' potential instructors, solve the problem
+    // for each possible selection and keep the best result
+    let (result, statistics, courses, participants) = if courses
+        .iter()
+        .any(|c| c.min_instructors > 0)
+    {
+        let selections = match instructor_selections(&courses) {
+            Ok(selections) => selections,
+            Err(e) => {
+                error!("{}", e);
+                return (None, bab::Statistics::default());
+            }
+        };
+        info!(
+            "Solving the problem for {} possible selections of course instructors.",
+            selections.len()
+        );
+        #[allow(clippy::type_complexity)]
+        let mut best: Option<(
+            Option<(Assignment, NodeScore)>,
+            Arc<Vec<Course>>,
+            Arc<Vec<Participant>>,
+        )> = None;
+        // Statistics of all runs together
+        let mut total_statistics = bab::Statistics::default();
+        for selection in selections.iter() {
+            let (selection_courses, selection_participants) =
+                select_instructors(&courses, &participants, selection);
+            let selection_courses = Arc::new(selection_courses);
+            let selection_participants = Arc::new(selection_participants);
+            let (result, statistics) = solve_problem(&selection_courses, &selection_participants);
+            total_statistics.accumulate(&statistics);
+            let is_better = match (&best, &result) {
+                (None, _) | (Some((None, ..)), Some(_)) => true,
+                (
+                    Some((Some((best_assignment, best_score)), best_courses, _)),
+                    Some((assignment, score)),
+                ) => {
+                    if objective == SolveObjective::MinimizeWorstChoice {
+                        let worst = worst_choice(&selection_courses, &participants, assignment);
+                        let best_worst = worst_choice(best_courses, &participants, best_assignment);
+                        worst < best_worst || (worst == best_worst && score > best_score)
+                    } else {
+                        score > best_score
+                    }
+                }
+                (Some(_), None) => false,
+            };
+            if is_better {
+                best = Some((result, selection_courses, selection_participants));
+            }
+            if total_statistics.aborted_by_timeout {
+                break;
+            }
+        }
+        match best {
+            Some((result, courses, participants)) => {
+                (result, total_statistics, courses, participants)
+            }
+            None => {
+                error!("No valid selection of course instructors exists.");
+                return (None, bab::Statistics::default());
+            }
+        }
+    } else {
+        let (result, statistics) = solve_problem(&courses, &participants);
+        (result, statistics, courses, participants)
+    };
+
+    if let Some(writer) = trace_writer {
+        if let Err(e) = writer.lock().unwrap().flush() {
+            error!("Could not write branch and bound trace: {}", e);
+        }
+    }
+    // In debug build: Self-check of the solution
+    if cfg!(debug_assertions) && require_full_assignment {
+        if let Some((ref assignment, _)) = result {
+            if let Err(errors) = validate_assignment(assignment, &courses, &participants, None) {
+                error!("Invalid assignment calculated:\n{}", errors.join("\n"));
+            }
+        }
+    }
+    (
+        result.map(|(assignment, node_score)| (assignment, node_score.score)),
+        statistics,
     )
 }
 
+/// Parameters for executing the branch and bound algorithm multiple times with randomized branch orderings. See
+/// [SolveConfig::random_restarts].
+#[derive(Clone, Copy, Debug)]
+pub struct RandomRestarts {
+    /// Number of executions of the branch and bound algorithm
+    pub num_runs: u32,
+    /// Seed for the random branch orderings. The same seed always results in the same branch orderings.
+    pub seed: u64,
+}
+
+/// Derive the seed for randomizing the order of a subproblem's branches from the run's seed and the subproblem's
+/// constraints. This way, the order does not depend on the order, in which the subproblems are solved by the worker
+/// threads.
+fn node_branch_seed(seed: u64, node: &BABNode) -> u64 {
+    let mut rng = SplitMix64::new(seed);
+    for c in node.cancelled_courses.iter() {
+        rng.feed(*c as u64);
+    }
+    // Separators, to distinguish the different lists of constraints
+    rng.feed(u64::MAX);
+    for c in node.enforced_courses.iter() {
+        rng.feed(*c as u64);
+    }
+    rng.feed(u64::MAX);
+    for (c, size) in node.shrinked_courses.iter() {
+        rng.feed(*c as u64);
+        rng.feed(*size as u64);
+    }
+    rng.next_u64()
+}
+
+/// Calculate a stable fingerprint of a course assignment, e.g. to check in regression tests, that the solver's output
+/// has not changed.
+///
+/// The fingerprint is the 64 bit FNV-1a hash of the assignment vector, where each entry is encoded as 8 bytes (little
+/// endian): 0 for unassigned participants, the course index + 1 otherwise. In contrast to Rust's `DefaultHasher`, it
+/// is guaranteed to be the same on every platform and with every version of cdecao and Rust.
+pub fn assignment_fingerprint(assignment: &Assignment) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+    let mut hash = FNV_OFFSET_BASIS;
+    for entry in assignment.iter() {
+        let value = entry.map_or(0, |c| c as u64 + 1);
+        for byte in value.to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+/// Group a course assignment by courses, e.g. for building reports.
+///
+/// Returns an iterator, yielding a tuple `(course, attendees, instructors)` for each course (in the order of the
+/// courses list). The assigned attendees (excl. instructors) and the assigned instructors are each given in the order
+/// of the participants list. Cancelled courses yield empty lists of attendees and instructors.
+pub fn iter_course_assignments<'a>(
+    assignment: &Assignment,
+    courses: &'a [Course],
+    participants: &'a [Participant],
+) -> impl Iterator<Item = (&'a Course, Vec<&'a Participant>, Vec<&'a Participant>)> {
+    let mut attendees: Vec<Vec<&Participant>> = vec![Vec::new(); courses.len()];
+    let mut instructors: Vec<Vec<&Participant>> = vec![Vec::new(); courses.len()];
+    for (participant, course) in participants.iter().zip(assignment.iter()) {
+        if let Some(c) = course {
+            if courses[*c].instructors.contains(&participant.index) {
+                instructors[*c].push(participant);
+            } else {
+                attendees[*c].push(participant);
+            }
+        }
+    }
+    courses
+        .iter()
+        .zip(attendees.into_iter().zip(instructors))
+        .map(|(course, (attendees, instructors))| (course, attendees, instructors))
+}
+
+/// List each filled course place of a course assignment together with the rank of the course in the
+/// participant's choices, e.g. for auditing the assignment place by place.
+///
+/// Returns a tuple `(course, participant, rank)` of indexes for each assigned participant, ordered by courses (in the
+/// order of the courses list) and participants (in the order of the participants list). The rank is the 0-based rank
+/// of the course in the participant's choices (see [solution_score::satisfaction_report]). It is None for instructors
+/// of the course and for participants, who did not choose the course. Unassigned participants are not listed.
+pub fn place_level_report(
+    assignment: &Assignment,
+    participants: &[Participant],
+    courses: &[Course],
+) -> Vec<(usize, usize, Option<u32>)> {
+    let mut places: Vec<(usize, usize, Option<u32>)> =
+        solution_score::satisfaction_report(assignment, participants, courses)
+            .into_iter()
+            .filter_map(|satisfaction| {
+                let rank = match satisfaction.assigned_rank {
+                    solution_score::AssignedRank::Rank(rank) => Some(rank as u32),
+                    _ => None,
+                };
+                satisfaction
+                    .assigned_course
+                    .map(|course| (course, satisfaction.participant, rank))
+            })
+            .collect();
+    // The sort is stable, so participants of each course keep their order
+    places.sort_by_key(|(course, _, _)| *course);
+    places
+}
+
+/// Constraints of a single Branch and Bound subproblem, which are additionally checked by [validate_assignment]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BABConstraints {
+    /// Indexes of the courses, which must not have any participants
+    pub cancelled_courses: Vec<usize>,
+    /// Index and maximum number of attendees (excl. instructors) of courses, which have been restricted (e.g. due
+    /// to room sizes)
+    pub shrinked_courses: Vec<(usize, usize)>,
+}
+
+impl From<&BABNode> for BABConstraints {
+    fn from(node: &BABNode) -> Self {
+        BABConstraints {
+            cancelled_courses: node.cancelled_courses.clone(),
+            shrinked_courses: node.shrinked_courses.clone(),
+        }
+    }
+}
+
+/// Check a course assignment for validity, e.g. to verify the result of the solver or of an external tool.
+///
+/// In contrast to [score_assignment], all violations are collected and returned as a list of string error
+/// messages. The following constraints are checked:
+/// * no course has more attendees than `num_max` or (unless it is cancelled, i.e. has no participants) less than
+///   `num_min` (excl. instructors)
+/// * all instructors and required attendees of courses taking place are assigned to their course
+/// * all other participants are assigned to one of their (acceptable) course choices, which is available and not
+///   forbidden for them, or are unassigned, if they may stay unassigned
+///
+/// If `constraints` are given, the cancelled courses must not have any participants, all other courses must have
+/// at least `num_min` attendees and the restricted course sizes must be respected.
+pub fn validate_assignment(
+    assignment: &Assignment,
+    courses: &[Course],
+    participants: &[Participant],
+    constraints: Option<&BABConstraints>,
+) -> Result<(), Vec<String>> {
+    if assignment.len() != participants.len() {
+        return Err(vec![format!(
+            "Assignment has {} entries, but there are {} participants",
+            assignment.len(),
+            participants.len()
+        )]);
+    }
+    let mut errors = Vec::new();
+
+    // Calculate course sizes
+    let mut course_size = vec![0usize; courses.len()];
+    for (p, course) in assignment.iter().enumerate() {
+        if let Some(c) = course {
+            if *c >= courses.len() {
+                return Err(vec![format!(
+                    "Participant {} is assigned to invalid course {}",
+                    p, c
+                )]);
+            }
+            course_size[*c] += 1;
+        }
+    }
+
+    // Check course sizes
+    for (c, course) in courses.iter().enumerate() {
+        let size = course_size[c];
+        let num_attendees = size.saturating_sub(course.instructors.len());
+        if size > course.num_max + course.instructors.len() {
+            errors.push(format!(
+                "Maximum size violation for course {}: {} places, {} participants",
+                c, course.num_max, num_attendees
+            ));
+        }
+        let is_cancelled = match constraints {
+            Some(constraints) => constraints.cancelled_courses.contains(&c),
+            None => size == 0,
+        };
+        if is_cancelled {
+            if size != 0 {
+                errors.push(format!("Cancelled course {} has {} participants", c, size));
+            }
+        } else if size < course.num_min + course.instructors.len() {
+            errors.push(format!(
+                "Minimum size violation for course {}: {} required, {} assigned",
+                c, course.num_min, num_attendees
+            ));
+        }
+    }
+    if let Some(constraints) = constraints {
+        for (c, max_size) in constraints.shrinked_courses.iter() {
+            let num_instructors = courses[*c].instructors.len();
+            if course_size[*c] > *max_size + num_instructors {
+                errors.push(format!(
+                    "Dynamic size constraint for course {} not satisfied: {} > {}",
+                    *c,
+                    course_size[*c] - num_instructors,
+                    *max_size
+                ));
+            }
+        }
+    }
+
+    // Check course instructors and required attendees
+    let mut is_assigned_by_course = vec![false; participants.len()];
+    for (c, course) in courses.iter().enumerate() {
+        if course_size[c] == 0 {
+            continue;
+        }
+        for i in course.instructors.iter() {
+            if assignment[*i] != Some(c) {
+                errors.push(format!(
+                    "Instructor {} of course {} is assigned to {:?}",
+                    *i, c, assignment[*i]
+                ));
+            }
+            is_assigned_by_course[*i] = true;
+        }
+        for a in course.required_attendees.iter() {
+            if assignment[*a] != Some(c) {
+                errors.push(format!(
+                    "Required attendee {} of course {} is assigned to {:?}",
+                    *a, c, assignment[*a]
+                ));
+            }
+            if !participants[*a].is_available_course(c) {
+                errors.push(format!(
+                    "Required attendee {} of course {} is not available for the course",
+                    *a, c
+                ));
+            }
+            is_assigned_by_course[*a] = true;
+        }
+    }
+
+    // Check all other participants' course choices
+    for (p, participant) in participants.iter().enumerate() {
+        if is_assigned_by_course[p] || participant.is_instructor_only() {
+            continue;
+        }
+        if assignment[p].is_none() && participant.may_stay_unassigned() {
+            continue;
+        }
+        if !participant
+            .choices
+            .iter()
+            .any(|c| Some(c.course_index) == assignment[p] && participant.is_acceptable_choice(c))
+        {
+            errors.push(format!(
+                "Course {:?} of participant {} is none of their choices ({:?})",
+                assignment[p], p, participant.choices
+            ));
+        }
+        if assignment[p].is_some_and(|c| participant.is_forbidden_course(c)) {
+            errors.push(format!(
+                "Course {:?} of participant {} is one of their forbidden courses",
+                assignment[p], p
+            ));
+        }
+        if assignment[p].is_some_and(|c| !participant.is_available_course(c)) {
+            errors.push(format!(
+                "Course {:?} of participant {} is not one of their available courses",
+                assignment[p], p
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Validate an (externally produced) course assignment and calculate its score.
+///
+/// The score is calculated in the same way as by the branch and bound solver (see `run_bab_node()`), such that it can
+/// be compared to the score of an assignment, returned by [solve_with_config] with the same `config`. Of the `config`,
+/// only the instructor scoring ([SolveConfig::max_scored_instructors] and [SolveConfig::count_instructors_in_score])
+/// and [SolveConfig::require_full_assignment] are considered; the soft penalties are not applied. A course is considered to be cancelled, if no participant is
+/// assigned to it.
+///
+/// # Errors
+///
+/// Fails with a string error message, describing the first violation found, if
+/// * the assignment does not match the list of participants or refers to non-existing courses
+/// * a course's instructors or required attendees are not assigned to the course, although it takes place
+/// * a course has more attendees than `num_max` or less than `num_min` (excl. instructors), although it takes place
+/// * a fixed course is cancelled
+/// * any other participant is assigned to a course, they did not (acceptably) choose or which is forbidden or not
+///   available for them, or is unassigned, although they may not stay unassigned.
+pub fn score_assignment(
+    assignment: &Assignment,
+    participants: &[Participant],
+    courses: &[Course],
+    config: &SolveConfig,
+) -> Result<Score, String> {
+    if assignment.len() != participants.len() {
+        return Err(format!(
+            "Assignment has {} entries, but there are {} participants",
+            assignment.len(),
+            participants.len()
+        ));
+    }
+    let mut course_size = vec![0usize; courses.len()];
+    for (p, c) in assignment.iter().enumerate() {
+        if let Some(c) = c {
+            if *c >= courses.len() {
+                return Err(format!(
+                    "Participant {} is assigned to invalid course {}",
+                    participants[p].name, c
+                ));
+            }
+            course_size[*c] += 1;
+        }
+    }
+
+    // Check instructors, required attendees and course sizes and score instructors
+    let mut is_assigned_by_course = vec![false; participants.len()];
+    let mut score: Score = 0;
+    for (c, course) in courses.iter().enumerate() {
+        if course_size[c] == 0 {
+            if course.fixed_course {
+                return Err(format!("Fixed course {} is cancelled", course.name));
+            }
+            if course.must_run && course.num_min > 0 {
+                return Err(format!("Must-run course {} is cancelled", course.name));
+            }
+            continue;
+        }
+        for instr in course.instructors.iter() {
+            if assignment[*instr] != Some(c) {
+                return Err(format!(
+                    "Instructor {} of course {} is not assigned to their course",
+                    participants[*instr].name, course.name
+                ));
+            }
+            is_assigned_by_course[*instr] = true;
+        }
+        score += INSTRUCTOR_SCORE
+            * num_scored_instructors(
+                course,
+                participants,
+                config.effective_max_scored_instructors(),
+            ) as Score;
+        for attendee in course.required_attendees.iter() {
+            if course.instructors.contains(attendee) {
+                continue;
+            }
+            if assignment[*attendee] != Some(c) {
+                return Err(format!(
+                    "Required attendee {} of course {} is not assigned to the course",
+                    participants[*attendee].name, course.name
+                ));
+            }
+            if !participants[*attendee].is_available_course(c) {
+                return Err(format!(
+                    "Required attendee {} of course {} is not available for the course",
+                    participants[*attendee].name, course.name
+                ));
+            }
+            is_assigned_by_course[*attendee] = true;
+            if !participants[*attendee].is_instructor_only() {
+                score += participants[*attendee]
+                    .choices
+                    .iter()
+                    .find(|choice| choice.course_index == c)
+                    .map(|choice| edge_weight(choice) as Score)
+                    .unwrap_or(INSTRUCTOR_SCORE);
+            }
+        }
+        let num_attendees = course_size[c] - course.instructors.len();
+        if num_attendees > course.num_max {
+            return Err(format!(
+                "Course {} has {} attendees, but only {} places",
+                course.name, num_attendees, course.num_max
+            ));
+        }
+        if num_attendees < course.num_min {
+            return Err(format!(
+                "Course {} has {} attendees, but requires at least {}",
+                course.name, num_attendees, course.num_min
+            ));
+        }
+    }
+
+    // Check and score all other participants
+    for (p, participant) in participants.iter().enumerate() {
+        if is_assigned_by_course[p] {
+            continue;
+        }
+        let course = assignment[p];
+        if participant.is_instructor_only() {
+            if let Some(c) = course {
+                return Err(format!(
+                    "Participant {} without course choices is assigned to course {}",
+                    participant.name, courses[c].name
+                ));
+            }
+            continue;
+        }
+        if !is_valid_assignment(participant, course, config.require_full_assignment) {
+            return Err(match course {
+                None => format!(
+                    "Participant {} is not assigned to any course",
+                    participant.name
+                ),
+                Some(c) => format!(
+                    "Participant {} is assigned to course {}, which is not an acceptable choice",
+                    participant.name, courses[c].name
+                ),
+            });
+        }
+        score += match course {
+            None => UNASSIGNED_WEIGHT as Score,
+            Some(c) => participant
+                .choices
+                .iter()
+                .find(|choice| choice.course_index == c)
+                .map(|choice| edge_weight(choice) as Score)
+                .unwrap_or(0),
+        };
+    }
+
+    Ok(score)
+}
+
+/// Check the course assignment problem for obvious reasons of unsolvability, without actually solving it.
+///
+/// This only checks some simple necessary conditions: Each participant, who may not stay unassigned (see
+/// `may_stay_unassigned()`) and is not a course instructor, must have an acceptable, available and not forbidden
+/// course choice, each course must have enough potential instructors to select its `min_instructors` from and the
+/// total number of course places must suffice for all of these participants. Thus, an empty result does not
+/// guarantee that a feasible assignment exists.
+///
+/// Returns a list of human readable descriptions of all problems found.
+pub fn check_obvious_infeasibility(
+    courses: &[Course],
+    participants: &[Participant],
+    require_full_assignment: bool,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut is_instructor = vec![false; participants.len()];
+    for course in courses.iter() {
+        for instr in course.instructors.iter() {
+            is_instructor[*instr] = true;
+        }
+    }
+
+    let mut num_to_assign = 0;
+    for (p, participant) in participants.iter().enumerate() {
+        if is_instructor[p]
+            || participant.is_instructor_only()
+            || may_stay_unassigned(participant, require_full_assignment)
+        {
+            continue;
+        }
+        num_to_assign += 1;
+        if !(0..courses.len()).any(|c| is_valid_assignment(participant, Some(c), true)) {
+            problems.push(format!(
+                "Participant {} has no acceptable course choice, but may not stay unassigned",
+                participant.name
+            ));
+        }
+    }
+
+    for course in courses.iter() {
+        if course.potential_instructors.len() < course.min_instructors {
+            problems.push(format!(
+                "Course {} has only {} potential instructors, but {} must be selected",
+                course.name,
+                course.potential_instructors.len(),
+                course.min_instructors
+            ));
+        }
+    }
+
+    let num_places: usize = courses.iter().map(|c| c.num_max).sum();
+    if num_places < num_to_assign {
+        problems.push(format!(
+            "There are only {} course places for {} participants, who must be assigned",
+            num_places, num_to_assign
+        ));
+    }
+
+    problems
+}
+
+/// Size of a course assignment problem, as reported by [problem_size]. Helps to predict the memory usage and solving
+/// time before actually solving the problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemSize {
+    pub num_participants: usize,
+    /// Number of participants, who are only instructors (i.e. have no course choices but instruct a course)
+    pub num_instructor_only: usize,
+    pub num_courses: usize,
+    /// Sum of the maximum sizes of all courses
+    pub total_max_capacity: usize,
+    /// Sum of the minimum sizes of all courses
+    pub total_min_capacity: usize,
+    /// Number of participants with 0, 1, 2, … course choices
+    pub num_choices_distribution: Vec<usize>,
+    /// Number of rows (`n`) of the adjacency matrix, i.e. participants and dummy participants
+    pub matrix_rows: usize,
+    /// Number of columns (`m`) of the adjacency matrix, i.e. course places and "unassigned" places
+    pub matrix_columns: usize,
+}
+
+impl fmt::Display for ProblemSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Participants:               {: >9} ({} instructor-only)",
+            self.num_participants, self.num_instructor_only
+        )?;
+        writeln!(f, "Courses:                    {: >9}", self.num_courses)?;
+        writeln!(
+            f,
+            "Total course capacity:      {: >9} (min {})",
+            self.total_max_capacity, self.total_min_capacity
+        )?;
+        writeln!(
+            f,
+            "Participants by number of choices (0 / 1 / 2 / …): {}",
+            self.num_choices_distribution
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(" / ")
+        )?;
+        writeln!(
+            f,
+            "Adjacency matrix size:      {: >9} x {} (n x m)",
+            self.matrix_rows, self.matrix_columns
+        )
+    }
+}
+
+/// Calculate the size of the given course assignment problem, esp. the dimensions of the adjacency matrix, which is
+/// used in every Branch and Bound node, without solving the problem.
+pub fn problem_size(
+    courses: &[Course],
+    participants: &[Participant],
+    require_full_assignment: bool,
+) -> ProblemSize {
+    let problem = precompute_problem(
+        courses,
+        participants,
+        None,
+        require_full_assignment,
+        None,
+        SolveObjective::default(),
+        None,
+        None,
+        false,
+        None,
+        false,
+    );
+    let (matrix_rows, matrix_columns) = problem.adjacency_matrix.dim();
+
+    let mut num_choices_distribution = Vec::new();
+    for p in participants.iter() {
+        if p.choices.len() >= num_choices_distribution.len() {
+            num_choices_distribution.resize(p.choices.len() + 1, 0);
+        }
+        num_choices_distribution[p.choices.len()] += 1;
+    }
+
+    ProblemSize {
+        num_participants: participants.len(),
+        num_instructor_only: participants
+            .iter()
+            .filter(|p| p.is_instructor_only())
+            .count(),
+        num_courses: courses.len(),
+        total_max_capacity: courses.iter().map(|c| c.num_max).sum(),
+        total_min_capacity: courses.iter().map(|c| c.num_min).sum(),
+        num_choices_distribution,
+        matrix_rows,
+        matrix_columns,
+    }
+}
+
+/// Callback function, which is called with every new best assignment and its score during solving
+pub type NewBestCallback = bab::NewBestCallback<Assignment, Score>;
+
+/// Output stream for the branch and bound trace, written during solving
+pub type TraceWriter = Box<dyn Write + Send>;
+
+/// Shared flag for cancelling the solving process from another thread. See [SolveConfig::cancellation_token].
+pub type CancellationToken = bab::CancellationToken;
+
+/// Atomic copy of the best score during solving. See [SolveConfig::best_score_handle].
+pub type BestScoreHandle = bab::BestScoreHandle;
+
+/// Get the number of instructors of the given course, which are considered in the score, if the course takes place.
+///
+/// instructor_only participants are never considered in the score. Otherwise they would have such a large influence
+/// that they effectively soft-enforce their course to take place. Additionally, at most `max_scored_instructors`
+/// instructors are considered, if given. See [SolveConfig::max_scored_instructors] and
+/// [SolveConfig::count_instructors_in_score].
+fn num_scored_instructors(
+    course: &Course,
+    participants: &[Participant],
+    max_scored_instructors: Option<usize>,
+) -> usize {
+    let num_instructors = course
+        .instructors
+        .iter()
+        .filter(|instr| !participants[**instr].is_instructor_only())
+        .count();
+    max_scored_instructors.map_or(num_instructors, |max| num_instructors.min(max))
+}
+
 /// Highest value for edge weights to be used. See docs of `super::hungarian::EdgeWeight` for more thoughts on that
 /// topic
 const WEIGHT_OFFSET: EdgeWeight = 50000;
 /// Generate edge weight from course choice
 fn edge_weight(choice: &Choice) -> EdgeWeight {
-    WEIGHT_OFFSET - choice.penalty as EdgeWeight
+    WEIGHT_OFFSET - choice.effective_penalty() as EdgeWeight
 }
 const INSTRUCTOR_SCORE: Score = WEIGHT_OFFSET as u32;
+/// Edge weight for leaving a participant unassigned, who may stay unassigned (see
+/// [Participant::may_stay_unassigned]). It is lower than any course choice's edge weight, but
+/// higher than the weight of an unchosen course, so leaving them unassigned is always preferred
+/// to assigning them to a course they did not (acceptably) choose.
+const UNASSIGNED_WEIGHT: EdgeWeight = 1;
+
+/// Score of a single Branch and Bound node's solution, used for comparing and bounding solutions.
+///
+/// Scores are compared lexicographically: The `num_running_courses` is only used with
+/// [SolveObjective::MaximizeCoursesRunning] (otherwise it is always 0) and takes precedence. Then,
+/// the `score` (sum of the matching's edge weights and instructor scores) is compared. The
+/// `tie_break` criteria (see [TieBreak]) are only considered for solutions with equal `score`, so
+/// any tie-break criterion is weighted well below the choice quality. Finally, the `fill_balance`
+/// (the negated variance of the courses' fill ratios, see [SolveConfig::even_fill_tie_break])
+/// decides between solutions with equal `tie_break` criteria.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+struct NodeScore {
+    num_running_courses: usize,
+    score: Score,
+    tie_break: TieBreak,
+    fill_balance: i64,
+}
+
+impl NodeScore {
+    /// Create a NodeScore to be used as upper bound for an infeasible solution's subproblems.
+    ///
+    /// The tie-break criteria of the subproblems' solutions cannot be estimated from the parent
+    /// node's solution, so we use the maximum value for each criterion, which is used by the
+    /// problem, to not bound any subproblem with equal `score`. Unused criteria are always 0, so
+    /// subproblems with equal `score` are bounded, if no tie-break criterion is used at all.
+    fn upper_bound(
+        num_running_courses: usize,
+        score: Score,
+        pre_computed_problem: &PreComputedProblem,
+    ) -> Self {
+        Self {
+            num_running_courses,
+            score,
+            tie_break: pre_computed_problem.tie_break_bound,
+            fill_balance: if pre_computed_problem.even_fill_tie_break {
+                i64::MAX
+            } else {
+                0
+            },
+        }
+    }
+}
+
+impl Bounded for NodeScore {
+    fn min_value() -> Self {
+        Self {
+            num_running_courses: usize::MIN,
+            score: Score::MIN,
+            tie_break: TieBreak::min_value(),
+            fill_balance: i64::MIN,
+        }
+    }
+
+    fn max_value() -> Self {
+        Self {
+            num_running_courses: usize::MAX,
+            score: Score::MAX,
+            tie_break: TieBreak::max_value(),
+            fill_balance: i64::MAX,
+        }
+    }
+}
+
+impl fmt::Display for NodeScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (tie-break: {})", self.score, self.tie_break)
+    }
+}
+
+/// Tie-break criteria of a [NodeScore] for solutions with equal `score`.
+///
+/// The criteria are compared lexicographically in the order of the fields, i.e. each criterion only
+/// decides between solutions, which are equal w.r.t. all previous criteria. Thus, the criteria
+/// cannot trade off against each other. Higher values are better, so penalties are negated.
+/// Criteria, which are not used by the given problem, are always 0.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, serde::Serialize)]
+struct TieBreak {
+    /// Negated highest assigned choice penalty, see [max_assigned_penalty] (only with
+    /// [SolveConfig::max_penalty_tie_break])
+    max_penalty: i64,
+    /// Negated (scaled) sum of the cancel costs of cancelled courses, see [Course::cancel_cost] and
+    /// [CANCEL_COST_SCALE]
+    cancel_cost: i64,
+    /// Number of running courses with [Course::keep_if_possible]
+    kept_if_possible_courses: i64,
+    /// Number of participants kept in their [Participant::current_course]
+    kept_current_courses: i64,
+    /// Number of reunited co-instructor pairs of cancelled courses, see
+    /// [num_reunited_co_instructors]
+    reunited_co_instructors: i64,
+    /// Negated deviation of the course sizes from their [Course::preferred_size]
+    preferred_size: i64,
+}
+
+impl TieBreak {
+    /// Upper bound for the tie-break criteria of all solutions of the given problem: The maximum
+    /// value for each criterion, which is used by the problem, and 0 for all unused criteria
+    /// (which are 0 for every solution).
+    fn upper_bound(
+        courses: &[Course],
+        participants: &[Participant],
+        max_penalty_tie_break: bool,
+        cancel_cost_penalty: Option<Score>,
+    ) -> Self {
+        let bound = |used: bool| if used { i64::MAX } else { 0 };
+        Self {
+            max_penalty: bound(max_penalty_tie_break),
+            cancel_cost: bound(
+                cancel_cost_penalty.is_none() && courses.iter().any(|c| c.cancel_cost != 0.0),
+            ),
+            kept_if_possible_courses: bound(courses.iter().any(|c| c.keep_if_possible)),
+            kept_current_courses: bound(participants.iter().any(|p| p.current_course.is_some())),
+            reunited_co_instructors: bound(courses.iter().any(|c| c.instructors.len() > 1)),
+            preferred_size: bound(courses.iter().any(|c| c.preferred_size.is_some())),
+        }
+    }
+}
+
+impl Bounded for TieBreak {
+    fn min_value() -> Self {
+        Self {
+            max_penalty: i64::MIN,
+            cancel_cost: i64::MIN,
+            kept_if_possible_courses: i64::MIN,
+            kept_current_courses: i64::MIN,
+            reunited_co_instructors: i64::MIN,
+            preferred_size: i64::MIN,
+        }
+    }
+
+    fn max_value() -> Self {
+        Self {
+            max_penalty: i64::MAX,
+            cancel_cost: i64::MAX,
+            kept_if_possible_courses: i64::MAX,
+            kept_current_courses: i64::MAX,
+            reunited_co_instructors: i64::MAX,
+            preferred_size: i64::MAX,
+        }
+    }
+}
+
+impl fmt::Display for TieBreak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}/{}/{}/{}",
+            self.max_penalty,
+            self.cancel_cost,
+            self.kept_if_possible_courses,
+            self.kept_current_courses,
+            self.reunited_co_instructors,
+            self.preferred_size
+        )
+    }
+}
 
 /// Precomputed problem definition for the hungarian method, that can be reused for every Branch and Bound node
 struct PreComputedProblem {
@@ -84,24 +1528,62 @@ struct PreComputedProblem {
     /// skip_x vector. In theory, we could remove the rows with skip_x_always[x]==true completely from the matrix, but
     /// that would require changes to the handling of the participant list to keep the indexes in sync.
     skip_x_always: ndarray::Array1<bool>,
-    /// Maps each column in the adjacency matrix to the course's index, the represented course place is belonging to
-    course_map: ndarray::Array1<usize>,
+    /// Maps each column in the adjacency matrix to the course's index, the represented course place is belonging to,
+    /// or None for the additional "unassigned" places of participants, who may stay unassigned
+    course_map: ndarray::Array1<Option<usize>>,
+    /// Number of additional "unassigned" places (columns) at the end of the adjacency matrix. There is one such place
+    /// for every participant who may stay unassigned.
+    num_unassigned_places: usize,
+    /// If false, any participant may stay unassigned. See [may_stay_unassigned].
+    require_full_assignment: bool,
     /// maps Course index to the first column index of its first course places
     inverse_course_map: Vec<usize>,
     /// Ordered list of rooms' sizes (descending), filled with zero entries to length of course list
     room_sizes: Option<Vec<usize>>,
+    /// If given, room size violations are not resolved by shrinking or cancelling courses, but reduce the score by
+    /// this penalty per exceeding place. See [SolveConfig::soft_room_penalty].
+    soft_room_penalty: Option<Score>,
+    /// The primary optimization objective. See [SolveConfig::objective].
+    objective: SolveObjective,
+    /// Number of instructors of each course, which are considered in the score, if the course takes place. See
+    /// [SolveConfig::max_scored_instructors].
+    num_scored_instructors: Vec<usize>,
+    /// If given, the imbalance of participant groups in the courses reduces the score by this penalty per place. See
+    /// [SolveConfig::group_balance_penalty].
+    group_balance_penalty: Option<Score>,
+    /// If true, equal-score solutions are compared by the variance of the courses' fill ratios. See
+    /// [SolveConfig::even_fill_tie_break].
+    even_fill_tie_break: bool,
+    /// If given, the cancel costs of the cancelled courses reduce the score by this penalty per unit. Otherwise, they
+    /// are only used as tie-break. See [SolveConfig::cancel_cost_penalty].
+    cancel_cost_penalty: Option<Score>,
+    /// If true, equal-score solutions are compared by the highest assigned choice penalty. See
+    /// [SolveConfig::max_penalty_tie_break].
+    max_penalty_tie_break: bool,
+    /// Upper bound for the tie-break criteria of all solutions, see [TieBreak::upper_bound]
+    tie_break_bound: TieBreak,
 }
 
 /// Generate the general precomputed problem defintion (esp. the adjacency matrix) based on the Course and Participant
 /// objects
+#[allow(clippy::too_many_arguments)]
 fn precompute_problem(
     courses: &[Course],
     participants: &[Participant],
     rooms: Option<&Vec<usize>>,
+    require_full_assignment: bool,
+    soft_room_penalty: Option<Score>,
+    objective: SolveObjective,
+    max_scored_instructors: Option<usize>,
+    group_balance_penalty: Option<Score>,
+    even_fill_tie_break: bool,
+    cancel_cost_penalty: Option<Score>,
+    max_penalty_tie_break: bool,
 ) -> PreComputedProblem {
     // To determine the required number of extra participant rows (which are filled with dummy
     // participants later), we need to know the maximum number of participants that may be skipped
-    // in any assignment. This includes all course instructors and all participants without choices.
+    // in any assignment. This includes all course instructors, required attendees and all
+    // participants without choices.
     let mut skippable_participants: Vec<bool> = participants
         .iter()
         .map(|p| p.is_instructor_only())
@@ -110,19 +1592,30 @@ fn precompute_problem(
         for instructor in course.instructors.iter() {
             skippable_participants[*instructor] = true;
         }
+        for attendee in course.required_attendees.iter() {
+            skippable_participants[*attendee] = true;
+        }
     }
     let max_num_skipped_x = skippable_participants.iter().filter(|x| **x).count();
+    // Participants, who may stay unassigned, get an additional "unassigned" place each
+    let num_unassigned_places = participants
+        .iter()
+        .filter(|p| may_stay_unassigned(p, require_full_assignment))
+        .count();
     // Calculate adjacency matrix size to allocate 1D-Arrays
-    let m = courses.iter().map(|c| c.num_max).sum();
-    let n = m + max_num_skipped_x;
+    let num_course_places: usize = courses.iter().map(|c| c.num_max).sum();
+    let m = num_course_places + num_unassigned_places;
+    // We need at least one row per participant, even if there are not enough course places for
+    // all of them (which is detected in each BaB node)
+    let n = std::cmp::max(m + max_num_skipped_x, participants.len());
 
     // Generate course_map, inverse_course_map and madatory_y from course list
-    let mut course_map = ndarray::Array1::<usize>::zeros([m]);
+    let mut course_map = ndarray::Array1::<Option<usize>>::from_elem([m], None);
     let mut inverse_course_map = Vec::<usize>::new();
     let mut k = 0;
     for (i, c) in courses.iter().enumerate() {
         for j in 0..c.num_max {
-            course_map[k + j] = i;
+            course_map[k + j] = Some(i);
         }
         inverse_course_map.push(k);
         k += c.num_max;
@@ -146,6 +1639,15 @@ fn precompute_problem(
     let mut adjacency_matrix = ndarray::Array2::<EdgeWeight>::zeros([n, m]);
     for (x, p) in participants.iter().enumerate() {
         for choice in p.choices.iter() {
+            // Forbidden and unavailable courses must not be assigned, even if they have been chosen.
+            // The same holds for choices with a rank worse than the participant's maximum
+            // acceptable rank.
+            if p.is_forbidden_course(choice.course_index)
+                || !p.is_available_course(choice.course_index)
+                || !p.is_acceptable_choice(choice)
+            {
+                continue;
+            }
             debug_assert!(
                 choice.course_index < inverse_course_map.len(),
                 "Invalid course choice index {} of participant {}",
@@ -157,25 +1659,134 @@ fn precompute_problem(
                 adjacency_matrix[[x, y]] = edge_weight(choice);
             }
         }
+        if may_stay_unassigned(p, require_full_assignment) {
+            for y in num_course_places..m {
+                adjacency_matrix[[x, y]] = UNASSIGNED_WEIGHT;
+            }
+        }
+    }
+
+    // Clone, fix and resize rooms Vec
+    let room_sizes = rooms.map(|r| {
+        let mut rooms = r.clone();
+        rooms.sort();
+        rooms.reverse();
+        rooms.resize(courses.len(), 0);
+        rooms
+    });
+
+    let num_scored_instructors = courses
+        .iter()
+        .map(|c| num_scored_instructors(c, participants, max_scored_instructors))
+        .collect();
+
+    PreComputedProblem {
+        adjacency_matrix,
+        dummy_x,
+        skip_x_always,
+        course_map,
+        num_unassigned_places,
+        require_full_assignment,
+        inverse_course_map,
+        room_sizes,
+        soft_room_penalty,
+        objective,
+        num_scored_instructors,
+        group_balance_penalty,
+        even_fill_tie_break,
+        cancel_cost_penalty,
+        max_penalty_tie_break,
+        tie_break_bound: TieBreak::upper_bound(
+            courses,
+            participants,
+            max_penalty_tie_break,
+            cancel_cost_penalty,
+        ),
+    }
+}
+
+/// Maximum number of cells of an adjacency matrix, which is formatted by [debug_format_matrix]
+const DEBUG_MATRIX_MAX_CELLS: usize = 10_000;
+
+/// Render the adjacency matrix of the precomputed problem in a human readable form for debugging (and teaching): Each
+/// row is labeled with the participant's name (or "(dummy)" for dummy participants), the columns (course places) are
+/// grouped and labeled by course, followed by the "unassigned" places. Cells without an edge are shown as ".".
+///
+/// # Errors
+///
+/// As this is only practical for small problems, an error message is returned, if the matrix has more than
+/// [DEBUG_MATRIX_MAX_CELLS] cells.
+fn debug_format_matrix(
+    problem: &PreComputedProblem,
+    courses: &[Course],
+    participants: &[Participant],
+) -> Result<String, String> {
+    let (n, m) = problem.adjacency_matrix.dim();
+    if n * m > DEBUG_MATRIX_MAX_CELLS {
+        return Err(format!(
+            "The adjacency matrix is too large for formatting ({} x {} > {} cells)",
+            n, m, DEBUG_MATRIX_MAX_CELLS
+        ));
+    }
+
+    // Column groups: the places of each course and the "unassigned" places
+    let mut groups: Vec<(&str, std::ops::Range<usize>)> = courses
+        .iter()
+        .filter(|c| c.num_max > 0)
+        .map(|c| {
+            let first = problem.inverse_course_map[c.index];
+            (c.name.as_str(), first..first + c.num_max)
+        })
+        .collect();
+    if problem.num_unassigned_places > 0 {
+        groups.push(("(unassigned)", m - problem.num_unassigned_places..m));
+    }
+
+    let row_labels: Vec<&str> = (0..n)
+        .map(|x| participants.get(x).map_or("(dummy)", |p| p.name.as_str()))
+        .collect();
+    let label_width = row_labels
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or(0);
+    let cell_width = problem
+        .adjacency_matrix
+        .iter()
+        .map(|w| w.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let group_width = |range: &std::ops::Range<usize>| range.len() * (cell_width + 1) - 1;
+
+    let mut result = format!("{:1$}", "", label_width);
+    for (name, range) in groups.iter() {
+        let name: String = name.chars().take(group_width(range)).collect();
+        result += &format!(" | {:1$}", name, group_width(range));
+    }
+    result += &format!("\n{:1$}", "", label_width);
+    for (_name, range) in groups.iter() {
+        result += " |";
+        for j in 0..range.len() {
+            result += &format!(" {:>1$}", j, cell_width);
+        }
+    }
+    result += "\n";
+    for (x, label) in row_labels.iter().enumerate() {
+        result += &format!("{:1$}", label, label_width);
+        for (_name, range) in groups.iter() {
+            result += " |";
+            for y in range.clone() {
+                let weight = problem.adjacency_matrix[[x, y]];
+                if weight == 0 {
+                    result += &format!(" {:>1$}", ".", cell_width);
+                } else {
+                    result += &format!(" {:>1$}", weight, cell_width);
+                }
+            }
+        }
+        result += "\n";
     }
-
-    // Clone, fix and resize rooms Vec
-    let room_sizes = rooms.map(|r| {
-        let mut rooms = r.clone();
-        rooms.sort();
-        rooms.reverse();
-        rooms.resize(courses.len(), 0);
-        rooms
-    });
-
-    PreComputedProblem {
-        adjacency_matrix,
-        dummy_x,
-        skip_x_always,
-        course_map,
-        inverse_course_map,
-        room_sizes,
-    }
+    Ok(result)
 }
 
 /// Parameter set for one subproblem of the Branch and Bound algorithm
@@ -240,23 +1851,50 @@ fn run_bab_node(
     pre_computed_problem: &PreComputedProblem,
     mut current_node: BABNode,
     report_no_solution: bool,
-) -> bab::NodeResult<BABNode, Assignment, Score> {
+    min_first_choice_count: Option<usize>,
+    prefer_flexible_cancellation: bool,
+) -> bab::NodeResult<BABNode, Assignment, NodeScore> {
     let n = pre_computed_problem.adjacency_matrix.dim().0;
     let m = pre_computed_problem.adjacency_matrix.dim().1;
 
     // We will modify the current_node later for creating a new subproblem. Until then, we want to use it readonly.
     let node = &current_node;
 
+    // With MaximizeCoursesRunning, no solution of this node's subproblems can have more courses taking place than the
+    // courses not cancelled in this node
+    let running_courses_bound = match pre_computed_problem.objective {
+        SolveObjective::ChoiceQuality | SolveObjective::MinimizeWorstChoice => 0,
+        SolveObjective::MaximizeCoursesRunning => (0..courses.len())
+            .filter(|c| !node.cancelled_courses.contains(c))
+            .count(),
+    };
+
     // Generate skip_x from course instructors of non-cancelled courses
     let mut skip_x = pre_computed_problem.skip_x_always.clone();
+    let mut claiming_course: Vec<Option<usize>> = vec![None; participants.len()];
     for (i, c) in courses.iter().enumerate() {
         if !node.cancelled_courses.contains(&i) {
             for instr in c.instructors.iter() {
+                // Each participant can only be assigned to a single course, so they cannot instruct
+                // more than one course taking place
+                if let Some(other) = claiming_course[*instr] {
+                    debug!(
+                        "Participant {} instructs course {} and course {}",
+                        instr, i, other
+                    );
+                    return Infeasible(
+                        cancel_course_branches(courses, node, &[i, other]),
+                        NodeScore::max_value(),
+                    );
+                }
                 skip_x[*instr] = true;
+                claiming_course[*instr] = Some(i);
             }
         }
     }
-    let num_skip_x = skip_x.iter().filter(|x| **x).count();
+    // Participants are considered as instructors for the feasibility check, if they are skipped
+    // before adding the required attendees
+    let is_instructor = skip_x.clone();
 
     // Generate effective_num_max from cancelled courses and shrinked courses
     let mut effective_num_max: Vec<usize> = courses.iter().map(|c| c.num_max).collect();
@@ -267,24 +1905,83 @@ fn run_bab_node(
         effective_num_max[*c] = std::cmp::min(effective_num_max[*c], *s);
     }
 
+    // Add required attendees of non-cancelled courses to skip_x. Like instructors, they are
+    // assigned to their course directly, but they occupy a course place, so we reduce the
+    // effective_num_max of the course (which is used for the matching) accordingly.
+    let mut num_required_attendees = vec![0usize; courses.len()];
+    let mut required_attendees: Vec<(usize, usize)> = Vec::new();
+    for (i, c) in courses.iter().enumerate() {
+        if node.cancelled_courses.contains(&i) {
+            continue;
+        }
+        for attendee in c.required_attendees.iter() {
+            if c.instructors.contains(attendee) {
+                continue;
+            }
+            if let Some(other) = claiming_course[*attendee] {
+                debug!(
+                    "Participant {} is required in course {} and course {}",
+                    attendee, i, other
+                );
+                return Infeasible(
+                    cancel_course_branches(courses, node, &[i, other]),
+                    NodeScore::max_value(),
+                );
+            }
+            if !participants[*attendee].is_available_course(i) {
+                debug!(
+                    "Required attendee {} of course {} is not available for the course",
+                    attendee, i
+                );
+                return Infeasible(
+                    cancel_course_branches(courses, node, &[i]),
+                    NodeScore::max_value(),
+                );
+            }
+            skip_x[*attendee] = true;
+            claiming_course[*attendee] = Some(i);
+            required_attendees.push((*attendee, i));
+            num_required_attendees[i] += 1;
+        }
+        if num_required_attendees[i] > effective_num_max[i] {
+            debug!(
+                "Required attendees of course {} exceed its effective size {}",
+                i, effective_num_max[i]
+            );
+            return Infeasible(
+                cancel_course_branches(courses, node, &[i]),
+                NodeScore::max_value(),
+            );
+        }
+        effective_num_max[i] -= num_required_attendees[i];
+    }
+    let num_skip_x = skip_x.iter().filter(|x| **x).count();
+
     // Check for general feasibility
     // (this is done after calculating the course instructors/skip_x, as we need their number here)
     if node
         .enforced_courses
         .iter()
-        .map(|c| courses[*c].num_min)
+        .map(|c| {
+            courses[*c]
+                .num_min
+                .saturating_sub(num_required_attendees[*c])
+        })
         .sum::<usize>()
         > participants.len() - num_skip_x
     {
         debug!("Skipping this branch, since too many course places are enforced");
         return NoSolution;
     }
-    if effective_num_max.iter().sum::<usize>() < participants.len() - num_skip_x {
+    if effective_num_max.iter().sum::<usize>() + pre_computed_problem.num_unassigned_places
+        < participants.len() - num_skip_x
+    {
         debug!("Skipping this branch, since not enough course places are left");
         return NoSolution;
     }
     for (x, p) in participants.iter().enumerate() {
         if !skip_x[x]
+            && !may_stay_unassigned(p, pre_computed_problem.require_full_assignment)
             && p.choices
                 .iter()
                 .all(|c| node.cancelled_courses.contains(&c.course_index))
@@ -300,6 +1997,13 @@ fn run_bab_node(
             return NoSolution;
         }
     }
+    if (0..courses.len()).all(|c| node.cancelled_courses.contains(&c)) {
+        debug!("Skipping this branch, since all courses are cancelled");
+        if report_no_solution {
+            info!("Cannot cancel all courses, since no course would take place anymore.");
+        }
+        return NoSolution;
+    }
 
     // Generate skip_y from effective_num_max
     let mut skip_y = ndarray::Array1::from_elem([m], false);
@@ -328,7 +2032,10 @@ fn run_bab_node(
     // Generate mandatory_y from enforced courses
     let mut mandatory_y = ndarray::Array1::from_elem([m], false);
     for c in node.enforced_courses.iter() {
-        for j in 0..courses[*c].num_min {
+        for j in 0..courses[*c]
+            .num_min
+            .saturating_sub(num_required_attendees[*c])
+        {
             let y = pre_computed_problem.inverse_course_map[*c] + j;
             mandatory_y[y] = true;
             assert!(
@@ -347,32 +2054,50 @@ fn run_bab_node(
         &mandatory_y,
         &skip_x,
         &skip_y,
+        None,
     );
 
     // Convert course place matching to course assignment
     let mut assignment: Assignment = vec![None; participants.len()];
     for (cp, p) in matching.iter().enumerate() {
         if !skip_y[cp] && *p < assignment.len() {
-            assignment[*p] = Some(pre_computed_problem.course_map[cp]);
+            assignment[*p] = pre_computed_problem.course_map[cp];
         }
     }
-    // Add instructors to matching and increase score w.r.t. instructors
+    // Add instructors to matching and increase score w.r.t. instructors (see
+    // num_scored_instructors())
     for (c, course) in courses.iter().enumerate() {
         if !node.cancelled_courses.contains(&c) {
             for instr in course.instructors.iter() {
                 assignment[*instr] = Some(c);
-                // Don't consider instructor_only participants in the score. Otherwise they will
-                // have such a large influence that they effectively soft-enforce their course to
-                // take place.
-                if !participants[*instr].is_instructor_only() {
-                    score += INSTRUCTOR_SCORE;
-                }
             }
+            score += INSTRUCTOR_SCORE * pre_computed_problem.num_scored_instructors[c] as Score;
+        }
+    }
+    // Add required attendees to matching and increase score w.r.t. their course choices. If they
+    // did not choose the course, they are considered as helpers and scored like instructors.
+    for (p, c) in required_attendees {
+        assignment[p] = Some(c);
+        if !participants[p].is_instructor_only() {
+            score += participants[p]
+                .choices
+                .iter()
+                .find(|choice| choice.course_index == c)
+                .map(|choice| edge_weight(choice) as Score)
+                .unwrap_or(INSTRUCTOR_SCORE);
         }
     }
 
-    // If room size list is given, check feasibility of solution w.r.t room sizes
-    if let Some(ref room_sizes) = pre_computed_problem.room_sizes {
+    // If room size list is given, check feasibility of solution w.r.t room sizes. With soft room constraints, the
+    // exceeding places are only penalized (for feasible solutions). The unpenalized score is still used as upper bound
+    // for the branches below, since they might have smaller room penalties.
+    let mut room_penalty: Score = 0;
+    if let (Some(ref room_sizes), Some(penalty)) = (
+        &pre_computed_problem.room_sizes,
+        pre_computed_problem.soft_room_penalty,
+    ) {
+        room_penalty = room_overflow(courses, &assignment, room_sizes) as Score * penalty;
+    } else if let Some(ref room_sizes) = pre_computed_problem.room_sizes {
         let (feasible, restrictions) =
             check_room_feasibility(courses, &assignment, room_sizes, &current_node);
         if !feasible {
@@ -391,13 +2116,23 @@ fn run_bab_node(
                     branches.push(new_node);
                 }
             }
-            return Infeasible(branches, score);
+            return Infeasible(
+                branches,
+                NodeScore::upper_bound(running_courses_bound, score, pre_computed_problem),
+            );
         }
     }
 
     // Check feasibility of the solution w.r.t course min size and participants, if not, get the most conflicting course
-    let (feasible, participant_problem, branch_course) =
-        check_feasibility(courses, participants, &assignment, node, &skip_x);
+    let (feasible, participant_problem, branch_course) = check_feasibility(
+        courses,
+        participants,
+        &assignment,
+        node,
+        &is_instructor,
+        pre_computed_problem.require_full_assignment,
+        prefer_flexible_cancellation,
+    );
     if !feasible {
         let mut branches = Vec::<BABNode>::new();
         if let Some(c) = branch_course {
@@ -420,10 +2155,505 @@ fn run_bab_node(
             }
         }
 
-        return Infeasible(branches, score);
+        return Infeasible(
+            branches,
+            NodeScore::upper_bound(running_courses_bound, score, pre_computed_problem),
+        );
+    }
+
+    // Check if the solution fulfills the minimum number of first choices. If not, it must not be
+    // accepted, but it is still used as bound for the branches, generated to find other solutions.
+    if let Some(min_count) = min_first_choice_count {
+        let num_first_choices = count_first_choices(participants, &assignment, &is_instructor);
+        if num_first_choices < min_count {
+            debug!(
+                "Solution only has {} of {} required first choices",
+                num_first_choices, min_count
+            );
+            let branches = first_choice_constraint_branches(
+                courses,
+                participants,
+                &assignment,
+                &is_instructor,
+                node,
+            );
+            return Infeasible(
+                branches,
+                NodeScore::upper_bound(running_courses_bound, score, pre_computed_problem),
+            );
+        }
+    }
+
+    let cancel_cost = cancelled_courses_cost(courses, &assignment);
+    let (cancel_penalty, cancel_tie_break) = match pre_computed_problem.cancel_cost_penalty {
+        Some(penalty) => ((cancel_cost * penalty as f32).round() as Score, 0),
+        None => (0, (cancel_cost * CANCEL_COST_SCALE).round() as i64),
+    };
+    let tie_break = TieBreak {
+        max_penalty: if pre_computed_problem.max_penalty_tie_break {
+            -(max_assigned_penalty(courses, participants, &assignment) as i64)
+        } else {
+            0
+        },
+        cancel_cost: -cancel_tie_break,
+        kept_if_possible_courses: num_kept_if_possible_courses(courses, &assignment) as i64,
+        kept_current_courses: num_kept_current_courses(participants, &assignment) as i64,
+        reunited_co_instructors: num_reunited_co_instructors(
+            courses,
+            &assignment,
+            &node.cancelled_courses,
+        ) as i64,
+        preferred_size: -preferred_size_penalty(courses, &assignment),
+    };
+    let num_running_courses = match pre_computed_problem.objective {
+        SolveObjective::ChoiceQuality | SolveObjective::MinimizeWorstChoice => 0,
+        SolveObjective::MaximizeCoursesRunning => num_running_courses(courses, &assignment),
+    };
+    let group_penalty = pre_computed_problem
+        .group_balance_penalty
+        .map_or(0, |penalty| {
+            (group_imbalance(courses, participants, &assignment) * penalty as f32).round() as Score
+        });
+    let fill_balance = if pre_computed_problem.even_fill_tie_break {
+        fill_balance(courses, &assignment)
+    } else {
+        0
+    };
+    Feasible(
+        assignment,
+        NodeScore {
+            num_running_courses,
+            score: score.saturating_sub(room_penalty + group_penalty + cancel_penalty),
+            tie_break,
+            fill_balance,
+        },
+    )
+}
+
+/// Scale factor for converting the fill ratio variance into the integer `fill_balance` of a
+/// [NodeScore]
+const FILL_BALANCE_SCALE: f64 = 1e6;
+
+/// Calculate the `fill_balance` of a [NodeScore] for the given assignment: The negated and scaled
+/// [fill_ratio_variance], such that more evenly filled courses result in a higher value.
+fn fill_balance(courses: &[Course], assignment: &Assignment) -> i64 {
+    -(fill_ratio_variance(courses, assignment) as f64 * FILL_BALANCE_SCALE).round() as i64
+}
+
+/// Calculate the variance of the fill ratios (number of assigned attendees, excluding instructors,
+/// divided by the maximum number of attendees) of all courses taking place in the given assignment.
+/// Courses without places for attendees are not considered. Returns 0, if no course is considered.
+fn fill_ratio_variance(courses: &[Course], assignment: &Assignment) -> f32 {
+    let mut num_assigned = vec![0usize; courses.len()];
+    let mut is_running = vec![false; courses.len()];
+    for (p, c) in assignment.iter().enumerate() {
+        if let Some(c) = c {
+            is_running[*c] = true;
+            if !courses[*c].instructors.contains(&p) {
+                num_assigned[*c] += 1;
+            }
+        }
+    }
+    let ratios: Vec<f32> = courses
+        .iter()
+        .filter(|c| is_running[c.index] && c.num_max > 0)
+        .map(|c| num_assigned[c.index] as f32 / c.num_max as f32)
+        .collect();
+    if ratios.is_empty() {
+        return 0.0;
+    }
+    let mean = ratios.iter().sum::<f32>() / ratios.len() as f32;
+    ratios.iter().map(|r| (r - mean).powi(2)).sum::<f32>() / ratios.len() as f32
+}
+
+/// Count the courses taking place in the given assignment, i.e. the courses with at least one
+/// assigned participant (incl. instructors).
+fn num_running_courses(courses: &[Course], assignment: &Assignment) -> usize {
+    let mut is_running = vec![false; courses.len()];
+    for c in assignment.iter().flatten() {
+        is_running[*c] = true;
+    }
+    is_running.iter().filter(|r| **r).count()
+}
+
+/// Scale factor for converting the (fractional) sum of cancel costs into the integer `cancel_cost`
+/// criterion of a [TieBreak], such that fractional cancel costs are still distinguished
+const CANCEL_COST_SCALE: f32 = 1000.0;
+
+/// Sum of the cancel costs (see [Course::cancel_cost]) of all courses, which do not take place in the given
+/// assignment (i.e. have no assigned participants)
+fn cancelled_courses_cost(courses: &[Course], assignment: &Assignment) -> f32 {
+    let mut is_running = vec![false; courses.len()];
+    for c in assignment.iter().flatten() {
+        is_running[*c] = true;
+    }
+    courses
+        .iter()
+        .filter(|c| !is_running[c.index])
+        .map(|c| c.cancel_cost)
+        .sum()
+}
+
+/// Create a branch for each of the given courses, in which the course is cancelled, e.g. to resolve
+/// a conflict of required attendees. Fixed and enforced courses are not cancelled.
+fn cancel_course_branches(courses: &[Course], node: &BABNode, cancel: &[usize]) -> Vec<BABNode> {
+    cancel
+        .iter()
+        .filter(|c| !courses[**c].fixed_course && !node.enforced_courses.contains(c))
+        .map(|c| {
+            let mut new_node = node.clone();
+            new_node.cancelled_courses.push(*c);
+            new_node
+        })
+        .collect()
+}
+
+/// Count the participants, who are assigned to their [Participant::current_course] in the given
+/// assignment. This number is used as a tie-break reward, to avoid needless changes of existing
+/// course assignments.
+fn num_kept_current_courses(participants: &[Participant], assignment: &Assignment) -> usize {
+    participants
+        .iter()
+        .zip(assignment.iter())
+        .filter(|(p, c)| p.current_course.is_some() && p.current_course == **c)
+        .count()
+}
+
+/// Count the courses with [Course::keep_if_possible], which take place in the given assignment.
+/// This number is used as a tie-break criterion (see [TieBreak]), such that these courses preferably
+/// survive.
+fn num_kept_if_possible_courses(courses: &[Course], assignment: &Assignment) -> usize {
+    let mut is_running = vec![false; courses.len()];
+    for c in assignment.iter().flatten() {
+        is_running[*c] = true;
+    }
+    courses
+        .iter()
+        .filter(|c| c.keep_if_possible && is_running[c.index])
+        .count()
+}
+
+/// Count the pairs of co-instructors of cancelled courses, who are assigned to the same course in
+/// the given assignment. This number is used as a tie-break criterion (see [TieBreak]), such that
+/// the instructors of a cancelled course preferably land in the same replacement course.
+fn num_reunited_co_instructors(
+    courses: &[Course],
+    assignment: &Assignment,
+    cancelled_courses: &[usize],
+) -> usize {
+    cancelled_courses
+        .iter()
+        .map(|c| {
+            let instructors = &courses[*c].instructors;
+            instructors
+                .iter()
+                .enumerate()
+                .map(|(i, instr)| {
+                    instructors[i + 1..]
+                        .iter()
+                        .filter(|other| {
+                            assignment[*instr].is_some()
+                                && assignment[*instr] == assignment[**other]
+                        })
+                        .count()
+                })
+                .sum::<usize>()
+        })
+        .sum()
+}
+
+/// Get the highest (effective) penalty of all assigned course choices. With
+/// [SolveConfig::max_penalty_tie_break], this number is used as a tie-break criterion, such that
+/// among assignments of equal score, the one with the best result for the worst-off participant is
+/// preferred.
+fn max_assigned_penalty(
+    courses: &[Course],
+    participants: &[Participant],
+    assignment: &Assignment,
+) -> u32 {
+    solution_score::PenaltyStatistics::calculate(participants, courses, assignment)
+        .map(|statistics| statistics.max)
+        .unwrap_or(0)
+}
+
+/// Rate the result of the worst-off participant in the given assignment for
+/// [SolveObjective::MinimizeWorstChoice]: Returns the number of participants (excl. course
+/// instructors and instructor-only participants), who are not assigned to one of their choices,
+/// and the highest (effective) penalty of all assigned course choices. Lower values are better,
+/// the first entry takes precedence.
+fn worst_choice(
+    courses: &[Course],
+    participants: &[Participant],
+    assignment: &Assignment,
+) -> (usize, u32) {
+    let num_unchosen = participants
+        .iter()
+        .zip(assignment.iter())
+        .enumerate()
+        .filter(|(p_index, (p, assigned))| {
+            !p.is_instructor_only()
+                && match assigned {
+                    None => true,
+                    Some(c) => {
+                        !courses[*c].instructors.contains(p_index)
+                            && !p.choices.iter().any(|choice| choice.course_index == *c)
+                    }
+                }
+        })
+        .count();
+    (
+        num_unchosen,
+        max_assigned_penalty(courses, participants, assignment),
+    )
+}
+
+/// Create a copy of the participants, whose course choices are restricted to the choices with an
+/// (effective) penalty of at most `max_penalty`, for [SolveObjective::MinimizeWorstChoice].
+/// Participants without any such choice keep their best choices, so they are not turned into
+/// instructor-only participants.
+fn restrict_choices(participants: &[Participant], max_penalty: u32) -> Vec<Participant> {
+    participants
+        .iter()
+        .map(|p| {
+            let limit = p
+                .choices
+                .iter()
+                .map(|choice| choice.effective_penalty())
+                .min()
+                .map_or(max_penalty, |best| std::cmp::max(best, max_penalty));
+            let mut p = p.clone();
+            p.choices
+                .retain(|choice| choice.effective_penalty() <= limit);
+            p
+        })
+        .collect()
+}
+
+/// A selection of instructors from the courses' potential instructors, given as a list of pairs of
+/// course index and selected participant indexes (see [instructor_selections])
+type InstructorSelection = Vec<(usize, Vec<usize>)>;
+
+/// Maximum number of selections of instructors from the courses' potential instructors, for which
+/// the problem is solved (see [instructor_selections])
+const MAX_INSTRUCTOR_SELECTIONS: usize = 1000;
+
+/// Enumerate all possible selections of instructors from the courses' potential instructors (see
+/// [Course::potential_instructors]): For each course with `min_instructors > 0`, exactly
+/// `min_instructors` of its potential instructors are selected. Selections, in which a participant
+/// would instruct more than one course, are skipped.
+///
+/// As the problem is solved once for each selection, the number of selections grows exponentially
+/// with the number of courses with potential instructors. Thus, an error is returned, if there are
+/// more than [MAX_INSTRUCTOR_SELECTIONS] selections.
+fn instructor_selections(courses: &[Course]) -> Result<Vec<InstructorSelection>, String> {
+    let too_many_error = || {
+        format!(
+            "There are more than {} possible selections of course instructors from the \
+             potential instructors. Please reduce the number of potential instructors.",
+            MAX_INSTRUCTOR_SELECTIONS
+        )
+    };
+    let mut selections: Vec<InstructorSelection> = vec![Vec::new()];
+    for course in courses.iter().filter(|c| c.min_instructors > 0) {
+        let candidates: Vec<usize> = course
+            .potential_instructors
+            .iter()
+            .copied()
+            .filter(|p| !courses.iter().any(|c| c.instructors.contains(p)))
+            .collect();
+        if binom_checked(candidates.len(), course.min_instructors)
+            .is_none_or(|n| n > MAX_INSTRUCTOR_SELECTIONS)
+        {
+            return Err(too_many_error());
+        }
+        let mut new_selections = Vec::new();
+        for selection in selections.iter() {
+            for subset in candidates[..].iter_selections(course.min_instructors) {
+                if selection
+                    .iter()
+                    .any(|(_, selected)| selected.iter().any(|p| subset.contains(&p)))
+                {
+                    continue;
+                }
+                if new_selections.len() == MAX_INSTRUCTOR_SELECTIONS {
+                    return Err(too_many_error());
+                }
+                let mut selection = selection.clone();
+                selection.push((course.index, subset.into_iter().copied().collect()));
+                new_selections.push(selection);
+            }
+        }
+        selections = new_selections;
+    }
+    Ok(selections)
+}
+
+/// Create the courses and participants for solving the problem with the given selection of
+/// instructors (see [instructor_selections]): The selected participants are added to the course's
+/// instructors and the other potential instructors of the course must not attend it. Thus, the
+/// selection can be derived from the resulting assignment (see [apply_instructor_selection]).
+fn select_instructors(
+    courses: &[Course],
+    participants: &[Participant],
+    selection: &[(usize, Vec<usize>)],
+) -> (Vec<Course>, Vec<Participant>) {
+    let mut courses = courses.to_vec();
+    let mut participants = participants.to_vec();
+    for (c, selected) in selection.iter() {
+        let course = &mut courses[*c];
+        for p in std::mem::take(&mut course.potential_instructors) {
+            if selected.contains(&p) {
+                course.instructors.push(p);
+            } else if !participants[p].forbidden_courses.contains(c) {
+                participants[p].forbidden_courses.push(*c);
+            }
+        }
+        course.min_instructors = 0;
+    }
+    (courses, participants)
+}
+
+/// Apply the selection of instructors of a calculated assignment to the courses: For each course
+/// with `min_instructors > 0`, the potential instructors, who are assigned to the course, are added
+/// to the course's instructors (see `Course::potential_instructors`). Afterwards, the courses have
+/// no potential instructors left and can be used for scoring and writing the assignment.
+pub fn apply_instructor_selection(courses: &mut [Course], assignment: &Assignment) {
+    for course in courses.iter_mut() {
+        if course.min_instructors > 0 {
+            let selected: Vec<usize> = course
+                .potential_instructors
+                .iter()
+                .copied()
+                .filter(|p| assignment[*p] == Some(course.index))
+                .take(course.min_instructors)
+                .collect();
+            course.instructors.extend(selected);
+        }
+        course.potential_instructors.clear();
+        course.min_instructors = 0;
+    }
+}
+
+/// Calculate the tie-break penalty for a feasible assignment, based on the deviation of each
+/// course's final number of attendees (excl. instructors) from its [Course::preferred_size].
+///
+/// Cancelled courses and courses without a preferred size are not considered.
+fn preferred_size_penalty(courses: &[Course], assignment: &Assignment) -> i64 {
+    let mut num_attendees = vec![0usize; courses.len()];
+    for (p, course) in assignment.iter().enumerate() {
+        if let Some(c) = course {
+            if !courses[*c].instructors.contains(&p) {
+                num_attendees[*c] += 1;
+            }
+        }
+    }
+    courses
+        .iter()
+        .zip(num_attendees)
+        .filter(|(_course, num)| *num > 0)
+        .filter_map(|(course, num)| {
+            course
+                .preferred_size
+                .map(|preferred| (num as i64 - preferred as i64).abs())
+        })
+        .sum()
+}
+
+/// Check if the given participant may be left without any course: Participants without choices are
+/// never assigned as attendees. Other participants may stay unassigned, if they have a maximum
+/// acceptable choice rank or if `require_full_assignment` is false.
+fn may_stay_unassigned(participant: &Participant, require_full_assignment: bool) -> bool {
+    !participant.is_instructor_only()
+        && (participant.may_stay_unassigned() || !require_full_assignment)
+}
+
+/// Check if the given (non-instructor) participant may be assigned to the given course (or stay
+/// unassigned, if `course` is None) w.r.t. their course choices, forbidden and available courses and
+/// maximum acceptable choice rank. See [may_stay_unassigned] for the meaning of `require_full_assignment`.
+fn is_valid_assignment(
+    participant: &Participant,
+    course: Option<usize>,
+    require_full_assignment: bool,
+) -> bool {
+    match course {
+        None => may_stay_unassigned(participant, require_full_assignment),
+        Some(c) => {
+            !participant.is_forbidden_course(c)
+                && participant.is_available_course(c)
+                && participant.choices.iter().any(|choice| {
+                    choice.course_index == c && participant.is_acceptable_choice(choice)
+                })
+        }
     }
+}
+
+/// Check if the given participant's assigned course is one of their first choices, i.e. one of the
+/// choices with the lowest penalty.
+fn is_first_choice(participant: &Participant, course: usize) -> bool {
+    let min_penalty = participant
+        .choices
+        .iter()
+        .map(|c| c.effective_penalty())
+        .min();
+    participant
+        .choices
+        .iter()
+        .any(|c| c.course_index == course && Some(c.effective_penalty()) == min_penalty)
+}
+
+/// Count the participants (excl. course instructors), who are assigned to one of their first
+/// choices in the given assignment.
+fn count_first_choices(
+    participants: &[Participant],
+    assignment: &Assignment,
+    is_instructor: &ndarray::Array1<bool>,
+) -> usize {
+    participants
+        .iter()
+        .zip(assignment.iter())
+        .enumerate()
+        .filter(|(p, _)| !is_instructor[*p])
+        .filter(|(_, (participant, c))| c.is_some_and(|c| is_first_choice(participant, c)))
+        .count()
+}
 
-    Feasible(assignment, score)
+/// Generate new subproblems for a solution that does not fulfill the minimum number of first
+/// choices.
+///
+/// As the hungarian method already finds the best matching within the current node's constraints,
+/// we can only get a different matching by adding constraints. Thus, we create a branch for each
+/// course with attendees that did not get their first choice, where this course is cancelled. This
+/// is a heuristic approach: It may not find a solution in all cases where one would exist.
+fn first_choice_constraint_branches(
+    courses: &[Course],
+    participants: &[Participant],
+    assignment: &Assignment,
+    is_instructor: &ndarray::Array1<bool>,
+    node: &BABNode,
+) -> Vec<BABNode> {
+    let mut relevant_courses = vec![false; courses.len()];
+    for (p, c) in assignment.iter().enumerate() {
+        if let Some(c) = c {
+            if !is_instructor[p] && !is_first_choice(&participants[p], *c) {
+                relevant_courses[*c] = true;
+            }
+        }
+    }
+    relevant_courses
+        .iter()
+        .enumerate()
+        .filter(|(c, relevant)| {
+            **relevant
+                && !courses[*c].fixed_course
+                && !node.enforced_courses.contains(c)
+                && !node.cancelled_courses.contains(c)
+        })
+        .map(|(c, _)| {
+            let mut new_node = node.clone();
+            new_node.cancelled_courses.push(c);
+            new_node
+        })
+        .collect()
 }
 
 /// A set of constraints to fix a specific room size violation.
@@ -474,9 +2704,9 @@ struct RoomConstraintSet {
 /// * `courses` - The list of all courses (as referenced by `node` and `assignment`)
 /// * `assignment` - The assignment to be checked (must include course instructors)
 /// * `rooms` - An ordered list of course rooms in **descending** order, filled with zero entries to
-///     length of course list
+///   length of course list
 /// * `node` – The current BaB node, used to avoid conflicting restrictions (cancelled vs. enforced)
-///            and redundant restrictions.
+///   and redundant restrictions.
 ///
 /// # Result
 ///
@@ -592,12 +2822,162 @@ fn check_room_feasibility(
     (false, Some(result))
 }
 
+/// Calculate the total number of places, by which the room-effective sizes of the courses exceed the sizes of their
+/// rooms in the given assignment, for soft room constraints (see [SolveConfig::soft_room_penalty]).
+///
+/// Like in [check_room_feasibility], the courses and rooms are sorted by size in descending order and each course is
+/// assigned to the room at the same list position. This minimizes the total number of exceeding places.
+///
+/// `rooms` is expected to be ordered in **descending** order and filled with zero entries to length of course list.
+fn room_overflow(courses: &[Course], assignment: &Assignment, rooms: &[usize]) -> usize {
+    fit_courses_into_rooms(courses, assignment, rooms)
+        .iter()
+        .map(|(_c, size, room_size)| size.saturating_sub(*room_size))
+        .sum()
+}
+
+/// Calculate the total imbalance of participant groups (see [Participant::group]) in the courses of the given
+/// assignment, for balancing the groups (see [SolveConfig::group_balance_penalty]).
+///
+/// For each course and each group, the number of the course's attendees of the group is compared to the expected
+/// number, i.e. the number of the course's attendees (with any group) multiplied by the fraction of the group among all
+/// participants (with any group). The absolute deviations are summed up over all groups and courses. Instructors,
+/// instructor-only participants and participants without a group are not considered.
+fn group_imbalance(
+    courses: &[Course],
+    participants: &[Participant],
+    assignment: &Assignment,
+) -> f32 {
+    let mut global_count = std::collections::BTreeMap::<u32, usize>::new();
+    for p in participants.iter().filter(|p| !p.is_instructor_only()) {
+        if let Some(group) = p.group {
+            *global_count.entry(group).or_insert(0) += 1;
+        }
+    }
+    let num_grouped: usize = global_count.values().sum();
+    if global_count.len() < 2 {
+        return 0.0;
+    }
+
+    let mut course_counts = vec![std::collections::BTreeMap::<u32, usize>::new(); courses.len()];
+    for (p, c) in participants.iter().zip(assignment.iter()) {
+        if let (Some(c), Some(group)) = (c, p.group) {
+            if !courses[*c].instructors.contains(&p.index) && !p.is_instructor_only() {
+                *course_counts[*c].entry(group).or_insert(0) += 1;
+            }
+        }
+    }
+
+    course_counts
+        .iter()
+        .map(|counts| {
+            let size: usize = counts.values().sum();
+            global_count
+                .iter()
+                .map(|(group, global)| {
+                    let expected = size as f32 * *global as f32 / num_grouped as f32;
+                    (*counts.get(group).unwrap_or(&0) as f32 - expected).abs()
+                })
+                .sum::<f32>()
+        })
+        .sum()
+}
+
+/// Check if the courses of the given assignment fit into the given rooms, without running the solver (e.g. for
+/// validating an externally produced assignment).
+///
+/// Like in the solver, the courses (by room-effective size, see [room_effective_course_sizes]) and the rooms are
+/// sorted by size in descending order and each course is assigned to the room at the same list position. If there are
+/// less rooms than courses taking place, the remaining courses don't get any room. The order of `rooms` does not
+/// matter.
+///
+/// # Errors
+///
+/// Returns the list of conflicting courses as pairs `(course_index, needed_size)`, ordered by size in descending
+/// order, if any course does not fit into its room.
+pub fn check_rooms(
+    assignment: &Assignment,
+    courses: &[Course],
+    rooms: &[usize],
+) -> Result<(), Vec<(usize, usize)>> {
+    let mut rooms = rooms.to_vec();
+    rooms.sort_unstable_by(|a, b| b.cmp(a));
+    rooms.resize(std::cmp::max(rooms.len(), courses.len()), 0);
+    let conflicts: Vec<(usize, usize)> = fit_courses_into_rooms(courses, assignment, &rooms)
+        .iter()
+        .filter(|(_c, size, room_size)| size > room_size)
+        .map(|(c, size, _room_size)| (c.index, *size))
+        .collect();
+    if conflicts.is_empty() {
+        Ok(())
+    } else {
+        Err(conflicts)
+    }
+}
+
+/// Check that every instructor of a course, which takes place in the given assignment (i.e. has at least one assigned
+/// participant), is assigned to this course. The solver always ensures this, so a violation indicates a bug (e.g. in
+/// the handling of required attendees) or a tampered assignment. Each violation is logged at ERROR level.
+///
+/// # Errors
+///
+/// Returns the list of misplaced instructors as pairs `(participant_index, course_index)`, ordered by course index.
+pub fn verify_instructor_assignment(
+    assignment: &Assignment,
+    courses: &[Course],
+) -> Result<(), Vec<(usize, usize)>> {
+    let mut is_running = vec![false; courses.len()];
+    for c in assignment.iter().flatten() {
+        is_running[*c] = true;
+    }
+    let misplaced: Vec<(usize, usize)> = courses
+        .iter()
+        .filter(|c| is_running[c.index])
+        .flat_map(|c| {
+            c.instructors
+                .iter()
+                .filter(move |instr| assignment[**instr] != Some(c.index))
+                .map(move |instr| (*instr, c.index))
+        })
+        .collect();
+    for (instructor, course) in misplaced.iter() {
+        error!(
+            "Instructor (participant {}) of course {} is not assigned to the course, although it takes place.",
+            instructor, courses[*course].name
+        );
+    }
+    if misplaced.is_empty() {
+        Ok(())
+    } else {
+        Err(misplaced)
+    }
+}
+
+/// Assign the courses to the rooms at the same list position, after sorting the courses by their room-effective size
+/// in descending order. Returns a tuple `(course, room-effective size, room size)` for each course.
+///
+/// `rooms` is expected to be ordered in **descending** order and filled with zero entries to length of course list.
+fn fit_courses_into_rooms<'a>(
+    courses: &'a [Course],
+    assignment: &Assignment,
+    rooms: &[usize],
+) -> Vec<(&'a Course, usize, usize)> {
+    let mut course_size = room_effective_course_sizes(assignment, courses);
+    course_size.sort_by_key(|(_c, s)| std::cmp::Reverse(*s));
+    course_size
+        .into_iter()
+        .zip(rooms)
+        .map(|((c, size), room_size)| (c, size, *room_size))
+        .collect()
+}
+
 /// Public helper function (also used by [check_room_feasibility]) for calculating the
 /// room-effective size of a course in the given assignment.
 ///
-/// This function counts the assigned participants of each course and considers the `room_offset`
-/// and `room_factor` of the courses, as long as a course is not cancelled (according to the
-/// assignment and the `fixed_course` flag).
+/// This function counts the assigned participants of each course (incl. instructors, unless
+/// `instructors_need_space` is false) and considers the `room_offset` and `room_factor` of the
+/// courses, as long as a course is not cancelled (according to the assignment and the
+/// `fixed_course` flag).
 ///
 /// # Result
 ///
@@ -607,8 +2987,12 @@ pub fn room_effective_course_sizes<'a>(
     courses: &'a [Course],
 ) -> Vec<(&'a Course, usize)> {
     let mut course_sizes: Vec<(&Course, usize)> = courses.iter().map(|c| (c, 0)).collect();
-    for course in assignment.iter().flatten() {
-        course_sizes[*course].1 += 1;
+    for (p, course) in assignment.iter().enumerate() {
+        if let Some(c) = course {
+            if courses[*c].instructors_need_space || !courses[*c].instructors.contains(&p) {
+                course_sizes[*c].1 += 1;
+            }
+        }
     }
     for (c, ref mut s) in course_sizes.iter_mut() {
         *s = if *s == 0 && !c.fixed_course {
@@ -633,11 +3017,11 @@ pub fn room_effective_course_sizes<'a>(
 /// # Arguments
 ///
 /// * `current_node` - The current branch-and-bound node, i.e. constraint set, to check for enforced
-///     and already shrinked courses
+///   and already shrinked courses
 /// * `courses` - The courses to create room constraints for
 /// * `to_size` - The room size to shrink the `courses` to
 /// * `all_required` – If true, a (new) constraint is expected to be applied to all given `courses`.
-///     If this cannot be fulfilled due to existing constraints, the function returns `None`.
+///   If this cannot be fulfilled due to existing constraints, the function returns `None`.
 ///
 /// # Result
 ///
@@ -663,12 +3047,13 @@ fn create_room_constraint_set<'a>(
         }
         if to_size
             >= ((course.room_offset
-                + course.room_factor * (course.num_min + course.instructors.len()) as f32)
+                + course.room_factor
+                    * (course.num_min + course.num_room_relevant_instructors()) as f32)
                 .ceil() as usize)
         {
             let shrink_size = (((to_size as f32) - course.room_offset) / course.room_factor).floor()
                 as usize
-                - course.instructors.len();
+                - course.num_room_relevant_instructors();
             // Don't shrink courses that are already shrinked further in the current node
             if current_node
                 .shrinked_courses
@@ -709,8 +3094,13 @@ fn create_room_constraint_set<'a>(
 /// infeasibility sets the second return flag. It may be, that no such course is found, which is signalled by
 /// returning None. In this case, additional restrictions are pointless and we can abandon the node.
 ///
+/// With `require_full_assignment`, leaving a participant unassigned, who may not stay unassigned (see
+/// [may_stay_unassigned]), is treated like a wrong assignment.
+///
 /// Additionally, the solution is infeasible, if any course has less participants than demanded. In this case we
-/// return the course with the highest discrepancy to apply further restrictions on it.
+/// return the course with the highest discrepancy to apply further restrictions on it. If multiple courses have the
+/// same discrepancy and `prefer_flexible_cancellation` is set, the course with the fewest unique choosers (see
+/// [num_unique_choosers]) is returned, since cancelling it displaces the fewest participants.
 ///
 /// # Result
 ///
@@ -721,6 +3111,8 @@ fn check_feasibility(
     assignment: &Assignment,
     node: &BABNode,
     is_instructor: &ndarray::Array1<bool>,
+    require_full_assignment: bool,
+    prefer_flexible_cancellation: bool,
 ) -> (bool, bool, Option<usize>) {
     // Calculate course sizes
     let mut course_size = vec![0usize; courses.len()];
@@ -732,14 +3124,13 @@ fn check_feasibility(
         }
     }
 
-    // Check if solution is infeasible, such that any participant is in an un-chosen course
+    // Check if solution is infeasible, such that any participant is in an un-chosen, forbidden,
+    // unavailable or unacceptable course (or unassigned, although they may not stay unassigned)
     for (p, c) in assignment.iter().enumerate() {
         if !is_instructor[p]
             && !participants[p].is_instructor_only()
-            && !participants[p]
-                .choices
-                .iter()
-                .any(|choice| Some(choice.course_index) == *c)
+            && !c.is_some_and(|c| courses[c].required_attendees.contains(&p))
+            && !is_valid_assignment(&participants[p], *c, require_full_assignment)
         {
             // If so, get smallest non-constrained course, that has an instructor, who chose c
             let mut relevant_courses: Vec<usize> = (0..courses.len())
@@ -776,7 +3167,12 @@ fn check_feasibility(
                 courses[c].num_min
             );
             let score = courses[c].num_min - *size;
-            if score > max_score {
+            let is_more_flexible = |other: usize| {
+                prefer_flexible_cancellation
+                    && num_unique_choosers(participants, c, node, is_instructor)
+                        < num_unique_choosers(participants, other, node, is_instructor)
+            };
+            if score > max_score || (score == max_score && course.is_some_and(is_more_flexible)) {
                 max_score = score;
                 course = Some(c);
             }
@@ -785,5 +3181,29 @@ fn check_feasibility(
     (course.is_none(), false, course)
 }
 
+/// Count the unique choosers of the given course: the participants (excl. instructors), who acceptably chose the
+/// course, but have no other acceptable course choice left, which is not cancelled in the given node. These
+/// participants are displaced, when the course is cancelled.
+fn num_unique_choosers(
+    participants: &[Participant],
+    course: usize,
+    node: &BABNode,
+    is_instructor: &ndarray::Array1<bool>,
+) -> usize {
+    participants
+        .iter()
+        .enumerate()
+        .filter(|(p, participant)| {
+            !is_instructor[*p]
+                && is_valid_assignment(participant, Some(course), true)
+                && !participant.choices.iter().any(|choice| {
+                    choice.course_index != course
+                        && !node.cancelled_courses.contains(&choice.course_index)
+                        && is_valid_assignment(participant, Some(choice.course_index), true)
+                })
+        })
+        .count()
+}
+
 #[cfg(test)]
 mod tests;
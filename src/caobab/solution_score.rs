@@ -1,13 +1,23 @@
 use std::fmt::Display;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use super::{edge_weight, Score, INSTRUCTOR_SCORE, WEIGHT_OFFSET};
+use super::{
+    edge_weight, num_scored_instructors, Score, INSTRUCTOR_SCORE, UNASSIGNED_WEIGHT, WEIGHT_OFFSET,
+};
 use crate::{Assignment, Course, Participant};
 
 /// Calculate a simple upper bound for the solution score of the given problem, assuming all course
 /// instructors can instruct their course and all participants can get their best choice.
-pub fn theoretical_max_score(participants: &[Participant], courses: &[Course]) -> Score {
+///
+/// If `max_scored_instructors` is given, only the first `max_scored_instructors` instructors of
+/// each course are scored as instructors (see [super::SolveConfig::max_scored_instructors]). The
+/// other instructors are assumed to get their best choice instead.
+pub fn theoretical_max_score(
+    participants: &[Participant],
+    courses: &[Course],
+    max_scored_instructors: Option<usize>,
+) -> Score {
     let mut participant_scores: Vec<Score> = participants
         .iter()
         .map(|p| {
@@ -19,6 +29,49 @@ pub fn theoretical_max_score(participants: &[Participant], courses: &[Course]) -
         })
         .collect();
 
+    for course in courses {
+        // instructor_only participants are not considered in the score. See run_bab_node().
+        for instructor in course
+            .instructors
+            .iter()
+            .filter(|instr| !participants[**instr].is_instructor_only())
+            .take(num_scored_instructors(
+                course,
+                participants,
+                max_scored_instructors,
+            ))
+        {
+            participant_scores[*instructor] = INSTRUCTOR_SCORE;
+        }
+    }
+    participant_scores.into_iter().sum()
+}
+
+/// Calculate a simple lower bound for the solution score of the given problem, assuming all course
+/// instructors can instruct their course and all participants get their worst (acceptable) choice
+/// or stay unassigned, if they may.
+///
+/// This is the counterpart of [theoretical_max_score]: The unpenalized score (sum of the assigned
+/// choices' edge weights and instructor scores) of any solution, which does not assign any
+/// participant to an unchosen course, lies between these two bounds. The penalties of
+/// [super::SolveConfig::soft_room_penalty], [super::SolveConfig::group_balance_penalty] and
+/// [super::SolveConfig::cancel_cost_penalty] are not considered, so the score of a solution
+/// including these penalties may be lower than this bound.
+pub fn theoretical_min_score(participants: &[Participant], courses: &[Course]) -> Score {
+    let mut participant_scores: Vec<Score> = participants
+        .iter()
+        .map(|p| {
+            if p.may_stay_unassigned() && !p.is_instructor_only() {
+                return UNASSIGNED_WEIGHT as Score;
+            }
+            p.choices
+                .iter()
+                .map(|choice| edge_weight(choice) as Score)
+                .min()
+                .unwrap_or(0)
+        })
+        .collect();
+
     for course in courses {
         for instructor in course.instructors.iter() {
             // instructor_only participants are not considered in the score. See run_bab_node().
@@ -40,6 +93,61 @@ pub fn solution_quality(score: Score, participants: &[Participant]) -> f32 {
         / num_real_participants as f32
 }
 
+/// Calculate a solution quality lack in the range from 0 to 1, which is normalized by the number of
+/// available course choices, such that events with different numbers of choices are comparable.
+///
+/// For each participant (excl. course instructors and instructor-only participants), the
+/// (effective) penalty of the assigned choice is divided by the maximum penalty of their choices,
+/// i.e. `penalty / (number of choices - 1)` for plain ranked choices. Participants, who are not
+/// assigned to one of their choices, count as 1. Participants without choices and participants
+/// with only a single choice (or only choices of equal penalty), who are assigned to one of them,
+/// count as 0. The result is the mean over all these participants, i.e. 0 if everyone gets their
+/// first choice and 1 if everyone gets their last choice.
+///
+/// Returns None, if there are no participants to consider.
+pub fn normalized_quality(
+    participants: &[Participant],
+    courses: &[Course],
+    assignment: &Assignment,
+) -> Option<f32> {
+    let lacks: Vec<f32> = participants
+        .iter()
+        .zip(assignment.iter())
+        .enumerate()
+        .filter(|(p_index, (p, assigned))| {
+            !p.is_instructor_only()
+                && !p.choices.is_empty()
+                && !assigned.is_some_and(|c| courses[c].instructors.contains(p_index))
+        })
+        .map(|(_p_index, (p, assigned))| {
+            let max_penalty = p
+                .choices
+                .iter()
+                .map(|choice| choice.effective_penalty())
+                .max()
+                .unwrap_or(0);
+            let min_penalty = p
+                .choices
+                .iter()
+                .map(|choice| choice.effective_penalty())
+                .min()
+                .unwrap_or(0);
+            match assigned.and_then(|c| p.choices.iter().find(|ch| ch.course_index == c)) {
+                None => 1.0,
+                Some(_) if max_penalty == min_penalty => 0.0,
+                Some(choice) => {
+                    (choice.effective_penalty() - min_penalty) as f32
+                        / (max_penalty - min_penalty) as f32
+                }
+            }
+        })
+        .collect();
+    if lacks.is_empty() {
+        return None;
+    }
+    Some(lacks.iter().sum::<f32>() / lacks.len() as f32)
+}
+
 /// Calculate a comparable solution quality score for a combined assignment from a cdecao solution
 /// and external assignment quality data
 pub fn combined_quality(
@@ -102,7 +210,7 @@ impl AssignmentQualityInfo {
                     .iter()
                     .find(|choice| choice.course_index == *c_index)
                 {
-                    assigned_course_choice_penalties.push(choice.penalty);
+                    assigned_course_choice_penalties.push(choice.effective_penalty());
                 } else {
                     assigned_course_choice_penalties.push(unfulfilled_choices_penalty);
                 }
@@ -150,16 +258,246 @@ pub fn assignment_quality(
     .get_quality()
 }
 
+/// Default `unfulfilled_choices_penalty` for [assignment_quality] and
+/// [AssignmentQualityInfo::from_caobab_assignment]: A participant assigned to a course they did not
+/// choose is penalized like a choice ranked right after their last one. With the event's number of
+/// course choices (e.g. from the "num_choices" entry of the simple input format), the penalty is
+/// this number; otherwise, it is the largest number of choices of any participant.
+pub fn default_unfulfilled_choices_penalty(
+    participants: &[Participant],
+    num_choices: Option<usize>,
+) -> u32 {
+    num_choices.unwrap_or_else(|| {
+        participants
+            .iter()
+            .map(|p| p.choices.len())
+            .max()
+            .unwrap_or(0)
+    }) as u32
+}
+
+/// Rank of the course, a participant has been assigned to, within their course choices. See
+/// [ParticipantSatisfaction].
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum AssignedRank {
+    /// The participant is assigned to one of their choices with the given rank (0 for the best
+    /// choice). Choices with equal (effective) penalty share the same rank.
+    Rank(usize),
+    /// The participant is assigned as instructor of their course
+    Instructor,
+    /// The participant is not assigned to any course
+    Unassigned,
+    /// The participant is assigned to a course, they did not choose
+    Wrong,
+}
+
+/// Satisfaction of a single participant with a course assignment, for auditing the fairness of
+/// the assignment. See [satisfaction_report].
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ParticipantSatisfaction {
+    /// Index of the participant in the list of participants
+    pub participant: usize,
+    /// Index of the assigned course (if any)
+    pub assigned_course: Option<usize>,
+    /// Rank of the assigned course within the participant's choices
+    pub assigned_rank: AssignedRank,
+    /// Best rank the participant could have gotten, considering only courses which take place in
+    /// the assignment. None, if none of their chosen courses takes place.
+    pub best_achievable_rank: Option<usize>,
+}
+
+/// Calculate the rank of each of the participant's choices, i.e. the number of choices with a
+/// strictly lower effective penalty.
+fn choice_rank(participant: &Participant, course_index: usize) -> Option<usize> {
+    let choice = participant
+        .choices
+        .iter()
+        .find(|c| c.course_index == course_index)?;
+    Some(
+        participant
+            .choices
+            .iter()
+            .filter(|c| c.effective_penalty() < choice.effective_penalty())
+            .count(),
+    )
+}
+
+/// Create a report of each participant's satisfaction with the given course assignment: the rank
+/// of their assigned course and the best rank they could have gotten, given the courses which take
+/// place in the assignment (i.e. courses with at least one assigned participant).
+pub fn satisfaction_report(
+    assignment: &Assignment,
+    participants: &[Participant],
+    courses: &[Course],
+) -> Vec<ParticipantSatisfaction> {
+    let mut course_takes_place = vec![false; courses.len()];
+    for c in assignment.iter().flatten() {
+        course_takes_place[*c] = true;
+    }
+
+    participants
+        .iter()
+        .zip(assignment.iter())
+        .enumerate()
+        .map(|(p_index, (p, assigned))| {
+            let assigned_rank = match assigned {
+                None => AssignedRank::Unassigned,
+                Some(c) if courses[*c].instructors.contains(&p_index) => AssignedRank::Instructor,
+                Some(c) => match choice_rank(p, *c) {
+                    Some(rank) => AssignedRank::Rank(rank),
+                    None => AssignedRank::Wrong,
+                },
+            };
+            let best_achievable_rank = p
+                .choices
+                .iter()
+                .filter(|choice| course_takes_place[choice.course_index])
+                .filter_map(|choice| choice_rank(p, choice.course_index))
+                .min();
+            ParticipantSatisfaction {
+                participant: p_index,
+                assigned_course: *assigned,
+                assigned_rank,
+                best_achievable_rank,
+            }
+        })
+        .collect()
+}
+
+/// Build the waitlist of each course for the given course assignment: the participants, who chose
+/// the course and would rather attend it than their assigned course, e.g. to fill places which
+/// become free when an attendee drops out.
+///
+/// A participant is on the waitlist of a course, if the course takes place in the assignment, they
+/// chose it with an acceptable rank (see [Participant::is_acceptable_choice]) and they are either
+/// unassigned or assigned (as attendee) to a course with a worse choice rank or a course they did
+/// not choose. Course instructors of courses taking place are not on any waitlist. Each waitlist is
+/// ordered by the participants' choice rank for the course (ties by participant index) and
+/// truncated to the course's `waitlist_capacity`, if given. Cancelled courses have an empty
+/// waitlist.
+pub fn build_waitlists(
+    assignment: &Assignment,
+    participants: &[Participant],
+    courses: &[Course],
+) -> Vec<Vec<usize>> {
+    let mut course_takes_place = vec![false; courses.len()];
+    for c in assignment.iter().flatten() {
+        course_takes_place[*c] = true;
+    }
+
+    let mut waitlists: Vec<Vec<(usize, usize)>> = vec![Vec::new(); courses.len()];
+    for (p_index, (p, assigned)) in participants.iter().zip(assignment.iter()).enumerate() {
+        let assigned_rank = match assigned {
+            Some(c) if courses[*c].instructors.contains(&p_index) => continue,
+            Some(c) => choice_rank(p, *c),
+            None => None,
+        };
+        for choice in p.choices.iter() {
+            let c = choice.course_index;
+            if !course_takes_place[c]
+                || *assigned == Some(c)
+                || p.is_forbidden_course(c)
+                || !p.is_available_course(c)
+                || !p.is_acceptable_choice(choice)
+            {
+                continue;
+            }
+            let rank = choice_rank(p, c).unwrap();
+            if assigned_rank.is_none_or(|assigned_rank| rank < assigned_rank) {
+                waitlists[c].push((rank, p_index));
+            }
+        }
+    }
+
+    waitlists
+        .into_iter()
+        .zip(courses.iter())
+        .map(|(mut waitlist, course)| {
+            waitlist.sort();
+            if let Some(capacity) = course.waitlist_capacity {
+                waitlist.truncate(capacity);
+            }
+            waitlist.into_iter().map(|(_, p_index)| p_index).collect()
+        })
+        .collect()
+}
+
+/// Statistics of the assigned course choice penalties of all participants (excl. course
+/// instructors), to judge the fairness of an assignment: Two assignments with the same score (i.e.
+/// the same mean penalty) may be very different for the worst-off participants.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct PenaltyStatistics {
+    pub min: u32,
+    pub max: u32,
+    pub mean: f32,
+    /// (Population) standard deviation of the penalties
+    pub stddev: f32,
+}
+
+impl PenaltyStatistics {
+    /// Calculate the statistics of the (effective) penalties of the assigned course choices from
+    /// the given assignment. Instructors, instructor-only participants and participants, who are
+    /// not assigned to one of their choices, are not considered.
+    ///
+    /// Returns None, if no participant is assigned to one of their choices.
+    pub fn calculate(
+        participants: &[Participant],
+        courses: &[Course],
+        assignment: &Assignment,
+    ) -> Option<Self> {
+        let penalties: Vec<u32> = participants
+            .iter()
+            .zip(assignment.iter())
+            .enumerate()
+            .filter_map(|(p_index, (p, assigned))| {
+                let c = (*assigned)?;
+                if courses[c].instructors.contains(&p_index) {
+                    return None;
+                }
+                p.choices
+                    .iter()
+                    .find(|choice| choice.course_index == c)
+                    .map(|choice| choice.effective_penalty())
+            })
+            .collect();
+        if penalties.is_empty() {
+            return None;
+        }
+        let n = penalties.len() as f32;
+        let mean = penalties.iter().sum::<u32>() as f32 / n;
+        let variance = penalties
+            .iter()
+            .map(|p| (*p as f32 - mean).powi(2))
+            .sum::<f32>()
+            / n;
+        Some(Self {
+            min: *penalties.iter().min().unwrap(),
+            max: *penalties.iter().max().unwrap(),
+            mean,
+            stddev: variance.sqrt(),
+        })
+    }
+}
+
 /// Combined struct of all the quality info that a user (human or wrapper program) might be
 /// interested in
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct QualityInfo {
     pub solution_score: Score,
     pub theoretical_max_score: Score,
+    pub theoretical_min_score: Score,
     pub solution_quality: f32,
     pub theoretical_max_quality: f32,
+    pub theoretical_min_quality: f32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub overall_quality: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub penalty_statistics: Option<PenaltyStatistics>,
+    /// Normalized quality lack from 0 to 1 (see [normalized_quality]), only calculated on request
+    /// by [QualityInfo::with_normalized_quality]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalized_quality: Option<f32>,
 }
 
 impl QualityInfo {
@@ -167,18 +505,35 @@ impl QualityInfo {
         solution_score: Score,
         participants: &[Participant],
         courses: &[Course],
+        assignment: &Assignment,
         external_assignment_data: Option<&AssignmentQualityInfo>,
     ) -> Self {
-        let theoretical_max_score = theoretical_max_score(participants, courses);
+        let theoretical_max_score = theoretical_max_score(participants, courses, None);
+        let theoretical_min_score = theoretical_min_score(participants, courses);
         Self {
             solution_score,
             theoretical_max_score,
+            theoretical_min_score,
             solution_quality: solution_quality(solution_score, participants),
             theoretical_max_quality: solution_quality(theoretical_max_score, participants),
+            theoretical_min_quality: solution_quality(theoretical_min_score, participants),
             overall_quality: external_assignment_data
                 .map(|external| combined_quality(solution_score, participants, external)),
+            penalty_statistics: PenaltyStatistics::calculate(participants, courses, assignment),
+            normalized_quality: None,
         }
     }
+
+    /// Add the normalized quality lack (see [normalized_quality]) of the assignment
+    pub fn with_normalized_quality(
+        mut self,
+        participants: &[Participant],
+        courses: &[Course],
+        assignment: &Assignment,
+    ) -> Self {
+        self.normalized_quality = normalized_quality(participants, courses, assignment);
+        self
+    }
 }
 
 impl Display for QualityInfo {
@@ -187,16 +542,32 @@ impl Display for QualityInfo {
             f,
             "Solution score:                     {: >9}
 (Perfect matching would have been:  {: >9})
+(Worst matching would have been:    {: >9})
 ----------------------------------------------
 Solution quality lack:               {: >8.6}
 (Perfect matching would have been:   {: >8.6})
-{}\n",
+(Worst matching would have been:     {: >8.6})
+{}{}{}\n",
             self.solution_score,
             self.theoretical_max_score,
+            self.theoretical_min_score,
             self.solution_quality,
             self.theoretical_max_quality,
+            self.theoretical_min_quality,
             match self.overall_quality {
-                Some(q) => format!("New overall assignment quality lack: {: >8.6}", q),
+                Some(q) => format!("New overall assignment quality lack: {: >8.6}\n", q),
+                None => "".to_owned(),
+            },
+            match self.normalized_quality {
+                Some(q) => format!("Normalized quality lack (0 to 1):    {: >8.6}\n", q),
+                None => "".to_owned(),
+            },
+            match self.penalty_statistics {
+                Some(ref s) => format!(
+                    "----------------------------------------------
+Assigned choice penalties: min {}, max {}, mean {:.3}, stddev {:.3}",
+                    s.min, s.max, s.mean, s.stddev
+                ),
                 None => "".to_owned(),
             },
         )
@@ -12,7 +12,7 @@
 use super::BABNode;
 use crate::bab::NodeResult;
 use crate::choices_from_list;
-use crate::{Assignment, Choice, Course, Participant};
+use crate::{choices_from_tiered_list, Assignment, Choice, ChoiceTier, Course, Participant};
 use std::sync::Arc;
 
 fn create_simple_problem() -> (Vec<Participant>, Vec<Course>) {
@@ -29,36 +29,42 @@ fn create_simple_problem() -> (Vec<Participant>, Vec<Course>) {
                 dbid: 0,
                 name: String::from("Participant 0"),
                 choices: choices_from_list(&[1, 2]),
+                ..Default::default()
             },
             Participant {
                 index: 1,
                 dbid: 1,
                 name: String::from("Participant 1"),
                 choices: choices_from_list(&[0, 2]),
+                ..Default::default()
             },
             Participant {
                 index: 2,
                 dbid: 2,
                 name: String::from("Participant 2"),
                 choices: choices_from_list(&[0, 1]),
+                ..Default::default()
             },
             Participant {
                 index: 3,
                 dbid: 3,
                 name: String::from("Participant 3"),
                 choices: choices_from_list(&[0, 1]),
+                ..Default::default()
             },
             Participant {
                 index: 4,
                 dbid: 4,
                 name: String::from("Participant 4"),
                 choices: choices_from_list(&[0, 2]),
+                ..Default::default()
             },
             Participant {
                 index: 5,
                 dbid: 5,
                 name: String::from("Participant 5"),
                 choices: choices_from_list(&[1, 2]),
+                ..Default::default()
             },
         ],
         vec![
@@ -69,10 +75,8 @@ fn create_simple_problem() -> (Vec<Participant>, Vec<Course>) {
                 num_max: 2,
                 num_min: 2,
                 instructors: vec![0],
-                room_factor: 1.0,
                 room_offset: 10.0,
-                fixed_course: false,
-                hidden_participant_names: vec![],
+                ..Default::default()
             },
             Course {
                 index: 1,
@@ -82,9 +86,7 @@ fn create_simple_problem() -> (Vec<Participant>, Vec<Course>) {
                 num_min: 2,
                 instructors: vec![1],
                 room_factor: 2.0,
-                room_offset: 0.0,
-                fixed_course: false,
-                hidden_participant_names: vec![],
+                ..Default::default()
             },
             Course {
                 index: 2,
@@ -94,9 +96,7 @@ fn create_simple_problem() -> (Vec<Participant>, Vec<Course>) {
                 num_min: 2,
                 instructors: vec![2],
                 room_factor: 1.5,
-                room_offset: 0.0,
-                fixed_course: false,
-                hidden_participant_names: vec![],
+                ..Default::default()
             },
         ],
     )
@@ -115,10 +115,7 @@ fn create_other_problem() -> (Vec<Course>, Vec<Participant>) {
             num_min: min,
             num_max: max,
             instructors: Vec::new(),
-            room_factor: 1.0,
-            room_offset: 0.0,
-            fixed_course: false,
-            hidden_participant_names: vec![],
+            ..Default::default()
         };
         next_corse_id += 1;
         c
@@ -132,6 +129,7 @@ fn create_other_problem() -> (Vec<Course>, Vec<Participant>) {
                 dbid: next_part_id,
                 name: format!("Participant {}", next_part_id),
                 choices: choices_from_list(&choices),
+                ..Default::default()
             });
             next_part_id += 1;
         }
@@ -159,7 +157,7 @@ fn create_other_problem() -> (Vec<Course>, Vec<Participant>) {
     //
     // course |  1   2   3
     // --------------------
-    // 0      | 14   4   2
+    // 0      | 14   5   2
     // 1      |  5   9   3
     // 2      |  0   6   9
     // 3      |  2   1   7
@@ -171,11 +169,211 @@ fn create_other_problem() -> (Vec<Course>, Vec<Participant>) {
     (courses, participants)
 }
 
+#[test]
+fn test_other_problem_demand_summary() {
+    // Reproduce the table of choices per course in the comment of create_other_problem()
+    let (courses, participants) = create_other_problem();
+    assert_eq!(
+        crate::io::course_demand_summary(&participants, &courses),
+        vec![vec![14, 5, 2], vec![5, 9, 3], vec![0, 6, 9], vec![2, 1, 7]]
+    );
+}
+
+fn create_tie_problem() -> (Vec<Participant>, Vec<Course>) {
+    // This problem is used for testing tie-break criteria: Only one of the two courses can take
+    // place, due to their minimum size. Cancelling either of them results in the same score.
+    let participants = [[0, 1], [0, 1], [1, 0], [1, 0]]
+        .iter()
+        .enumerate()
+        .map(|(i, choices)| Participant {
+            index: i,
+            dbid: i,
+            name: format!("Participant {}", i),
+            choices: choices_from_list(choices),
+            ..Default::default()
+        })
+        .collect();
+    let courses = (0..2)
+        .map(|i| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max: 10,
+            num_min: 3,
+            instructors: vec![],
+            ..Default::default()
+        })
+        .collect();
+
+    (participants, courses)
+}
+
+fn create_first_choice_problem() -> (Vec<Participant>, Vec<Course>) {
+    // This problem is used for testing the minimum number of first choices: The optimal assignment
+    // (P0 -> B, P1 -> C, P2 -> A; penalty 2) has only one first choice. The alternative assignment
+    // (P0 -> A, P1 -> B, P2 -> D; penalty 3) has two first choices and requires cancelling course C.
+    let make_choice = |course_index, penalty| Choice {
+        course_index,
+        penalty,
+        tier: ChoiceTier::Preferred,
+    };
+    let participants = vec![
+        vec![make_choice(0, 0), make_choice(1, 1)],
+        vec![make_choice(1, 0), make_choice(2, 1)],
+        vec![make_choice(0, 0), make_choice(3, 3)],
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, choices)| Participant {
+        index: i,
+        dbid: i,
+        name: format!("Participant {}", i),
+        choices,
+        ..Default::default()
+    })
+    .collect();
+    let courses = ["A", "B", "C", "D"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", name),
+            num_max: 1,
+            num_min: 0,
+            instructors: vec![],
+            ..Default::default()
+        })
+        .collect();
+
+    (participants, courses)
+}
+
+#[test]
+fn test_problem_size() {
+    let (participants, courses) = create_simple_problem();
+    let size = super::problem_size(&courses, &participants, true);
+
+    let problem = super::precompute_problem(
+        &courses,
+        &participants,
+        None,
+        true,
+        None,
+        super::SolveObjective::ChoiceQuality,
+        None,
+        None,
+        false,
+        None,
+        false,
+    );
+    assert_eq!(size.matrix_rows, problem.adjacency_matrix.dim().0);
+    assert_eq!(size.matrix_columns, problem.adjacency_matrix.dim().1);
+    // 20 course places, 3 instructors
+    assert_eq!(size.matrix_columns, 20);
+    assert_eq!(size.matrix_rows, 23);
+    assert_eq!(size.num_participants, 6);
+    assert_eq!(size.num_instructor_only, 0);
+    assert_eq!(size.num_courses, 3);
+    assert_eq!(size.total_max_capacity, 20);
+    assert_eq!(size.total_min_capacity, 6);
+    assert_eq!(size.num_choices_distribution, vec![0, 0, 6]);
+
+    // Each participant, who may stay unassigned, gets an additional "unassigned" place
+    let size = super::problem_size(&courses, &participants, false);
+    assert_eq!(size.matrix_columns, 26);
+    assert_eq!(size.matrix_rows, 29);
+}
+
+#[test]
+fn test_debug_format_matrix() {
+    let (participants, courses) = create_simple_problem();
+    let problem = super::precompute_problem(
+        &courses,
+        &participants,
+        None,
+        false,
+        None,
+        super::SolveObjective::ChoiceQuality,
+        None,
+        None,
+        false,
+        None,
+        false,
+    );
+    let matrix = super::debug_format_matrix(&problem, &courses, &participants).unwrap();
+    let lines: Vec<&str> = matrix.lines().collect();
+    // 2 header lines and 29 rows (6 participants, 3 instructors, 20 course places)
+    assert_eq!(lines.len(), 2 + 29);
+
+    // Column groups: 3 courses and the "unassigned" places (1 for each participant). Course names
+    // are truncated to the width of their group.
+    let groups = |line: &str| -> Vec<String> {
+        line.split(" | ")
+            .map(|group| group.trim().to_owned())
+            .collect()
+    };
+    assert_eq!(
+        groups(lines[0])[1..],
+        [
+            "Wanted Cour",
+            "Okay Course 1",
+            "Boring Course 2",
+            "(unassigned)"
+        ]
+    );
+    assert!(groups(lines[1])[2].starts_with("0     1     2"));
+
+    // Participant 0 chose courses 1 and 2
+    let row = groups(lines[2]);
+    assert_eq!(row[0], "Participant 0");
+    assert_eq!(row[1], ".     .");
+    assert_eq!(row[2], ["50000"; 8].join(" "));
+    assert_eq!(row[3], ["49999"; 10].join(" "));
+    assert_eq!(row[4], ["1"; 6].join(" ").replace(' ', "     "));
+
+    // Dummy rows have no edges
+    assert_eq!(groups(lines[30])[0], "(dummy)");
+    assert!(groups(lines[30])[1..]
+        .iter()
+        .all(|group| group.split_whitespace().all(|cell| cell == ".")));
+
+    // Large problems are rejected
+    let (participants, mut courses) = create_simple_problem();
+    courses[2].num_max = 1000;
+    let problem = super::precompute_problem(
+        &courses,
+        &participants,
+        None,
+        true,
+        None,
+        super::SolveObjective::ChoiceQuality,
+        None,
+        None,
+        false,
+        None,
+        false,
+    );
+    assert!(super::debug_format_matrix(&problem, &courses, &participants).is_err());
+}
+
 #[test]
 fn test_precompute_problem() {
     let (participants, courses) = create_simple_problem();
 
-    let problem = super::precompute_problem(&courses, &participants, Some(&vec![8, 10]));
+    let problem = super::precompute_problem(
+        &courses,
+        &participants,
+        Some(&vec![8, 10]),
+        true,
+        None,
+        super::SolveObjective::ChoiceQuality,
+        None,
+        None,
+        false,
+        None,
+        false,
+    );
 
     // check vector sizes
     let m = courses.iter().fold(0, |acc, c| acc + c.num_max);
@@ -193,7 +391,7 @@ fn test_precompute_problem() {
             let base_column = problem.inverse_course_map[i];
             assert_eq!(
                 problem.course_map[base_column + j],
-                i,
+                Some(i),
                 "Column {} should be mapped to course {}, as it is within {} columns after {}",
                 base_column + j,
                 i,
@@ -209,11 +407,11 @@ fn test_precompute_problem() {
             let choice = p
                 .choices
                 .iter()
-                .find(|c| c.course_index == problem.course_map[y]);
+                .find(|c| Some(c.course_index) == problem.course_map[y]);
             assert_eq!(
                 problem.adjacency_matrix[(x, y)],
                 match choice {
-                    Some(c) => super::WEIGHT_OFFSET - c.penalty as i32,
+                    Some(c) => super::WEIGHT_OFFSET - c.effective_penalty() as i32,
                     None => 0,
                 },
                 "Edge weigth for participant {} with course place {} is not expected.",
@@ -246,7 +444,19 @@ fn test_precompute_problem() {
     assert_eq!(problem.room_sizes, Some(vec![10, 8, 0]));
 
     // A second try, without rooms given
-    let problem = super::precompute_problem(&courses, &participants, None);
+    let problem = super::precompute_problem(
+        &courses,
+        &participants,
+        None,
+        true,
+        None,
+        super::SolveObjective::ChoiceQuality,
+        None,
+        None,
+        false,
+        None,
+        false,
+    );
     assert_eq!(problem.room_sizes, None);
 }
 
@@ -323,7 +533,9 @@ fn test_check_feasibility() {
             &participants,
             &assignment,
             &node,
-            &course_instructors
+            &course_instructors,
+            true,
+            false
         ),
         (true, false, None)
     );
@@ -342,7 +554,9 @@ fn test_check_feasibility() {
             &participants,
             &assignment,
             &node,
-            &course_instructors
+            &course_instructors,
+            true,
+            false
         ),
         (false, false, Some(2))
     );
@@ -362,10 +576,36 @@ fn test_check_feasibility() {
             &participants,
             &assignment,
             &node,
-            &course_instructors
+            &course_instructors,
+            true,
+            false
         ),
         (false, true, Some(2))
     );
+
+    // The first (feasible) assignment becomes infeasible, if participant 3 must not be assigned to
+    // course 0
+    let mut participants = participants;
+    participants[3].forbidden_courses = vec![0];
+    let assignment: Assignment = vec![Some(0), Some(1), Some(1), Some(0), Some(0), Some(1)];
+    let course_instructors =
+        ndarray::Array1::from_vec(vec![true, true, false, false, false, false]);
+    let node = BABNode {
+        cancelled_courses: vec![2],
+        enforced_courses: vec![],
+        shrinked_courses: vec![],
+    };
+    let (feasible, participant_problem, _branch_course) = super::check_feasibility(
+        &courses,
+        &participants,
+        &assignment,
+        &node,
+        &course_instructors,
+        true,
+        false,
+    );
+    assert!(!feasible);
+    assert!(participant_problem);
 }
 
 /// Testing helper function to check correctness of a feasible solution for the full branch and bound problem or a
@@ -377,96 +617,118 @@ fn check_assignment(
     assignment: &Assignment,
     node: Option<&BABNode>,
 ) {
-    // Calculate course sizes
-    let mut course_size = vec![0usize; courses.len()];
-    for course in assignment.iter().flatten() {
-        course_size[*course] += 1;
-    }
-
-    // Check course sizes
-    for (c, size) in course_size.iter().enumerate() {
-        let course = &courses[c];
-        assert!(
-            *size <= course.num_max + course.instructors.len(),
-            "Maximum size violation for course {}: {} places, {} participants",
-            c,
-            course.num_max,
-            size - course.instructors.len()
+    let constraints = node.map(super::BABConstraints::from);
+    if let Err(errors) =
+        super::validate_assignment(assignment, courses, participants, constraints.as_ref())
+    {
+        panic!(
+            "Invalid assignment {:?}:\n{}",
+            assignment,
+            errors.join("\n")
         );
-        // Feasible solutions must not have too few participants
-        if let Some(n) = node {
-            if !n.cancelled_courses.contains(&c) {
-                assert!(
-                    *size >= course.num_min + course.instructors.len(),
-                    "Minimum size violation for course {}: {} required, {} assigned",
-                    c,
-                    course.num_min,
-                    size - course.instructors.len()
-                );
-            } else {
-                assert_eq!(
-                    course_size[c], 0,
-                    "Cancelled course {} has {} participants",
-                    c, course_size[c]
-                );
-            }
-        } else {
-            assert!(
-                *size == 0 || *size >= course.num_min + course.instructors.len(),
-                "Minimum size violation for course {}: {} required, {} assigned",
-                c,
-                course.num_min,
-                size - course.instructors.len()
-            );
-        }
-    }
-    if let Some(n) = node {
-        // Check shrinked courses' sizes
-        for (c, s) in n.shrinked_courses.iter() {
-            let course = &courses[*c];
-            assert!(
-                course_size[*c] <= *s + course.instructors.len(),
-                "Dynamic size constraint for course {} not satisfied: {} > {}",
-                *c,
-                course_size[*c] - course.instructors.len(),
-                *s
-            )
-        }
     }
+}
 
-    // Check course instructor assignment
-    let mut course_instructors = vec![false; participants.len()];
-    for (c, course) in courses.iter().enumerate() {
-        if course_size[c] != 0 {
-            for i in course.instructors.iter() {
-                assert_eq!(
-                    assignment[*i],
-                    Some(c),
-                    "Instructor {} of course {} is assigned to {:?}",
-                    *i,
-                    c,
-                    assignment[*i]
-                );
-                course_instructors[*i] = true;
-            }
+#[test]
+fn test_validate_assignment() {
+    let (mut participants, courses) = create_simple_problem();
+    let validate = |assignment: &Assignment,
+                    participants: &[Participant],
+                    constraints: Option<&super::BABConstraints>| {
+        super::validate_assignment(assignment, &courses, participants, constraints)
+    };
+    let assert_errors = |result: Result<(), Vec<String>>, expected: &[&str]| {
+        let errors = result.expect_err("Expected the assignment to be invalid");
+        assert_eq!(errors.len(), expected.len(), "{:?}", errors);
+        for (error, expected) in errors.iter().zip(expected.iter()) {
+            assert!(error.starts_with(expected), "{:?}", errors);
         }
-    }
+    };
 
-    // Feasible solutions must not have wrong assigned participants
-    for (p, participant) in participants.iter().enumerate() {
-        if !course_instructors[p] && !participant.is_instructor_only() {
-            assert!(
-                participant
-                    .choices
-                    .iter()
-                    .any(|c| Some(c.course_index) == assignment[p]),
-                "Course {:?} of participant {} is none of their choices ({:?})",
-                assignment[p],
-                p,
-                participant.choices
-            );
-        }
-    }
+    // The optimal solution: Course 2 is cancelled
+    let assignment: Assignment = vec![Some(0), Some(1), Some(1), Some(0), Some(0), Some(1)];
+    assert_eq!(validate(&assignment, &participants, None), Ok(()));
+    let constraints = super::BABConstraints {
+        cancelled_courses: vec![2],
+        shrinked_courses: vec![(1, 2)],
+    };
+    assert_eq!(
+        validate(&assignment, &participants, Some(&constraints)),
+        Ok(())
+    );
+
+    // Subproblem constraints
+    let constraints = super::BABConstraints {
+        cancelled_courses: vec![1],
+        shrinked_courses: vec![],
+    };
+    assert_errors(
+        validate(&assignment, &participants, Some(&constraints)),
+        &[
+            "Cancelled course 1 has 3 participants",
+            "Minimum size violation for course 2",
+        ],
+    );
+    let constraints = super::BABConstraints {
+        cancelled_courses: vec![2],
+        shrinked_courses: vec![(1, 1)],
+    };
+    assert_errors(
+        validate(&assignment, &participants, Some(&constraints)),
+        &["Dynamic size constraint for course 1 not satisfied: 2 > 1"],
+    );
+
+    // Course sizes
+    let assignment: Assignment = vec![Some(0), Some(1), Some(1), Some(1), Some(0), Some(1)];
+    assert_errors(
+        validate(&assignment, &participants, None),
+        &["Minimum size violation for course 0: 2 required, 1 assigned"],
+    );
+    let assignment: Assignment = vec![Some(0), Some(1), Some(0), Some(0), Some(0), Some(1)];
+    assert_errors(
+        validate(&assignment, &participants, None),
+        &[
+            "Maximum size violation for course 0: 2 places, 3 participants",
+            "Minimum size violation for course 1: 2 required, 1 assigned",
+        ],
+    );
+
+    // Instructors
+    let assignment: Assignment = vec![Some(1), Some(1), Some(1), Some(0), Some(0), Some(1)];
+    assert_errors(
+        validate(&assignment, &participants, None),
+        &[
+            "Minimum size violation for course 0",
+            "Instructor 0 of course 0 is assigned to Some(1)",
+        ],
+    );
+
+    // Course choices (Participants 4 and 5 swapped) and forbidden courses
+    let assignment: Assignment = vec![Some(0), Some(1), Some(1), Some(0), Some(1), Some(0)];
+    assert_errors(
+        validate(&assignment, &participants, None),
+        &[
+            "Course Some(1) of participant 4 is none of their choices",
+            "Course Some(0) of participant 5 is none of their choices",
+        ],
+    );
+    let assignment: Assignment = vec![Some(0), Some(1), Some(1), Some(0), Some(0), Some(1)];
+    participants[3].forbidden_courses = vec![0];
+    assert_errors(
+        validate(&assignment, &participants, None),
+        &["Course Some(0) of participant 3 is one of their forbidden courses"],
+    );
+
+    // Malformed assignments
+    assert_errors(
+        validate(&vec![Some(0); 3], &participants, None),
+        &["Assignment has 3 entries, but there are 6 participants"],
+    );
+    let assignment: Assignment = vec![Some(0), Some(1), Some(1), Some(0), Some(0), Some(3)];
+    assert_errors(
+        validate(&assignment, &participants, None),
+        &["Participant 5 is assigned to invalid course 3"],
+    );
 }
 
 #[test]
@@ -475,7 +737,19 @@ fn test_bab_node_simple() {
     // so if it fails, please check their test results first.
 
     let (participants, courses) = create_simple_problem();
-    let problem = super::precompute_problem(&courses, &participants, None);
+    let problem = super::precompute_problem(
+        &courses,
+        &participants,
+        None,
+        true,
+        None,
+        super::SolveObjective::ChoiceQuality,
+        None,
+        None,
+        false,
+        None,
+        false,
+    );
 
     // Let's get a feasible solution
     let node = BABNode {
@@ -483,12 +757,20 @@ fn test_bab_node_simple() {
         enforced_courses: vec![],
         shrinked_courses: vec![],
     };
-    let result = super::run_bab_node(&courses, &participants, &problem, node.clone(), false);
+    let result = super::run_bab_node(
+        &courses,
+        &participants,
+        &problem,
+        node.clone(),
+        false,
+        None,
+        false,
+    );
     match result {
         NodeResult::Feasible(assignment, score) => {
             println!("test_bab_node_simple: 1. assignment: {:?}", assignment);
             check_assignment(&courses, &participants, &assignment, Some(&node));
-            assert!(score > participants.len() as u32 * (super::WEIGHT_OFFSET as u32 - 1));
+            assert!(score.score > participants.len() as u32 * (super::WEIGHT_OFFSET as u32 - 1));
         }
         x => panic!("Expected feasible result, got {:?}", x),
     };
@@ -499,12 +781,20 @@ fn test_bab_node_simple() {
         enforced_courses: vec![1],
         shrinked_courses: vec![],
     };
-    let result = super::run_bab_node(&courses, &participants, &problem, node.clone(), false);
+    let result = super::run_bab_node(
+        &courses,
+        &participants,
+        &problem,
+        node.clone(),
+        false,
+        None,
+        false,
+    );
     match result {
         NodeResult::Feasible(assignment, score) => {
             println!("test_bab_node_simple 2. assignment: {:?}", assignment);
             check_assignment(&courses, &participants, &assignment, Some(&node));
-            assert!(score > participants.len() as u32 * (super::WEIGHT_OFFSET as u32 - 1));
+            assert!(score.score > participants.len() as u32 * (super::WEIGHT_OFFSET as u32 - 1));
         }
         x => panic!("Expected feasible result, got {:?}", x),
     };
@@ -515,7 +805,7 @@ fn test_bab_node_simple() {
         enforced_courses: vec![],
         shrinked_courses: vec![],
     };
-    let result = super::run_bab_node(&courses, &participants, &problem, node, false);
+    let result = super::run_bab_node(&courses, &participants, &problem, node, false, None, false);
     match result {
         NodeResult::NoSolution => (),
         x => panic!("Expected no result, got {:?}", x),
@@ -527,7 +817,7 @@ fn test_bab_node_simple() {
         enforced_courses: vec![],
         shrinked_courses: vec![],
     };
-    let result = super::run_bab_node(&courses, &participants, &problem, node, false);
+    let result = super::run_bab_node(&courses, &participants, &problem, node, false, None, false);
     match result {
         NodeResult::Infeasible(_, _) => (), // TODO check new nodes and score
         x => panic!("Expected infeasible result, got {:?}", x),
@@ -550,10 +840,7 @@ fn test_bab_node_large() {
             num_min: MIN_PLACES_PER_COURSE,
             num_max: MAX_PLACES_PER_COURSE,
             instructors: Vec::new(),
-            room_factor: 1.0,
-            room_offset: 0.0,
-            fixed_course: false,
-            hidden_participant_names: vec![],
+            ..Default::default()
         });
     }
 
@@ -564,11 +851,13 @@ fn test_bab_node_large() {
             dbid: p,
             name: format!("Participant {}", p),
             choices: Vec::new(),
+            ..Default::default()
         };
         for i in 0..3 {
             participant.choices.push(Choice {
                 course_index: (p + i) % NUM_COURSES,
                 penalty: i as u32,
+                tier: ChoiceTier::Preferred,
             });
         }
         participants.push(participant);
@@ -581,22 +870,42 @@ fn test_bab_node_large() {
         }
     }
 
-    let problem = super::precompute_problem(&courses, &participants, None);
+    let problem = super::precompute_problem(
+        &courses,
+        &participants,
+        None,
+        true,
+        None,
+        super::SolveObjective::ChoiceQuality,
+        None,
+        None,
+        false,
+        None,
+        false,
+    );
     let node = BABNode {
         cancelled_courses: vec![],
         enforced_courses: vec![],
         shrinked_courses: vec![],
     };
 
-    let result = super::run_bab_node(&courses, &participants, &problem, node.clone(), false);
+    let result = super::run_bab_node(
+        &courses,
+        &participants,
+        &problem,
+        node.clone(),
+        false,
+        None,
+        false,
+    );
 
     match result {
         NodeResult::Feasible(assignment, score) => {
             println!("test_bab_node_large: assignment: {:?}", assignment);
             check_assignment(&courses, &participants, &assignment, Some(&node));
-            assert!(score > participants.len() as u32 * (super::WEIGHT_OFFSET as u32 - 1));
+            assert!(score.score > participants.len() as u32 * (super::WEIGHT_OFFSET as u32 - 1));
             assert_eq!(
-                super::solution_score::solution_quality(score, &participants),
+                super::solution_score::solution_quality(score.score, &participants),
                 super::solution_score::assignment_quality(
                     &participants,
                     &courses,
@@ -648,8 +957,237 @@ fn test_caobab_simple() {
     };
 }
 
+#[test]
+fn test_caobab_no_courses() {
+    // Without any course, no assignment is calculated (not even for instructor-only participants)
+    let (mut participants, _courses) = create_simple_problem();
+    for p in participants.iter_mut() {
+        p.choices = vec![];
+    }
+    let (result, statistics) = super::solve_with_config(
+        Arc::new(vec![]),
+        Arc::new(participants),
+        super::SolveConfig::new().num_threads(1),
+    );
+    assert!(result.is_none());
+    assert_eq!(statistics.num_executed_subproblems, 0);
+
+    let (result, _statistics) = super::solve_with_config(
+        Arc::new(vec![]),
+        Arc::new(vec![]),
+        super::SolveConfig::new().num_threads(1),
+    );
+    assert!(result.is_none());
+}
+
+#[test]
+fn test_caobab_single_participant() {
+    let (mut participants, mut courses) = create_simple_problem();
+    participants.truncate(1);
+    participants[0].choices = choices_from_list(&[1, 0]);
+    courses.truncate(2);
+    for c in courses.iter_mut() {
+        c.instructors = vec![];
+        c.num_min = 0;
+    }
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let (result, _statistics) = super::solve_with_config(
+        courses.clone(),
+        participants.clone(),
+        super::SolveConfig::new().num_threads(1),
+    );
+    let (assignment, score) = result.expect("Expected to get a result.");
+    assert_eq!(assignment, vec![Some(1)]);
+    assert_eq!(score, super::WEIGHT_OFFSET as u32);
+
+    // If the single course cannot take place, all courses are cancelled, which does not give a
+    // solution, even if the participant may stay unassigned
+    let mut courses = Arc::try_unwrap(courses).ok().unwrap();
+    courses.truncate(1);
+    courses[0].num_min = 2;
+    let mut participants = Arc::try_unwrap(participants).ok().unwrap();
+    participants[0].choices = choices_from_list(&[0]);
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    for require_full_assignment in [true, false] {
+        let (result, statistics) = super::solve_with_config(
+            courses.clone(),
+            participants.clone(),
+            super::SolveConfig::new()
+                .num_threads(1)
+                .require_full_assignment(require_full_assignment),
+        );
+        assert!(result.is_none());
+        assert!(statistics.num_no_solution > 0);
+    }
+}
+
+#[test]
+fn test_caobab_cancellation() {
+    let (participants, courses) = create_simple_problem();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+
+    // Cancelling from the new best callback (i.e. from a worker thread) returns the first solution
+    let token = super::CancellationToken::default();
+    let callback_token = token.clone();
+    let (result, statistics) = super::solve_with_config(
+        courses.clone(),
+        participants.clone(),
+        super::SolveConfig::new()
+            .num_threads(1)
+            .new_best_callback(Box::new(move |_assignment, _score| {
+                callback_token.store(true, std::sync::atomic::Ordering::Relaxed);
+            }))
+            .cancellation_token(token.clone()),
+    );
+    let (assignment, _score) = result.expect("Expected to get the best-so-far result.");
+    check_assignment(&courses, &participants, &assignment, None);
+    assert_eq!(statistics.num_new_best, 1);
+
+    // With the token already set, no subproblem is solved at all
+    let (result, statistics) = super::solve_with_config(
+        courses.clone(),
+        participants.clone(),
+        super::SolveConfig::new()
+            .num_threads(2)
+            .cancellation_token(token),
+    );
+    assert!(result.is_none());
+    assert_eq!(statistics.num_executed_subproblems, 0);
+    assert!(statistics
+        .to_string()
+        .contains("Executed subproblems:       0"));
+}
+
+#[test]
+fn test_caobab_timeout() {
+    // A truly infeasible problem: There are no course places for the attendees. The search is
+    // completed within the timeout, so the missing result is a proof of infeasibility.
+    let (participants, mut courses) = create_simple_problem();
+    for course in courses.iter_mut() {
+        course.num_min = 0;
+        course.num_max = 0;
+    }
+    let (result, statistics) = super::solve_with_config(
+        Arc::new(courses),
+        Arc::new(participants),
+        super::SolveConfig::new()
+            .num_threads(2)
+            .timeout(std::time::Duration::from_secs(60)),
+    );
+    assert!(result.is_none());
+    assert!(statistics.num_executed_subproblems > 0);
+    assert!(!statistics.aborted_by_timeout);
+
+    // A problem requiring a larger search (with rooms), which is stopped by the timeout before
+    // solving any subproblem. This also holds for all runs of random restarts.
+    let (courses, participants) = create_other_problem();
+    let (result, statistics) = super::solve_with_config(
+        Arc::new(courses),
+        Arc::new(participants),
+        super::SolveConfig::new()
+            .num_threads(2)
+            .rooms(vec![10, 5, 8])
+            .random_restarts(super::RandomRestarts {
+                num_runs: 3,
+                seed: 42,
+            })
+            .timeout(std::time::Duration::ZERO),
+    );
+    assert!(result.is_none());
+    assert_eq!(statistics.num_executed_subproblems, 0);
+    assert!(statistics.aborted_by_timeout);
+    assert!(statistics.to_string().contains("Aborted by timeout"));
+}
+
 // TODO test solve with large problem
 
+#[test]
+fn test_caobab_stop_at_first_feasible() {
+    let (courses, participants) = create_other_problem();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let config = || {
+        super::SolveConfig::new()
+            .num_threads(1)
+            .rooms(vec![10, 5, 8])
+    };
+    let (result, statistics) =
+        super::solve_with_config(courses.clone(), participants.clone(), config());
+    let (_assignment, best_score) = result.expect("Expected to get a result.");
+
+    // The search stops directly after the first feasible assignment, which is valid, but not
+    // necessarily optimal
+    let (result, first_statistics) = super::solve_with_config(
+        courses.clone(),
+        participants.clone(),
+        config().stop_at_first_feasible(true),
+    );
+    let (assignment, score) = result.expect("Expected to get the first feasible result.");
+    check_assignment(&courses, &participants, &assignment, None);
+    assert!(score <= best_score);
+    assert_eq!(first_statistics.num_feasible, 1);
+    assert!(first_statistics.num_executed_subproblems < statistics.num_executed_subproblems);
+    assert!(!first_statistics.aborted_by_timeout);
+
+    // Same with random restarts: No further runs are started after the first feasible assignment
+    let (result, restart_statistics) = super::solve_with_config(
+        courses.clone(),
+        participants.clone(),
+        config()
+            .stop_at_first_feasible(true)
+            .random_restarts(super::RandomRestarts {
+                num_runs: 3,
+                seed: 42,
+            }),
+    );
+    assert!(result.is_some());
+    assert_eq!(restart_statistics.num_feasible, 1);
+}
+
+#[test]
+fn test_caobab_multi_threaded() {
+    // The multi-threaded search (with its wakeups of idle worker threads) must terminate and find
+    // a solution of the same score as the single-threaded search
+    let (courses, participants) = create_other_problem();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let mut scores = Vec::new();
+    for num_threads in [1, 4] {
+        let (result, statistics) = super::solve_with_config(
+            courses.clone(),
+            participants.clone(),
+            super::SolveConfig::new()
+                .num_threads(num_threads)
+                .rooms(vec![10, 5, 8]),
+        );
+        let (assignment, score) = result.expect("Expected to get a result.");
+        check_assignment(&courses, &participants, &assignment, None);
+        assert!(statistics.num_executed_subproblems > 0);
+        scores.push(score);
+    }
+    assert_eq!(scores[0], scores[1]);
+}
+
+#[test]
+fn test_caobab_best_score_handle() {
+    let (participants, courses) = create_simple_problem();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let handle = super::BestScoreHandle::default();
+    let (result, _statistics) = super::solve_with_config(
+        courses,
+        participants,
+        super::SolveConfig::new()
+            .num_threads(2)
+            .best_score_handle(handle.clone()),
+    );
+    let (_assignment, score) = result.expect("Expected to get a result.");
+    assert_eq!(handle.load(std::sync::atomic::Ordering::Relaxed), score);
+}
+
 #[test]
 fn test_caobab_rooms() {
     let (courses, participants) = create_other_problem();
@@ -710,42 +1248,793 @@ fn test_caobab_rooms() {
 }
 
 #[test]
-fn test_caobab_rooms_fixed_course() {
-    let (mut courses, participants) = create_other_problem();
-    courses[2].fixed_course = true;
+fn test_check_rooms() {
+    let (_participants, courses) = create_simple_problem();
+    // Room-effective course sizes: course 0: 3 + 10 = 13, course 1: 3 * 2 = 6, course 2: cancelled
+    let assignment: Assignment = vec![Some(0), Some(1), Some(1), Some(0), Some(0), Some(1)];
+
+    assert_eq!(super::check_rooms(&assignment, &courses, &[15, 6]), Ok(()));
+    assert_eq!(
+        super::check_rooms(&assignment, &courses, &[6, 15, 2]),
+        Ok(())
+    );
+    assert_eq!(
+        super::check_rooms(&assignment, &courses, &[15, 5]),
+        Err(vec![(1, 6)])
+    );
+    // Only one room: Course 1 does not get any room
+    assert_eq!(
+        super::check_rooms(&assignment, &courses, &[10]),
+        Err(vec![(0, 13), (1, 6)])
+    );
+    // Cancelled courses don't need a room
+    let assignment: Assignment = vec![Some(0), Some(0), Some(0), Some(0), Some(0), None];
+    assert_eq!(super::check_rooms(&assignment, &courses, &[20]), Ok(()));
+}
+
+#[test]
+fn test_verify_instructor_assignment() {
+    let (participants, courses) = create_simple_problem();
     let courses = Arc::new(courses);
     let participants = Arc::new(participants);
-    crate::io::assert_data_consitency(&participants, &courses);
-    let rooms = vec![10, 5, 8];
-
-    // Run caobab
-    let (result, _statistics) = super::solve(
+    let (result, _statistics) = super::solve_with_config(
         courses.clone(),
         participants.clone(),
-        Some(&rooms),
-        false,
-        1,
+        super::SolveConfig::new().num_threads(1),
+    );
+    let (assignment, _score) = result.expect("Expected to get a result.");
+    assert_eq!(
+        super::verify_instructor_assignment(&assignment, &courses),
+        Ok(())
     );
 
-    match result {
-        None => panic!("Expected to get a result."),
+    // Instructors of cancelled courses may attend other courses
+    let assignment: Assignment = vec![Some(0), Some(1), Some(1), Some(0), Some(0), Some(1)];
+    assert_eq!(
+        super::verify_instructor_assignment(&assignment, &courses),
+        Ok(())
+    );
 
-        Some((assignment, score)) => {
-            println!(
-                "test_caobab_rooms_fixed_course: assignment: {:?}",
-                assignment
-            );
+    // Tampered: The instructor of course 0 attends course 1 and the instructor of course 2 attends
+    // course 1, although course 2 takes place
+    let assignment: Assignment = vec![Some(1), Some(1), Some(1), Some(0), Some(0), Some(2)];
+    assert_eq!(
+        super::verify_instructor_assignment(&assignment, &courses),
+        Err(vec![(0, 0), (2, 2)])
+    );
+}
 
-            // Check general feasibility of assignment
-            check_assignment(&courses, &participants, &assignment, None);
+#[test]
+fn test_caobab_soft_rooms() {
+    // With rooms = [15, 5], course 0 (effective size 13) and course 1 (effective size 6) cannot take place together.
+    // With hard room constraints, one of them is cancelled. With soft room constraints, both may take place using an
+    // overflow space at the cost of the room penalty (1 exceeding place).
+    let (mut participants, courses) = create_simple_problem();
+    // Make cancelling course 1 more expensive: Participant 5 really dislikes their second choice
+    participants[5].choices = vec![
+        Choice {
+            course_index: 1,
+            penalty: 0,
+            tier: ChoiceTier::Preferred,
+        },
+        Choice {
+            course_index: 2,
+            penalty: 10,
+            tier: ChoiceTier::Preferred,
+        },
+    ];
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let solve = |config: super::SolveConfig| {
+        let (result, _statistics) =
+            super::solve_with_config(courses.clone(), participants.clone(), config.num_threads(1));
+        result.expect("Expected to get a result.")
+    };
 
-            // Check score
-            assert!(score > participants.len() as u32 * (super::WEIGHT_OFFSET as u32 - 2));
-            assert!(score < participants.len() as u32 * (super::WEIGHT_OFFSET as u32));
-            assert_eq!(
-                super::solution_score::solution_quality(score, &participants),
-                super::solution_score::assignment_quality(
-                    &participants,
+    let (unconstrained_assignment, unconstrained_score) = solve(super::SolveConfig::new());
+    assert!(unconstrained_assignment.contains(&Some(0)));
+    assert!(unconstrained_assignment.contains(&Some(1)));
+    let (hard_assignment, hard_score) = solve(super::SolveConfig::new().rooms(vec![15, 5]));
+    assert!(!hard_assignment.contains(&Some(0)));
+    assert_eq!(hard_score, unconstrained_score - 2);
+
+    let (soft_assignment, soft_score) = solve(
+        super::SolveConfig::new()
+            .rooms(vec![15, 5])
+            .soft_room_penalty(1),
+    );
+    check_assignment(&courses, &participants, &soft_assignment, None);
+    assert_eq!(soft_assignment, unconstrained_assignment);
+    assert_eq!(soft_score, unconstrained_score - 1);
+    assert!(soft_score > hard_score);
+}
+
+#[test]
+fn test_caobab_maximize_courses_running() {
+    // Without rooms, the best assignment w.r.t. choice quality cancels course 2, which is nobody's
+    // first choice. Keeping it alive costs one choice rank.
+    let (courses, participants) = create_other_problem();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let solve = |objective: super::SolveObjective| {
+        let config = super::SolveConfig::new()
+            .num_threads(1)
+            .objective(objective);
+        let (result, _statistics) =
+            super::solve_with_config(courses.clone(), participants.clone(), config);
+        result.expect("Expected to get a result.")
+    };
+
+    let (default_assignment, default_score) = solve(super::SolveObjective::ChoiceQuality);
+    assert!(!default_assignment.contains(&Some(2)));
+    assert_eq!(super::num_running_courses(&courses, &default_assignment), 3);
+
+    let (assignment, score) = solve(super::SolveObjective::MaximizeCoursesRunning);
+    check_assignment(&courses, &participants, &assignment, None);
+    assert!(assignment.contains(&Some(2)));
+    assert_eq!(super::num_running_courses(&courses, &assignment), 4);
+    assert_eq!(score, default_score - 1);
+}
+
+#[test]
+fn test_caobab_minimize_worst_choice() {
+    // Five courses with a single place each. The best assignment w.r.t. the sum of the choices
+    // assigns participant 3 to their third choice (course 3) and everyone else to their first
+    // choice. Assigning participant 3 to their first choice (course 0) instead pushes participants
+    // 0 to 2 to their second choice, which is worse in sum, but better for the worst-off
+    // participant.
+    let participants: Vec<Participant> = [&[0, 1][..], &[1, 2], &[2, 3], &[0, 4, 3], &[4]]
+        .iter()
+        .enumerate()
+        .map(|(i, choices)| Participant {
+            index: i,
+            dbid: i,
+            name: format!("Participant {}", i),
+            choices: choices_from_list(choices),
+            ..Default::default()
+        })
+        .collect();
+    let courses: Vec<Course> = (0..5)
+        .map(|i| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max: 1,
+            num_min: 0,
+            instructors: vec![],
+            ..Default::default()
+        })
+        .collect();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let solve = |objective: super::SolveObjective| {
+        let config = super::SolveConfig::new()
+            .num_threads(1)
+            .objective(objective);
+        let (result, _statistics) =
+            super::solve_with_config(courses.clone(), participants.clone(), config);
+        result.expect("Expected to get a result.")
+    };
+
+    let (sum_assignment, sum_score) = solve(super::SolveObjective::ChoiceQuality);
+    assert_eq!(
+        sum_assignment,
+        vec![Some(0), Some(1), Some(2), Some(3), Some(4)]
+    );
+    assert_eq!(
+        super::worst_choice(&courses, &participants, &sum_assignment),
+        (0, 2)
+    );
+
+    let (assignment, score) = solve(super::SolveObjective::MinimizeWorstChoice);
+    check_assignment(&courses, &participants, &assignment, None);
+    assert_eq!(
+        assignment,
+        vec![Some(1), Some(2), Some(3), Some(0), Some(4)]
+    );
+    assert_eq!(
+        super::worst_choice(&courses, &participants, &assignment),
+        (0, 1)
+    );
+    assert_eq!(score, sum_score - 1);
+}
+
+#[test]
+fn test_caobab_max_scored_instructors() {
+    // Only one of the two courses can take place, due to the single room. Course 0 has five
+    // instructors, who prefer to attend course 1 otherwise, course 1 has two instructors, who
+    // prefer to attend course 0 otherwise. The two other participants prefer course 0.
+    let participants: Vec<Participant> = [
+        &[1][..],
+        &[1],
+        &[1],
+        &[1],
+        &[1],
+        &[0],
+        &[0],
+        &[0, 1],
+        &[0, 1],
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, choices)| Participant {
+        index: i,
+        dbid: i,
+        name: format!("Participant {}", i),
+        choices: choices_from_list(choices),
+        ..Default::default()
+    })
+    .collect();
+    let courses: Vec<Course> = [vec![0, 1, 2, 3, 4], vec![5, 6]]
+        .iter()
+        .enumerate()
+        .map(|(i, instructors)| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max: 10,
+            num_min: 1,
+            instructors: instructors.clone(),
+            ..Default::default()
+        })
+        .collect();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let solve = |config: super::SolveConfig| {
+        let (result, _statistics) = super::solve_with_config(
+            courses.clone(),
+            participants.clone(),
+            config.num_threads(1).rooms(vec![20]),
+        );
+        result.expect("Expected to get a result.")
+    };
+
+    // Uncapped, all instructors score as much as a first choice, so the attendees' choices decide
+    let (assignment, score) = solve(super::SolveConfig::new());
+    check_assignment(&courses, &participants, &assignment, None);
+    assert!(assignment.iter().all(|c| *c == Some(0)));
+    assert!(score <= super::solution_score::theoretical_max_score(&participants, &courses, None));
+
+    // With at most two scored instructors per course, the large course 0 loses its advantage
+    let (assignment, score) = solve(super::SolveConfig::new().max_scored_instructors(2));
+    check_assignment(&courses, &participants, &assignment, None);
+    assert!(assignment.iter().all(|c| *c == Some(1)));
+    // score_assignment() must reproduce the capped score
+    assert_eq!(
+        super::score_assignment(
+            &assignment,
+            &participants,
+            &courses,
+            &super::SolveConfig::new().max_scored_instructors(2)
+        ),
+        Ok(score)
+    );
+    // With the cap, the uncapped solution scores worse (only two of five instructors are scored)
+    let uncapped_assignment = vec![Some(0); participants.len()];
+    assert!(
+        super::score_assignment(
+            &uncapped_assignment,
+            &participants,
+            &courses,
+            &super::SolveConfig::new().max_scored_instructors(2)
+        )
+        .unwrap()
+            < score
+    );
+    assert!(
+        score <= super::solution_score::theoretical_max_score(&participants, &courses, Some(2))
+    );
+}
+
+#[test]
+fn test_caobab_count_instructors_in_score() {
+    // Course 0 is the second choice of its attendees. Its instructor only chose their own course,
+    // so they stay unassigned, if it is cancelled.
+    let participants: Vec<Participant> = [&[0][..], &[1, 0], &[1, 0]]
+        .iter()
+        .enumerate()
+        .map(|(i, choices)| Participant {
+            index: i,
+            dbid: i,
+            name: format!("Participant {}", i),
+            choices: choices_from_list(choices),
+            ..Default::default()
+        })
+        .collect();
+    let courses: Vec<Course> = [(vec![0], 2), (vec![], 0)]
+        .iter()
+        .enumerate()
+        .map(|(i, (instructors, num_min))| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max: 10,
+            num_min: *num_min,
+            instructors: instructors.clone(),
+            ..Default::default()
+        })
+        .collect();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let config = |count_instructors_in_score: bool| {
+        super::SolveConfig::new()
+            .num_threads(1)
+            .require_full_assignment(false)
+            .count_instructors_in_score(count_instructors_in_score)
+    };
+    let solve = |count_instructors_in_score: bool| {
+        let (result, _statistics) = super::solve_with_config(
+            courses.clone(),
+            participants.clone(),
+            config(count_instructors_in_score),
+        );
+        result.expect("Expected to get a result.")
+    };
+
+    // The instructor's score keeps course 0 alive
+    let (assignment, score) = solve(true);
+    assert_eq!(assignment, vec![Some(0), Some(0), Some(0)]);
+    assert_eq!(
+        super::score_assignment(&assignment, &participants, &courses, &config(true)),
+        Ok(score)
+    );
+
+    // Without it, the attendees' first choice wins. score_assignment() must agree with the solver
+    // on both assignments.
+    let (assignment, score) = solve(false);
+    assert_eq!(assignment, vec![None, Some(1), Some(1)]);
+    assert_eq!(
+        super::score_assignment(&assignment, &participants, &courses, &config(false)),
+        Ok(score)
+    );
+    let instructor_assignment = vec![Some(0), Some(0), Some(0)];
+    assert!(
+        super::score_assignment(
+            &instructor_assignment,
+            &participants,
+            &courses,
+            &config(false)
+        )
+        .unwrap()
+            < score
+    );
+}
+
+#[test]
+fn test_caobab_group_balance() {
+    // Course 0 requires 3 attendees, so either one of the participants of group 1 joins the two
+    // participants of group 0 in course 0 (losing one choice rank), or course 0 is cancelled and
+    // everybody attends course 1 (losing two choice ranks). The latter is perfectly balanced.
+    let participants: Vec<Participant> = [([0, 1], 0), ([0, 1], 0), ([1, 0], 1), ([1, 0], 1)]
+        .iter()
+        .enumerate()
+        .map(|(i, (choices, group))| Participant {
+            index: i,
+            dbid: i,
+            name: format!("Participant {}", i),
+            choices: choices_from_list(choices),
+            group: Some(*group),
+            ..Default::default()
+        })
+        .collect();
+    let courses: Vec<Course> = [3, 0]
+        .iter()
+        .enumerate()
+        .map(|(i, num_min)| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max: 4,
+            num_min: *num_min,
+            instructors: vec![],
+            ..Default::default()
+        })
+        .collect();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let solve = |config: super::SolveConfig| {
+        let (result, _statistics) =
+            super::solve_with_config(courses.clone(), participants.clone(), config.num_threads(1));
+        result.expect("Expected to get a result.")
+    };
+
+    let (assignment, score) = solve(super::SolveConfig::new());
+    check_assignment(&courses, &participants, &assignment, None);
+    assert_eq!(assignment.iter().filter(|c| **c == Some(0)).count(), 3);
+    assert!((super::group_imbalance(&courses, &participants, &assignment) - 2.0).abs() < 1e-6);
+
+    let (assignment, balanced_score) = solve(super::SolveConfig::new().group_balance_penalty(1));
+    check_assignment(&courses, &participants, &assignment, None);
+    assert_eq!(assignment, vec![Some(1); 4]);
+    assert_eq!(
+        super::group_imbalance(&courses, &participants, &assignment),
+        0.0
+    );
+    assert_eq!(balanced_score, score - 1);
+}
+
+#[test]
+fn test_caobab_trace() {
+    /// Writer to collect the trace output in a shared buffer
+    struct SharedBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let (courses, participants) = create_other_problem();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let rooms = vec![10, 5, 8];
+    let buffer = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let (result, statistics) = super::solve_with_config(
+        courses.clone(),
+        participants.clone(),
+        super::SolveConfig::new()
+            .num_threads(2)
+            .rooms(rooms.clone())
+            .trace_writer(Box::new(SharedBuffer(buffer.clone()))),
+    );
+    assert!(result.is_some());
+
+    let trace = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+    let nodes: Vec<serde_json::Value> = trace
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(nodes.len(), statistics.num_executed_subproblems as usize);
+    assert_eq!(
+        nodes.iter().filter(|n| n["result"] == "feasible").count(),
+        statistics.num_feasible as usize
+    );
+    assert_eq!(
+        nodes.iter().filter(|n| n["result"] == "infeasible").count(),
+        statistics.num_infeasible as usize
+    );
+    // The root node has no cancelled, enforced or shrinked courses
+    assert!(nodes
+        .iter()
+        .any(|n| n["cancelled_courses"].as_array().unwrap().is_empty()
+            && n["enforced_courses"].as_array().unwrap().is_empty()
+            && n["shrinked_courses"].as_array().unwrap().is_empty()));
+}
+
+#[test]
+fn test_caobab_random_restarts() {
+    let (courses, participants) = create_other_problem();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let rooms = vec![10, 5, 8];
+
+    let run = |random_restarts: Option<super::RandomRestarts>| {
+        let mut config = super::SolveConfig::new()
+            .num_threads(1)
+            .rooms(rooms.clone());
+        if let Some(random_restarts) = random_restarts {
+            config = config.random_restarts(random_restarts);
+        }
+        super::solve_with_config(courses.clone(), participants.clone(), config)
+    };
+    let random_restarts = Some(super::RandomRestarts {
+        num_runs: 3,
+        seed: 42,
+    });
+
+    let (deterministic_result, _) = run(None);
+    let (result1, statistics1) = run(random_restarts);
+    let (result2, statistics2) = run(random_restarts);
+
+    // A fixed seed reproduces the same result with the same effort
+    assert_eq!(result1, result2);
+    assert_eq!(
+        statistics1.num_executed_subproblems,
+        statistics2.num_executed_subproblems
+    );
+
+    // The randomized branch ordering must not change the optimal score
+    let (assignment, score) = result1.unwrap();
+    check_assignment(&courses, &participants, &assignment, None);
+    assert_eq!(score, deterministic_result.unwrap().1);
+}
+
+#[test]
+fn test_solve_config() {
+    // The defaults must match the behaviour of the command line interface without any options
+    let config = super::SolveConfig::default();
+    assert!(config.rooms.is_none());
+    assert!(!config.report_no_solution);
+    assert!(config.min_first_choice_count.is_none());
+    assert!(config.require_full_assignment);
+    assert!(config.num_threads >= 1);
+    assert!(config.new_best_callback.is_none());
+    assert!(config.trace_writer.is_none());
+    assert!(config.random_restarts.is_none());
+    assert!(!config.collect_thread_statistics);
+    assert!(config.soft_room_penalty.is_none());
+    assert!(!config.prefer_flexible_cancellation);
+    assert_eq!(config.objective, super::SolveObjective::ChoiceQuality);
+
+    let config = super::SolveConfig::new()
+        .rooms(vec![15, 7])
+        .min_first_choice_count(2)
+        .require_full_assignment(false)
+        .num_threads(1)
+        .collect_thread_statistics(true);
+    assert_eq!(config.rooms, Some(vec![15, 7]));
+    assert_eq!(config.min_first_choice_count, Some(2));
+    assert!(!config.require_full_assignment);
+    assert_eq!(config.num_threads, 1);
+    assert!(config.collect_thread_statistics);
+
+    // solve() and solve_with_config() with equivalent options must give the same result
+    let (participants, courses) = create_simple_problem();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let rooms = vec![15, 7];
+    let (result, _statistics) = super::solve(
+        courses.clone(),
+        participants.clone(),
+        Some(&rooms),
+        false,
+        1,
+    );
+    let (config_result, _statistics) = super::solve_with_config(
+        courses.clone(),
+        participants.clone(),
+        super::SolveConfig::new()
+            .rooms(rooms.clone())
+            .num_threads(1),
+    );
+    assert!(result.is_some());
+    assert_eq!(result, config_result);
+}
+
+#[test]
+fn test_iter_course_assignments() {
+    let (participants, courses) = create_simple_problem();
+    // Course 2 is cancelled, so its instructor P2 attends course 1
+    let assignment: Assignment = vec![Some(0), Some(1), Some(1), Some(0), Some(0), Some(1)];
+    let result: Vec<(usize, Vec<usize>, Vec<usize>)> =
+        super::iter_course_assignments(&assignment, &courses, &participants)
+            .map(|(course, attendees, instructors)| {
+                (
+                    course.index,
+                    attendees.iter().map(|p| p.index).collect(),
+                    instructors.iter().map(|p| p.index).collect(),
+                )
+            })
+            .collect();
+    assert_eq!(
+        result,
+        vec![
+            (0, vec![3, 4], vec![0]),
+            (1, vec![2, 5], vec![1]),
+            (2, vec![], vec![]),
+        ]
+    );
+
+    // Unassigned participants are not yielded at all
+    let assignment: Assignment = vec![Some(0), None, None, Some(0), None, None];
+    let counts: Vec<(usize, usize)> =
+        super::iter_course_assignments(&assignment, &courses, &participants)
+            .map(|(_course, attendees, instructors)| (attendees.len(), instructors.len()))
+            .collect();
+    assert_eq!(counts, vec![(1, 1), (0, 0), (0, 0)]);
+}
+
+#[test]
+fn test_place_level_report() {
+    let (participants, courses) = create_simple_problem();
+    // P0 and P1 are instructors of their courses, P2 gets their 2nd choice, P3 their 1st choice,
+    // P4 is assigned to a course they did not choose and P5 is unassigned
+    let assignment: Assignment = vec![Some(0), Some(1), Some(1), Some(0), Some(1), None];
+    assert_eq!(
+        super::place_level_report(&assignment, &participants, &courses),
+        vec![
+            (0, 0, None),
+            (0, 3, Some(0)),
+            (1, 1, None),
+            (1, 2, Some(1)),
+            (1, 4, None),
+        ]
+    );
+
+    // Cancelled courses have no places at all
+    let assignment: Assignment = vec![None; participants.len()];
+    assert!(super::place_level_report(&assignment, &participants, &courses).is_empty());
+}
+
+#[test]
+fn test_assignment_fingerprint() {
+    // Reference values must never change, as they are used to compare results of different versions
+    assert_eq!(
+        super::assignment_fingerprint(&vec![]),
+        0xcbf2_9ce4_8422_2325
+    );
+    let fingerprint = super::assignment_fingerprint(&vec![Some(0), None, Some(1)]);
+    assert_eq!(fingerprint, 0x9920_24ad_58b0_cce6);
+    assert_ne!(
+        fingerprint,
+        super::assignment_fingerprint(&vec![None, Some(0), Some(1)])
+    );
+    assert_ne!(
+        fingerprint,
+        super::assignment_fingerprint(&vec![Some(0), None, Some(1), None])
+    );
+
+    // The fingerprint of a deterministic solve is stable across runs
+    let (participants, courses) = create_simple_problem();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let fingerprints: Vec<u64> = (0..3)
+        .map(|_| {
+            let (result, _statistics) = super::solve_with_config(
+                courses.clone(),
+                participants.clone(),
+                super::SolveConfig::new().rooms(vec![15, 7]).num_threads(1),
+            );
+            let (assignment, _score) = result.expect("Expected to get a result.");
+            super::assignment_fingerprint(&assignment)
+        })
+        .collect();
+    assert!(fingerprints.iter().all(|f| *f == fingerprints[0]));
+}
+
+#[test]
+fn test_score_assignment() {
+    // The score of the solver's solutions must be reproduced by score_assignment()
+    let (participants, courses) = create_simple_problem();
+    let (other_courses, other_participants) = create_other_problem();
+    let (tie_participants, tie_courses) = create_tie_problem();
+    for (participants, courses, rooms) in [
+        (participants, courses, None),
+        (other_participants, other_courses, Some(vec![10, 5, 8])),
+        (tie_participants, tie_courses, None),
+    ] {
+        let courses = Arc::new(courses);
+        let participants = Arc::new(participants);
+        let (result, _statistics) = super::solve(
+            courses.clone(),
+            participants.clone(),
+            rooms.as_ref(),
+            false,
+            1,
+        );
+        let (assignment, score) = result.expect("Expected to get a result.");
+        assert_eq!(
+            super::score_assignment(
+                &assignment,
+                &participants,
+                &courses,
+                &super::SolveConfig::new()
+            ),
+            Ok(score)
+        );
+    }
+
+    let (participants, courses) = create_simple_problem();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let (result, _statistics) = super::solve(courses.clone(), participants.clone(), None, false, 1);
+    let (assignment, score) = result.expect("Expected to get a result.");
+    assert_eq!(
+        assignment,
+        vec![Some(0), Some(1), Some(0), Some(1), Some(0), Some(1)]
+    );
+
+    // Another valid assignment (cancelling course 1 instead of course 2) must not have a better
+    // score
+    let other_assignment = vec![Some(0), Some(2), Some(2), Some(0), Some(0), Some(2)];
+    let other_score = super::score_assignment(
+        &other_assignment,
+        &participants,
+        &courses,
+        &super::SolveConfig::new(),
+    )
+    .expect("Expected assignment with cancelled course 1 to be valid");
+    assert!(other_score < score);
+
+    // Invalid assignments must be rejected
+    let mut wrong_length = assignment.clone();
+    wrong_length.pop();
+    let mut invalid_course = assignment.clone();
+    invalid_course[3] = Some(17);
+    let mut missing_instructor = assignment.clone();
+    missing_instructor[0] = Some(1);
+    missing_instructor[2] = Some(0);
+    let mut too_large = assignment.clone();
+    too_large[5] = Some(0);
+    let mut too_small = assignment.clone();
+    too_small[5] = None;
+    let mut unchosen = assignment.clone();
+    unchosen.swap(3, 4);
+    for (name, invalid_assignment) in [
+        ("wrong length", wrong_length),
+        ("invalid course", invalid_course),
+        ("missing instructor", missing_instructor),
+        ("too large", too_large),
+        ("too small", too_small),
+        ("unchosen", unchosen),
+    ] {
+        assert!(
+            super::score_assignment(
+                &invalid_assignment,
+                &participants,
+                &courses,
+                &super::SolveConfig::new()
+            )
+            .is_err(),
+            "Expected {} assignment {:?} to be rejected",
+            name,
+            invalid_assignment
+        );
+    }
+}
+
+#[test]
+fn test_check_obvious_infeasibility() {
+    let (mut participants, mut courses) = create_simple_problem();
+    assert!(super::check_obvious_infeasibility(&courses, &participants, true).is_empty());
+
+    // Participant 3 has forbidden all of their choices
+    participants[3].forbidden_courses = vec![0, 1];
+    let problems = super::check_obvious_infeasibility(&courses, &participants, true);
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].contains("Participant 3"));
+    assert!(super::check_obvious_infeasibility(&courses, &participants, false).is_empty());
+
+    // Only 2 course places for 3 non-instructor participants
+    participants[3].forbidden_courses = vec![];
+    for course in courses.iter_mut() {
+        course.num_max = if course.index == 0 { 2 } else { 0 };
+    }
+    let problems = super::check_obvious_infeasibility(&courses, &participants, true);
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].contains("only 2 course places"));
+}
+
+#[test]
+fn test_caobab_rooms_fixed_course() {
+    let (mut courses, participants) = create_other_problem();
+    courses[2].fixed_course = true;
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    crate::io::assert_data_consitency(&participants, &courses);
+    let rooms = vec![10, 5, 8];
+
+    // Run caobab
+    let (result, _statistics) = super::solve(
+        courses.clone(),
+        participants.clone(),
+        Some(&rooms),
+        false,
+        1,
+    );
+
+    match result {
+        None => panic!("Expected to get a result."),
+
+        Some((assignment, score)) => {
+            println!(
+                "test_caobab_rooms_fixed_course: assignment: {:?}",
+                assignment
+            );
+
+            // Check general feasibility of assignment
+            check_assignment(&courses, &participants, &assignment, None);
+
+            // Check score
+            assert!(score > participants.len() as u32 * (super::WEIGHT_OFFSET as u32 - 2));
+            assert!(score < participants.len() as u32 * (super::WEIGHT_OFFSET as u32));
+            assert_eq!(
+                super::solution_score::solution_quality(score, &participants),
+                super::solution_score::assignment_quality(
+                    &participants,
                     &courses,
                     &assignment,
                     0,
@@ -823,6 +2112,7 @@ fn test_caobab_instructor_only_participant_cancelled() {
         dbid: 6,
         name: String::from("Participant 6 (instr. only)"),
         choices: vec![],
+        ..Default::default()
     });
     courses[2].instructors.push(6);
     let courses = Arc::new(courses);
@@ -856,6 +2146,7 @@ fn test_caobab_instructor_only_participant_active() {
         dbid: 6,
         name: String::from("Participant 6 (instr. only)"),
         choices: vec![],
+        ..Default::default()
     });
     courses[1].instructors.push(6);
     let courses = Arc::new(courses);
@@ -878,9 +2169,11 @@ fn test_caobab_instructor_only_participant_active() {
             check_assignment(&courses, &participants, &assignment, None);
             assert!(score > (participants.len() as u32 - 1) * (super::WEIGHT_OFFSET as u32 - 2));
             assert!(score < (participants.len() as u32 - 1) * (super::WEIGHT_OFFSET as u32));
-            assert!(score < super::solution_score::theoretical_max_score(&participants, &courses));
             assert!(
-                super::solution_score::theoretical_max_score(&participants, &courses)
+                score < super::solution_score::theoretical_max_score(&participants, &courses, None)
+            );
+            assert!(
+                super::solution_score::theoretical_max_score(&participants, &courses, None)
                     <= (participants.len() as u32 - 1) * (super::WEIGHT_OFFSET as u32)
             );
             assert_eq!(
@@ -992,6 +2285,41 @@ fn test_caobab_rooms_scaling() {
     }
 }
 
+#[test]
+fn test_caobab_rooms_instructors_need_no_space() {
+    // With rooms = [15, 5], Course 1 cannot take place in the simple problem, as it requires
+    // 2 * (1 instructor + 2 attendees) = 6 places. If its instructor does not need space, it fits
+    // the smaller room with up to 2 attendees and Course 2 should be cancelled instead.
+    let (participants, mut courses) = create_simple_problem();
+    courses[1].instructors_need_space = false;
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    crate::io::assert_data_consitency(&participants, &courses);
+
+    let rooms = vec![15, 5];
+    let (result, _statistics) = super::solve(
+        courses.clone(),
+        participants.clone(),
+        Some(&rooms),
+        false,
+        1,
+    );
+    let (assignment, _score) = result.expect("Expected to get a result.");
+    check_assignment(&courses, &participants, &assignment, None);
+    assert_eq!(assignment[1], Some(1), "Course 1 should take place");
+    assert!(
+        !assignment.contains(&Some(2)),
+        "Course 2 should be cancelled. Assignment is {:?}",
+        assignment
+    );
+
+    // The reported room-effective size must not include the instructor
+    let num_attendees = assignment.iter().filter(|c| **c == Some(1)).count() - 1;
+    let sizes = super::room_effective_course_sizes(&assignment, &courses);
+    assert_eq!(sizes[1].1, 2 * num_attendees);
+    assert!(sizes[1].1 <= 5);
+}
+
 // When a course is fixed its room offset needs to be considered, even if there are no further choices for this course.
 // This is important for considering existing course assignments (using --ignore-assigned).
 #[test]
@@ -1014,6 +2342,7 @@ fn test_caobab_rooms_scaling_preassigned_course() {
             "Mister Z".to_owned(),
             "Mister A".to_owned(),
         ],
+        ..Default::default()
     });
 
     let courses = Arc::new(courses);
@@ -1068,3 +2397,1429 @@ fn test_caobab_rooms_scaling_preassigned_course() {
         }
     };
 }
+
+#[test]
+fn test_caobab_preferred_size() {
+    // Each case is given in both orientations, so the preferred sizes (and not the order of
+    // exploring the branches) must decide, which course is kept
+    for (preferred_sizes, expected_course) in [
+        ((Some(2), Some(4)), 1),
+        ((Some(4), Some(2)), 0),
+        ((Some(3), None), 1),
+        ((None, Some(3)), 0),
+    ] {
+        let (participants, mut courses) = create_tie_problem();
+        courses[0].preferred_size = preferred_sizes.0;
+        courses[1].preferred_size = preferred_sizes.1;
+        let assignment = solve_tie_problem(participants, courses);
+        assert_eq!(
+            assignment,
+            vec![Some(expected_course); 4],
+            "All participants should be assigned to course {} with preferred sizes {:?}",
+            expected_course,
+            preferred_sizes
+        );
+    }
+}
+
+#[test]
+fn test_upper_bound_tie_break() {
+    // Without any tie-break criterion, the upper bound of subproblems does not exceed a solution
+    // with equal score, so they are bounded
+    let (participants, mut courses) = create_tie_problem();
+    let precompute = |courses: &[Course], even_fill: bool| {
+        super::precompute_problem(
+            courses,
+            &participants,
+            None,
+            true,
+            None,
+            super::SolveObjective::ChoiceQuality,
+            None,
+            None,
+            even_fill,
+            None,
+            false,
+        )
+    };
+    let solution = super::NodeScore {
+        num_running_courses: 0,
+        score: 10,
+        tie_break: super::TieBreak::default(),
+        fill_balance: 0,
+    };
+    let problem = precompute(&courses, false);
+    assert_eq!(super::NodeScore::upper_bound(0, 10, &problem), solution);
+
+    // With a preferred size, only the respective criterion is unbounded
+    courses[0].preferred_size = Some(2);
+    let problem = precompute(&courses, false);
+    let bound = super::NodeScore::upper_bound(0, 10, &problem);
+    assert!(bound > solution);
+    assert_eq!(
+        bound.tie_break,
+        super::TieBreak {
+            preferred_size: i64::MAX,
+            ..super::TieBreak::default()
+        }
+    );
+    assert_eq!(bound.fill_balance, 0);
+
+    let problem = precompute(&courses, true);
+    assert_eq!(
+        super::NodeScore::upper_bound(0, 10, &problem).fill_balance,
+        i64::MAX
+    );
+}
+
+#[test]
+fn test_even_fill_tie_break() {
+    // All participants choose both courses with equal penalty, so both assignments have the same
+    // score, but the first one fills both courses evenly.
+    let (mut participants, mut courses) = create_tie_problem();
+    for p in participants.iter_mut() {
+        for choice in p.choices.iter_mut() {
+            choice.penalty = 0;
+        }
+    }
+    for c in courses.iter_mut() {
+        c.num_min = 1;
+        c.num_max = 4;
+    }
+    let even: Assignment = vec![Some(0), Some(0), Some(1), Some(1)];
+    let uneven: Assignment = vec![Some(0), Some(0), Some(0), Some(1)];
+    let even_score =
+        super::score_assignment(&even, &participants, &courses, &super::SolveConfig::new())
+            .unwrap();
+    let uneven_score =
+        super::score_assignment(&uneven, &participants, &courses, &super::SolveConfig::new())
+            .unwrap();
+    assert_eq!(even_score, uneven_score);
+
+    assert!(super::fill_ratio_variance(&courses, &even).abs() < 1e-6);
+    assert!((super::fill_ratio_variance(&courses, &uneven) - 0.0625).abs() < 1e-6);
+
+    let node_score = |assignment: &Assignment| super::NodeScore {
+        num_running_courses: 0,
+        score: even_score,
+        tie_break: super::TieBreak::default(),
+        fill_balance: super::fill_balance(&courses, assignment),
+    };
+    assert!(node_score(&even) > node_score(&uneven));
+    // The fill balance is only considered for solutions with equal tie-break score
+    let uneven_with_tie_break = super::NodeScore {
+        tie_break: super::TieBreak {
+            preferred_size: 1,
+            ..super::TieBreak::default()
+        },
+        ..node_score(&uneven)
+    };
+    assert!(uneven_with_tie_break > node_score(&even));
+
+    // Cancelled courses are not considered
+    let single_course: Assignment = vec![Some(0); 4];
+    assert!(super::fill_ratio_variance(&courses, &single_course).abs() < 1e-6);
+}
+
+#[test]
+fn test_tie_break_order() {
+    // Tie-break criteria are compared lexicographically, so a single kept current course outweighs
+    // any number of reunited co-instructors or any preferred size deviation
+    let keep_current = super::TieBreak {
+        kept_current_courses: 1,
+        ..super::TieBreak::default()
+    };
+    let reunite = super::TieBreak {
+        reunited_co_instructors: 5,
+        preferred_size: 10,
+        ..super::TieBreak::default()
+    };
+    assert!(keep_current > reunite);
+    let keep_current_worse_size = super::TieBreak {
+        preferred_size: -10,
+        ..keep_current
+    };
+    assert!(keep_current_worse_size > reunite);
+    assert!(keep_current > keep_current_worse_size);
+}
+
+#[test]
+fn test_caobab_keep_current_courses() {
+    for (current_courses, expected_course) in [
+        ([Some(1), Some(1), None, Some(0)], 1),
+        ([Some(0), Some(0), Some(0), Some(1)], 0),
+    ] {
+        let (mut participants, courses) = create_tie_problem();
+        for (p, current_course) in participants.iter_mut().zip(current_courses.iter()) {
+            p.current_course = *current_course;
+        }
+        let assignment = solve_tie_problem(participants, courses);
+        assert_eq!(
+            assignment,
+            vec![Some(expected_course); 4],
+            "All participants should be assigned to course {} with current courses {:?}",
+            expected_course,
+            current_courses
+        );
+    }
+}
+
+#[test]
+fn test_caobab_keep_if_possible() {
+    // Add an instructor-only participant (without choices) as instructor of one of the courses of
+    // the tie problem. They do not influence the score, so only one of the courses takes place,
+    // regardless of the instructor. A preferred size of the instructed course, which is far off,
+    // does not outweigh the keep_if_possible flag, as it is a subsequent tie-break criterion.
+    let solve =
+        |instructed_course: usize, keep_if_possible: bool, preferred_size: Option<usize>| {
+            let (mut participants, mut courses) = create_tie_problem();
+            participants.push(Participant {
+                index: 4,
+                dbid: 4,
+                name: "Instructor".to_owned(),
+                choices: vec![],
+                ..Default::default()
+            });
+            courses[instructed_course].instructors = vec![4];
+            courses[instructed_course].keep_if_possible = keep_if_possible;
+            courses[instructed_course].preferred_size = preferred_size;
+            let courses = Arc::new(courses);
+            let participants = Arc::new(participants);
+            let (result, _statistics) = super::solve_with_config(
+                courses.clone(),
+                participants.clone(),
+                super::SolveConfig::new().num_threads(1),
+            );
+            let (assignment, _score) = result.expect("Expected to get a result.");
+            check_assignment(&courses, &participants, &assignment, None);
+            assignment
+        };
+
+    for instructed_course in 0..2 {
+        for preferred_size in [None, Some(0)] {
+            let assignment = solve(instructed_course, true, preferred_size);
+            assert_eq!(
+                assignment,
+                vec![Some(instructed_course); 5],
+                "Course {} should be kept with preferred size {:?}",
+                instructed_course,
+                preferred_size
+            );
+        }
+    }
+    // Without the flag, one of the instructed courses is cancelled
+    assert!((0..2).any(|c| solve(c, false, None)[4].is_none()));
+}
+
+#[test]
+fn test_caobab_cancel_cost() {
+    let solve =
+        |courses: Vec<Course>, participants: Vec<Participant>, config: super::SolveConfig| {
+            let courses = Arc::new(courses);
+            let participants = Arc::new(participants);
+            let (result, _statistics) =
+                super::solve_with_config(courses.clone(), participants.clone(), config);
+            let (assignment, _score) = result.expect("Expected to get a result.");
+            check_assignment(&courses, &participants, &assignment, None);
+            assignment
+        };
+
+    // In the tie problem, the course with the higher cancel cost is kept, even if the cancel costs
+    // only differ in their fractional part
+    for (kept_cost, cancelled_cost) in [(2.0, 1.0), (0.4, 0.3)] {
+        for kept_course in 0..2 {
+            let (participants, mut courses) = create_tie_problem();
+            courses[kept_course].cancel_cost = kept_cost;
+            courses[1 - kept_course].cancel_cost = cancelled_cost;
+            assert_eq!(
+                solve(
+                    courses,
+                    participants,
+                    super::SolveConfig::new().num_threads(1)
+                ),
+                vec![Some(kept_course); 4]
+            );
+        }
+    }
+
+    // With an additional participant choosing course 0 first, cancelling course 1 is better by 1
+    // choice rank. By default, the cancel cost of course 1 does not outweigh this ...
+    let (mut participants, mut courses) = create_tie_problem();
+    let mut new_participant = participants[0].clone();
+    new_participant.index = 4;
+    new_participant.dbid = 4;
+    participants.push(new_participant);
+    courses[1].cancel_cost = 3.0;
+    assert_eq!(
+        solve(
+            courses.clone(),
+            participants.clone(),
+            super::SolveConfig::new().num_threads(1)
+        ),
+        vec![Some(0); 5]
+    );
+    // ... unless it is weighted in the score
+    assert_eq!(
+        solve(
+            courses,
+            participants,
+            super::SolveConfig::new()
+                .num_threads(1)
+                .cancel_cost_penalty(1)
+        ),
+        vec![Some(1); 5]
+    );
+}
+
+#[test]
+fn test_caobab_instructor_of_multiple_courses() {
+    // Participant 1 instructs course 0 and course 1, so these courses cannot both take place
+    let (participants, mut courses) = create_simple_problem();
+    courses[0].instructors.push(1);
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    crate::io::assert_data_consitency(&participants, &courses);
+    let (result, _statistics) = super::solve_with_config(
+        courses.clone(),
+        participants.clone(),
+        super::SolveConfig::new().num_threads(1),
+    );
+    let (assignment, _score) = result.expect("Expected to get a result.");
+    check_assignment(&courses, &participants, &assignment, None);
+    assert!(
+        !(assignment.contains(&Some(0)) && assignment.contains(&Some(1))),
+        "Course 0 and 1 must not both take place: {:?}",
+        assignment
+    );
+}
+
+#[test]
+fn test_caobab_must_run() {
+    // Course 2 of the simple problem is cancelled without further constraints (see
+    // test_caobab_basic)
+    let (participants, mut courses) = create_simple_problem();
+    courses[2].must_run = true;
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let (result, _statistics) = super::solve_with_config(
+        courses.clone(),
+        participants.clone(),
+        super::SolveConfig::new().num_threads(1),
+    );
+    let (assignment, score) = result.expect("Expected to get a result.");
+    check_assignment(&courses, &participants, &assignment, None);
+    let num_attendees = assignment
+        .iter()
+        .enumerate()
+        .filter(|(p, c)| **c == Some(2) && !courses[2].instructors.contains(p))
+        .count();
+    assert!(num_attendees >= courses[2].num_min);
+    assert_eq!(
+        super::score_assignment(
+            &assignment,
+            &participants,
+            &courses,
+            &super::SolveConfig::new()
+        ),
+        Ok(score)
+    );
+
+    // An assignment with the must-run course cancelled is invalid
+    let cancelled: Assignment = vec![Some(0), Some(1), Some(1), Some(0), Some(0), Some(1)];
+    assert_eq!(
+        super::score_assignment(
+            &cancelled,
+            &participants,
+            &courses,
+            &super::SolveConfig::new()
+        ),
+        Err("Must-run course Boring Course 2 is cancelled".to_owned())
+    );
+}
+
+#[test]
+fn test_must_run_vs_fixed_course() {
+    // An additional course without instructors, which nobody chose
+    let create_courses = || {
+        let (_participants, mut courses) = create_simple_problem();
+        courses.push(Course {
+            index: 3,
+            dbid: 3,
+            name: String::from("Unwanted Course 3"),
+            num_max: 5,
+            num_min: 0,
+            instructors: vec![],
+            room_offset: 4.0,
+            ..Default::default()
+        });
+        courses
+    };
+    let (participants, _courses) = create_simple_problem();
+    let assignment: Assignment = vec![Some(0), Some(1), Some(0), Some(1), Some(0), Some(1)];
+
+    // A fixed course is assumed to have fixed participants outside of the assignment, so it takes
+    // place and requires a room, even without any assigned participant.
+    let mut fixed_courses = create_courses();
+    fixed_courses[3].fixed_course = true;
+    assert_eq!(
+        super::room_effective_course_sizes(&assignment, &fixed_courses)[3].1,
+        4
+    );
+    assert!(super::score_assignment(
+        &assignment,
+        &participants,
+        &fixed_courses,
+        &super::SolveConfig::new()
+    )
+    .is_err());
+
+    // A must-run course only needs to reach its minimum size
+    let mut must_run_courses = create_courses();
+    must_run_courses[3].must_run = true;
+    assert_eq!(
+        super::room_effective_course_sizes(&assignment, &must_run_courses)[3].1,
+        0
+    );
+    assert!(super::score_assignment(
+        &assignment,
+        &participants,
+        &must_run_courses,
+        &super::SolveConfig::new()
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_check_feasibility_flexible_cancellation() {
+    // Both courses lack one participant. P0 and P1 chose only course 0, so they would be displaced
+    // by cancelling course 0, while P2 and P3 may still be moved from course 1 to course 0.
+    let (mut participants, courses) = create_tie_problem();
+    participants[0].choices = choices_from_list(&[0]);
+    participants[1].choices = choices_from_list(&[0]);
+    let assignment: Assignment = vec![Some(0), Some(0), Some(1), Some(1)];
+    let is_instructor = ndarray::Array1::from_elem([participants.len()], false);
+    let node = BABNode {
+        cancelled_courses: vec![],
+        enforced_courses: vec![],
+        shrinked_courses: vec![],
+    };
+    for (prefer_flexible_cancellation, expected_course) in [(false, 0), (true, 1)] {
+        assert_eq!(
+            super::check_feasibility(
+                &courses,
+                &participants,
+                &assignment,
+                &node,
+                &is_instructor,
+                true,
+                prefer_flexible_cancellation
+            ),
+            (false, false, Some(expected_course)),
+            "Expected to branch on course {} with prefer_flexible_cancellation = {}",
+            expected_course,
+            prefer_flexible_cancellation
+        );
+    }
+}
+
+#[test]
+fn test_caobab_reunite_co_instructors() {
+    // Course 2 is always cancelled, such that its instructors P5 and P6 have to be assigned to
+    // other courses. Like in the tie problem, only one of the courses 0 and 1 can take place. Both
+    // options result in the same score, but only one of them allows to assign P5 and P6 to the
+    // same course (course 3). With a preferred size of 1 for course 3, reuniting them deviates from
+    // the preferred size, but the co-instructors' tie-break criterion takes precedence.
+    for (choices, expected_assignment, preferred_size) in [
+        (
+            [[0, 1], [0, 1], [1, 0], [1, 0], [1, 0], [0, 3], [3, 0]],
+            [
+                Some(1),
+                Some(1),
+                Some(1),
+                Some(1),
+                Some(1),
+                Some(3),
+                Some(3),
+            ],
+            None,
+        ),
+        (
+            [[0, 1], [0, 1], [1, 0], [1, 0], [1, 0], [0, 3], [3, 0]],
+            [
+                Some(1),
+                Some(1),
+                Some(1),
+                Some(1),
+                Some(1),
+                Some(3),
+                Some(3),
+            ],
+            Some(1),
+        ),
+        (
+            [[0, 1], [0, 1], [0, 1], [1, 0], [1, 0], [1, 3], [3, 1]],
+            [
+                Some(0),
+                Some(0),
+                Some(0),
+                Some(0),
+                Some(0),
+                Some(3),
+                Some(3),
+            ],
+            None,
+        ),
+    ] {
+        let participants: Vec<Participant> = choices
+            .iter()
+            .enumerate()
+            .map(|(i, choices)| Participant {
+                index: i,
+                dbid: i,
+                name: format!("Participant {}", i),
+                choices: choices_from_list(choices),
+                ..Default::default()
+            })
+            .collect();
+        let courses: Vec<Course> = vec![
+            (4, 10, vec![]),
+            (4, 10, vec![]),
+            (10, 10, vec![5, 6]),
+            (0, 10, vec![]),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (num_min, num_max, instructors))| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max,
+            num_min,
+            instructors,
+            preferred_size: if i == 3 { preferred_size } else { None },
+            ..Default::default()
+        })
+        .collect();
+        let courses = Arc::new(courses);
+        let participants = Arc::new(participants);
+        crate::io::assert_data_consitency(&participants, &courses);
+
+        let (result, _statistics) =
+            super::solve(courses.clone(), participants.clone(), None, false, 1);
+        let (assignment, score) = result.expect("Expected to get a result.");
+        check_assignment(&courses, &participants, &assignment, None);
+        assert_eq!(
+            score,
+            participants.len() as u32 * super::WEIGHT_OFFSET as u32 - 3
+        );
+        assert_eq!(assignment, expected_assignment.to_vec());
+    }
+}
+
+#[test]
+fn test_caobab_forbidden_courses() {
+    // Both participants choose course 0 first, which requires two attendees. If participant 1 must
+    // not be assigned to course 0, the course cannot be filled and has to be cancelled.
+    for (forbidden_courses, expected_assignment) in [
+        (vec![], vec![Some(0), Some(0)]),
+        (vec![0], vec![Some(1), Some(1)]),
+    ] {
+        let participants: Vec<Participant> = (0..2)
+            .map(|i| Participant {
+                index: i,
+                dbid: i,
+                name: format!("Participant {}", i),
+                choices: choices_from_list(&[0, 1]),
+                forbidden_courses: if i == 1 {
+                    forbidden_courses.clone()
+                } else {
+                    vec![]
+                },
+                ..Default::default()
+            })
+            .collect();
+        let courses: Vec<Course> = [(2, 2), (0, 5)]
+            .iter()
+            .enumerate()
+            .map(|(i, (num_min, num_max))| Course {
+                index: i,
+                dbid: i,
+                name: format!("Course {}", i),
+                num_max: *num_max,
+                num_min: *num_min,
+                instructors: vec![],
+                ..Default::default()
+            })
+            .collect();
+        let courses = Arc::new(courses);
+        let participants = Arc::new(participants);
+        crate::io::assert_data_consitency(&participants, &courses);
+
+        let (result, _statistics) =
+            super::solve(courses.clone(), participants.clone(), None, false, 1);
+        let (assignment, _score) = result.expect("Expected to get a result.");
+        check_assignment(&courses, &participants, &assignment, None);
+        assert_eq!(
+            assignment, expected_assignment,
+            "Unexpected assignment with forbidden courses {:?}",
+            forbidden_courses
+        );
+    }
+}
+
+#[test]
+fn test_caobab_instructor_selection() {
+    // Participants 0 and 1 could both instruct course 0, but only one instructor is needed.
+    // Participant 0 wants to take part in course 0 anyway, while participant 1 prefers course 1.
+    // Thus, participant 0 should be selected, although participant 1 comes first in the pool.
+    let participants: Vec<Participant> = [&[0, 1][..], &[1, 0], &[0, 1]]
+        .iter()
+        .enumerate()
+        .map(|(i, choices)| Participant {
+            index: i,
+            dbid: i,
+            name: format!("Participant {}", i),
+            choices: choices_from_list(choices),
+            ..Default::default()
+        })
+        .collect();
+    let mut courses: Vec<Course> = (0..2)
+        .map(|i| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max: 5,
+            num_min: 0,
+            instructors: vec![],
+            ..Default::default()
+        })
+        .collect();
+    courses[0].potential_instructors = vec![1, 0];
+    courses[0].min_instructors = 1;
+    crate::io::assert_data_consitency(&participants, &courses);
+
+    assert_eq!(
+        super::instructor_selections(&courses),
+        Ok(vec![vec![(0, vec![1])], vec![(0, vec![0])]])
+    );
+
+    let participants = Arc::new(participants);
+    let (result, statistics) = super::solve_with_config(
+        Arc::new(courses.clone()),
+        participants.clone(),
+        super::SolveConfig::new().num_threads(1),
+    );
+    let (assignment, _score) = result.expect("Expected to get a result.");
+    assert_eq!(assignment, vec![Some(0), Some(1), Some(0)]);
+    // The statistics are summed up over the runs for both selections
+    let num_subproblems: u32 = vec![vec![1], vec![0]]
+        .into_iter()
+        .map(|selected| {
+            let (selection_courses, selection_participants) =
+                super::select_instructors(&courses, &participants, &[(0, selected)]);
+            let (_result, statistics) = super::solve_with_config(
+                Arc::new(selection_courses),
+                Arc::new(selection_participants),
+                super::SolveConfig::new().num_threads(1),
+            );
+            statistics.num_executed_subproblems
+        })
+        .sum();
+    assert_eq!(statistics.num_executed_subproblems, num_subproblems);
+
+    super::apply_instructor_selection(&mut courses, &assignment);
+    assert_eq!(courses[0].instructors, vec![0]);
+    assert!(courses[0].potential_instructors.is_empty());
+    check_assignment(&courses, &participants, &assignment, None);
+}
+
+#[test]
+fn test_instructor_selections_limit() {
+    let participants: Vec<Participant> = (0..20)
+        .map(|i| Participant {
+            index: i,
+            dbid: i,
+            name: format!("Participant {}", i),
+            choices: choices_from_list(&[0, 1]),
+            ..Default::default()
+        })
+        .collect();
+    let mut courses: Vec<Course> = (0..2)
+        .map(|i| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max: 20,
+            num_min: 0,
+            instructors: vec![],
+            ..Default::default()
+        })
+        .collect();
+    // 10 * (10 choose 2) = 450 selections are solved
+    courses[0].potential_instructors = (0..10).collect();
+    courses[0].min_instructors = 1;
+    courses[1].potential_instructors = (10..20).collect();
+    courses[1].min_instructors = 2;
+    assert_eq!(super::instructor_selections(&courses).unwrap().len(), 450);
+
+    // 10 * (10 choose 3) = 1200 selections are too many, although each course has few enough
+    courses[1].min_instructors = 3;
+    assert!(super::instructor_selections(&courses).is_err());
+    // (20 choose 7) = 77520 selections of a single course are too many
+    courses[0].min_instructors = 0;
+    courses[1].potential_instructors = (0..20).collect();
+    courses[1].min_instructors = 7;
+    assert!(super::instructor_selections(&courses).is_err());
+
+    let (result, statistics) = super::solve_with_config(
+        Arc::new(courses),
+        Arc::new(participants),
+        super::SolveConfig::new().num_threads(1),
+    );
+    assert!(result.is_none());
+    assert_eq!(statistics.num_executed_subproblems, 0);
+}
+
+#[test]
+fn test_caobab_available_courses() {
+    // All participants choose course 0 first, but participants 0 and 2 are only available for some
+    // of the other courses, so they must get a worse choice.
+    let participants: Vec<Participant> = [Some(vec![2]), None, Some(vec![1, 2])]
+        .iter()
+        .enumerate()
+        .map(|(i, available_courses)| Participant {
+            index: i,
+            dbid: i,
+            name: format!("Participant {}", i),
+            choices: choices_from_list(&[0, 1, 2]),
+            available_courses: available_courses.clone(),
+            ..Default::default()
+        })
+        .collect();
+    let courses: Vec<Course> = (0..3)
+        .map(|i| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max: 5,
+            num_min: 0,
+            instructors: vec![],
+            ..Default::default()
+        })
+        .collect();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    crate::io::assert_data_consitency(&participants, &courses);
+
+    let (result, _statistics) = super::solve(courses.clone(), participants.clone(), None, false, 1);
+    let (assignment, _score) = result.expect("Expected to get a result.");
+    check_assignment(&courses, &participants, &assignment, None);
+    assert_eq!(assignment, vec![Some(2), Some(0), Some(1)]);
+
+    // Assigning a participant to an unavailable course is infeasible
+    let unavailable_assignment = vec![Some(0), Some(0), Some(1)];
+    assert!(super::score_assignment(
+        &unavailable_assignment,
+        &participants,
+        &courses,
+        &super::SolveConfig::new()
+    )
+    .is_err());
+    assert!(
+        super::validate_assignment(&unavailable_assignment, &courses, &participants, None).is_err()
+    );
+}
+
+#[test]
+fn test_caobab_max_acceptable_rank() {
+    // Course 0 has a single place, which is taken by participant 1 (their only choice). Participant
+    // 0 would normally be assigned to their second choice, course 1, along with participant 2. With
+    // a maximum acceptable rank of 1, participant 0 must stay unassigned instead.
+    for (max_acceptable_rank, expected_assignment) in [
+        (None, vec![Some(1), Some(0), Some(1)]),
+        (Some(2), vec![Some(1), Some(0), Some(1)]),
+        (Some(1), vec![None, Some(0), Some(1)]),
+    ] {
+        let participants: Vec<Participant> = vec![vec![0, 1], vec![0], vec![1]]
+            .into_iter()
+            .enumerate()
+            .map(|(i, choices)| Participant {
+                index: i,
+                dbid: i,
+                name: format!("Participant {}", i),
+                choices: choices_from_list(&choices),
+                max_acceptable_rank: if i == 0 { max_acceptable_rank } else { None },
+                ..Default::default()
+            })
+            .collect();
+        let courses: Vec<Course> = [(0, 1), (0, 2)]
+            .iter()
+            .enumerate()
+            .map(|(i, (num_min, num_max))| Course {
+                index: i,
+                dbid: i,
+                name: format!("Course {}", i),
+                num_max: *num_max,
+                num_min: *num_min,
+                instructors: vec![],
+                ..Default::default()
+            })
+            .collect();
+        let courses = Arc::new(courses);
+        let participants = Arc::new(participants);
+        crate::io::assert_data_consitency(&participants, &courses);
+
+        let (result, _statistics) =
+            super::solve(courses.clone(), participants.clone(), None, false, 1);
+        let (assignment, _score) = result.expect("Expected to get a result.");
+        check_assignment(&courses, &participants, &assignment, None);
+        assert_eq!(
+            assignment, expected_assignment,
+            "Unexpected assignment with maximum acceptable rank {:?}",
+            max_acceptable_rank
+        );
+    }
+}
+
+#[test]
+fn test_caobab_require_full_assignment() {
+    // There are only three course places for four participants, so at least one participant cannot be
+    // assigned to any course.
+    let participants: Vec<Participant> = vec![vec![0, 1], vec![0, 1], vec![1, 0], vec![1, 0]]
+        .into_iter()
+        .enumerate()
+        .map(|(i, choices)| Participant {
+            index: i,
+            dbid: i,
+            name: format!("Participant {}", i),
+            choices: choices_from_list(&choices),
+            ..Default::default()
+        })
+        .collect();
+    let courses: Vec<Course> = [(1, 2), (1, 1)]
+        .iter()
+        .enumerate()
+        .map(|(i, (num_min, num_max))| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max: *num_max,
+            num_min: *num_min,
+            instructors: vec![],
+            ..Default::default()
+        })
+        .collect();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    crate::io::assert_data_consitency(&participants, &courses);
+
+    // If everyone must be assigned, there is no solution
+    let (result, _statistics) = super::solve(courses.clone(), participants.clone(), None, false, 1);
+    assert!(result.is_none());
+
+    // Otherwise, we get a partial assignment
+    let (result, _statistics) = super::solve_with_config(
+        courses.clone(),
+        participants.clone(),
+        super::SolveConfig::new()
+            .num_threads(1)
+            .require_full_assignment(false),
+    );
+    let (assignment, _score) = result.expect("Expected to get a result.");
+    assert_eq!(assignment.iter().filter(|c| c.is_none()).count(), 1);
+    assert_eq!(assignment.iter().filter(|c| **c == Some(0)).count(), 2);
+    assert_eq!(assignment.iter().filter(|c| **c == Some(1)).count(), 1);
+    // The unassigned participant must be one of those, who did not get their first choice anyway
+    let unassigned = assignment.iter().position(|c| c.is_none()).unwrap();
+    assert!(unassigned >= 2);
+
+    // check_feasibility() must reject unassigned participants only with require_full_assignment
+    let node = BABNode {
+        cancelled_courses: vec![],
+        enforced_courses: vec![],
+        shrinked_courses: vec![],
+    };
+    let is_instructor = ndarray::Array1::from_elem([participants.len()], false);
+    let (feasible, participant_problem, _branch_course) = super::check_feasibility(
+        &courses,
+        &participants,
+        &assignment,
+        &node,
+        &is_instructor,
+        true,
+        false,
+    );
+    assert!(!feasible);
+    assert!(participant_problem);
+    let (feasible, _participant_problem, _branch_course) = super::check_feasibility(
+        &courses,
+        &participants,
+        &assignment,
+        &node,
+        &is_instructor,
+        false,
+        false,
+    );
+    assert!(feasible);
+}
+
+#[test]
+fn test_caobab_required_attendees() {
+    for (name, course_sizes, required_attendees, choices, expected_assignment) in [
+        (
+            // Course 0 takes place, so its required attendee P2 must attend it (instead of their
+            // first choice)
+            "run",
+            vec![(1, 3), (0, 3)],
+            vec![vec![2], vec![]],
+            vec![vec![0, 1], vec![0, 1], vec![1, 0]],
+            vec![Some(0), Some(0), Some(0)],
+        ),
+        (
+            // Course 0 is too small for its required attendees, so it must be cancelled
+            "cancel",
+            vec![(0, 1), (0, 4)],
+            vec![vec![2, 3], vec![]],
+            vec![vec![0, 1], vec![0, 1], vec![1, 0], vec![1, 0]],
+            vec![Some(1), Some(1), Some(1), Some(1)],
+        ),
+        (
+            // P2 is required in course 0 and course 1, so one of them must be cancelled
+            "conflict",
+            vec![(0, 3), (0, 3), (0, 5)],
+            vec![vec![2], vec![2], vec![]],
+            vec![vec![0, 2], vec![0, 2], vec![0, 1], vec![1, 2]],
+            vec![Some(0), Some(0), Some(0), Some(2)],
+        ),
+    ] {
+        let participants: Vec<Participant> = choices
+            .iter()
+            .enumerate()
+            .map(|(i, choices)| Participant {
+                index: i,
+                dbid: i,
+                name: format!("Participant {}", i),
+                choices: choices_from_list(choices),
+                ..Default::default()
+            })
+            .collect();
+        let courses: Vec<Course> = course_sizes
+            .into_iter()
+            .zip(required_attendees)
+            .enumerate()
+            .map(|(i, ((num_min, num_max), required_attendees))| Course {
+                index: i,
+                dbid: i,
+                name: format!("Course {}", i),
+                num_max,
+                num_min,
+                instructors: vec![],
+                required_attendees,
+                ..Default::default()
+            })
+            .collect();
+        let courses = Arc::new(courses);
+        let participants = Arc::new(participants);
+        crate::io::assert_data_consitency(&participants, &courses);
+
+        let (result, _statistics) =
+            super::solve(courses.clone(), participants.clone(), None, false, 1);
+        let (assignment, _score) = result.expect("Expected to get a result.");
+        check_assignment(&courses, &participants, &assignment, None);
+        assert_eq!(
+            assignment, expected_assignment,
+            "Unexpected assignment in {} case",
+            name
+        );
+    }
+}
+
+/// Helper function for the tie-break tests: Solve the given (modified) tie problem and check that
+/// the result has the expected score (i.e. tie-break criteria must not influence the score).
+fn solve_tie_problem(participants: Vec<Participant>, courses: Vec<Course>) -> Assignment {
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    crate::io::assert_data_consitency(&participants, &courses);
+    let (result, _statistics) = super::solve(courses.clone(), participants.clone(), None, false, 1);
+
+    match result {
+        Some((assignment, score)) => {
+            check_assignment(&courses, &participants, &assignment, None);
+            assert_eq!(
+                score,
+                participants.len() as u32 * super::WEIGHT_OFFSET as u32 - 2
+            );
+            assignment
+        }
+        None => panic!("Expected to get a result."),
+    }
+}
+
+#[test]
+fn test_caobab_min_first_choice_count() {
+    let (participants, courses) = create_first_choice_problem();
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    crate::io::assert_data_consitency(&participants, &courses);
+
+    for (min_first_choice_count, expected_assignment, expected_penalty) in [
+        (None, vec![Some(1), Some(2), Some(0)], 2),
+        (Some(1), vec![Some(1), Some(2), Some(0)], 2),
+        (Some(2), vec![Some(0), Some(1), Some(3)], 3),
+    ] {
+        let mut config = super::SolveConfig::new().num_threads(1);
+        if let Some(count) = min_first_choice_count {
+            config = config.min_first_choice_count(count);
+        }
+        let (result, _statistics) =
+            super::solve_with_config(courses.clone(), participants.clone(), config);
+        match result {
+            Some((assignment, score)) => {
+                check_assignment(&courses, &participants, &assignment, None);
+                assert_eq!(
+                    assignment, expected_assignment,
+                    "Unexpected assignment with min_first_choice_count {:?}",
+                    min_first_choice_count
+                );
+                assert_eq!(
+                    score,
+                    participants.len() as u32 * super::WEIGHT_OFFSET as u32 - expected_penalty
+                );
+            }
+            None => panic!("Expected to get a result."),
+        }
+    }
+
+    // Three first choices are not possible at all
+    let (result, _statistics) = super::solve_with_config(
+        courses.clone(),
+        participants.clone(),
+        super::SolveConfig::new()
+            .num_threads(1)
+            .min_first_choice_count(3),
+    );
+    assert!(result.is_none());
+}
+
+#[test]
+fn test_theoretical_score_bounds() {
+    let (participants, courses) = create_simple_problem();
+    // Participants 0-2 are course instructors; participants 3-5 have two choices each.
+    assert_eq!(
+        super::solution_score::theoretical_max_score(&participants, &courses, None),
+        6 * super::WEIGHT_OFFSET as u32
+    );
+    assert_eq!(
+        super::solution_score::theoretical_min_score(&participants, &courses),
+        6 * super::WEIGHT_OFFSET as u32 - 3
+    );
+
+    let (participants, courses) = create_first_choice_problem();
+    // No course instructors; worst choice penalties are 1, 1 and 3.
+    assert_eq!(
+        super::solution_score::theoretical_min_score(&participants, &courses),
+        3 * super::WEIGHT_OFFSET as u32 - 5
+    );
+
+    // The solution's score must be within these bounds
+    let quality_info = super::solution_score::QualityInfo::calculate(
+        3 * super::WEIGHT_OFFSET as u32 - 2,
+        &participants,
+        &courses,
+        &vec![Some(1), Some(2), Some(0)],
+        None,
+    );
+    assert!(quality_info.theoretical_min_score <= quality_info.solution_score);
+    assert!(quality_info.solution_score <= quality_info.theoretical_max_score);
+    assert!(quality_info.theoretical_min_quality >= quality_info.solution_quality);
+}
+
+#[test]
+fn test_satisfaction_report() {
+    use super::solution_score::AssignedRank::*;
+
+    let (participants, courses) = create_simple_problem();
+
+    // The optimal solution of the simple problem: Course 2 is cancelled
+    let assignment: Assignment = vec![Some(0), Some(1), Some(1), Some(0), Some(0), Some(1)];
+    let report = super::solution_score::satisfaction_report(&assignment, &participants, &courses);
+    assert_eq!(report.len(), participants.len());
+    assert_eq!(
+        report
+            .iter()
+            .map(|s| (s.assigned_course, s.assigned_rank, s.best_achievable_rank))
+            .collect::<Vec<_>>(),
+        vec![
+            (Some(0), Instructor, Some(0)),
+            (Some(1), Instructor, Some(0)),
+            (Some(1), Rank(1), Some(0)),
+            (Some(0), Rank(0), Some(0)),
+            (Some(0), Rank(0), Some(0)),
+            (Some(1), Rank(0), Some(0)),
+        ]
+    );
+    assert!(report.iter().enumerate().all(|(i, s)| s.participant == i));
+
+    // Course 0 is cancelled, Participant 3 is assigned to a wrong course and Participant 5 is not
+    // assigned at all
+    let assignment: Assignment = vec![Some(1), Some(1), Some(2), Some(2), Some(2), None];
+    let report = super::solution_score::satisfaction_report(&assignment, &participants, &courses);
+    assert_eq!(
+        report
+            .iter()
+            .map(|s| (s.assigned_rank, s.best_achievable_rank))
+            .collect::<Vec<_>>(),
+        vec![
+            (Rank(0), Some(0)),
+            (Instructor, Some(1)),
+            (Instructor, Some(1)),
+            (Wrong, Some(1)),
+            (Rank(1), Some(1)),
+            (Unassigned, Some(0)),
+        ]
+    );
+}
+
+#[test]
+fn test_build_waitlists() {
+    let participants: Vec<Participant> = [&[0, 1, 2][..], &[1, 0], &[1, 2], &[2, 0], &[0]]
+        .iter()
+        .enumerate()
+        .map(|(i, choices)| Participant {
+            index: i,
+            dbid: i,
+            name: format!("Participant {}", i),
+            choices: choices_from_list(choices),
+            ..Default::default()
+        })
+        .collect();
+    let mut courses: Vec<Course> = (0..3)
+        .map(|i| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max: 10,
+            num_min: 0,
+            instructors: if i == 1 { vec![4] } else { vec![] },
+            ..Default::default()
+        })
+        .collect();
+
+    // Participant 0 gets their 3rd choice, Participant 2 is unassigned and Participant 3 gets their
+    // 2nd choice. Participant 1 gets their 1st choice and Participant 4 is instructor, so they are
+    // not on any waitlist.
+    let assignment: Assignment = vec![Some(2), Some(1), None, Some(0), Some(1)];
+    assert_eq!(
+        super::solution_score::build_waitlists(&assignment, &participants, &courses),
+        vec![vec![0], vec![2, 0], vec![3, 2]]
+    );
+
+    // Waitlists are truncated to the capacity, keeping the best ranks
+    courses[1].waitlist_capacity = Some(1);
+    assert_eq!(
+        super::solution_score::build_waitlists(&assignment, &participants, &courses),
+        vec![vec![0], vec![2], vec![3, 2]]
+    );
+
+    // Cancelled courses have no waitlist
+    let assignment: Assignment = vec![Some(1), Some(1), None, Some(0), Some(1)];
+    assert_eq!(
+        super::solution_score::build_waitlists(&assignment, &participants, &courses),
+        vec![vec![0], vec![2], vec![]]
+    );
+}
+
+#[test]
+fn test_normalized_quality() {
+    use super::solution_score::{normalized_quality, solution_quality};
+
+    // Two events with 3 resp. 5 course choices per participant, where each participant gets their
+    // middle choice
+    let create_event = |num_choices: usize| {
+        let choices: Vec<usize> = (0..num_choices).collect();
+        let participants: Vec<Participant> = (0..4)
+            .map(|i| Participant {
+                index: i,
+                dbid: i,
+                name: format!("Participant {}", i),
+                choices: choices_from_list(&choices),
+                ..Default::default()
+            })
+            .collect();
+        let courses: Vec<Course> = (0..num_choices)
+            .map(|i| Course {
+                index: i,
+                dbid: i,
+                name: format!("Course {}", i),
+                num_max: 4,
+                num_min: 0,
+                instructors: vec![],
+                ..Default::default()
+            })
+            .collect();
+        (participants, courses)
+    };
+    let (small_participants, small_courses) = create_event(3);
+    let (large_participants, large_courses) = create_event(5);
+    let small_assignment: Assignment = vec![Some(1); 4];
+    let large_assignment: Assignment = vec![Some(2); 4];
+
+    // The plain solution quality differs, the normalized quality is comparable
+    let small_score = super::score_assignment(
+        &small_assignment,
+        &small_participants,
+        &small_courses,
+        &super::SolveConfig::new(),
+    )
+    .unwrap();
+    let large_score = super::score_assignment(
+        &large_assignment,
+        &large_participants,
+        &large_courses,
+        &super::SolveConfig::new(),
+    )
+    .unwrap();
+    assert_ne!(
+        solution_quality(small_score, &small_participants),
+        solution_quality(large_score, &large_participants)
+    );
+    assert_eq!(
+        normalized_quality(&small_participants, &small_courses, &small_assignment),
+        Some(0.5)
+    );
+    assert_eq!(
+        normalized_quality(&large_participants, &large_courses, &large_assignment),
+        Some(0.5)
+    );
+
+    // Best and worst choices are mapped to 0 and 1, unassigned participants count as 1
+    assert_eq!(
+        normalized_quality(&small_participants, &small_courses, &vec![Some(0); 4]),
+        Some(0.0)
+    );
+    assert_eq!(
+        normalized_quality(&large_participants, &large_courses, &vec![Some(4); 4]),
+        Some(1.0)
+    );
+    assert_eq!(
+        normalized_quality(
+            &large_participants,
+            &large_courses,
+            &vec![Some(0), Some(0), Some(0), None]
+        ),
+        Some(0.25)
+    );
+
+    // The normalized quality is only added to the quality info on request
+    let quality_info = super::solution_score::QualityInfo::calculate(
+        large_score,
+        &large_participants,
+        &large_courses,
+        &large_assignment,
+        None,
+    );
+    assert_eq!(quality_info.normalized_quality, None);
+    let quality_info = quality_info.with_normalized_quality(
+        &large_participants,
+        &large_courses,
+        &large_assignment,
+    );
+    assert_eq!(quality_info.normalized_quality, Some(0.5));
+}
+
+#[test]
+fn test_penalty_statistics() {
+    use super::solution_score::PenaltyStatistics;
+
+    let (participants, courses) = create_simple_problem();
+    // Participants 0 & 1 are instructors; P2 gets their second choice, P3-P5 their first choice
+    let assignment: Assignment = vec![Some(0), Some(1), Some(1), Some(0), Some(0), Some(1)];
+    let statistics = PenaltyStatistics::calculate(&participants, &courses, &assignment).unwrap();
+    assert_eq!(statistics.min, 0);
+    assert_eq!(statistics.max, 1);
+    assert_eq!(statistics.mean, 0.25);
+
+    // Two assignments with equal mean penalty, but different spread
+    let participants: Vec<Participant> = (0..4)
+        .map(|i| Participant {
+            index: i,
+            dbid: i,
+            name: format!("Participant {}", i),
+            choices: choices_from_list(&[0, 1, 2]),
+            ..Default::default()
+        })
+        .collect();
+    let courses: Vec<Course> = (0..3)
+        .map(|i| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max: 4,
+            num_min: 0,
+            instructors: vec![],
+            ..Default::default()
+        })
+        .collect();
+    let even = PenaltyStatistics::calculate(&participants, &courses, &vec![Some(1); 4]).unwrap();
+    let uneven = PenaltyStatistics::calculate(
+        &participants,
+        &courses,
+        &vec![Some(0), Some(0), Some(2), Some(2)],
+    )
+    .unwrap();
+    assert_eq!(
+        even,
+        PenaltyStatistics {
+            min: 1,
+            max: 1,
+            mean: 1.0,
+            stddev: 0.0
+        }
+    );
+    assert_eq!(
+        uneven,
+        PenaltyStatistics {
+            min: 0,
+            max: 2,
+            mean: 1.0,
+            stddev: 1.0
+        }
+    );
+
+    // No participant is assigned to a chosen course
+    assert!(PenaltyStatistics::calculate(&participants, &courses, &vec![None; 4]).is_none());
+}
+
+#[test]
+fn test_caobab_minimize_max_penalty() {
+    // Only one of the courses can take place (course 0 requires 3 attendees, course 1 requires 2).
+    // Both options result in the same total penalty (4), but only one of them avoids assigning a
+    // choice with a high penalty to the worst-off participant. The test is executed with both
+    // options being the preferred one, to make sure the result does not depend on the order of
+    // exploring the branches. The tie-break is only used with the respective option.
+    for (penalties, expected_course) in [([3, 1, 2, 2], 0), ([2, 2, 1, 3], 1)] {
+        let make_choice = |course_index, penalty| Choice {
+            course_index,
+            penalty,
+            tier: ChoiceTier::Preferred,
+        };
+        let participants: Vec<Participant> = vec![
+            vec![make_choice(0, 0), make_choice(1, penalties[0])],
+            vec![make_choice(0, 0), make_choice(1, penalties[1])],
+            vec![make_choice(1, 0), make_choice(0, penalties[2])],
+            vec![make_choice(1, 0), make_choice(0, penalties[3])],
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, choices)| Participant {
+            index: i,
+            dbid: i,
+            name: format!("Participant {}", i),
+            choices,
+            ..Default::default()
+        })
+        .collect();
+        let courses: Vec<Course> = [3, 2]
+            .iter()
+            .enumerate()
+            .map(|(i, num_min)| Course {
+                index: i,
+                dbid: i,
+                name: format!("Course {}", i),
+                num_max: 10,
+                num_min: *num_min,
+                instructors: vec![],
+                ..Default::default()
+            })
+            .collect();
+        let courses = Arc::new(courses);
+        let participants = Arc::new(participants);
+        crate::io::assert_data_consitency(&participants, &courses);
+
+        let (result, _statistics) = super::solve_with_config(
+            courses.clone(),
+            participants.clone(),
+            super::SolveConfig::new()
+                .num_threads(1)
+                .max_penalty_tie_break(true),
+        );
+        let (assignment, score) = result.expect("Expected to get a result.");
+        check_assignment(&courses, &participants, &assignment, None);
+        assert_eq!(score, 4 * super::WEIGHT_OFFSET as u32 - 4);
+        assert_eq!(
+            assignment,
+            vec![Some(expected_course); 4],
+            "All participants should be assigned to course {} with penalties {:?}",
+            expected_course,
+            penalties
+        );
+    }
+}
+
+#[test]
+fn test_caobab_choice_tiers() {
+    // A single participant with three choices of free courses: Only the tiers of the choices
+    // decide, which course is assigned.
+    let courses: Vec<Course> = (0..3)
+        .map(|i| Course {
+            index: i,
+            dbid: i,
+            name: format!("Course {}", i),
+            num_max: 1,
+            num_min: 0,
+            instructors: vec![],
+            ..Default::default()
+        })
+        .collect();
+    let courses = Arc::new(courses);
+
+    for (tiers, expected_course, expected_penalty) in [
+        ([ChoiceTier::Preferred; 3], 0, 0),
+        (
+            [
+                ChoiceTier::Acceptable,
+                ChoiceTier::Acceptable,
+                ChoiceTier::Preferred,
+            ],
+            2,
+            2,
+        ),
+        (
+            [
+                ChoiceTier::Acceptable,
+                ChoiceTier::Preferred,
+                ChoiceTier::Preferred,
+            ],
+            1,
+            1,
+        ),
+        (
+            [ChoiceTier::Acceptable; 3],
+            0,
+            crate::ACCEPTABLE_TIER_PENALTY,
+        ),
+    ] {
+        let choices: Vec<(usize, ChoiceTier)> = (0..3).zip(tiers.iter().copied()).collect();
+        let participants = Arc::new(vec![Participant {
+            index: 0,
+            dbid: 0,
+            name: String::from("Participant 0"),
+            choices: choices_from_tiered_list(&choices),
+            ..Default::default()
+        }]);
+        let (result, _statistics) =
+            super::solve(courses.clone(), participants.clone(), None, false, 1);
+        match result {
+            Some((assignment, score)) => {
+                assert_eq!(
+                    assignment,
+                    vec![Some(expected_course)],
+                    "Unexpected assignment with choice tiers {:?}",
+                    tiers
+                );
+                assert_eq!(score, super::WEIGHT_OFFSET as u32 - expected_penalty);
+            }
+            None => panic!("Expected to get a result."),
+        }
+    }
+}
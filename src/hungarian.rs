@@ -38,7 +38,7 @@ pub type Score = u32;
 pub type EdgeWeight = i32;
 
 pub type Label = i32;
-const LARGE_LABEL: Label = std::i32::MAX;
+const LARGE_LABEL: Label = i32::MAX;
 
 /// Execute the hungarian algorithm
 ///
@@ -60,26 +60,50 @@ const LARGE_LABEL: Label = std::i32::MAX;
 /// # Arguments
 ///
 /// * `adjacency_matrix` - The adjacency matrix of the matching graph. Contains the weights of the edges between X and
-///     Y nodes
+///   Y nodes
 /// * `dummy_x` - A vector that tags certain X nodes as "dummy" nodes. Rows with `dummy_x[x] == true` must not be
-///     matched with `mandatory_y` nodes
+///   matched with `mandatory_y` nodes
 /// * `mandatory_y` - A vector that tags certain Y nodes as "mandatory" nodes. Those Y nodes/columns with
-///     `dummy_y[y] == true` will not be matched with `dummy_x` nodes
+///   `dummy_y[y] == true` will not be matched with `dummy_x` nodes
 /// * `skip_x` - A vector that marks rows to be skipped. Rows in the adjacency matrix with `skip_x[x] == true` are
-///     completely ignored by the algorithm.
+///   completely ignored by the algorithm.
 /// * `skip_y` - A vector that marks columns to be skipped. Columns in the adjacency matrix with `skip_y[x] == true`
-///     are completely ignored by the algorithm.
+///   are completely ignored by the algorithm.
+/// * `initial_matching` - An optional (partial) matching to start with (warm start), e.g. the matching of a similar,
+///   previously solved problem. See [seed_matching] for the entries, which are actually used. Each used entry saves
+///   the construction of one augmenting path.
 ///
 /// The dummy_x and skip_x vectors' dimension must match the adjacency matrix' first Axis' dimension (number of rows).
-/// The same holds for mandatory_y, skip_y and the adjacency matrix' second Axis' dimension. These conditions are
-/// checked in with assertions in debug builds.
+/// The same holds for mandatory_y, skip_y, initial_matching and the adjacency matrix' second Axis' dimension. These
+/// conditions are checked in with assertions in debug builds.
 pub fn hungarian_algorithm(
     adjacency_matrix: &Array2<EdgeWeight>,
     dummy_x: &Array1<bool>,
     mandatory_y: &Array1<bool>,
     skip_x: &Array1<bool>,
     skip_y: &Array1<bool>,
+    initial_matching: Option<&Matching>,
 ) -> (Matching, Score) {
+    let (matching, score, _num_augmenting_paths) = run_hungarian_algorithm(
+        adjacency_matrix,
+        dummy_x,
+        mandatory_y,
+        skip_x,
+        skip_y,
+        initial_matching,
+    );
+    (matching, score)
+}
+
+/// Implementation of [hungarian_algorithm], additionally returning the number of constructed augmenting paths
+fn run_hungarian_algorithm(
+    adjacency_matrix: &Array2<EdgeWeight>,
+    dummy_x: &Array1<bool>,
+    mandatory_y: &Array1<bool>,
+    skip_x: &Array1<bool>,
+    skip_y: &Array1<bool>,
+    initial_matching: Option<&Matching>,
+) -> (Matching, Score, usize) {
     let nx = adjacency_matrix.dim().0;
     let ny = adjacency_matrix.dim().1;
 
@@ -92,6 +116,9 @@ pub fn hungarian_algorithm(
         let count_skip_x = skip_x.fold(0, |acc, x| if *x { acc + 1 } else { acc });
         let count_skip_y = skip_y.fold(0, |acc, x| if *x { acc + 1 } else { acc });
         assert_eq!(nx - count_skip_x, ny - count_skip_y);
+        if let Some(initial_matching) = initial_matching {
+            assert_eq!(initial_matching.dim(), ny);
+        }
     }
 
     // Initialize labels
@@ -109,10 +136,26 @@ pub fn hungarian_algorithm(
         .filter(|(_i, skip)| !*skip)
         .map(|(i, _skip)| i)
         .collect();
+    if let Some(initial_matching) = initial_matching {
+        let is_matched_x = seed_matching(
+            initial_matching,
+            adjacency_matrix,
+            &labels_x,
+            dummy_x,
+            mandatory_y,
+            skip_x,
+            skip_y,
+            &mut m,
+            &mut m_match,
+        );
+        free_x.retain(|x| !is_matched_x[*x]);
+    }
+    let mut num_augmenting_paths = 0;
 
     // Main loop to construct augmenting paths until matching is perfect
     // -> Chose root u of the alternating tree
     while let Some(u) = free_x.pop() {
+        num_augmenting_paths += 1;
         // Reset the node sets of the alternating tree
         // The set of row (X) nodes in the alternating tree
         let mut s = Array1::<bool>::from_elem([nx], false);
@@ -238,14 +281,53 @@ pub fn hungarian_algorithm(
         .map(|(y, x)| adjacency_matrix[(*x, y)] as Score)
         .fold(Score::from(0u8), |acc, x| acc + x);
 
-    (m_match, score)
+    (m_match, score, num_augmenting_paths)
+}
+
+/// Seed the matching state of the hungarian algorithm (`m` and `m_match`) from a given initial matching (mapping each
+/// column to a row, like the algorithm's result) and return the set of rows (X nodes) that have been matched.
+///
+/// An entry of the initial matching is only used, if it is consistent with the current problem: The column and row
+/// must not be skipped, a dummy row must not be matched with a mandatory column, the row must not already be matched
+/// by a previous entry and the edge must be part of the equality graph w.r.t. the initial labels (i.e. its weight is
+/// the maximum weight of the row). The latter is required for the algorithm's invariants, such that the result is
+/// still optimal. All other entries are ignored and the respective rows are matched by the algorithm as usual.
+#[allow(clippy::too_many_arguments)]
+fn seed_matching(
+    initial_matching: &Matching,
+    adjacency_matrix: &Array2<EdgeWeight>,
+    labels_x: &Array1<Label>,
+    dummy_x: &Array1<bool>,
+    mandatory_y: &Array1<bool>,
+    skip_x: &Array1<bool>,
+    skip_y: &Array1<bool>,
+    m: &mut Array1<bool>,
+    m_match: &mut Matching,
+) -> Array1<bool> {
+    let mut is_matched_x = Array1::<bool>::from_elem([adjacency_matrix.dim().0], false);
+    for (y, x) in initial_matching.indexed_iter() {
+        let x = *x;
+        if skip_y[y]
+            || x >= is_matched_x.dim()
+            || skip_x[x]
+            || is_matched_x[x]
+            || (dummy_x[x] && mandatory_y[y])
+            || adjacency_matrix[(x, y)] as Label != labels_x[x]
+        {
+            continue;
+        }
+        m[y] = true;
+        m_match[y] = x;
+        is_matched_x[x] = true;
+    }
+    is_matched_x
 }
 
 // =============================================================================
 // Tests
 #[cfg(test)]
 mod tests {
-    use super::{hungarian_algorithm, EdgeWeight};
+    use super::{hungarian_algorithm, run_hungarian_algorithm, EdgeWeight};
     use ndarray::{Array1, Array2};
 
     #[test]
@@ -286,7 +368,14 @@ mod tests {
         let skip_x = ndarray::arr1(&[f, f, f, t, f, f, f, f, f, f, t, t, t, f, f, f, f, f, f, f]);
 
         let (matching, score) =
-            hungarian_algorithm(&adjacency_matrix, &dummy_x, &mandatory_y, &skip_x, &skip_y);
+            hungarian_algorithm(
+                &adjacency_matrix,
+                &dummy_x,
+                &mandatory_y,
+                &skip_x,
+                &skip_y,
+                None,
+            );
 
         // Every participant must be assigned to one course place
         let mut is_assigned = Array1::<bool>::from_elem([20], false);
@@ -359,8 +448,14 @@ mod tests {
         let skip_x = Array1::from_vec(vec![false, false, true, false, false, false, false]);
         let skip_y = Array1::from_vec(vec![false, false, false, false, false, false, true]);
 
-        let (matching, score) =
-            hungarian_algorithm(&adjacency_matrix, &dummy_x, &mandatory_y, &skip_x, &skip_y);
+        let (matching, score) = hungarian_algorithm(
+            &adjacency_matrix,
+            &dummy_x,
+            &mandatory_y,
+            &skip_x,
+            &skip_y,
+            None,
+        );
 
         assert_eq!(matching.len(), 7);
         assert!(score <= 4020);
@@ -407,6 +502,7 @@ mod tests {
             &Array1::<bool>::from_elem([n], false),
             &Array1::<bool>::from_elem([n], false),
             &Array1::<bool>::from_elem([n], false),
+            None,
         );
 
         assert_eq!(matching.len(), n);
@@ -429,4 +525,64 @@ mod tests {
             assert!(ia, "participant {} is not assigned to any course place", p);
         }
     }
+
+    #[test]
+    fn seeded_matching_problem() {
+        // Every row has a unique best column, so the optimal matching only consists of edges with the row's maximum
+        // weight. Row 3 is a dummy row and column 3 is skipped.
+        let adjacency_matrix = ndarray::arr2(&[
+            [1005, 1000, 1000, 0, 0],
+            [1000, 1005, 0, 0, 1000],
+            [0, 1000, 1005, 0, 1000],
+            [0, 0, 0, 0, 0],
+        ]);
+        let dummy_x = Array1::from_vec(vec![false, false, false, true]);
+        let mandatory_y = Array1::from_vec(vec![false, false, false, false, false]);
+        let skip_x = Array1::from_vec(vec![false, false, false, false]);
+        let skip_y = Array1::from_vec(vec![false, false, false, true, false]);
+
+        let (matching, score, num_augmenting_paths) = run_hungarian_algorithm(
+            &adjacency_matrix,
+            &dummy_x,
+            &mandatory_y,
+            &skip_x,
+            &skip_y,
+            None,
+        );
+        assert_eq!(num_augmenting_paths, 4);
+        assert_eq!(score, 3015);
+
+        // Seeding the optimal matching does not require any augmenting path
+        let (seeded_matching, seeded_score, num_augmenting_paths) = run_hungarian_algorithm(
+            &adjacency_matrix,
+            &dummy_x,
+            &mandatory_y,
+            &skip_x,
+            &skip_y,
+            Some(&matching),
+        );
+        assert_eq!(num_augmenting_paths, 0);
+        assert_eq!(seeded_score, score);
+        for y in [0, 1, 2, 4] {
+            assert_eq!(seeded_matching[y], matching[y]);
+        }
+
+        // Inconsistent entries are ignored: Column 1 and row 1 are skipped and the edge (2, 4) is not part of the
+        // equality graph. Only the entry (0, 0) is used.
+        let skip_x = Array1::from_vec(vec![false, true, false, false]);
+        let skip_y = Array1::from_vec(vec![false, true, false, true, false]);
+        let (seeded_matching, seeded_score, num_augmenting_paths) = run_hungarian_algorithm(
+            &adjacency_matrix,
+            &dummy_x,
+            &mandatory_y,
+            &skip_x,
+            &skip_y,
+            Some(&Array1::from_vec(vec![0, 0, 1, 1, 2])),
+        );
+        assert_eq!(num_augmenting_paths, 2);
+        assert_eq!(seeded_score, 2010);
+        assert_eq!(seeded_matching[0], 0);
+        assert_eq!(seeded_matching[2], 2);
+        assert_eq!(seeded_matching[4], 3);
+    }
 }
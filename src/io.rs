@@ -10,12 +10,227 @@
 // specific language governing permissions and limitations under the License.
 
 pub mod cdedb;
+pub mod config;
+pub mod csv;
+pub mod json_long;
+pub mod report;
 pub mod rooms;
 pub mod simple;
 
-use super::{Assignment, Course, Participant};
+use super::{Assignment, Choice, Course, Participant};
+use crate::caobab::solution_score::{
+    build_waitlists, satisfaction_report, AssignedRank, QualityInfo,
+};
+use crate::caobab::{iter_course_assignments, room_effective_course_sizes};
+use crate::util::{max_display_width, pad_right};
+use log::{info, warn};
 use std::fmt::Write;
 
+/// Structured representation of a course assignment, grouped by course, e.g. for rendering the
+/// assignment in custom output formats. See [build_assignment_view].
+pub type AssignmentView = Vec<CourseView>;
+
+/// The participants and further information of a single course in an [AssignmentView]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CourseView {
+    /// Index of the course in the list of courses
+    pub course_index: usize,
+    /// Indexes of the participants, assigned to the course as attendees (excl. instructors),
+    /// ordered by participant index
+    pub attendees: Vec<usize>,
+    /// Indexes of the course instructors, assigned to the course, ordered by participant index
+    pub instructors: Vec<usize>,
+    /// Names of further attendees, which are not considered by the assignment algorithm
+    pub hidden: Vec<String>,
+    /// Description of the possible course rooms, if available
+    pub possible_rooms: Option<String>,
+}
+
+impl CourseView {
+    /// Total number of participants of the course, incl. instructors and hidden participants
+    pub fn num_participants(&self) -> usize {
+        self.attendees.len() + self.instructors.len() + self.hidden.len()
+    }
+}
+
+/// Group the calculated course assignment by courses, to create an [AssignmentView] with one
+/// [CourseView] per course (in the order of the courses list).
+pub fn build_assignment_view(
+    assignment: &Assignment,
+    courses: &[Course],
+    participants: &[Participant],
+    possible_rooms: Option<&[String]>,
+) -> AssignmentView {
+    iter_course_assignments(assignment, courses, participants)
+        .map(|(c, attendees, instructors)| CourseView {
+            course_index: c.index,
+            attendees: attendees.iter().map(|p| p.index).collect(),
+            instructors: instructors.iter().map(|p| p.index).collect(),
+            hidden: c.hidden_participant_names.clone(),
+            possible_rooms: possible_rooms.map(|rooms| rooms[c.index].clone()),
+        })
+        .collect()
+}
+
+/// A participant, who is assigned as an attendee to a course, which is not one of their course
+/// choices (e.g. as a required attendee), as found by [list_wrongly_assigned]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrongAssignment {
+    /// Index of the participant in the list of participants
+    pub participant_index: usize,
+    /// Index of the (un-chosen) course, the participant is assigned to
+    pub course_index: usize,
+    /// Indexes of the participant's chosen courses, which are fully booked in the assignment
+    pub full_choices: Vec<usize>,
+    /// Indexes of the participant's chosen courses, which do not take place in the assignment
+    pub cancelled_choices: Vec<usize>,
+}
+
+impl WrongAssignment {
+    /// Describe the wrong assignment in a human readable String, e.g. for a log message
+    pub fn describe(&self, participants: &[Participant], courses: &[Course]) -> String {
+        let course_names = |indexes: &[usize]| {
+            indexes
+                .iter()
+                .map(|c| courses[*c].name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        format!(
+            "Participant {} is assigned to course {}, which they did not choose (full choices: \
+             [{}], cancelled choices: [{}]).",
+            participants[self.participant_index].name,
+            courses[self.course_index].name,
+            course_names(&self.full_choices),
+            course_names(&self.cancelled_choices)
+        )
+    }
+}
+
+/// Find all participants, who are assigned to a course, which is not one of their course choices,
+/// excluding the course instructors of the respective course. For each of them, the chosen courses
+/// are listed, which are full or cancelled in the given assignment.
+pub fn list_wrongly_assigned(
+    assignment: &Assignment,
+    participants: &[Participant],
+    courses: &[Course],
+) -> Vec<WrongAssignment> {
+    let view = build_assignment_view(assignment, courses, participants, None);
+    let mut result = Vec::new();
+    for (participant_index, course_index) in assignment.iter().enumerate() {
+        let course_index = match course_index {
+            Some(c) => *c,
+            None => continue,
+        };
+        let participant = &participants[participant_index];
+        if courses[course_index]
+            .instructors
+            .contains(&participant_index)
+            || participant
+                .choices
+                .iter()
+                .any(|choice| choice.course_index == course_index)
+        {
+            continue;
+        }
+        let mut full_choices = Vec::new();
+        let mut cancelled_choices = Vec::new();
+        for choice in participant.choices.iter() {
+            let course_view = &view[choice.course_index];
+            if course_view.attendees.is_empty() && course_view.instructors.is_empty() {
+                cancelled_choices.push(choice.course_index);
+            } else if course_view.attendees.len() >= courses[choice.course_index].num_max {
+                full_choices.push(choice.course_index);
+            }
+        }
+        result.push(WrongAssignment {
+            participant_index,
+            course_index,
+            full_choices,
+            cancelled_choices,
+        });
+    }
+    result
+}
+
+/// Explain for each course, which is cancelled in the given assignment (i.e. which has no assigned
+/// participants and is not fixed), why it has been cancelled. The reason is reconstructed from the
+/// final assignment:
+/// * Too few participants (who do not instruct a course taking place) acceptably chose the course
+///   to reach its minimum size.
+/// * No room is left for the course (with its minimum size), when the courses taking place are
+///   fitted into the given `rooms` (like in the solver, see `caobab::check_rooms()`).
+/// * Otherwise, the overall assignment is better with the course cancelled.
+///
+/// Returns one human readable explanation per cancelled course, in the order of the courses list.
+pub fn explain_cancellations(
+    assignment: &Assignment,
+    participants: &[Participant],
+    courses: &[Course],
+    rooms: Option<&[usize]>,
+) -> Vec<String> {
+    let course_sizes = room_effective_course_sizes(assignment, courses);
+    let is_running: Vec<bool> = course_sizes
+        .iter()
+        .map(|(c, size)| *size > 0 || c.fixed_course)
+        .collect();
+    let is_busy_instructor: Vec<bool> = assignment
+        .iter()
+        .enumerate()
+        .map(|(p, c)| c.is_some_and(|c| courses[c].instructors.contains(&p)))
+        .collect();
+
+    // Rooms, which are left after fitting the courses taking place into the largest rooms
+    let free_rooms: Vec<usize> = rooms
+        .map(|rooms| {
+            let mut rooms = rooms.to_vec();
+            rooms.sort_unstable_by(|a, b| b.cmp(a));
+            rooms
+                .into_iter()
+                .skip(is_running.iter().filter(|r| **r).count())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut result = Vec::new();
+    for course in courses.iter().filter(|c| !is_running[c.index]) {
+        let num_choosers = participants
+            .iter()
+            .filter(|p| !is_busy_instructor[p.index] && !course.instructors.contains(&p.index))
+            .filter(|p| !p.is_forbidden_course(course.index) && p.is_available_course(course.index))
+            .filter(|p| {
+                p.choices.iter().any(|choice| {
+                    choice.course_index == course.index && p.is_acceptable_choice(choice)
+                })
+            })
+            .count();
+        let min_size = (course.room_offset
+            + course.room_factor
+                * ((course.num_min + course.num_room_relevant_instructors()) as f32))
+            .ceil() as usize;
+        let explanation = if num_choosers < course.num_min {
+            format!(
+                "Only {} available participants chose the course, but at least {} attendees are \
+                 required.",
+                num_choosers, course.num_min
+            )
+        } else if rooms.is_some() && free_rooms.first().is_none_or(|room| *room < min_size) {
+            format!(
+                "No room is left for the course (with a room-effective size of at least {}).",
+                min_size
+            )
+        } else {
+            format!(
+                "{} available participants chose the course (at least {} required), but the \
+                 overall assignment is better with the course cancelled.",
+                num_choosers, course.num_min
+            )
+        };
+        result.push(format!("{}: {}", course.name, explanation));
+    }
+    result
+}
+
 /// Format the calculated course assignment into a human readable String (e.g. to print it to
 /// stdout).
 ///
@@ -41,36 +256,39 @@ pub fn format_assignment(
     possible_rooms: Option<&[String]>,
 ) -> String {
     let mut result = String::new();
-    for c in courses.iter() {
+    for course_view in build_assignment_view(assignment, courses, participants, possible_rooms) {
+        let c = &courses[course_view.course_index];
         write!(result, "\n===== {} =====\n", c.name).unwrap();
-        let assigned: Vec<&Participant> = assignment
-            .iter()
-            .enumerate()
-            .filter(|(_, course_index)| **course_index == Some(c.index))
-            .map(|(participant_index, _)| &participants[participant_index])
-            .collect();
-        let num = assigned.len() + c.hidden_participant_names.len();
-        writeln!(result, "({} participants incl. instructors)", num).unwrap();
-        if let Some(rooms) = possible_rooms {
-            writeln!(result, "(possible course rooms: {})", rooms[c.index]).unwrap();
+        writeln!(
+            result,
+            "({} participants incl. instructors)",
+            course_view.num_participants()
+        )
+        .unwrap();
+        if let Some(ref rooms) = course_view.possible_rooms {
+            writeln!(result, "(possible course rooms: {})", rooms).unwrap();
         }
 
-        for participant in assigned {
+        // Print attendees and instructors in the order of the participants list
+        let mut assigned: Vec<(usize, bool)> = course_view
+            .attendees
+            .iter()
+            .map(|p| (*p, false))
+            .chain(course_view.instructors.iter().map(|p| (*p, true)))
+            .collect();
+        assigned.sort_unstable();
+        for (participant_index, is_instructor) in assigned {
             writeln!(
                 result,
                 "- {}{}",
-                participant.name,
-                if c.instructors.contains(&participant.index) {
-                    " (instr)"
-                } else {
-                    ""
-                }
+                participants[participant_index].name,
+                if is_instructor { " (instr)" } else { "" }
             )
             .unwrap();
         }
-        if !c.hidden_participant_names.is_empty() {
+        if !course_view.hidden.is_empty() {
             writeln!(result, "further attendees (not optimized):").unwrap();
-            for name in c.hidden_participant_names.iter() {
+            for name in course_view.hidden.iter() {
                 writeln!(result, "- {}", name).unwrap();
             }
         }
@@ -79,16 +297,860 @@ pub fn format_assignment(
     result
 }
 
+/// Build the partial course assignment, which is known from the input data without solving: Each
+/// instructor is assigned to (the first of) their courses, all other participants are unassigned.
+pub fn instructor_assignment(participants: &[Participant], courses: &[Course]) -> Assignment {
+    let mut assignment: Assignment = vec![None; participants.len()];
+    for course in courses {
+        for instructor in course.instructors.iter() {
+            assignment[*instructor].get_or_insert(course.index);
+        }
+    }
+    assignment
+}
+
+/// Format the calculated course assignment grouped by participant into a human readable String,
+/// e.g. to tell each participant their course. The participants are sorted by name. For each
+/// participant, the assigned course and the rank of this course within their choices (1 for the
+/// best choice) are given.
+///
+/// The output format will look like
+/// ```text
+/// Anton Administrator: Course A (rank 1)
+/// Bertalotta Beispiel: Course B (instr)
+/// Charlie Clown:       unassigned
+/// Dora Dünnbier:       Course A (not chosen)
+/// ```
+pub fn format_participant_schedule(
+    assignment: &Assignment,
+    participants: &[Participant],
+    courses: &[Course],
+) -> String {
+    let mut satisfaction = satisfaction_report(assignment, participants, courses);
+    satisfaction.sort_by(|a, b| {
+        participants[a.participant]
+            .name
+            .cmp(&participants[b.participant].name)
+    });
+    let name_width = max_display_width(participants.iter().map(|p| p.name.as_str())) + 1;
+    let mut result = String::new();
+    for entry in satisfaction {
+        let name = pad_right(
+            &format!("{}:", participants[entry.participant].name),
+            name_width,
+        );
+        let course = entry.assigned_course.map(|c| courses[c].name.as_str());
+        match (course, entry.assigned_rank) {
+            (Some(course), AssignedRank::Rank(rank)) => {
+                writeln!(result, "{} {} (rank {})", name, course, rank + 1)
+            }
+            (Some(course), AssignedRank::Instructor) => {
+                writeln!(result, "{} {} (instr)", name, course)
+            }
+            (Some(course), _) => writeln!(result, "{} {} (not chosen)", name, course),
+            (None, _) => writeln!(result, "{} unassigned", name),
+        }
+        .unwrap();
+    }
+    result
+}
+
+/// Format the waitlists of all courses (see [crate::caobab::solution_score::build_waitlists]) into
+/// a human readable String (e.g. to print it to stdout).
+///
+/// The output format will look like
+/// ```text
+/// Course A: Charlie Clown, Anton Administrator
+/// Course B: (cancelled)
+/// Course C: -
+/// ```
+pub fn format_waitlists(
+    assignment: &Assignment,
+    participants: &[Participant],
+    courses: &[Course],
+) -> String {
+    let waitlists = build_waitlists(assignment, participants, courses);
+    let mut course_takes_place = vec![false; courses.len()];
+    for c in assignment.iter().flatten() {
+        course_takes_place[*c] = true;
+    }
+    let name_width = max_display_width(courses.iter().map(|c| c.name.as_str())) + 1;
+    let mut result = String::new();
+    for (course, waitlist) in courses.iter().zip(waitlists) {
+        let name = pad_right(&format!("{}:", course.name), name_width);
+        if !course_takes_place[course.index] {
+            writeln!(result, "{} (cancelled)", name)
+        } else if waitlist.is_empty() {
+            writeln!(result, "{} -", name)
+        } else {
+            writeln!(
+                result,
+                "{} {}",
+                name,
+                waitlist
+                    .iter()
+                    .map(|p| participants[*p].name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            )
+        }
+        .unwrap();
+    }
+    result
+}
+
+/// Format a side-by-side comparison of two calculated course assignments (e.g. the results of two
+/// solver runs with different parameters) and their quality info into a human readable String.
+///
+/// The output format will look like
+/// ```text
+///                             first     second
+/// Solution score:            299999     299998
+/// Solution quality lack:   0.250000   0.500000
+/// Max. assigned penalty:          1          2
+/// Mean assigned penalty:      0.500      0.600
+/// 2 participants are assigned differently:
+/// - participant 1: course 0 -> course 2
+/// - participant 3: unassigned -> course 1
+/// ```
+///
+/// # Errors
+///
+/// Fails with a string error message, if the assignments have a different number of
+/// participants, i.e. they cannot be results of the same problem.
+pub fn format_result_comparison(
+    first: (&Assignment, &QualityInfo),
+    second: (&Assignment, &QualityInfo),
+) -> Result<String, String> {
+    let ((first_assignment, first_quality), (second_assignment, second_quality)) = (first, second);
+    if first_assignment.len() != second_assignment.len() {
+        return Err(format!(
+            "The assignments have a different number of participants ({} vs. {})",
+            first_assignment.len(),
+            second_assignment.len()
+        ));
+    }
+    let penalty_max = |q: &QualityInfo| {
+        q.penalty_statistics
+            .as_ref()
+            .map_or("-".to_owned(), |s| s.max.to_string())
+    };
+    let penalty_mean = |q: &QualityInfo| {
+        q.penalty_statistics
+            .as_ref()
+            .map_or("-".to_owned(), |s| format!("{:.3}", s.mean))
+    };
+    let rows = [
+        (
+            "Solution score:",
+            first_quality.solution_score.to_string(),
+            second_quality.solution_score.to_string(),
+        ),
+        (
+            "Solution quality lack:",
+            format!("{:.6}", first_quality.solution_quality),
+            format!("{:.6}", second_quality.solution_quality),
+        ),
+        (
+            "Max. assigned penalty:",
+            penalty_max(first_quality),
+            penalty_max(second_quality),
+        ),
+        (
+            "Mean assigned penalty:",
+            penalty_mean(first_quality),
+            penalty_mean(second_quality),
+        ),
+    ];
+
+    let mut result = String::new();
+    writeln!(result, "{:<22} {:>10} {:>10}", "", "first", "second").unwrap();
+    for (label, first_value, second_value) in rows.iter() {
+        writeln!(
+            result,
+            "{:<22} {:>10} {:>10}",
+            label, first_value, second_value
+        )
+        .unwrap();
+    }
+
+    let format_course = |c: &Option<usize>| match c {
+        Some(c) => format!("course {}", c),
+        None => "unassigned".to_owned(),
+    };
+    let differences: Vec<(usize, &Option<usize>, &Option<usize>)> = first_assignment
+        .iter()
+        .zip(second_assignment.iter())
+        .enumerate()
+        .filter(|(_p, (a, b))| a != b)
+        .map(|(p, (a, b))| (p, a, b))
+        .collect();
+    writeln!(
+        result,
+        "{} participants are assigned differently{}",
+        differences.len(),
+        if differences.is_empty() { "." } else { ":" }
+    )
+    .unwrap();
+    for (p, a, b) in differences {
+        writeln!(
+            result,
+            "- participant {}: {} -> {}",
+            p,
+            format_course(a),
+            format_course(b)
+        )
+        .unwrap();
+    }
+
+    Ok(result)
+}
+
+/// Lock the course placements of a previous run's assignment (e.g. of the previous day of a
+/// multi-day event) for the new run: Each participant, who attended a course in the previous
+/// assignment, becomes a required attendee of the same course in the new data (see
+/// `Course::required_attendees`) and the course is marked as fixed, so it cannot be cancelled.
+///
+/// Participants and courses of the previous run are matched with the new ones by their CdE
+/// Datenbank id, if `match_by_dbid` is true, otherwise by their name. Unassigned participants and
+/// course instructors of the previous run are not locked. Participants and courses, which cannot be
+/// found in the new data, and participants, who instruct another course in the new data, are
+/// skipped with a warning.
+///
+/// Returns the number of locked participants.
+///
+/// # Errors
+///
+/// Fails with a string error message, if the previous assignment does not match the previous
+/// participants list or if a name is ambiguous in the new data.
+pub fn lock_previous_assignment(
+    previous_assignment: &Assignment,
+    previous_participants: &[Participant],
+    previous_courses: &[Course],
+    participants: &[Participant],
+    courses: &mut [Course],
+    match_by_dbid: bool,
+) -> Result<usize, String> {
+    if previous_assignment.len() != previous_participants.len() {
+        return Err(format!(
+            "The previous assignment has {} entries, but there are {} previous participants",
+            previous_assignment.len(),
+            previous_participants.len()
+        ));
+    }
+    let mut num_locked = 0;
+    for (previous_participant, previous_course) in
+        previous_participants.iter().zip(previous_assignment.iter())
+    {
+        let previous_course = match previous_course {
+            Some(c) => previous_courses.get(*c).ok_or(format!(
+                "Previous course {} of {} does not exist",
+                c, previous_participant.name
+            ))?,
+            None => continue,
+        };
+        if previous_course
+            .instructors
+            .contains(&previous_participant.index)
+        {
+            continue;
+        }
+        let participant = find_matching(
+            participants,
+            |p| (p.dbid, p.name.as_str()),
+            (previous_participant.dbid, &previous_participant.name),
+            match_by_dbid,
+        )?;
+        let course_index = find_matching(
+            courses,
+            |c| (c.dbid, c.name.as_str()),
+            (previous_course.dbid, &previous_course.name),
+            match_by_dbid,
+        )?
+        .map(|c| c.index);
+        let (participant, course_index) = match (participant, course_index) {
+            (Some(p), Some(c)) => (p, c),
+            _ => {
+                warn!(
+                    "Cannot lock {} in course {}, since they cannot be found in the new data.",
+                    previous_participant.name, previous_course.name
+                );
+                continue;
+            }
+        };
+        if let Some(other) = courses
+            .iter()
+            .find(|c| c.instructors.contains(&participant.index))
+        {
+            if other.index != course_index {
+                warn!(
+                    "Cannot lock {} in course {}, since they instruct course {}.",
+                    participant.name, courses[course_index].name, other.name
+                );
+            }
+            continue;
+        }
+        let course = &mut courses[course_index];
+        if !course.required_attendees.contains(&participant.index) {
+            course.required_attendees.push(participant.index);
+        }
+        course.fixed_course = true;
+        num_locked += 1;
+    }
+    Ok(num_locked)
+}
+
+/// Find the unique item (participant or course) with the given CdE Datenbank id (if
+/// `match_by_dbid` is true) or name. Helper function for [lock_previous_assignment].
+fn find_matching<'a, T, F>(
+    items: &'a [T],
+    key: F,
+    (dbid, name): (usize, &str),
+    match_by_dbid: bool,
+) -> Result<Option<&'a T>, String>
+where
+    F: Fn(&T) -> (usize, &str),
+{
+    let mut matches = items.iter().filter(|item| {
+        let (item_dbid, item_name) = key(item);
+        if match_by_dbid {
+            item_dbid == dbid
+        } else {
+            item_name == name
+        }
+    });
+    let result = matches.next();
+    if matches.next().is_some() {
+        return Err(format!("The name '{}' is ambiguous in the new data", name));
+    }
+    Ok(result)
+}
+
+/// Reader wrapper, which fails with an error, if the wrapped reader provides more than a given
+/// maximum number of bytes. It is used to protect the input readers against (accidentally or
+/// maliciously) huge input files, which could exhaust the memory.
+///
+/// The nesting depth of JSON data is already limited by serde_json's recursion limit.
+pub struct SizeLimitedReader<R> {
+    inner: R,
+    max_bytes: Option<u64>,
+    remaining: u64,
+}
+
+impl<R: std::io::Read> SizeLimitedReader<R> {
+    /// Wrap the given reader. If `max_bytes` is None, the size is not limited.
+    pub fn new(inner: R, max_bytes: Option<u64>) -> Self {
+        SizeLimitedReader {
+            inner,
+            max_bytes,
+            remaining: max_bytes.unwrap_or(u64::MAX),
+        }
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for SizeLimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let max_bytes = match self.max_bytes {
+            Some(max_bytes) => max_bytes,
+            None => return self.inner.read(buf),
+        };
+        // Read one more byte than allowed, to detect oversized input
+        let len = std::cmp::min(buf.len() as u64, self.remaining.saturating_add(1)) as usize;
+        let num_read = self.inner.read(&mut buf[..len])?;
+        if num_read as u64 > self.remaining {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Input exceeds the maximum size of {} bytes", max_bytes),
+            ));
+        }
+        self.remaining -= num_read as u64;
+        Ok(num_read)
+    }
+}
+
+/// Write a file atomically, by first writing to a temporary file in the same directory and
+/// afterwards renaming it to the target path.
+///
+/// This way, the file at `path` is never left in a partially written state, e.g. if the program
+/// is interrupted while writing. The given `write_fn` is called with the opened temporary file to
+/// write the actual contents.
+pub fn write_file_atomically<F>(path: &str, write_fn: F) -> Result<(), String>
+where
+    F: FnOnce(std::fs::File) -> Result<(), String>,
+{
+    let tmp_path = format!("{}.tmp", path);
+    let file = std::fs::File::create(&tmp_path)
+        .map_err(|e| format!("Could not open file {}: {}", tmp_path, e))?;
+    write_fn(file)?;
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Could not move {} to {}: {}", tmp_path, path, e))
+}
+
+/// Apply global overrides of the course sizes to all courses.
+///
+/// If `cap_max` is given, the maximum size of every course is reduced to at most this number. If
+/// `floor_min` is given, the minimum size of every course is raised to at least this number.
+///
+/// # Errors
+///
+/// Returns an error message, if any course's minimum size exceeds its maximum size after applying
+/// the overrides.
+pub fn apply_course_size_overrides(
+    courses: &mut [Course],
+    cap_max: Option<usize>,
+    floor_min: Option<usize>,
+) -> Result<(), String> {
+    for course in courses.iter_mut() {
+        if let Some(cap) = cap_max {
+            course.num_max = std::cmp::min(course.num_max, cap);
+        }
+        if let Some(floor) = floor_min {
+            course.num_min = std::cmp::max(course.num_min, floor);
+        }
+        if course.num_min > course.num_max {
+            return Err(format!(
+                "Min size ({}) > max size ({}) of course '{}' after applying course size overrides",
+                course.num_min, course.num_max, course.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Mark the courses with the given ids (CdE Datenbank ids resp. the `dbid` of the simple input
+/// format, which defaults to the course index) as `must_run`: They are never cancelled by the
+/// assignment algorithm, but must reach their minimum size.
+///
+/// # Errors
+///
+/// Returns an error message, if no course with one of the given ids exists.
+pub fn apply_must_run(courses: &mut [Course], course_ids: &[usize]) -> Result<(), String> {
+    for id in course_ids.iter() {
+        let course = courses
+            .iter_mut()
+            .find(|c| c.dbid == *id)
+            .ok_or(format!("No course with id {} found", id))?;
+        course.must_run = true;
+    }
+    Ok(())
+}
+
+/// Merge duplicate courses, i.e. courses with identical `dbid` (e.g. due to data glitches in the
+/// import), into a single course. Otherwise, the assignment algorithm would treat the duplicates
+/// as separate (small) courses.
+///
+/// The first occurrence of each course is kept with its sizes and properties. The instructors,
+/// potential instructors, required attendees and hidden participants of all duplicates are combined and the course is
+/// fixed/must run, if any of the duplicates is. The participants' course choices (keeping only the
+/// first choice of each merged course), current, forbidden and available courses are remapped to
+/// the new course indexes. A warning is logged for each merged course.
+///
+/// Must be applied before `split_oversized_courses()`, which intentionally creates courses with
+/// identical `dbid`.
+pub fn dedup_courses(
+    courses: Vec<Course>,
+    mut participants: Vec<Participant>,
+) -> (Vec<Course>, Vec<Participant>) {
+    let mut new_index_of = Vec::with_capacity(courses.len());
+    let mut result: Vec<Course> = Vec::with_capacity(courses.len());
+    for course in courses {
+        match result.iter_mut().find(|c| c.dbid == course.dbid) {
+            Some(merged) => {
+                warn!(
+                    "Course '{}' (id {}) is a duplicate of course '{}' and is merged into it.",
+                    course.name, course.dbid, merged.name
+                );
+                new_index_of.push(merged.index);
+                for instructor in course.instructors {
+                    if !merged.instructors.contains(&instructor) {
+                        merged.instructors.push(instructor);
+                    }
+                }
+                for attendee in course.required_attendees {
+                    if !merged.required_attendees.contains(&attendee) {
+                        merged.required_attendees.push(attendee);
+                    }
+                }
+                for instructor in course.potential_instructors {
+                    if !merged.potential_instructors.contains(&instructor) {
+                        merged.potential_instructors.push(instructor);
+                    }
+                }
+                merged
+                    .hidden_participant_names
+                    .extend(course.hidden_participant_names);
+                merged.fixed_course |= course.fixed_course;
+                merged.must_run |= course.must_run;
+            }
+            None => {
+                new_index_of.push(result.len());
+                result.push(Course {
+                    index: result.len(),
+                    ..course
+                });
+            }
+        }
+    }
+    if result.len() == new_index_of.len() {
+        return (result, participants);
+    }
+
+    let remap = |indexes: &mut Vec<usize>| {
+        let mut remapped: Vec<usize> = Vec::with_capacity(indexes.len());
+        for c in indexes.iter() {
+            if !remapped.contains(&new_index_of[*c]) {
+                remapped.push(new_index_of[*c]);
+            }
+        }
+        *indexes = remapped;
+    };
+    for p in participants.iter_mut() {
+        let mut choices: Vec<Choice> = Vec::with_capacity(p.choices.len());
+        for choice in p.choices.drain(..) {
+            let course_index = new_index_of[choice.course_index];
+            if !choices.iter().any(|c| c.course_index == course_index) {
+                choices.push(Choice {
+                    course_index,
+                    ..choice
+                });
+            }
+        }
+        p.choices = choices;
+        p.current_course = p.current_course.map(|c| new_index_of[c]);
+        remap(&mut p.forbidden_courses);
+        if let Some(available_courses) = p.available_courses.as_mut() {
+            remap(available_courses);
+        }
+    }
+    (result, participants)
+}
+
+/// Split oversized courses into two instances: Each `splittable` course, which is chosen as first
+/// choice by more than `max_demand` participants, is duplicated into a new course (appended to the
+/// list of courses) with the same sizes and properties. Every participant choosing the original
+/// course also chooses the new instance with the same penalty, so the assignment algorithm can
+/// distribute the choosers across both instances (or cancel one of them).
+///
+/// The instructors of the course are distributed between both instances. If the course has less
+/// than two instructors, the new instance has no instructors, i.e. it requires a new instructor.
+/// Required attendees, fixed participants and hidden participants stay with the original course.
+/// The new instance has the same `dbid` and its name is suffixed with " (2)".
+///
+/// Returns the pairs of original and new course index of all split courses.
+pub fn split_oversized_courses(
+    courses: &mut Vec<Course>,
+    participants: &mut [Participant],
+    max_demand: usize,
+) -> Vec<(usize, usize)> {
+    let demand = course_demand_summary(participants, courses);
+    let mut result = Vec::new();
+    for original in 0..courses.len() {
+        let num_first_choices = demand[original].first().copied().unwrap_or(0);
+        if !courses[original].splittable || num_first_choices <= max_demand {
+            continue;
+        }
+        let new_index = courses.len();
+        let course = &mut courses[original];
+        let num_instructors = course.instructors.len();
+        let new_instructors = if num_instructors >= 2 {
+            course.instructors.split_off(num_instructors.div_ceil(2))
+        } else {
+            warn!(
+                "Course '{}' has less than two instructors, so its second instance requires a \
+                 new instructor.",
+                course.name
+            );
+            Vec::new()
+        };
+        // The second instance takes over the course's properties, but no fixed participants and
+        // no constraints, which only apply to the original course
+        let new_course = Course {
+            index: new_index,
+            name: format!("{} (2)", course.name),
+            instructors: new_instructors,
+            fixed_course: false,
+            cancel_cost: 0.0,
+            must_run: false,
+            splittable: false,
+            required_attendees: vec![],
+            hidden_participant_names: vec![],
+            potential_instructors: vec![],
+            min_instructors: 0,
+            ..course.clone()
+        };
+        info!(
+            "Course '{}' is split into two instances (new course index {}).",
+            course.name, new_index
+        );
+        courses.push(new_course);
+
+        for p in participants.iter_mut() {
+            if p.is_forbidden_course(original) {
+                p.forbidden_courses.push(new_index);
+            }
+            if let Some(available_courses) = p.available_courses.as_mut() {
+                if available_courses.contains(&original) {
+                    available_courses.push(new_index);
+                }
+            }
+            let new_choice = p
+                .choices
+                .iter()
+                .find(|c| c.course_index == original)
+                .map(|c| Choice {
+                    course_index: new_index,
+                    penalty: c.penalty,
+                    tier: c.tier,
+                });
+            if let Some(choice) = new_choice {
+                p.choices.push(choice);
+            }
+        }
+        result.push((original, new_index));
+    }
+    result
+}
+
+/// Raise the minimum size of every course to the given fraction of its `num_max` (rounded up), for
+/// events with the rule that courses which do not reach this fill ratio are cancelled, even if they
+/// reach their individual minimum size. The minimum size is never lowered.
+///
+/// This is equivalent to checking the fill ratio of each running course after each matching in the
+/// branch and bound algorithm: A course below its (raised) minimum size makes the solver branch into
+/// cancelling the course or enforcing it to reach the minimum size.
+pub fn apply_min_fill_ratio(courses: &mut [Course], min_fill_ratio: f64) {
+    for course in courses.iter_mut() {
+        // Subtract a small epsilon to compensate floating point errors for exact results
+        let required = (course.num_max as f64 * min_fill_ratio - 1e-9)
+            .ceil()
+            .max(0.0) as usize;
+        course.num_min = std::cmp::max(course.num_min, std::cmp::min(required, course.num_max));
+    }
+}
+
+/// Lower the maximum size of every course to the given percentage of its `num_max` (rounded down),
+/// to keep courses comfortable. The maximum size is never lowered below the course's minimum size.
+pub fn apply_max_fill_percent(courses: &mut [Course], max_fill_percent: u32) {
+    for course in courses.iter_mut() {
+        let capped = course.num_max * max_fill_percent as usize / 100;
+        course.num_max = std::cmp::min(course.num_max, std::cmp::max(capped, course.num_min));
+    }
+}
+
+/// Replace the default room size factor (1.0) and offset (0.0) of all courses, which did not
+/// specify their own value in the input data, by global defaults, e.g. for events, where all
+/// courses need more space per participant.
+///
+/// The offset is added to the course's offset, such that the additional offset for invisible
+/// participants of courses from a CdEDB export (see `cdedb::read()`) is kept.
+pub fn apply_default_room_parameters(
+    courses: &mut [Course],
+    room_factor: Option<f32>,
+    room_offset: Option<f32>,
+) {
+    for course in courses.iter_mut() {
+        if let Some(room_factor) = room_factor {
+            if !course.room_factor_given {
+                course.room_factor = room_factor;
+            }
+        }
+        if let Some(room_offset) = room_offset {
+            if !course.room_offset_given {
+                course.room_offset += room_offset;
+            }
+        }
+    }
+}
+
+/// Lower the maximum size of every course to the number of attendees (excl. instructors), who fit
+/// into the largest of the given rooms, considering the course's `room_factor`, `room_offset` and
+/// its instructors (unless `instructors_need_space` is false). This prunes course sizes early,
+/// which are impossible w.r.t. the available rooms. The maximum size is never lowered below the
+/// course's minimum size.
+///
+/// # Errors
+///
+/// Returns an error message, if not even the minimum size of a course fits into the largest room.
+pub fn cap_course_sizes_to_rooms(courses: &mut [Course], rooms: &[usize]) -> Result<(), String> {
+    let largest_room = rooms.iter().copied().max().unwrap_or(0);
+    for course in courses.iter_mut() {
+        let num_instructors = if course.instructors_need_space {
+            course.instructors.len()
+        } else {
+            0
+        };
+        let fits = |num_attendees: usize| {
+            (course.room_offset + course.room_factor * ((num_attendees + num_instructors) as f32))
+                .ceil() as usize
+                <= largest_room
+        };
+        match (course.num_min..=course.num_max).rev().find(|n| fits(*n)) {
+            Some(num_max) => course.num_max = num_max,
+            None => {
+                return Err(format!(
+                    "Min size ({}) of course '{}' does not fit into the largest room (size {})",
+                    course.num_min, course.name, largest_room
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn debug_list_of_courses(courses: &[Course]) -> String {
+    let index_width = course_index_width(courses);
     courses
         .iter()
-        .map(|c| format!("{:02} {}", c.index, c.name))
+        .map(|c| format!("{:0width$} {}", c.index, c.name, width = index_width))
         .collect::<Vec<String>>()
         .join("\n")
 }
 
+/// Number of digits required to print all course indexes aligned (at least 2)
+fn course_index_width(courses: &[Course]) -> usize {
+    std::cmp::max(2, courses.len().saturating_sub(1).to_string().len())
+}
+
+/// Count the course choices each course received: For each course (in the order of the courses
+/// list), the number of participants choosing it as their 1st, 2nd, 3rd, … choice (in the order of
+/// their list of choices). All inner vectors have the length of the longest list of choices.
+///
+/// This summary is independent of any assignment and helps anticipating course cancellations.
+pub fn course_demand_summary(participants: &[Participant], courses: &[Course]) -> Vec<Vec<usize>> {
+    let num_choices = participants
+        .iter()
+        .map(|p| p.choices.len())
+        .max()
+        .unwrap_or(0);
+    let mut counts = vec![vec![0usize; num_choices]; courses.len()];
+    for p in participants.iter() {
+        for (i, choice) in p.choices.iter().enumerate() {
+            counts[choice.course_index][i] += 1;
+        }
+    }
+    counts
+}
+
+/// Generate a human readable overview of the distribution of course choices: For each course, the
+/// number of participants choosing it as their 1st, 2nd, 3rd, … choice (in the order of their
+/// list of choices), e.g.
+///
+/// ```text
+/// 00 A course name:       3 / 1 / 0 (size 5-15, 2 instructors)
+/// 01 Another course name: 0 / 2 / 4 (size 2-10, 1 instructors)
+/// ```
+///
+/// The course names are padded to their maximum display width, such that the columns are aligned
+/// in a terminal, even for course names with non-ASCII characters.
+pub fn format_choice_statistics(participants: &[Participant], courses: &[Course]) -> String {
+    let counts = course_demand_summary(participants, courses);
+    let index_width = course_index_width(courses);
+    let name_width = max_display_width(courses.iter().map(|c| c.name.as_str())) + 1;
+    let mut result = String::new();
+    for (c, course_counts) in courses.iter().zip(counts) {
+        writeln!(
+            result,
+            "{:0width$} {} {} (size {}-{}, {} instructors)",
+            c.index,
+            pad_right(&format!("{}:", c.name), name_width),
+            course_counts
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<String>>()
+                .join(" / "),
+            c.num_min,
+            c.num_max,
+            c.instructors.len(),
+            width = index_width
+        )
+        .unwrap();
+    }
+    result
+}
+
+/// Find the courses, which are at risk of being cancelled, because they are chosen (at any rank) by
+/// fewer participants than their minimum size. Returns the course indexes.
+///
+/// This is only a rough prediction based on the [course_demand_summary]: A course may still be
+/// cancelled, if its choosers are assigned to better choices, and courses with enough choosers may
+/// be filled by instructors of cancelled courses.
+pub fn courses_at_risk(participants: &[Participant], courses: &[Course]) -> Vec<usize> {
+    course_demand_summary(participants, courses)
+        .iter()
+        .zip(courses.iter())
+        .filter(|(demand, course)| demand.iter().sum::<usize>() < course.num_min)
+        .map(|(_, course)| course.index)
+        .collect()
+}
+
+/// Generate a human readable overview of the course demand before solving: the distribution of
+/// course choices (see [format_choice_statistics]), the number of first choices of each course
+/// compared to its size (over-/undersubscription) and the courses at risk of cancellation (see
+/// [courses_at_risk]).
+pub fn format_demand_report(participants: &[Participant], courses: &[Course]) -> String {
+    let demand = course_demand_summary(participants, courses);
+    let index_width = course_index_width(courses);
+    let name_width = max_display_width(courses.iter().map(|c| c.name.as_str())) + 1;
+    let mut result = format!(
+        "Number of course choices per course (1st / 2nd / …):\n{}\nFirst choices per course \
+        compared to the course size:\n",
+        format_choice_statistics(participants, courses)
+    );
+    for (c, course_demand) in courses.iter().zip(demand.iter()) {
+        let num_first_choices = course_demand.first().copied().unwrap_or(0);
+        write!(
+            result,
+            "{:0width$} {} {} for {}-{} places",
+            c.index,
+            pad_right(&format!("{}:", c.name), name_width),
+            num_first_choices,
+            c.num_min,
+            c.num_max,
+            width = index_width
+        )
+        .unwrap();
+        if num_first_choices > c.num_max {
+            write!(
+                result,
+                " (oversubscribed by {})",
+                num_first_choices - c.num_max
+            )
+            .unwrap();
+        } else if num_first_choices < c.num_min {
+            write!(
+                result,
+                " (undersubscribed by {})",
+                c.num_min - num_first_choices
+            )
+            .unwrap();
+        }
+        result.push('\n');
+    }
+
+    let at_risk = courses_at_risk(participants, courses);
+    if at_risk.is_empty() {
+        result.push_str("\nNo courses at risk of cancellation.\n");
+    } else {
+        result.push_str("\nCourses at risk of cancellation (fewer choices than minimum size):\n");
+        for c in at_risk {
+            writeln!(
+                result,
+                "{:0width$} {}: chosen by {}, minimum size {}",
+                c,
+                courses[c].name,
+                demand[c].iter().sum::<usize>(),
+                courses[c].num_min,
+                width = index_width
+            )
+            .unwrap();
+        }
+    }
+    result
+}
+
 /// Assert that a given courses/participants data structure is consistent (in terms of object's
-/// indexes and cross referencing indexes)
+/// indexes and cross referencing indexes). Additionally, no participant may instruct more than one
+/// course, which cannot be cancelled (fixed or must-run courses), as they cannot both take place.
 pub fn assert_data_consitency(participants: &[Participant], courses: &[Course]) {
     for (i, p) in participants.iter().enumerate() {
         assert_eq!(i, p.index, "Index of {}. participant is {}", i, p.index);
@@ -100,6 +1162,38 @@ pub fn assert_data_consitency(participants: &[Participant], courses: &[Course])
                 i
             );
         }
+        if let Some(c) = p.current_course {
+            assert!(
+                c < courses.len(),
+                "Current course {} of {}. participant is invalid",
+                c,
+                i
+            );
+        }
+        for c in p.forbidden_courses.iter() {
+            assert!(
+                *c < courses.len(),
+                "Forbidden course {} of {}. participant is invalid",
+                c,
+                i
+            );
+        }
+        for c in p.available_courses.iter().flatten() {
+            assert!(
+                *c < courses.len(),
+                "Available course {} of {}. participant is invalid",
+                c,
+                i
+            );
+        }
+        if let Some(rank) = p.max_acceptable_rank {
+            assert!(
+                rank >= 1,
+                "Maximum acceptable rank {} of {}. participant is invalid",
+                rank,
+                i
+            );
+        }
     }
     for (i, c) in courses.iter().enumerate() {
         assert_eq!(i, c.index, "Index of {}. course is {}", i, c.index);
@@ -111,6 +1205,22 @@ pub fn assert_data_consitency(participants: &[Participant], courses: &[Course])
                 i
             );
         }
+        for attendee in c.required_attendees.iter() {
+            assert!(
+                *attendee < participants.len(),
+                "Required attendee {} of {}. course is invalid",
+                attendee,
+                i
+            );
+        }
+        for instr in c.potential_instructors.iter() {
+            assert!(
+                *instr < participants.len(),
+                "Potential instructor {} of {}. course is invalid",
+                instr,
+                i
+            );
+        }
 
         assert!(
             c.num_min <= c.num_max,
@@ -120,4 +1230,802 @@ pub fn assert_data_consitency(participants: &[Participant], courses: &[Course])
             c.index
         );
     }
+    let mut uncancellable_course: Vec<Option<usize>> = vec![None; participants.len()];
+    for c in courses.iter().filter(|c| c.fixed_course || c.must_run) {
+        for instr in c.instructors.iter() {
+            if let Some(other) = uncancellable_course[*instr] {
+                panic!(
+                    "Instructor {} of {}. course also instructs {}. course, but both cannot be cancelled",
+                    instr, c.index, other
+                );
+            }
+            uncancellable_course[*instr] = Some(c.index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::caobab::solution_score::{
+        satisfaction_report, AssignedRank, PenaltyStatistics, QualityInfo,
+    };
+    use crate::{choices_from_list, Assignment, Course, Participant};
+    use std::sync::Arc;
+
+    fn create_courses(sizes: &[(usize, usize)]) -> Vec<Course> {
+        sizes
+            .iter()
+            .enumerate()
+            .map(|(i, (num_min, num_max))| Course {
+                index: i,
+                dbid: i,
+                name: format!("Course {}", i),
+                num_min: *num_min,
+                num_max: *num_max,
+                instructors: vec![],
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn sizes(courses: &[Course]) -> Vec<(usize, usize)> {
+        courses.iter().map(|c| (c.num_min, c.num_max)).collect()
+    }
+
+    #[test]
+    fn test_min_fill_ratio() {
+        let mut courses = create_courses(&[(2, 10), (5, 20), (0, 6), (8, 9)]);
+        // 60% of 6 is 3.6, which is rounded up. The last course already has a larger minimum.
+        super::apply_min_fill_ratio(&mut courses, 0.6);
+        assert_eq!(sizes(&courses), vec![(6, 10), (12, 20), (4, 6), (8, 9)]);
+
+        // Course 0 is the first choice of 3 participants and reaches its minimum size, but not
+        // 60% of its maximum size, so it is cancelled.
+        let courses = create_courses(&[(2, 10), (0, 5)]);
+        let participants: Vec<Participant> = (0..5)
+            .map(|i| Participant {
+                index: i,
+                dbid: i,
+                name: format!("Participant {}", i),
+                choices: choices_from_list(if i < 3 { &[0, 1] } else { &[1, 0] }),
+                ..Default::default()
+            })
+            .collect();
+        let participants = Arc::new(participants);
+        let solve = |courses: Vec<Course>| {
+            let (result, _statistics) = crate::caobab::solve_with_config(
+                Arc::new(courses),
+                participants.clone(),
+                crate::caobab::SolveConfig::new().num_threads(1),
+            );
+            result.unwrap().0
+        };
+        assert_eq!(
+            solve(courses),
+            vec![Some(0), Some(0), Some(0), Some(1), Some(1)]
+        );
+        let mut courses = create_courses(&[(2, 10), (0, 5)]);
+        super::apply_min_fill_ratio(&mut courses, 0.6);
+        assert_eq!(solve(courses), vec![Some(1); 5]);
+    }
+
+    #[test]
+    fn test_max_fill_percent() {
+        let mut courses = create_courses(&[(2, 10), (5, 20), (0, 6), (8, 9)]);
+        super::apply_max_fill_percent(&mut courses, 100);
+        assert_eq!(sizes(&courses), vec![(2, 10), (5, 20), (0, 6), (8, 9)]);
+
+        // 90% of 6 is 5.4, which is rounded down. The last course is not capped below its minimum.
+        super::apply_max_fill_percent(&mut courses, 90);
+        assert_eq!(sizes(&courses), vec![(2, 9), (5, 18), (0, 5), (8, 8)]);
+
+        let mut courses = create_courses(&[(2, 10), (5, 20), (0, 6), (8, 9)]);
+        super::apply_max_fill_percent(&mut courses, 30);
+        assert_eq!(sizes(&courses), vec![(2, 3), (5, 6), (0, 1), (8, 8)]);
+    }
+
+    #[test]
+    fn test_default_room_parameters() {
+        let data = br#"{"courses": [
+            {"name": "A", "num_min": 0, "num_max": 5, "instructors": []},
+            {"name": "B", "num_min": 0, "num_max": 5, "instructors": [], "room_factor": 1.0},
+            {"name": "C", "num_min": 0, "num_max": 5, "instructors": [], "room_offset": 0.0,
+             "room_factor": 2.0}
+        ], "participants": []}"#;
+        let (_participants, mut courses) = super::simple::read(&data[..], false, None).unwrap();
+        super::apply_default_room_parameters(&mut courses, Some(2.5), Some(3.0));
+        // Only the defaulted values are replaced, even if the explicit value equals the default
+        let room_parameters: Vec<(f32, f32)> = courses
+            .iter()
+            .map(|c| (c.room_factor, c.room_offset))
+            .collect();
+        assert_eq!(room_parameters, vec![(2.5, 3.0), (1.0, 3.0), (2.0, 0.0)]);
+
+        // Missing global defaults do not change anything
+        super::apply_default_room_parameters(&mut courses, None, None);
+        assert_eq!(courses[0].room_factor, 2.5);
+        assert_eq!(courses[0].room_offset, 3.0);
+    }
+
+    #[test]
+    fn test_cap_course_sizes_to_rooms() {
+        let mut courses = create_courses(&[(2, 20), (5, 20), (0, 6), (3, 10)]);
+        // 2 instructors need space in the room
+        courses[1].instructors = vec![0, 1];
+        // The course needs 1.5 times the space per participant
+        courses[2].room_factor = 1.5;
+        // The instructor does not need any space, but the course has an additional offset
+        courses[3].instructors = vec![2];
+        courses[3].instructors_need_space = false;
+        courses[3].room_offset = 2.0;
+        super::cap_course_sizes_to_rooms(&mut courses, &[8, 12, 10]).unwrap();
+        assert_eq!(sizes(&courses), vec![(2, 12), (5, 10), (0, 6), (3, 10)]);
+
+        super::cap_course_sizes_to_rooms(&mut courses, &[7]).unwrap();
+        assert_eq!(sizes(&courses), vec![(2, 7), (5, 5), (0, 4), (3, 5)]);
+
+        // Not even the minimum size of course 1 (incl. instructors) fits into the rooms
+        assert!(super::cap_course_sizes_to_rooms(&mut courses, &[6, 5]).is_err());
+    }
+
+    #[test]
+    fn test_apply_must_run() {
+        let mut courses = create_courses(&[(2, 10), (5, 20), (0, 6)]);
+        courses[2].dbid = 42;
+        super::apply_must_run(&mut courses, &[42, 0]).unwrap();
+        assert!(courses[0].must_run && !courses[1].must_run && courses[2].must_run);
+        assert!(!courses[2].fixed_course);
+
+        assert!(super::apply_must_run(&mut courses, &[2]).is_err());
+    }
+
+    #[test]
+    fn test_dedup_courses() {
+        // Course 2 is a duplicate of course 0 with another instructor
+        let mut courses = create_courses(&[(0, 5), (0, 10), (0, 5)]);
+        courses[0].instructors = vec![3];
+        courses[2].dbid = 0;
+        courses[2].instructors = vec![4];
+        courses[2].must_run = true;
+        let participants: Vec<Participant> = [&[2, 1][..], &[0, 2], &[1, 2], &[], &[]]
+            .iter()
+            .enumerate()
+            .map(|(i, choices)| Participant {
+                index: i,
+                dbid: i,
+                name: format!("Participant {}", i),
+                choices: choices_from_list(choices),
+                current_course: if i == 0 { Some(2) } else { None },
+                forbidden_courses: if i == 2 { vec![0, 2] } else { vec![] },
+                ..Default::default()
+            })
+            .collect();
+
+        let (courses, participants) = super::dedup_courses(courses, participants);
+        super::assert_data_consitency(&participants, &courses);
+        assert_eq!(courses.len(), 2);
+        assert_eq!(courses[0].instructors, vec![3, 4]);
+        assert!(courses[0].must_run);
+        assert_eq!(courses[1].dbid, 1);
+        assert_eq!(courses[1].index, 1);
+        let choice_indexes =
+            |p: &Participant| -> Vec<usize> { p.choices.iter().map(|c| c.course_index).collect() };
+        assert_eq!(choice_indexes(&participants[0]), vec![0, 1]);
+        assert_eq!(participants[0].choices[1].penalty, 1);
+        assert_eq!(choice_indexes(&participants[1]), vec![0]);
+        assert_eq!(choice_indexes(&participants[2]), vec![1, 0]);
+        assert_eq!(participants[0].current_course, Some(0));
+        assert_eq!(participants[2].forbidden_courses, vec![0]);
+
+        // Without duplicates, nothing is changed
+        let (courses, participants) = super::dedup_courses(courses, participants);
+        assert_eq!(courses.len(), 2);
+        assert_eq!(choice_indexes(&participants[0]), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_split_oversized_courses() {
+        // Course 0 is the first choice of all 4 participants, but only has 2 places
+        let mut courses = create_courses(&[(0, 2), (0, 10)]);
+        courses[0].instructors = vec![4, 5];
+        let participants: Vec<Participant> = (0..6)
+            .map(|i| Participant {
+                index: i,
+                dbid: i,
+                name: format!("Participant {}", i),
+                choices: if i < 4 {
+                    choices_from_list(&[0, 1])
+                } else {
+                    vec![]
+                },
+                ..Default::default()
+            })
+            .collect();
+        // Solve the problem and count the participants getting their first choice
+        let solve = |courses: Vec<Course>, participants: Vec<Participant>| {
+            let courses = Arc::new(courses);
+            let participants = Arc::new(participants);
+            let (result, _statistics) = crate::caobab::solve_with_config(
+                courses.clone(),
+                participants.clone(),
+                crate::caobab::SolveConfig::new().num_threads(1),
+            );
+            let (assignment, _score) = result.unwrap();
+            let num_first_choices = satisfaction_report(&assignment, &participants, &courses)
+                .iter()
+                .filter(|s| s.assigned_rank == AssignedRank::Rank(0))
+                .count();
+            (
+                num_first_choices,
+                Arc::try_unwrap(courses).ok().unwrap(),
+                Arc::try_unwrap(participants).ok().unwrap(),
+            )
+        };
+        let (num_first_choices, mut courses, mut participants) = solve(courses, participants);
+        assert_eq!(num_first_choices, 2);
+
+        // Courses are only split, if they are splittable
+        assert!(super::split_oversized_courses(&mut courses, &mut participants, 3).is_empty());
+        courses[0].splittable = true;
+        // ... and their demand exceeds the threshold
+        assert!(super::split_oversized_courses(&mut courses, &mut participants, 4).is_empty());
+        assert_eq!(
+            super::split_oversized_courses(&mut courses, &mut participants, 3),
+            vec![(0, 2)]
+        );
+        super::assert_data_consitency(&participants, &courses);
+        assert_eq!(courses.len(), 3);
+        assert_eq!(courses[2].name, "Course 0 (2)");
+        assert_eq!(courses[2].dbid, courses[0].dbid);
+        assert_eq!(courses[0].instructors, vec![4]);
+        assert_eq!(courses[2].instructors, vec![5]);
+        assert_eq!(participants[0].choices[2].course_index, 2);
+        assert_eq!(participants[0].choices[2].penalty, 0);
+        assert!(participants[4].choices.is_empty());
+
+        // With both instances, all participants get their first choice
+        let (num_first_choices, _courses, _participants) = solve(courses, participants);
+        assert_eq!(num_first_choices, 4);
+    }
+
+    #[test]
+    fn test_course_size_overrides() {
+        let mut courses = create_courses(&[(2, 10), (5, 20), (0, 6)]);
+        super::apply_course_size_overrides(&mut courses, None, None).unwrap();
+        assert_eq!(sizes(&courses), vec![(2, 10), (5, 20), (0, 6)]);
+
+        super::apply_course_size_overrides(&mut courses, Some(8), None).unwrap();
+        assert_eq!(sizes(&courses), vec![(2, 8), (5, 8), (0, 6)]);
+
+        super::apply_course_size_overrides(&mut courses, None, Some(3)).unwrap();
+        assert_eq!(sizes(&courses), vec![(3, 8), (5, 8), (3, 6)]);
+
+        let mut courses = create_courses(&[(2, 10), (5, 20), (0, 6)]);
+        super::apply_course_size_overrides(&mut courses, Some(12), Some(6)).unwrap();
+        assert_eq!(sizes(&courses), vec![(6, 10), (6, 12), (6, 6)]);
+    }
+
+    #[test]
+    fn test_course_size_overrides_conflict() {
+        // The floor exceeds the cap
+        let mut courses = create_courses(&[(2, 10), (5, 20)]);
+        let result = super::apply_course_size_overrides(&mut courses, Some(4), Some(5));
+        assert!(result.is_err());
+
+        // The floor exceeds an individual course's maximum size
+        let mut courses = create_courses(&[(2, 10), (0, 6)]);
+        let result = super::apply_course_size_overrides(&mut courses, None, Some(8));
+        assert!(result.unwrap_err().contains("Course 1"));
+
+        // The cap is below an individual course's minimum size
+        let mut courses = create_courses(&[(2, 10), (5, 20)]);
+        let result = super::apply_course_size_overrides(&mut courses, Some(4), None);
+        assert!(result.unwrap_err().contains("Course 1"));
+    }
+
+    #[test]
+    fn test_checkpoint_output() {
+        let courses = Arc::new(create_courses(&[(1, 2), (1, 2)]));
+        let participants: Vec<Participant> = [[0, 1], [0, 1], [1, 0]]
+            .iter()
+            .enumerate()
+            .map(|(i, choices)| Participant {
+                index: i,
+                dbid: i,
+                name: format!("Participant {}", i),
+                choices: choices_from_list(choices),
+                ..Default::default()
+            })
+            .collect();
+        let participants = Arc::new(participants);
+
+        let path = std::env::temp_dir()
+            .join(format!(
+                "cdecao_test_checkpoint_{}.json",
+                std::process::id()
+            ))
+            .to_str()
+            .unwrap()
+            .to_owned();
+        let callback_path = path.clone();
+        let callback_participants = participants.clone();
+        let callback_courses = courses.clone();
+        let (result, _statistics) = crate::caobab::solve_with_config(
+            courses.clone(),
+            participants.clone(),
+            crate::caobab::SolveConfig::new()
+                .num_threads(1)
+                .new_best_callback(Box::new(move |assignment: &Assignment, score: u32| {
+                    let quality_info = crate::caobab::solution_score::QualityInfo::calculate(
+                        score,
+                        &callback_participants,
+                        &callback_courses,
+                        assignment,
+                        None,
+                    );
+                    super::write_file_atomically(&callback_path, |file| {
+                        super::simple::write(file, assignment, &quality_info, None)
+                    })
+                    .unwrap();
+                })),
+        );
+        let (assignment, _score) = result.expect("Expected to get a result");
+
+        // The checkpoint file should contain the final (best) assignment
+        let file = std::fs::File::open(&path).expect("Checkpoint file should exist");
+        let mut data: serde_json::Value = serde_json::from_reader(file).unwrap();
+        let checkpoint_assignment =
+            serde_json::from_value::<Assignment>(data["assignment"].take()).unwrap();
+        assert_eq!(checkpoint_assignment, assignment);
+        assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_lock_previous_assignment() {
+        // Previous run: Anton and Berta attended course 1, Emil attended course 0 (all of them got
+        // their second choice), Charlie and Dora were instructors, Zoe left the event.
+        let (previous_courses, mut previous_participants, _assignment) = create_view_problem();
+        previous_participants.push(Participant {
+            index: 5,
+            dbid: 5,
+            name: "Zoe".to_owned(),
+            choices: choices_from_list(&[2]),
+            ..Default::default()
+        });
+        let previous_assignment = vec![Some(1), Some(1), Some(0), Some(1), Some(0), Some(2)];
+
+        // New run: Participants are listed in a different order and Fritz joined
+        let mut courses = create_courses(&[(0, 5), (0, 5), (0, 5)]);
+        courses[0].instructors = vec![2];
+        courses[1].instructors = vec![1];
+        let participants: Vec<Participant> = ["Emil", "Dora", "Charlie", "Berta", "Anton", "Fritz"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Participant {
+                index: i,
+                dbid: 10 + i,
+                name: name.to_string(),
+                choices: choices_from_list(&[0, 1]),
+                ..Default::default()
+            })
+            .collect();
+
+        let num_locked = super::lock_previous_assignment(
+            &previous_assignment,
+            &previous_participants,
+            &previous_courses,
+            &participants,
+            &mut courses,
+            false,
+        )
+        .unwrap();
+        assert_eq!(num_locked, 3);
+        assert_eq!(courses[0].required_attendees, vec![0]);
+        assert_eq!(courses[1].required_attendees, vec![4, 3]);
+        assert!(courses[0].fixed_course && courses[1].fixed_course && !courses[2].fixed_course);
+
+        // The previous placements are preserved in the re-run, although everyone prefers course 0
+        let courses = Arc::new(courses);
+        let participants = Arc::new(participants);
+        let (result, _statistics) = crate::caobab::solve_with_config(
+            courses.clone(),
+            participants.clone(),
+            crate::caobab::SolveConfig::new().num_threads(1),
+        );
+        let (assignment, _score) = result.expect("Expected to get a result");
+        assert_eq!(
+            assignment,
+            vec![Some(0), Some(1), Some(0), Some(1), Some(1), Some(0)]
+        );
+
+        // Matching by dbid fails, since the new data has different ids
+        let mut courses = create_courses(&[(0, 5), (0, 5), (0, 5)]);
+        assert_eq!(
+            super::lock_previous_assignment(
+                &previous_assignment,
+                &previous_participants,
+                &previous_courses,
+                &participants,
+                &mut courses,
+                true,
+            ),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn test_list_wrongly_assigned() {
+        // Berta is required to attend course 2, although she chose course 0 (which will be full)
+        // and course 1 (which will be cancelled for lack of participants). Dora instructs course 2
+        // without choosing it.
+        let mut courses = create_courses(&[(0, 1), (2, 5), (0, 5)]);
+        courses[2].instructors = vec![3];
+        courses[2].required_attendees = vec![1];
+        let participants: Vec<Participant> = [&[0][..], &[0, 1], &[2], &[]]
+            .iter()
+            .enumerate()
+            .map(|(i, choices)| Participant {
+                index: i,
+                dbid: i,
+                name: format!("Participant {}", i),
+                choices: choices_from_list(choices),
+                ..Default::default()
+            })
+            .collect();
+
+        let courses = Arc::new(courses);
+        let participants = Arc::new(participants);
+        let (result, _statistics) = crate::caobab::solve_with_config(
+            courses.clone(),
+            participants.clone(),
+            crate::caobab::SolveConfig::new().num_threads(1),
+        );
+        let (assignment, _score) = result.expect("Expected to get a result");
+        assert_eq!(assignment, vec![Some(0), Some(2), Some(2), Some(2)]);
+
+        assert_eq!(
+            super::list_wrongly_assigned(&assignment, &participants, &courses),
+            vec![super::WrongAssignment {
+                participant_index: 1,
+                course_index: 2,
+                full_choices: vec![0],
+                cancelled_choices: vec![1],
+            }]
+        );
+
+        assert_eq!(
+            super::list_wrongly_assigned(&assignment, &participants, &courses)[0]
+                .describe(&participants, &courses),
+            "Participant Participant 1 is assigned to course Course 2, which they did not choose \
+             (full choices: [Course 0], cancelled choices: [Course 1])."
+        );
+
+        // Nobody is wrongly assigned, if everyone gets one of their choices
+        let assignment = vec![Some(0), Some(1), Some(2), Some(2)];
+        assert!(super::list_wrongly_assigned(&assignment, &participants, &courses).is_empty());
+    }
+
+    fn create_view_problem() -> (Vec<Course>, Vec<Participant>, Assignment) {
+        let mut courses = create_courses(&[(0, 5), (0, 5), (0, 5)]);
+        courses[0].instructors = vec![2];
+        courses[1].instructors = vec![3];
+        courses[1].hidden_participant_names = vec!["Hannah Hidden".to_owned()];
+        let participants = ["Anton", "Berta", "Charlie", "Dora", "Emil"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Participant {
+                index: i,
+                dbid: i,
+                name: name.to_string(),
+                choices: choices_from_list(&[0, 1]),
+                ..Default::default()
+            })
+            .collect();
+        let assignment = vec![Some(0), Some(1), Some(0), Some(1), Some(0)];
+        (courses, participants, assignment)
+    }
+
+    #[test]
+    fn test_build_assignment_view() {
+        let (courses, participants, assignment) = create_view_problem();
+        let rooms = vec!["Room A".to_owned(), "Room B".to_owned(), "".to_owned()];
+        let view = super::build_assignment_view(&assignment, &courses, &participants, Some(&rooms));
+
+        assert_eq!(
+            view,
+            vec![
+                super::CourseView {
+                    course_index: 0,
+                    attendees: vec![0, 4],
+                    instructors: vec![2],
+                    hidden: vec![],
+                    possible_rooms: Some("Room A".to_owned()),
+                },
+                super::CourseView {
+                    course_index: 1,
+                    attendees: vec![1],
+                    instructors: vec![3],
+                    hidden: vec!["Hannah Hidden".to_owned()],
+                    possible_rooms: Some("Room B".to_owned()),
+                },
+                super::CourseView {
+                    course_index: 2,
+                    attendees: vec![],
+                    instructors: vec![],
+                    hidden: vec![],
+                    possible_rooms: Some("".to_owned()),
+                },
+            ]
+        );
+        assert_eq!(view[1].num_participants(), 3);
+    }
+
+    #[test]
+    fn test_format_waitlists() {
+        let (courses, participants, mut assignment) = create_view_problem();
+        // Anton is unassigned, so they are waiting for both courses. Berta and Emil are assigned to
+        // their 2nd choice, so they are waiting for course 0.
+        assignment[4] = Some(1);
+        assignment[0] = None;
+        assert_eq!(
+            super::format_waitlists(&assignment, &participants, &courses),
+            "Course 0: Anton, Berta, Emil
+Course 1: Anton
+Course 2: (cancelled)
+"
+        );
+    }
+
+    #[test]
+    fn test_format_result_comparison() {
+        let make_quality = |solution_score, solution_quality, max, mean| QualityInfo {
+            solution_score,
+            theoretical_max_score: 300_000,
+            theoretical_min_score: 299_990,
+            solution_quality,
+            theoretical_max_quality: 0.0,
+            theoretical_min_quality: 1.0,
+            overall_quality: None,
+            penalty_statistics: Some(PenaltyStatistics {
+                min: 0,
+                max,
+                mean,
+                stddev: 0.0,
+            }),
+            normalized_quality: None,
+        };
+        let first_assignment = vec![Some(0), Some(0), Some(1), None];
+        let first_quality = make_quality(299_999, 0.25, 1, 0.5);
+        let second_assignment = vec![Some(0), Some(2), Some(1), Some(1)];
+        let second_quality = make_quality(299_998, 0.5, 2, 0.6);
+
+        assert_eq!(
+            super::format_result_comparison(
+                (&first_assignment, &first_quality),
+                (&second_assignment, &second_quality)
+            )
+            .unwrap(),
+            "                            first     second
+Solution score:            299999     299998
+Solution quality lack:   0.250000   0.500000
+Max. assigned penalty:          1          2
+Mean assigned penalty:      0.500      0.600
+2 participants are assigned differently:
+- participant 1: course 0 -> course 2
+- participant 3: unassigned -> course 1
+"
+        );
+        assert!(super::format_result_comparison(
+            (&first_assignment, &first_quality),
+            (&first_assignment, &first_quality)
+        )
+        .unwrap()
+        .ends_with("0 participants are assigned differently.\n"));
+        assert!(super::format_result_comparison(
+            (&first_assignment, &first_quality),
+            (&vec![Some(0)], &second_quality)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_course_demand_summary() {
+        let (courses, mut participants, _assignment) = create_view_problem();
+        participants[1].choices = choices_from_list(&[2, 1, 0]);
+        participants[3].choices = vec![];
+        assert_eq!(
+            super::course_demand_summary(&participants, &courses),
+            vec![vec![3, 0, 1], vec![0, 4, 0], vec![1, 0, 0]]
+        );
+        assert_eq!(
+            super::course_demand_summary(&[], &courses),
+            vec![Vec::<usize>::new(); 3]
+        );
+    }
+
+    #[test]
+    fn test_format_choice_statistics() {
+        let (courses, mut participants, _assignment) = create_view_problem();
+        participants[1].choices = choices_from_list(&[2, 1, 0]);
+        assert_eq!(
+            super::format_choice_statistics(&participants, &courses),
+            "00 Course 0: 4 / 0 / 1 (size 0-5, 1 instructors)
+01 Course 1: 0 / 5 / 0 (size 0-5, 1 instructors)
+02 Course 2: 1 / 0 / 0 (size 0-5, 0 instructors)
+"
+        );
+    }
+
+    #[test]
+    fn test_courses_at_risk() {
+        // Course 2 is chosen by a single participant only, but requires 2 attendees
+        let (mut courses, mut participants, _assignment) = create_view_problem();
+        participants[1].choices = choices_from_list(&[2, 1, 0]);
+        courses[0].num_max = 3;
+        courses[1].num_min = 2;
+        courses[2].num_min = 2;
+        assert_eq!(super::courses_at_risk(&participants, &courses), vec![2]);
+        assert_eq!(
+            super::format_demand_report(&participants, &courses),
+            "Number of course choices per course (1st / 2nd / …):
+00 Course 0: 4 / 0 / 1 (size 0-3, 1 instructors)
+01 Course 1: 0 / 5 / 0 (size 2-5, 1 instructors)
+02 Course 2: 1 / 0 / 0 (size 2-5, 0 instructors)
+
+First choices per course compared to the course size:
+00 Course 0: 4 for 0-3 places (oversubscribed by 1)
+01 Course 1: 0 for 2-5 places (undersubscribed by 2)
+02 Course 2: 1 for 2-5 places (undersubscribed by 1)
+
+Courses at risk of cancellation (fewer choices than minimum size):
+02 Course 2: chosen by 1, minimum size 2
+"
+        );
+
+        courses[2].num_min = 1;
+        assert!(super::courses_at_risk(&participants, &courses).is_empty());
+        assert!(super::format_demand_report(&participants, &courses)
+            .ends_with("\nNo courses at risk of cancellation.\n"));
+    }
+
+    #[test]
+    fn test_format_choice_statistics_alignment() {
+        let (mut courses, participants, _assignment) = create_view_problem();
+        // 'γ' and 'ä' take two bytes each, but only one column in the terminal
+        courses[0].name = "γ. Kurz".to_owned();
+        courses[1].name = "Kaffeekränzchen".to_owned();
+        courses[2].name = "Backup".to_owned();
+        // The counts must be aligned by display width, not by the number of bytes
+        assert_eq!(
+            super::format_choice_statistics(&participants, &courses),
+            "00 γ. Kurz:         5 / 0 (size 0-5, 1 instructors)
+01 Kaffeekränzchen: 0 / 5 (size 0-5, 1 instructors)
+02 Backup:          0 / 0 (size 0-5, 0 instructors)
+"
+        );
+    }
+
+    #[test]
+    fn test_explain_cancellations() {
+        let courses = create_courses(&[(1, 5), (3, 5), (1, 5)]);
+        let participants: Vec<Participant> = [[0, 1], [0, 1], [0, 2], [2, 0]]
+            .iter()
+            .enumerate()
+            .map(|(i, choices)| Participant {
+                index: i,
+                dbid: i,
+                name: format!("Participant {}", i),
+                choices: choices_from_list(choices),
+                ..Default::default()
+            })
+            .collect();
+
+        // Course 1 is only chosen by 2 participants
+        let assignment = vec![Some(0), Some(0), Some(0), Some(2)];
+        assert_eq!(
+            super::explain_cancellations(&assignment, &participants, &courses, None),
+            vec![
+                "Course 1: Only 2 available participants chose the course, but at least 3 \
+                 attendees are required."
+            ]
+        );
+
+        // Course 2 could take place, but there is only a single room or the assignment is better
+        let assignment = vec![Some(0), Some(0), Some(0), Some(0)];
+        let explanations =
+            super::explain_cancellations(&assignment, &participants, &courses, Some(&[5]));
+        assert_eq!(explanations.len(), 2);
+        assert_eq!(
+            explanations[1],
+            "Course 2: No room is left for the course (with a room-effective size of at least 1)."
+        );
+        let explanations =
+            super::explain_cancellations(&assignment, &participants, &courses, Some(&[5, 2]));
+        assert_eq!(
+            explanations[1],
+            "Course 2: 2 available participants chose the course (at least 1 required), but the \
+             overall assignment is better with the course cancelled."
+        );
+    }
+
+    #[test]
+    fn test_instructor_assignment() {
+        let (mut courses, participants, _) = create_view_problem();
+        // Dora instructs two courses and is assigned to the first one
+        courses[2].instructors = vec![3];
+        assert_eq!(
+            super::instructor_assignment(&participants, &courses),
+            vec![None, None, Some(0), Some(1), None]
+        );
+    }
+
+    #[test]
+    fn test_format_participant_schedule() {
+        let (courses, mut participants, mut assignment) = create_view_problem();
+        participants[0].name = "Zoe".to_owned();
+        // Berta gets her 2nd choice, Emil is unassigned and Anton/Zoe is in an un-chosen course
+        assignment[0] = Some(2);
+        assignment[4] = None;
+        assert_eq!(
+            super::format_participant_schedule(&assignment, &participants, &courses),
+            "Berta:   Course 1 (rank 2)
+Charlie: Course 0 (instr)
+Dora:    Course 1 (instr)
+Emil:    unassigned
+Zoe:     Course 2 (not chosen)
+"
+        );
+    }
+
+    #[test]
+    fn test_format_assignment() {
+        let (courses, participants, assignment) = create_view_problem();
+        let view = super::build_assignment_view(&assignment, &courses, &participants, None);
+        assert!(view.iter().all(|c| c.possible_rooms.is_none()));
+
+        let text = super::format_assignment(&assignment, &courses, &participants, None);
+        assert_eq!(
+            text,
+            "
+===== Course 0 =====
+(3 participants incl. instructors)
+- Anton
+- Charlie (instr)
+- Emil
+
+===== Course 1 =====
+(3 participants incl. instructors)
+- Berta
+- Dora (instr)
+further attendees (not optimized):
+- Hannah Hidden
+
+===== Course 2 =====
+(0 participants incl. instructors)
+"
+        );
+
+        // Each course's view data must be reflected in the text output
+        for course_view in view {
+            let course = &courses[course_view.course_index];
+            let section = text
+                .split(&format!("===== {} =====", course.name))
+                .nth(1)
+                .unwrap()
+                .split("=====")
+                .next()
+                .unwrap();
+            assert!(section.contains(&format!(
+                "({} participants incl. instructors)",
+                course_view.num_participants()
+            )));
+            for p in course_view.attendees {
+                assert!(section.contains(&format!("- {}\n", participants[p].name)));
+            }
+            for p in course_view.instructors {
+                assert!(section.contains(&format!("- {} (instr)\n", participants[p].name)));
+            }
+        }
+    }
 }
@@ -11,32 +11,38 @@
 
 //! IO functionality for use of this program with the CdE Datenbank export and import file formats.
 
+use crate::util::{max_display_width, pad_left};
 use crate::{
     caobab::{self, solution_score::AssignmentQualityInfo},
-    Assignment, Choice, Course, Participant,
+    Assignment, Choice, ChoiceTier, Course, Participant,
 };
 use std::collections::HashMap;
 
 use chrono::{SecondsFormat, Utc};
 use serde_json::json;
-use std::cmp::max;
 
-use log::{info, warn};
+use log::{debug, info, warn};
 
 const MINIMUM_EXPORT_VERSION: (u64, u64) = (7, 0);
-const MAXIMUM_EXPORT_VERSION: (u64, u64) = (17, std::u64::MAX);
+const MAXIMUM_EXPORT_VERSION: (u64, u64) = (17, u64::MAX);
 const OUTPUT_EXPORT_VERSION: (u64, u64) = (17, 1);
 
 pub struct ImportAmbienceData {
     event_id: u64,
-    track_id: u64,
+    /// Ids of the selected course tracks. See `read()` for merging multiple tracks.
+    track_ids: Vec<u64>,
+    /// For each course (by CdEDB id), the ids of the selected course tracks, in which it is offered
+    course_track_ids: HashMap<usize, Vec<u64>>,
+    /// For each registration (by CdEDB id), the ids of the selected course tracks, in which it is
+    /// a participant
+    registration_track_ids: HashMap<usize, Vec<u64>>,
     /// If some participants are omitted during import (e.g. through the `ignore_assigned` option),
     /// this field contains the relevant information to calculate the overall assignment quality
     pub external_assignment_quality_info: Option<caobab::solution_score::AssignmentQualityInfo>,
     /// Generation timestamp of the used CdE Datenbank export file (used for summary comment)
     export_timestamp: chrono::DateTime<chrono::Utc>,
-    /// If it is an event with multiple course tracks, the shortname of the selected track (used for
-    /// summary comment)
+    /// If it is an event with multiple course tracks, the shortname(s) of the selected track(s)
+    /// (used for summary comment)
     track_name: Option<String>,
     /// If ignore_inactive_courses was used for reading the input file, the number of ignored
     /// courses (used for summary comment)
@@ -46,15 +52,76 @@ pub struct ImportAmbienceData {
     ignored_assigned_participants: Option<usize>,
 }
 
+/// Options for reading a CdE Datenbank event export with [read]. The default options select the only
+/// course track of the event and consider all courses and participants.
+#[derive(Clone, Debug, Default)]
+pub struct ReadOptions<'a> {
+    /// The CdEDB ids of the event's course tracks, if the user specified any on the command line.
+    /// If empty and the event has only one course track, it is selected automatically. If multiple
+    /// ids are given, the tracks are merged (see [read]).
+    pub tracks: &'a [u64],
+    /// If true, courses with an inactive segment in the relevant track are not added to the
+    /// results.
+    pub ignore_inactive_courses: bool,
+    /// If true, participants who are assigned to a valid course are not added to the results. If
+    /// `ignore_inactive_courses` is true, participants assigned to a cancelled course are not
+    /// ignored.
+    pub ignore_assigned: bool,
+    /// Name of the custom course field, containing the room size factor of each course. See
+    /// `extract_room_factor_fields()`.
+    pub room_factor_field: Option<&'a str>,
+    /// Name of the custom course field, containing the room size offset of each course. See
+    /// `extract_room_factor_fields()`.
+    pub room_offset_field: Option<&'a str>,
+    /// Name of the custom registration field, containing the course ids, which must not be
+    /// assigned to the participant. See `extract_forbidden_courses()`.
+    pub forbidden_courses_field: Option<&'a str>,
+    /// Name of the custom registration field, containing the course ids, the participant may be
+    /// assigned to at all (e.g. due to their arrival dates). See `extract_available_courses()`.
+    pub available_courses_field: Option<&'a str>,
+    /// Name of the custom registration field, containing the worst course choice rank, the
+    /// participant accepts to be assigned to. See `extract_max_acceptable_rank()`.
+    pub max_rank_field: Option<&'a str>,
+    /// Name of the custom registration field, containing an explicit penalty for each of the
+    /// participant's course choices, which is used instead of the positional rank. See
+    /// `extract_choice_weights()`.
+    pub choice_weight_field: Option<&'a str>,
+    /// If true, an export with a version outside of the supported version range is not rejected,
+    /// but only a warning is logged. Use with care: the data might not be interpreted correctly.
+    pub allow_any_export_version: bool,
+    /// If true, the course choices are interpreted as an unranked set of preferences, i.e. all
+    /// chosen courses get the same penalty (0), regardless of their position in the list of
+    /// choices. The CdEDB export does not tell, whether a track's choices are meant to be ranked,
+    /// so this must be given by the user.
+    pub unranked_choices: bool,
+    /// If given, the input is rejected, if it is larger than this number of bytes. See
+    /// `io::SizeLimitedReader`.
+    pub max_input_bytes: Option<u64>,
+}
+
 /// Read course and participant data from an JSON event export of the CdE Datenbank
 ///
 /// This function takes a Reader (e.g. an open filehandle), reads its contents and interprets them
 /// as a partial event export from the CdE Datenbank 2.
 ///
-/// If the event data comprises multiple course tracks and no track id is selected via the `track`
-/// parameter, this function fails with "Event has more than one course track". Otherwise, the only
+/// If the event data comprises multiple course tracks and no track id is selected via the `tracks`
+/// option, this function fails with "Event has more than one course track". Otherwise, the only
 /// existing track is selected automatically.
 ///
+/// If multiple track ids are selected, the courses and registrations of these tracks are merged
+/// into a single course assignment problem, e.g. for events, where the tracks are only an
+/// administrative split of a common pool of courses:
+/// * A course is considered, if it is offered in any of the tracks. It is only considered to be
+///   cancelled, if it is cancelled in all of these tracks.
+/// * A registration is considered, if it has the status *Participant* in the part of any of the
+///   tracks. Its course choices are the union of its choices in these tracks. If a course is
+///   chosen in multiple tracks with different ranks, the best rank is used for its penalty. The
+///   assigned and instructed course are taken from the first of the tracks (in the given order),
+///   in which the participant is assigned resp. instructs a course.
+/// * The calculated assignment is written to all of the tracks by `write()`: Each participant is
+///   assigned to their course in all of the tracks they participate in and each course is
+///   activated or cancelled in all of the tracks it is offered in.
+///
 /// Only registrations with status *Participant* in the relevant part (the part of the selected
 /// course track) are considered. Existing course assignments and cancelled course segments are
 /// ignored—they will be overridden by importing the result file into the CdE Datenbank.
@@ -64,37 +131,44 @@ pub struct ImportAmbienceData {
 /// If no maximum size is given for a course, we assume num_max = 25 (incl. instructors).
 /// If no minimum size is given for a course, we assume num_min = 0 (excl. instructors).
 ///
-/// # Arguments
-///
-/// * reader: The Reader (e.g. open file) to read the json data from
-/// * track: The CdEDB id of the event's course track, if the user specified one on the command
-///   line. If None and the event has only one course track, it is selected automatically.
-/// * ignore_inactive_courses: If true, courses with an inactive segment in the relevant track are
-///   not added to the results.
-/// * ignore_assigned: If true, participants who are assigned to a valid course are not added to the
-///   results. If `ignore_inactive_courses` is true, participants assigned to a cancelled course are
-///   not ignored.
+/// See [ReadOptions] for the available options.
 ///
 /// # Errors
 ///
 /// Fails with a string error message to be displayed to the user, if
-/// * the file has invalid JSON syntax (the string representation of the serde_json error is returned)
+/// * the file has invalid JSON syntax or is nested too deeply (the string representation of the
+///   serde_json error is returned)
+/// * the file is larger than `max_input_bytes`
 /// * the file is not a 'partial' CdEDB export
 /// * the file has no version within the supported version range (MINIMUM_/MAXIMUM_EXPORT_VERSION)
+///   and `allow_any_export_version` is false
 /// * any expected entry in the json fields is missing
 /// * the event has no course tracks
-/// * the event has more than one course track, but no `track` is given.
+/// * the event has more than one course track, but no `tracks` are given
+/// * any of the given `tracks` does not exist.
 ///
 pub fn read<R: std::io::Read>(
     reader: R,
-    track: Option<u64>,
-    ignore_inactive_courses: bool,
-    ignore_assigned: bool,
-    room_factor_field: Option<&str>,
-    room_offset_field: Option<&str>,
+    options: &ReadOptions,
 ) -> Result<(Vec<Participant>, Vec<Course>, ImportAmbienceData), String> {
-    let data: serde_json::Value = serde_json::from_reader(reader).map_err(|err| err.to_string())?;
-    check_export_type_and_version(&data)?;
+    let ReadOptions {
+        tracks,
+        ignore_inactive_courses,
+        ignore_assigned,
+        room_factor_field,
+        room_offset_field,
+        forbidden_courses_field,
+        available_courses_field,
+        max_rank_field,
+        choice_weight_field,
+        allow_any_export_version,
+        unranked_choices,
+        max_input_bytes,
+    } = *options;
+    let data: serde_json::Value =
+        serde_json::from_reader(super::SizeLimitedReader::new(reader, max_input_bytes))
+            .map_err(|err| err.to_string())?;
+    check_export_type_and_version(&data, allow_any_export_version).map_err(|e| e.to_string())?;
     let export_timestamp: chrono::DateTime<chrono::Utc> = data["timestamp"]
         .as_str()
         .ok_or("No 'timestamp' string found in data.".to_owned())?
@@ -109,12 +183,26 @@ pub fn read<R: std::io::Read>(
         .get("parts")
         .and_then(|v| v.as_object())
         .ok_or("No 'parts' object found in event.")?;
-    let (part_id, track_id, track_data) = find_track(parts_data, track)?;
+    let selected_tracks = if tracks.is_empty() {
+        vec![find_track(parts_data, None)?]
+    } else {
+        tracks
+            .iter()
+            .map(|t| find_track(parts_data, Some(*t)))
+            .collect::<Result<Vec<_>, String>>()?
+    };
+    let track_ids: Vec<u64> = selected_tracks.iter().map(|(_, t, _)| *t).collect();
+    let unchosen_course_penalty = selected_tracks
+        .iter()
+        .map(|(_, _, track_data)| penalty_for_unchosen_course(track_data))
+        .max()
+        .unwrap_or(1);
 
     // Parse courses
     let mut courses = Vec::new();
     let mut skipped_course_ids = Vec::new(); // Used to ignore KeyErrors for those later
     let mut num_ignored_inactive_courses = 0usize;
+    let mut course_track_ids = HashMap::new();
     let courses_data = data
         .get("courses")
         .and_then(|v| v.as_object())
@@ -125,7 +213,7 @@ pub fn read<R: std::io::Read>(
             .map_err(|e: std::num::ParseIntError| e.to_string())?;
 
         let (course_name, course_status, num_min, num_max, sort_key) =
-            parse_course_base_data(course_id, course_data, track_id)?;
+            parse_course_base_data(course_id, course_data, &track_ids)?;
 
         if matches!(course_status, CourseStatus::NotOffered) {
             skipped_course_ids.push(course_id);
@@ -137,12 +225,22 @@ pub fn read<R: std::io::Read>(
             continue;
         }
 
+        course_track_ids.insert(
+            course_id,
+            track_ids
+                .iter()
+                .copied()
+                .filter(|t| course_data["segments"].get(format!("{}", t)).is_some())
+                .collect::<Vec<u64>>(),
+        );
+
         let (room_factor, room_offset) = extract_room_factor_fields(
             course_data,
             &course_name,
             room_factor_field,
             room_offset_field,
         )?;
+        let (room_factor_given, room_offset_given) = (room_factor.is_some(), room_offset.is_some());
 
         courses.push((
             sort_key,
@@ -151,12 +249,27 @@ pub fn read<R: std::io::Read>(
                 dbid: course_id,
                 name: course_name,
                 num_min,
+                sizes_include_instructors: false,
                 num_max,
                 instructors: Vec::new(),
-                room_factor,
-                room_offset,
+                room_factor: room_factor.unwrap_or(1.0),
+                room_offset: room_offset.unwrap_or(0.0),
+                room_factor_given,
+                room_offset_given,
                 fixed_course: false,
+                keep_if_possible: false,
+                cancel_cost: 0.0,
+                must_run: false,
+                waitlist_capacity: None,
+                splittable: false,
+                preferred_size: None,
+                required_attendees: vec![],
+                instructors_need_space: true,
+                shareable: false,
                 hidden_participant_names: Vec::new(),
+                potential_instructors: vec![],
+                min_instructors: 0,
+                slot: None,
             },
         ));
     }
@@ -185,6 +298,7 @@ pub fn read<R: std::io::Read>(
     let mut registrations = Vec::new();
     let mut external_assignment_quality_info = AssignmentQualityInfo::new(0, vec![]);
     let mut num_ignored_assigned_registrations = 0usize;
+    let mut registration_track_ids = HashMap::new();
     let registrations_data = data
         .get("registrations")
         .and_then(|v| v.as_object())
@@ -195,18 +309,37 @@ pub fn read<R: std::io::Read>(
             .parse()
             .map_err(|e: std::num::ParseIntError| e.to_string())?;
 
-        let (reg_state, reg_name) = extract_participant_base_data(reg_id, reg_data, part_id)?;
-
-        if !matches!(reg_state, ParticipationState::Participant) {
+        // Find the selected tracks, in which the registration is a participant
+        let mut reg_name = String::new();
+        let mut participant_track_ids = Vec::new();
+        for (part_id, track_id, _track_data) in selected_tracks.iter() {
+            let (reg_state, name) = extract_participant_base_data(reg_id, reg_data, *part_id)?;
+            reg_name = name;
+            if matches!(reg_state, ParticipationState::Participant) {
+                participant_track_ids.push(*track_id);
+            }
+        }
+        if participant_track_ids.is_empty() {
             continue;
         }
 
-        let participant_course_data = parse_participant_course_data(
-            &format!("{} (id={})", reg_name, reg_id),
-            reg_data,
-            track_id,
-            &course_index_by_id,
-        )?;
+        let choice_weights = extract_choice_weights(reg_data, &reg_name, choice_weight_field)?;
+        let participant_course_data = merge_participant_course_data(
+            &reg_name,
+            participant_track_ids
+                .iter()
+                .map(|track_id| {
+                    parse_participant_course_data(
+                        &format!("{} (id={})", reg_name, reg_id),
+                        reg_data,
+                        *track_id,
+                        &course_index_by_id,
+                        unranked_choices,
+                        choice_weights.as_deref(),
+                    )
+                })
+                .collect::<Result<Vec<_>, String>>()?,
+        );
 
         // Skip already assigned participants (if wanted)
         if ignore_assigned {
@@ -224,7 +357,7 @@ pub fn read<R: std::io::Read>(
                             penalty_for_assigned_course_choice(
                                 course_index,
                                 &participant_course_data.choices,
-                                track_data,
+                                unchosen_course_penalty,
                             ),
                         );
                     }
@@ -238,14 +371,36 @@ pub fn read<R: std::io::Read>(
         }
 
         // Filter out registrations without choices
-        if participant_course_data.choices.is_empty()
-            && participant_course_data.instructed_course_index.is_none()
-        {
-            warn!(
-                "Ignoring participant '{}', who has no (valid) course choices.",
-                reg_name
-            );
-            continue;
+        if participant_course_data.choices.is_empty() {
+            match (
+                participant_course_data.choices_kind,
+                participant_course_data.instructed_course_index,
+            ) {
+                (ChoicesKind::Empty, Some(_)) => debug!(
+                    "Participant '{}' made no course choices and is only considered as course \
+                     instructor.",
+                    reg_name
+                ),
+                (ChoicesKind::Empty, None) => {
+                    warn!(
+                        "Ignoring participant '{}', who made no course choices.",
+                        reg_name
+                    );
+                    continue;
+                }
+                (_, Some(_)) => debug!(
+                    "Participant '{}' only chose cancelled courses and is only considered as \
+                     course instructor.",
+                    reg_name
+                ),
+                (_, None) => {
+                    warn!(
+                        "Ignoring participant '{}', who only chose cancelled courses.",
+                        reg_name
+                    );
+                    continue;
+                }
+            }
         }
 
         // Add course instructors to courses
@@ -253,11 +408,31 @@ pub fn read<R: std::io::Read>(
             courses[instructed_course_index].instructors.push(i);
         }
 
+        let forbidden_courses = extract_forbidden_courses(
+            reg_data,
+            &reg_name,
+            forbidden_courses_field,
+            &course_index_by_id,
+        )?;
+        let available_courses = extract_available_courses(
+            reg_data,
+            &reg_name,
+            available_courses_field,
+            &course_index_by_id,
+        )?;
+        let max_acceptable_rank = extract_max_acceptable_rank(reg_data, &reg_name, max_rank_field)?;
+
+        registration_track_ids.insert(reg_id as usize, participant_track_ids);
         registrations.push(crate::Participant {
             index: i,
             dbid: reg_id as usize,
             name: reg_name,
             choices: participant_course_data.choices,
+            current_course: participant_course_data.assigned_course_index,
+            forbidden_courses,
+            available_courses,
+            max_acceptable_rank,
+            group: None,
         });
         i += 1;
     }
@@ -269,6 +444,11 @@ pub fn read<R: std::io::Read>(
             invisible_course_participants[course.index].1,
         )
     }
+    if ignore_assigned {
+        for problem in check_adapted_courses(&courses, &registrations) {
+            warn!("{}", problem);
+        }
+    }
 
     Ok((
         registrations,
@@ -278,19 +458,30 @@ pub fn read<R: std::io::Read>(
                 .get("id")
                 .and_then(|v| v.as_u64())
                 .ok_or("No event 'id' found in data")?,
-            track_id,
+            track_name: if tracks.is_empty() {
+                None
+            } else {
+                Some(
+                    selected_tracks
+                        .iter()
+                        .map(|(_, _, track_data)| {
+                            track_data
+                                .get("shortname")
+                                .and_then(|v| v.as_str())
+                                .ok_or("Missing 'shortname' in event track.")
+                        })
+                        .collect::<Result<Vec<&str>, &str>>()?
+                        .join(", "),
+                )
+            },
+            track_ids,
+            course_track_ids,
+            registration_track_ids,
             external_assignment_quality_info: if ignore_assigned {
                 Some(external_assignment_quality_info)
             } else {
                 None
             },
-            track_name: track.is_some().then_some(
-                track_data
-                    .get("shortname")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_owned())
-                    .ok_or("Missing 'shortname' in event track.")?,
-            ),
             export_timestamp,
             ignored_inactive_courses: ignore_inactive_courses
                 .then_some(num_ignored_inactive_courses),
@@ -300,22 +491,89 @@ pub fn read<R: std::io::Read>(
     ))
 }
 
+/// Reason for rejecting a CdEDB export file, as returned by [check_export_type_and_version]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportCheckError {
+    /// The data lacks the export kind or version fields or they have an invalid type, i.e. it is
+    /// probably not a CdEDB export at all. Contains a description of the problem.
+    Malformed(String),
+    /// The data is a CdEDB export of another kind than a "partial" export. Contains the kind.
+    WrongKind(String),
+    /// The export's version (contained) is older than the oldest supported version
+    TooOld((u64, u64)),
+    /// The export's version (contained) is newer than the latest supported version
+    TooNew((u64, u64)),
+}
+
+impl std::fmt::Display for ExportCheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportCheckError::Malformed(description) => write!(
+                f,
+                "{} Is this a correct CdE Datenbank export file?",
+                description
+            ),
+            ExportCheckError::WrongKind(kind) => write!(
+                f,
+                "The given JSON file is no 'Partial Export' of the CdE Datenbank, but of kind \
+                '{}'. Please use the partial export of the event.",
+                kind
+            ),
+            ExportCheckError::TooOld(version) => write!(
+                f,
+                "{} It is older than the oldest supported version. Please create a new export \
+                from the CdE Datenbank. Use --allow-export-version to try reading it anyway, but \
+                the data might not be interpreted correctly.",
+                version_range_message(*version)
+            ),
+            ExportCheckError::TooNew(version) => write!(
+                f,
+                "{} It is newer than the latest supported version, so it might contain changes, \
+                which are unknown to this version of cdecao. Please check for a newer cdecao \
+                release. If the changes of the export format do not affect the course assignment \
+                data, use --allow-export-version to read it anyway.",
+                version_range_message(*version)
+            ),
+        }
+    }
+}
+
+/// Describe the given export version and the supported range of export versions
+fn version_range_message(version: (u64, u64)) -> String {
+    format!(
+        "The given CdE Datenbank export has version {}.{}, but only versions from {}.{} to {}.x \
+        are supported.",
+        version.0,
+        version.1,
+        MINIMUM_EXPORT_VERSION.0,
+        MINIMUM_EXPORT_VERSION.1,
+        MAXIMUM_EXPORT_VERSION.0,
+    )
+}
+
 /**
  * Check the JSON data structure for the correct CdEDB export type ("partial") and version number
  *
  * # Arguments
  * - `data` -- The full JSON document parsed from the CdEDB export file
+ * - `allow_any_version` -- If true, a version outside of the supported range only results in a
+ *   warning instead of an error
  *
  * # Result
- * Returns Ok(()) when the data indicates correct format and version; an error message otherwise.
+ * Returns Ok(()) when the data indicates correct format and version; the classified reason for
+ * rejecting the data otherwise. Its string representation is a verbose explanation for the user.
  */
-fn check_export_type_and_version(data: &serde_json::Value) -> Result<(), String> {
+pub fn check_export_type_and_version(
+    data: &serde_json::Value,
+    allow_any_version: bool,
+) -> Result<(), ExportCheckError> {
+    let malformed = |description: &str| ExportCheckError::Malformed(description.to_owned());
     let export_kind = data
         .get("kind")
         .and_then(|v| v.as_str())
-        .ok_or("No 'kind' field found in data. Is this a correct CdEdb export file?")?;
+        .ok_or_else(|| malformed("No 'kind' field found in data."))?;
     if export_kind != "partial" {
-        return Err("The given JSON file is no 'Partial Export' of the CdE Datenbank".to_owned());
+        return Err(ExportCheckError::WrongKind(export_kind.to_owned()));
     }
     let export_version = if let Some(version_tag) = data.get("EVENT_SCHEMA_VERSION") {
         version_tag
@@ -341,24 +599,26 @@ fn check_export_type_and_version(data: &serde_json::Value) -> Result<(), String>
         // Support for old export schema version field
         version_tag
             .as_u64()
-            .ok_or("'CDEDB_EXPORT_EVENT_VERSION' is not an u64 value")
+            .ok_or("'CDEDB_EXPORT_EVENT_VERSION' is not an u64 value.")
             .map(|v| (v, 0))
     } else {
-        Err(
-            "No 'EVENT_SCHEMA_VERSION' field found in data. Is this a correct CdEdb \
-            export file?",
-        )
-    }?;
-    if export_version < MINIMUM_EXPORT_VERSION || export_version > MAXIMUM_EXPORT_VERSION {
-        return Err(format!(
-            "The given CdE Datenbank Export is not within the supported version range \
-            [{}.{},{}.{}]",
-            MINIMUM_EXPORT_VERSION.0,
-            MINIMUM_EXPORT_VERSION.1,
-            MAXIMUM_EXPORT_VERSION.0,
-            MAXIMUM_EXPORT_VERSION.1
-        ));
+        Err("No 'EVENT_SCHEMA_VERSION' field found in data.")
+    }
+    .map_err(malformed)?;
+    let error = if export_version < MINIMUM_EXPORT_VERSION {
+        ExportCheckError::TooOld(export_version)
+    } else if export_version > MAXIMUM_EXPORT_VERSION {
+        ExportCheckError::TooNew(export_version)
+    } else {
+        return Ok(());
+    };
+    if !allow_any_version {
+        return Err(error);
     }
+    warn!(
+        "{} Continuing anyway, as requested. The data might not be interpreted correctly!",
+        version_range_message(export_version)
+    );
 
     Ok(())
 }
@@ -375,21 +635,23 @@ enum CourseStatus {
  * # Arguments
  * - `course_id` -- CdEDB id of the course for error message output
  * - `course_data` -- The course object from the CdEDB JSON export
- * - `track_id` -- The id of the event track for which the data shall be extracted
+ * - `track_ids` -- The ids of the (merged) event tracks for which the data shall be extracted
  *
  * # Return value
  * Returns a tuple (course_name, status, num_min, num_max).
  *
  * - The `course_name` is meant for stdout output and error messages. Thus, it is composed from the
- * course number and short name.
- * - `status` is determined with regard to the given `track_id`.
+ *   course number and short name.
+ * - `status` is determined with regard to the given `track_ids`: The course takes place, if it
+ *   takes place in any of the tracks; it is cancelled, if it is offered but cancelled in all of
+ *   the tracks, in which it is offered.
  * - `num_min` and `num_max` are -- according to the CdEDB convention -- counted excl. instructors
  * - A `sort_key` (based on the course number) for a simple sorting of the courses
  */
 fn parse_course_base_data(
     course_id: usize,
     course_data: &serde_json::Value,
-    track_id: u64,
+    track_ids: &[u64],
 ) -> Result<(String, CourseStatus, usize, usize, String), String> {
     let course_segments_data = course_data
         .get("segments")
@@ -399,18 +661,19 @@ fn parse_course_base_data(
             course_id
         ))?;
 
-    let course_status = if let Some(v) = course_segments_data.get(&format!("{}", track_id)) {
-        let v = v
-            .as_bool()
-            .ok_or(format!("Segment of course {} is not a boolean.", course_id))?;
-        if v {
-            CourseStatus::TakesPlace
-        } else {
-            CourseStatus::Cancelled
+    let mut course_status = CourseStatus::NotOffered;
+    for track_id in track_ids {
+        if let Some(v) = course_segments_data.get(&format!("{}", track_id)) {
+            let v = v
+                .as_bool()
+                .ok_or(format!("Segment of course {} is not a boolean.", course_id))?;
+            if v {
+                course_status = CourseStatus::TakesPlace;
+            } else if matches!(course_status, CourseStatus::NotOffered) {
+                course_status = CourseStatus::Cancelled;
+            }
         }
-    } else {
-        CourseStatus::NotOffered
-    };
+    }
 
     let course_nr = course_data
         .get("nr")
@@ -458,7 +721,7 @@ fn parse_course_base_data(
  * # Return value
  * Returns a tuple (room_factor, room_offset).
  *
- * Each of the values will be set to default (1.0 resp. 0.0) if
+ * Each of the values will be None, i.e. the default (1.0 resp. 0.0) is to be used, if
  * - no field name is specified or
  * - the field is not present in this course's data
  * - the field contains data in a wrong data type.
@@ -468,35 +731,35 @@ fn extract_room_factor_fields(
     course_name: &str,
     room_factor_field: Option<&str>,
     room_offset_field: Option<&str>,
-) -> Result<(f32, f32), String> {
+) -> Result<(Option<f32>, Option<f32>), String> {
     let fields = course_data
         .get("fields")
         .and_then(|v| v.as_object())
         .ok_or(format!("No 'fields' found for course {}", course_name))?;
     let room_factor = if let Some(field_name) = room_factor_field {
         match fields.get(field_name).and_then(|v| v.as_f64()) {
-            Some(v) => v,
+            Some(v) => Some(v as f32),
             None => {
-                warn!("No numeric field '{}' as room_factor field found in course '{}'. Using the default value 1.0.", field_name, course_name);
-                1.0
+                warn!("No numeric field '{}' as room_factor field found in course '{}'. Using the default value.", field_name, course_name);
+                None
             }
         }
     } else {
-        1.0
+        None
     };
     let room_offset = if let Some(field_name) = room_offset_field {
         match fields.get(field_name).and_then(|v| v.as_f64()) {
-            Some(v) => v,
+            Some(v) => Some(v as f32),
             None => {
-                warn!("No numeric field '{}' as room_offset field found in course '{}'. Using the default value 0.0.", field_name, course_name);
-                0.0
+                warn!("No numeric field '{}' as room_offset field found in course '{}'. Using the default value.", field_name, course_name);
+                None
             }
         }
     } else {
-        0.0
+        None
     };
 
-    Ok((room_factor as f32, room_offset as f32))
+    Ok((room_factor, room_offset))
 }
 
 enum ParticipationState {
@@ -573,11 +836,234 @@ fn extract_participant_base_data(
     Ok((participation_state, reg_name))
 }
 
+/**
+ * Determine the list of forbidden courses for a participant from the registration's JSON object
+ *
+ * # Arguments
+ * - `reg_data` -- The registration object from the CdEDB JSON export
+ * - `reg_name` -- name of the participant for error logging output
+ * - `forbidden_courses_field` -- Name of the CdEDB custom registration field, containing the
+ *   forbidden courses, if given by the user. The field is expected to be a string field with a
+ *   comma-separated list of CdEDB course ids.
+ * - `course_index_by_id` -- Map of CdEDB course ids to course indexes (or None for ignored courses)
+ *
+ * # Return value
+ * Returns the list of indexes of the forbidden courses. Ignored courses are skipped. The list is
+ * empty, if no field name is specified or the field is empty or not present in the registration's
+ * data.
+ *
+ * # Errors
+ * Fails, if the field contains anything else than a list of course ids of known courses.
+ */
+fn extract_forbidden_courses(
+    reg_data: &serde_json::Value,
+    reg_name: &str,
+    forbidden_courses_field: Option<&str>,
+    course_index_by_id: &HashMap<u64, Option<usize>>,
+) -> Result<Vec<usize>, String> {
+    Ok(extract_course_list(
+        reg_data,
+        reg_name,
+        forbidden_courses_field,
+        course_index_by_id,
+        "forbidden",
+    )?
+    .unwrap_or_default())
+}
+
+/**
+ * Determine the list of available courses for a participant from the registration's JSON object
+ *
+ * The field has the same format as the forbidden courses field (see
+ * `extract_forbidden_courses()`), i.e. a string field with a comma-separated list of CdEDB course
+ * ids.
+ *
+ * # Return value
+ * Returns the list of indexes of the courses, the participant may be assigned to. Ignored courses
+ * are skipped. Returns None (all courses are available), if no field name is specified or the field
+ * is empty or not present in the registration's data.
+ *
+ * # Errors
+ * Fails, if the field contains anything else than a list of course ids of known courses.
+ */
+fn extract_available_courses(
+    reg_data: &serde_json::Value,
+    reg_name: &str,
+    available_courses_field: Option<&str>,
+    course_index_by_id: &HashMap<u64, Option<usize>>,
+) -> Result<Option<Vec<usize>>, String> {
+    extract_course_list(
+        reg_data,
+        reg_name,
+        available_courses_field,
+        course_index_by_id,
+        "available",
+    )
+}
+
+/// Parse a comma-separated list of CdEDB course ids from the given custom registration field into
+/// a list of course indexes (skipping ignored courses). Returns None, if no field name is given or
+/// the field is empty or not present. `list_name` is used for error messages only.
+fn extract_course_list(
+    reg_data: &serde_json::Value,
+    reg_name: &str,
+    field_name: Option<&str>,
+    course_index_by_id: &HashMap<u64, Option<usize>>,
+    list_name: &str,
+) -> Result<Option<Vec<usize>>, String> {
+    let field_name = match field_name {
+        Some(f) => f,
+        None => return Ok(None),
+    };
+    let value = match reg_data.get("fields").and_then(|v| v.get(field_name)) {
+        None | Some(serde_json::Value::Null) => return Ok(None),
+        Some(v) => v.as_str().ok_or(format!(
+            "Field '{}' of participant {} is not a string field",
+            field_name, reg_name
+        ))?,
+    };
+    let mut course_ids = value
+        .split(',')
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .peekable();
+    if course_ids.peek().is_none() {
+        return Ok(None);
+    }
+    let mut result = Vec::new();
+    for course_id in course_ids {
+        let course_id: u64 = course_id.parse().map_err(|e: std::num::ParseIntError| {
+            format!(
+                "Invalid course id '{}' in {} courses of participant {}: {}",
+                course_id, list_name, reg_name, e
+            )
+        })?;
+        let course_index = *course_index_by_id.get(&course_id).ok_or(format!(
+            "Course id {} in {} courses of participant {} does not exist",
+            course_id, list_name, reg_name
+        ))?;
+        if let Some(course_index) = course_index {
+            result.push(course_index);
+        }
+    }
+    Ok(Some(result))
+}
+
+/**
+ * Determine the maximum acceptable course choice rank of a participant from the registration's
+ * JSON object
+ *
+ * # Arguments
+ * - `reg_data` -- The registration object from the CdEDB JSON export
+ * - `reg_name` -- name of the participant for error logging output
+ * - `max_rank_field` -- Name of the CdEDB custom registration field, containing the maximum
+ *   acceptable rank (1 for the first choice), if given by the user. The field is expected to be an
+ *   integer field.
+ *
+ * # Return value
+ * Returns the maximum acceptable rank or None, if no field name is specified or the field is empty
+ * or not present in the registration's data.
+ *
+ * # Errors
+ * Fails, if the field contains anything else than a positive integer.
+ */
+fn extract_max_acceptable_rank(
+    reg_data: &serde_json::Value,
+    reg_name: &str,
+    max_rank_field: Option<&str>,
+) -> Result<Option<u32>, String> {
+    let field_name = match max_rank_field {
+        Some(f) => f,
+        None => return Ok(None),
+    };
+    match reg_data.get("fields").and_then(|v| v.get(field_name)) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(v) => v
+            .as_u64()
+            .filter(|rank| *rank >= 1 && *rank <= u32::MAX as u64)
+            .map(|rank| Some(rank as u32))
+            .ok_or(format!(
+                "Field '{}' of participant {} is not a positive integer",
+                field_name, reg_name
+            )),
+    }
+}
+
+/**
+ * Determine the explicit penalties of a participant's course choices from the registration's JSON
+ * object
+ *
+ * # Arguments
+ * - `reg_data` -- The registration object from the CdEDB JSON export
+ * - `reg_name` -- name of the participant for error logging output
+ * - `choice_weight_field` -- Name of the CdEDB custom registration field, containing the choice
+ *   weights, if given by the user. The field is expected to be a string field with a
+ *   comma-separated list of non-negative integers, e.g. "0,2,5": one penalty for each entry of the
+ *   registration track's list of choices (in the same order). Like the positional ranks, lower
+ *   values are better, i.e. 0 is the best possible choice.
+ *
+ * # Return value
+ * Returns the list of penalties or None, if no field name is specified or the field is empty or
+ * not present in the registration's data. In this case, the positional ranks are used.
+ *
+ * # Errors
+ * Fails, if the field contains anything else than a list of non-negative integers.
+ */
+fn extract_choice_weights(
+    reg_data: &serde_json::Value,
+    reg_name: &str,
+    choice_weight_field: Option<&str>,
+) -> Result<Option<Vec<u32>>, String> {
+    let field_name = match choice_weight_field {
+        Some(f) => f,
+        None => return Ok(None),
+    };
+    let value = match reg_data.get("fields").and_then(|v| v.get(field_name)) {
+        None | Some(serde_json::Value::Null) => return Ok(None),
+        Some(v) => v.as_str().ok_or(format!(
+            "Field '{}' of participant {} is not a string field",
+            field_name, reg_name
+        ))?,
+    };
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+    value
+        .split(',')
+        .map(|weight| {
+            weight.trim().parse::<u32>().map_err(|e| {
+                format!(
+                    "Invalid choice weight '{}' of participant {}: {}",
+                    weight.trim(),
+                    reg_name,
+                    e
+                )
+            })
+        })
+        .collect::<Result<Vec<u32>, String>>()
+        .map(Some)
+}
+
 /// Helper struct for the result of `parse_participant_course_data()`
 struct ParticipantCourseData {
     assigned_course_index: Option<usize>,
     instructed_course_index: Option<usize>,
     choices: Vec<Choice>,
+    choices_kind: ChoicesKind,
+}
+
+/// Kind of a participant's list of course choices in the CdEDB export, to distinguish participants
+/// who did not make any course choices from participants who only chose cancelled (or ignored)
+/// courses. When merging tracks, the greatest kind of all tracks is used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ChoicesKind {
+    /// The list of choices is empty, i.e. the participant did not choose any course (e.g. because
+    /// they are a course instructor)
+    Empty,
+    /// The participant only chose courses, which are cancelled or ignored
+    OnlyCancelled,
+    /// The participant chose at least one course, which is considered by the assignment
+    Valid,
 }
 
 /**
@@ -589,6 +1075,14 @@ struct ParticipantCourseData {
  * - `track_id` -- The id of the event track for which the data shall be extracted
  * - `courses_by_id` -- A Map (CdEDB course id) -> (course index or None). Iff a course exists but
  *   ignored by the assignment algorithm, the map shall contain a None value for this course id.
+ * - `unranked_choices` -- If true, all course choices get the same penalty (0) instead of a
+ *   penalty according to their position in the list of choices.
+ * - `choice_weights` -- Explicit penalties for the entries of the list of choices (see
+ *   `extract_choice_weights()`). If given, they are used instead of the positional penalties
+ *   (and `unranked_choices`). Their number must match the number of entries.
+ *
+ * Each entry of the list of choices may also be a list of course ids, which are equally preferred
+ * alternatives at the same rank. They all get the penalty of the entry's position.
  *
  * All courses are referenced by index according to `courses_by_id`.
  * assigned_course and instructed_course are None, iff no course is assigned/instructed or the
@@ -599,6 +1093,8 @@ fn parse_participant_course_data(
     reg_data: &serde_json::Value,
     track_id: u64,
     courses_by_id: &HashMap<u64, Option<usize>>,
+    unranked_choices: bool,
+    choice_weights: Option<&[u32]>,
 ) -> Result<ParticipantCourseData, String> {
     let registration_track_data = reg_data
         .get("tracks")
@@ -658,53 +1154,135 @@ fn parse_participant_course_data(
 
     let choices_data = registration_track_data
         .get("choices")
-        .and_then(|v| v.as_array())
         .ok_or(format!(
             "No 'choices' found in registration track data of {}",
             registration_name
+        ))?
+        .as_array()
+        .ok_or(format!(
+            "'choices' in registration track data of {} is not a list",
+            registration_name
         ))?;
 
+    if let Some(weights) = choice_weights {
+        if weights.len() != choices_data.len() {
+            return Err(format!(
+                "Registration {} has {} choice weights, but {} course choices",
+                registration_name,
+                weights.len(),
+                choices_data.len()
+            ));
+        }
+    }
+
+    // Each entry is either a single course id or a list of equally preferred alternative course ids,
+    // which share the same rank (and thus penalty)
     let mut choices = Vec::<Choice>::with_capacity(choices_data.len());
     for (i, v) in choices_data.iter().enumerate() {
-        let course_id = v
-            .as_u64()
-            .ok_or(format!("Course choice {:?} is no integer.", v))?;
-        let course_index = courses_by_id.get(&course_id).ok_or(format!(
-            "Course choice {} of registration {} does not exist.",
-            course_id, registration_name
-        ))?;
-        if let Some(c) = course_index {
-            choices.push(Choice {
-                course_index: *c,
-                penalty: penalty_for_choice(i),
-            });
+        let alternatives = match v.as_array() {
+            Some(alternatives) => alternatives.iter().collect(),
+            None => vec![v],
+        };
+        for alternative in alternatives {
+            let course_id = alternative
+                .as_u64()
+                .ok_or(format!("Course choice {:?} is no integer.", alternative))?;
+            let course_index = courses_by_id.get(&course_id).ok_or(format!(
+                "Course choice {} of registration {} does not exist.",
+                course_id, registration_name
+            ))?;
+            if let Some(c) = course_index {
+                choices.push(Choice {
+                    course_index: *c,
+                    penalty: match choice_weights {
+                        Some(weights) => weights[i],
+                        None if unranked_choices => 0,
+                        None => penalty_for_choice(i),
+                    },
+                    tier: ChoiceTier::Preferred,
+                });
+            }
         }
     }
 
-    if choices.is_empty() && !choices_data.is_empty() {
+    let choices_kind = if choices_data.is_empty() {
+        ChoicesKind::Empty
+    } else if choices.is_empty() {
         info!(
             "Participant {}, only chose cancelled courses.",
             registration_name
         );
-    }
+        ChoicesKind::OnlyCancelled
+    } else {
+        ChoicesKind::Valid
+    };
 
     Ok(ParticipantCourseData {
         assigned_course_index,
         instructed_course_index,
         choices,
+        choices_kind,
     })
 }
 
+/**
+ * Merge the course choice and assignment information of a participant from multiple (merged)
+ * course tracks, as returned by `parse_participant_course_data()` for each track.
+ *
+ * The choices are united, using the lowest penalty for courses, which are chosen in multiple
+ * tracks, and sorted by penalty. The assigned and instructed course are taken from the first
+ * track, in which they are present. For a single track, its data is returned unchanged.
+ */
+fn merge_participant_course_data(
+    registration_name: &str,
+    track_course_data: Vec<ParticipantCourseData>,
+) -> ParticipantCourseData {
+    let mut result = ParticipantCourseData {
+        assigned_course_index: None,
+        instructed_course_index: None,
+        choices: Vec::new(),
+        choices_kind: ChoicesKind::Empty,
+    };
+    for data in track_course_data {
+        if let (Some(a), Some(b)) = (result.instructed_course_index, data.instructed_course_index) {
+            if a != b {
+                warn!(
+                    "Participant {} instructs different courses in the merged tracks. Only the \
+                     first one is considered.",
+                    registration_name
+                );
+            }
+        }
+        result.assigned_course_index = result.assigned_course_index.or(data.assigned_course_index);
+        result.instructed_course_index = result
+            .instructed_course_index
+            .or(data.instructed_course_index);
+        result.choices_kind = std::cmp::max(result.choices_kind, data.choices_kind);
+        for choice in data.choices {
+            match result
+                .choices
+                .iter_mut()
+                .find(|c| c.course_index == choice.course_index)
+            {
+                Some(c) => c.penalty = std::cmp::min(c.penalty, choice.penalty),
+                None => result.choices.push(choice),
+            }
+        }
+    }
+    result.choices.sort_by_key(|c| c.penalty);
+    result
+}
+
 fn penalty_for_assigned_course_choice(
     assigned_course: usize,
     course_choices: &[Choice],
-    track_data: &serde_json::Map<String, serde_json::Value>,
+    unchosen_course_penalty: u32,
 ) -> u32 {
     course_choices
         .iter()
-        .position(|c| c.course_index == assigned_course)
-        .map(penalty_for_choice)
-        .unwrap_or(penalty_for_unchosen_course(track_data))
+        .find(|c| c.course_index == assigned_course)
+        .map(|c| c.penalty)
+        .unwrap_or(unchosen_course_penalty)
 }
 
 /// Calculate penalty (edge weight offset) for a course choice based on its index in the list of
@@ -732,38 +1310,77 @@ fn penalty_for_unchosen_course(track_data: &serde_json::Map<String, serde_json::
 /// * the fixed_course flag is set if there are any invisible participants (attendees + instructors)
 /// * the min and max size of the course are reduced by the number of invisible attendees
 /// * the room offset is increased by the number of invisible participants (attendees + instructors)
+///   times the room factor. As the offset depends on the room factor then, the room factor is
+///   marked as explicitly given, such that it is not changed by a global default afterwards.
+///
+/// The course sizes are expected excl. instructors (the CdEDB convention, i.e. the course's
+/// `sizes_include_instructors` flag is not set), so invisible instructors do not reduce them.
 fn adapt_course_for_invisible_participants(
     course: &mut Course,
     invisible_instructors: usize,
     invisible_attendees: usize,
 ) {
     let total_invisible_course_participants = invisible_instructors + invisible_attendees;
-    course.num_min = if invisible_attendees > course.num_min {
-        0
-    } else {
-        course.num_min - invisible_attendees
-    };
-    course.num_max = if invisible_attendees > course.num_max {
-        0
-    } else {
-        course.num_max - invisible_attendees
-    };
+    course.num_min = course.num_min.saturating_sub(invisible_attendees);
+    course.num_max = course.num_max.saturating_sub(invisible_attendees);
     course.fixed_course = total_invisible_course_participants != 0;
     course.room_offset += total_invisible_course_participants as f32 * course.room_factor;
+    if total_invisible_course_participants != 0 {
+        course.room_factor_given = true;
+    }
+}
+
+/// Check the courses, adapted by `adapt_course_for_invisible_participants()`, for confusing
+/// situations, caused by the invisible participants, e.g. when the `ignore_assigned` option is used
+/// with already overfull courses.
+///
+/// Returns a human readable description for each course
+/// * whose adjusted `num_max` is 0, but which is still chosen by any of the (visible) participants
+/// * whose adjusted `num_min` is greater than its adjusted `num_max`.
+fn check_adapted_courses(courses: &[Course], participants: &[Participant]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for course in courses.iter() {
+        if course.num_max == 0 {
+            let num_choosers = participants
+                .iter()
+                .filter(|p| p.choices.iter().any(|c| c.course_index == course.index))
+                .count();
+            if num_choosers > 0 {
+                problems.push(format!(
+                    "Course {} has no places left due to already assigned participants, but is \
+                     chosen by {} of the remaining participants.",
+                    course.name, num_choosers
+                ));
+            }
+        }
+        if course.num_min > course.num_max {
+            problems.push(format!(
+                "Course {} has a minimum size of {} but a maximum size of {} after considering \
+                 the already assigned participants.",
+                course.name, course.num_min, course.num_max
+            ));
+        }
+    }
+    problems
 }
 
 /// Write the calculated course assignment as a CdE Datenbank partial import JSON string to a Writer
 /// (e.g. an output file).
+///
+/// If `include_instructors` is true, the registration ids of the instructors of each course taking
+/// place are restated as an additional "instructors" list in the course's entry. Only instructors,
+/// who are actually assigned to the course, are listed. Cancelled courses have no such list.
 #[allow(clippy::too_many_arguments)]
 pub fn write<W: std::io::Write>(
     writer: W,
     assignment: &Assignment,
     participants: &[Participant],
     courses: &[Course],
-    ambience_data: ImportAmbienceData,
+    ambience_data: &ImportAmbienceData,
     quality_info: &caobab::solution_score::QualityInfo,
     possible_rooms_field: Option<&str>,
     possible_rooms: Option<&[String]>,
+    include_instructors: bool,
 ) -> Result<(), String> {
     // Calculate course sizes
     let mut course_size = vec![0usize; courses.len()];
@@ -779,11 +1396,17 @@ pub fn write<W: std::io::Write>(
             (
                 format!("{}", participants[pid].dbid),
                 json!({
-                "tracks": {
-                    format!("{}", ambience_data.track_id): {
-                        "course_id": courses[cid.unwrap()].dbid
-                    }
-                }}),
+                "tracks": ambience_data
+                    .registration_track_ids
+                    .get(&participants[pid].dbid)
+                    .into_iter()
+                    .flatten()
+                    .map(|track_id| (
+                        format!("{}", track_id),
+                        json!({"course_id": courses[cid.unwrap()].dbid})
+                    ))
+                    .collect::<serde_json::Map<String, serde_json::Value>>()
+                }),
             )
         })
         .collect::<serde_json::Map<String, serde_json::Value>>();
@@ -793,9 +1416,17 @@ pub fn write<W: std::io::Write>(
         .enumerate()
         .map(|(cid, size)| {
             let mut value = json!({
-            "segments": {
-                format!("{}", ambience_data.track_id): *size > 0 || courses[cid].fixed_course
-            }});
+            "segments": ambience_data
+                .course_track_ids
+                .get(&courses[cid].dbid)
+                .into_iter()
+                .flatten()
+                .map(|track_id| (
+                    format!("{}", track_id),
+                    json!(*size > 0 || courses[cid].fixed_course)
+                ))
+                .collect::<serde_json::Map<String, serde_json::Value>>()
+            });
             if let Some(rooms_field) = possible_rooms_field {
                 if let Some(rooms) = possible_rooms {
                     // serde_json::Map::from_iter([(rooms_field.to_owned(), rooms[cid])].into_iter())
@@ -807,6 +1438,18 @@ pub fn write<W: std::io::Write>(
                     );
                 }
             }
+            if include_instructors && *size > 0 {
+                let instructors: Vec<usize> = courses[cid]
+                    .instructors
+                    .iter()
+                    .filter(|i| assignment[**i] == Some(cid))
+                    .map(|i| participants[*i].dbid)
+                    .collect();
+                value
+                    .as_object_mut()
+                    .unwrap()
+                    .insert("instructors".into(), json!(instructors));
+            }
             (format!("{}", courses[cid].dbid), value)
         })
         .collect::<serde_json::Map<String, serde_json::Value>>();
@@ -816,7 +1459,7 @@ pub fn write<W: std::io::Write>(
         "kind": "partial",
         "id": ambience_data.event_id,
         "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, false),
-        "summary": generate_summery_comment(&ambience_data, quality_info),
+        "summary": generate_summery_comment(ambience_data, quality_info),
         "courses": courses_json,
         "registrations": registrations_json
     });
@@ -840,6 +1483,7 @@ fn generate_summery_comment(
     }
     let ignore_options_info = ignore_options_info.join(" and");
     let track_info = match ambience_data.track_name.as_ref() {
+        Some(t) if ambience_data.track_ids.len() > 1 => format!(" for merged course tracks {}", t),
         Some(t) => format!(" for course track {}", t),
         None => "".to_owned(),
     };
@@ -946,7 +1590,8 @@ fn find_track(
 /// * parts_data: The JSON 'parts' object from the 'event' part of the export file
 ///
 /// # Returns
-/// A String containing a listing of the track ids and names to be printed to the command line
+/// A String containing a listing of the track ids and names to be printed to the command line. The
+/// track ids are right-aligned by their display width.
 ///
 /// # Errors
 /// Returns an error String, when
@@ -955,7 +1600,6 @@ fn track_summary(
     parts_data: &serde_json::Map<String, serde_json::Value>,
 ) -> Result<String, String> {
     let mut tracks = Vec::new();
-    let mut max_id_len = 0;
 
     for (_part_id, part) in parts_data {
         let tracks_data = part
@@ -963,7 +1607,6 @@ fn track_summary(
             .and_then(|v| v.as_object())
             .ok_or("Missing 'tracks' in event part.")?;
         for (track_id, track) in tracks_data {
-            max_id_len = max(max_id_len, track_id.len());
             tracks.push((
                 track_id,
                 track
@@ -979,9 +1622,10 @@ fn track_summary(
     }
 
     tracks.sort_by_key(|e| e.2);
+    let max_id_len = max_display_width(tracks.iter().map(|(id, _, _)| id.as_str()));
     let result = tracks
         .iter()
-        .map(|(id, title, _)| format!("{:>1$} : {2}", id, max_id_len, title))
+        .map(|(id, title, _)| format!("{} : {}", pad_left(id, max_id_len), title))
         .collect::<Vec<_>>()
         .join("\n");
 
@@ -995,8 +1639,14 @@ mod tests {
     #[test]
     fn parse_testaka_sitzung() {
         let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
-        let (participants, courses, import_ambience) =
-            super::read(&data[..], Some(3), false, false, None, None).unwrap();
+        let (participants, courses, import_ambience) = super::read(
+            &data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                ..Default::default()
+            },
+        )
+        .unwrap();
 
         super::super::assert_data_consitency(&participants, &courses);
         // Check courses
@@ -1036,18 +1686,171 @@ mod tests {
             vec![
                 Choice {
                     course_index: find_course_by_id(&courses, 4).unwrap().index,
-                    penalty: 0
+                    penalty: 0,
+                    tier: crate::ChoiceTier::Preferred,
                 },
                 Choice {
                     course_index: find_course_by_id(&courses, 2).unwrap().index,
-                    penalty: 1
+                    penalty: 1,
+                    tier: crate::ChoiceTier::Preferred,
                 }
             ]
         );
 
+        // Emilia is assigned to course 'α. Heldentum' (id=1)
+        assert_eq!(
+            find_participant_by_id(&participants, 2)
+                .unwrap()
+                .current_course,
+            Some(find_course_by_id(&courses, 1).unwrap().index)
+        );
+
         // Check import_ambience
         assert_eq!(import_ambience.event_id, 1);
-        assert_eq!(import_ambience.track_id, 3);
+        assert_eq!(import_ambience.track_ids, vec![3]);
+    }
+
+    #[test]
+    fn parse_testaka_unranked_choices() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+        let (ranked_participants, _courses, _import_ambience) = super::read(
+            &data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let (participants, courses, _import_ambience) = super::read(
+            &data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                unranked_choices: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        super::super::assert_data_consitency(&participants, &courses);
+
+        assert!(ranked_participants
+            .iter()
+            .any(|p| p.choices.iter().any(|c| c.penalty > 0)));
+        assert_eq!(participants.len(), ranked_participants.len());
+        for (p, ranked) in participants.iter().zip(ranked_participants.iter()) {
+            // The same courses are chosen, but all with penalty 0
+            assert_eq!(
+                p.choices.iter().map(|c| c.course_index).collect::<Vec<_>>(),
+                ranked
+                    .choices
+                    .iter()
+                    .map(|c| c.course_index)
+                    .collect::<Vec<_>>()
+            );
+            assert!(
+                p.choices.iter().all(|c| c.penalty == 0),
+                "Participant {} has ranked choices {:?}",
+                p.name,
+                p.choices
+            );
+        }
+    }
+
+    #[test]
+    fn parse_testaka_choice_alternatives() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+        let mut json_data: serde_json::Value = serde_json::from_slice(data).unwrap();
+        json_data["registrations"]["1"]["tracks"]["3"]["choices"] = serde_json::json!([[1, 4], 2]);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let (participants, courses, _import_ambience) = super::read(
+            &modified_data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        super::super::assert_data_consitency(&participants, &courses);
+
+        // The alternatives share the first rank, the following choice is the second rank
+        let participant = find_participant_by_id(&participants, 1).unwrap();
+        let choices: Vec<(usize, u32)> = participant
+            .choices
+            .iter()
+            .map(|c| (courses[c.course_index].dbid, c.penalty))
+            .collect();
+        assert_eq!(choices, vec![(1, 0), (4, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn test_absent_empty_and_cancelled_choices() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+        let json_data: serde_json::Value = serde_json::from_slice(data).unwrap();
+        // Courses 3 and 4 are cancelled/ignored
+        let courses_by_id: std::collections::HashMap<u64, Option<usize>> = [
+            (1, Some(0)),
+            (2, Some(1)),
+            (3, None),
+            (4, None),
+            (5, Some(4)),
+        ]
+        .iter()
+        .copied()
+        .collect();
+        let parse = |choices: Option<serde_json::Value>| {
+            let mut reg_data = json_data["registrations"]["1"].clone();
+            let track_data = reg_data["tracks"]["3"].as_object_mut().unwrap();
+            match choices {
+                Some(choices) => track_data.insert("choices".to_owned(), choices),
+                None => track_data.remove("choices"),
+            };
+            super::parse_participant_course_data(
+                "Test (id=1)",
+                &reg_data,
+                3,
+                &courses_by_id,
+                false,
+                None,
+            )
+        };
+
+        // Absent or malformed list of choices
+        let error = parse(None).err().unwrap();
+        assert!(error.contains("No 'choices'"), "{}", error);
+        let error = parse(Some(serde_json::json!(null))).err().unwrap();
+        assert!(error.contains("is not a list"), "{}", error);
+
+        // No choices at all
+        let result = parse(Some(serde_json::json!([]))).unwrap();
+        assert!(result.choices.is_empty());
+        assert_eq!(result.choices_kind, super::ChoicesKind::Empty);
+
+        // Only cancelled courses
+        let result = parse(Some(serde_json::json!([3, 4]))).unwrap();
+        assert!(result.choices.is_empty());
+        assert_eq!(result.choices_kind, super::ChoicesKind::OnlyCancelled);
+
+        // At least one valid choice
+        let result = parse(Some(serde_json::json!([3, 1]))).unwrap();
+        assert_eq!(result.choices.len(), 1);
+        assert_eq!(result.choices_kind, super::ChoicesKind::Valid);
+
+        // When merging tracks, valid choices in any track take precedence
+        let merged = super::merge_participant_course_data(
+            "Test (id=1)",
+            vec![
+                parse(Some(serde_json::json!([]))).unwrap(),
+                parse(Some(serde_json::json!([3]))).unwrap(),
+            ],
+        );
+        assert_eq!(merged.choices_kind, super::ChoicesKind::OnlyCancelled);
+        let merged = super::merge_participant_course_data(
+            "Test (id=1)",
+            vec![
+                parse(Some(serde_json::json!([3]))).unwrap(),
+                parse(Some(serde_json::json!([1]))).unwrap(),
+            ],
+        );
+        assert_eq!(merged.choices_kind, super::ChoicesKind::Valid);
     }
 
     #[test]
@@ -1057,16 +1860,28 @@ mod tests {
         // Check that only participants are parsed (no not_applied, applied, waitlist, guest,
         // cancelled or rejected registration parts)
         // Morgenkreis
-        let (participants, courses, _import_ambience) =
-            super::read(&data[..], Some(1), false, false, None, None).unwrap();
+        let (participants, courses, _import_ambience) = super::read(
+            &data[..],
+            &super::ReadOptions {
+                tracks: &[1],
+                ..Default::default()
+            },
+        )
+        .unwrap();
         super::super::assert_data_consitency(&participants, &courses);
         assert_eq!(courses.len(), 5);
         assert_eq!(participants.len(), 2);
         assert!(find_participant_by_id(&participants, 3).is_some());
 
         // Kaffee
-        let (participants, courses, _import_ambience) =
-            super::read(&data[..], Some(2), false, false, None, None).unwrap();
+        let (participants, courses, _import_ambience) = super::read(
+            &data[..],
+            &super::ReadOptions {
+                tracks: &[2],
+                ..Default::default()
+            },
+        )
+        .unwrap();
         super::super::assert_data_consitency(&participants, &courses);
         assert_eq!(courses.len(), 5);
         assert_eq!(participants.len(), 2);
@@ -1074,23 +1889,148 @@ mod tests {
     }
 
     #[test]
-    fn test_no_track_error() {
+    fn test_merge_tracks() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+        let (participants, courses, import_ambience) = super::read(
+            &data[..],
+            &super::ReadOptions {
+                tracks: &[2, 3],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        super::super::assert_data_consitency(&participants, &courses);
+
+        // Course 'γ. Kurz' (id=3) is only offered in track 'Kaffeekränzchen' (id=2), course
+        // 'α. Heldentum' (id=1) only in track 'Sitzung' (id=3)
+        assert_eq!(courses.len(), 6);
+        assert!(find_course_by_id(&courses, 3).is_some());
+        assert!(find_course_by_id(&courses, 1).is_some());
+        // Anton, Emilia and Inga only participate in the second part (with track 'Sitzung'), Garcia
+        // and Akira in both parts and Bertålotta has no course choices
+        assert_eq!(participants.len(), 5);
+
+        // Akira's choices are united: Course 2 (1st choice in track 2), course 1 (1st choice in
+        // track 3) and course 4 (2nd choice in track 3)
+        let akira = find_participant_by_id(&participants, 5).unwrap();
+        assert_eq!(
+            akira.choices,
+            vec![
+                Choice {
+                    course_index: find_course_by_id(&courses, 2).unwrap().index,
+                    penalty: 0,
+                    tier: crate::ChoiceTier::Preferred,
+                },
+                Choice {
+                    course_index: find_course_by_id(&courses, 1).unwrap().index,
+                    penalty: 0,
+                    tier: crate::ChoiceTier::Preferred,
+                },
+                Choice {
+                    course_index: find_course_by_id(&courses, 4).unwrap().index,
+                    penalty: 1,
+                    tier: crate::ChoiceTier::Preferred,
+                },
+            ]
+        );
+        // The assigned course is taken from the first track
+        assert_eq!(
+            akira.current_course,
+            Some(find_course_by_id(&courses, 2).unwrap().index)
+        );
+        assert_eq!(
+            import_ambience.track_name,
+            Some("Kaffee, Sitzung".to_owned())
+        );
+
+        // The assignment is written to all tracks of the participants and courses
+        let assignment: Assignment = participants
+            .iter()
+            .map(|p| Some(p.choices[0].course_index))
+            .collect();
+        let quality_info = crate::caobab::solution_score::QualityInfo::calculate(
+            0,
+            &participants,
+            &courses,
+            &assignment,
+            None,
+        );
+        let mut buffer = Vec::<u8>::new();
+        super::write(
+            &mut buffer,
+            &assignment,
+            &participants,
+            &courses,
+            &import_ambience,
+            &quality_info,
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        let data: serde_json::Value = serde_json::from_reader(&buffer[..]).unwrap();
+        let track_keys = |value: &serde_json::Value| {
+            value
+                .as_object()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(
+            track_keys(&data["registrations"]["5"]["tracks"]),
+            vec!["2", "3"]
+        );
+        assert_eq!(track_keys(&data["registrations"]["1"]["tracks"]), vec!["3"]);
+        assert_eq!(track_keys(&data["courses"]["3"]["segments"]), vec!["2"]);
+        assert_eq!(
+            track_keys(&data["courses"]["4"]["segments"]),
+            vec!["2", "3"]
+        );
+    }
+
+    #[test]
+    fn test_no_track_error() {
         let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
 
         // Check that only participants are parsed (no not_applied, applied, waitlist, guest,
         // cancelled or rejected registration parts)
         // Morgenkreis
-        let result = super::read(&data[..], None, false, false, None, None);
+        let result = super::read(&data[..], &super::ReadOptions::default());
         assert!(result.is_err());
         assert!(result.err().unwrap().contains("Kaffeekränzchen"));
     }
 
+    #[test]
+    fn test_track_summary_alignment() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+
+        // Add a track with a longer id and a multibyte title
+        let parts_data = json_data["event"]["parts"].as_object_mut().unwrap();
+        let (_part_id, part) = parts_data.iter_mut().next().unwrap();
+        part["tracks"]["1234"] = serde_json::json!({
+            "title": "Große Sitzung 🎉",
+            "shortname": "Groß",
+            "sortkey": 4,
+        });
+
+        // The track ids must be aligned by display width
+        assert_eq!(
+            super::track_summary(json_data["event"]["parts"].as_object().unwrap()).unwrap(),
+            "   1 : Morgenkreis (Erste Hälfte)
+   2 : Kaffeekränzchen (Erste Hälfte)
+   3 : Arbeitssitzung (Zweite Hälfte)
+1234 : Große Sitzung 🎉"
+        );
+    }
+
     #[test]
     fn test_single_track_event() {
         let data = include_bytes!("test_ressources/cyta_partial_export_event.json");
 
         let (participants, courses, _import_ambience) =
-            super::read(&data[..], None, false, false, None, None).unwrap();
+            super::read(&data[..], &super::ReadOptions::default()).unwrap();
         super::super::assert_data_consitency(&participants, &courses);
         println!(
             "{:?}",
@@ -1131,8 +2071,15 @@ mod tests {
         p4_choices.extend_from_slice(&[2.into(), 1.into()]);
         let modified_data = serde_json::to_vec(&json_data).unwrap();
 
-        let (participants, courses, import_ambience) =
-            super::read(&modified_data[..], Some(3), false, true, None, None).unwrap();
+        let (participants, courses, import_ambience) = super::read(
+            &modified_data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                ignore_assigned: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
         super::super::assert_data_consitency(&participants, &courses);
 
         assert_eq!(courses.len(), 5);
@@ -1157,6 +2104,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ignore_assigned_overfull_course() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+
+        // Akira and Inga are assigned to course 'α. Heldentum' (id=1) as attendees. Reduce its
+        // maximum size, such that no places are left for Anton, who chose it.
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+        json_data["courses"]["1"]["max_size"] = serde_json::json!(2);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+
+        let (participants, mut courses, _import_ambience) = super::read(
+            &modified_data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                ignore_assigned: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        super::super::assert_data_consitency(&participants, &courses);
+        assert_eq!(find_course_by_id(&courses, 1).unwrap().num_max, 0);
+        let problems = super::check_adapted_courses(&courses, &participants);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("α. Heldentum"));
+
+        // Without the reduced maximum size, there's nothing to report
+        let (unchanged_participants, unchanged_courses, _import_ambience) = super::read(
+            &data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                ignore_assigned: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(
+            super::check_adapted_courses(&unchanged_courses, &unchanged_participants).is_empty()
+        );
+
+        // A minimum size above the maximum size should be reported as well
+        let course = courses
+            .iter_mut()
+            .find(|c| c.dbid == 4)
+            .expect("Course 'δ. Lang' should exist");
+        course.num_min = 5;
+        course.num_max = 3;
+        let problems = super::check_adapted_courses(&courses, &participants);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[1].contains("δ. Lang"));
+    }
+
     #[test]
     fn test_course_room_factor_fields() {
         use assert_float_eq::*;
@@ -1207,11 +2205,13 @@ mod tests {
 
         let (participants, courses, _import_ambience) = super::read(
             &modified_data[..],
-            Some(3),
-            false,
-            true,
-            Some("my_factor_field"),
-            Some("my_offset_field"),
+            &super::ReadOptions {
+                tracks: &[3],
+                ignore_assigned: true,
+                room_factor_field: Some("my_factor_field"),
+                room_offset_field: Some("my_offset_field"),
+                ..Default::default()
+            },
         )
         .unwrap();
         super::super::assert_data_consitency(&participants, &courses);
@@ -1225,13 +2225,305 @@ mod tests {
         assert_f32_near!(find_course_by_id(&courses, 13).unwrap().room_offset, 1.5);
         assert_f32_near!(find_course_by_id(&courses, 13).unwrap().room_factor, 1.0);
         // default
+        // Only values from the fields are considered as explicitly given
+        assert!(find_course_by_id(&courses, 4).unwrap().room_factor_given);
+        assert!(!find_course_by_id(&courses, 4).unwrap().room_offset_given);
+        assert!(!find_course_by_id(&courses, 13).unwrap().room_factor_given);
+    }
+
+    #[test]
+    fn test_forbidden_courses_field() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+
+        // Modify JSON to insert the forbidden courses field for some registrations
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+        json_data["registrations"]["1"]["fields"]["not_my_courses"] = serde_json::json!("4, 13");
+        json_data["registrations"]["2"]["fields"]["not_my_courses"] = serde_json::json!("");
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+
+        let (participants, courses, _import_ambience) = super::read(
+            &modified_data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                forbidden_courses_field: Some("not_my_courses"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        super::super::assert_data_consitency(&participants, &courses);
+
+        let anton = find_participant_by_id(&participants, 1).unwrap();
+        assert_eq!(
+            anton.forbidden_courses,
+            vec![
+                find_course_by_id(&courses, 4).unwrap().index,
+                find_course_by_id(&courses, 13).unwrap().index
+            ]
+        );
+        assert!(find_participant_by_id(&participants, 2)
+            .unwrap()
+            .forbidden_courses
+            .is_empty());
+
+        // Invalid course ids should be reported
+        json_data["registrations"]["1"]["fields"]["not_my_courses"] = serde_json::json!("4, 99");
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let result = super::read(
+            &modified_data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                forbidden_courses_field: Some("not_my_courses"),
+                ..Default::default()
+            },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_available_courses_field() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+
+        // Modify JSON to insert the available courses field for some registrations
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+        json_data["registrations"]["1"]["fields"]["my_courses"] = serde_json::json!("4, 13");
+        json_data["registrations"]["2"]["fields"]["my_courses"] = serde_json::json!("");
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+
+        let (participants, courses, _import_ambience) = super::read(
+            &modified_data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                available_courses_field: Some("my_courses"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        super::super::assert_data_consitency(&participants, &courses);
+
+        let anton = find_participant_by_id(&participants, 1).unwrap();
+        assert_eq!(
+            anton.available_courses,
+            Some(vec![
+                find_course_by_id(&courses, 4).unwrap().index,
+                find_course_by_id(&courses, 13).unwrap().index
+            ])
+        );
+        assert!(find_participant_by_id(&participants, 2)
+            .unwrap()
+            .available_courses
+            .is_none());
+        assert!(find_participant_by_id(&participants, 3)
+            .unwrap()
+            .available_courses
+            .is_none());
+    }
+
+    #[test]
+    fn test_choice_weight_field() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+
+        // Modify JSON to insert explicit choice weights for some registrations
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+        json_data["registrations"]["1"]["tracks"]["3"]["choices"] = serde_json::json!([1, 4, 2]);
+        json_data["registrations"]["1"]["fields"]["choice_weights"] = serde_json::json!("0, 5,1");
+        json_data["registrations"]["2"]["fields"]["choice_weights"] = serde_json::Value::Null;
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+
+        let read = |data: &[u8]| {
+            super::read(
+                data,
+                &super::ReadOptions {
+                    tracks: &[3],
+                    choice_weight_field: Some("choice_weights"),
+                    ..Default::default()
+                },
+            )
+        };
+        let (participants, courses, _import_ambience) = read(&modified_data).unwrap();
+        super::super::assert_data_consitency(&participants, &courses);
+        // The choices are sorted by penalty
+        let penalties = |p: &Participant| -> Vec<(u64, u32)> {
+            p.choices
+                .iter()
+                .map(|c| (courses[c.course_index].dbid as u64, c.penalty))
+                .collect()
+        };
+        assert_eq!(
+            penalties(find_participant_by_id(&participants, 1).unwrap()),
+            vec![(1, 0), (2, 1), (4, 5)]
+        );
+        // Without choice weights, the positional ranks are used
+        assert_eq!(
+            penalties(find_participant_by_id(&participants, 2).unwrap())
+                .iter()
+                .map(|(_, penalty)| *penalty)
+                .collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+
+        // The number of weights must match the number of choices
+        json_data["registrations"]["1"]["fields"]["choice_weights"] = serde_json::json!("0,1");
+        assert!(read(&serde_json::to_vec(&json_data).unwrap()).is_err());
+        // Invalid weights should be reported
+        json_data["registrations"]["1"]["fields"]["choice_weights"] = serde_json::json!("0,-1,2");
+        assert!(read(&serde_json::to_vec(&json_data).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_max_rank_field() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+
+        // Modify JSON to insert the maximum rank field for some registrations
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+        json_data["registrations"]["1"]["fields"]["max_rank"] = serde_json::json!(2);
+        json_data["registrations"]["2"]["fields"]["max_rank"] = serde_json::Value::Null;
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+
+        let (participants, courses, _import_ambience) = super::read(
+            &modified_data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                max_rank_field: Some("max_rank"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        super::super::assert_data_consitency(&participants, &courses);
+        assert_eq!(
+            find_participant_by_id(&participants, 1)
+                .unwrap()
+                .max_acceptable_rank,
+            Some(2)
+        );
+        assert_eq!(
+            find_participant_by_id(&participants, 2)
+                .unwrap()
+                .max_acceptable_rank,
+            None
+        );
+
+        // Invalid ranks should be reported
+        json_data["registrations"]["1"]["fields"]["max_rank"] = serde_json::json!(0);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let result = super::read(
+            &modified_data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                max_rank_field: Some("max_rank"),
+                ..Default::default()
+            },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_export_check_classification() {
+        use super::{check_export_type_and_version, ExportCheckError};
+        let check = |data: serde_json::Value| check_export_type_and_version(&data, false);
+
+        assert_eq!(
+            check(serde_json::json!({"kind": "partial", "EVENT_SCHEMA_VERSION": [15, 3]})),
+            Ok(())
+        );
+        // Old version field is still supported
+        assert_eq!(
+            check(serde_json::json!({"kind": "partial", "CDEDB_EXPORT_EVENT_VERSION": 7})),
+            Ok(())
+        );
+
+        let too_new =
+            check(serde_json::json!({"kind": "partial", "EVENT_SCHEMA_VERSION": [18, 1]}));
+        assert_eq!(too_new, Err(ExportCheckError::TooNew((18, 1))));
+        let message = too_new.unwrap_err().to_string();
+        assert!(message.contains("version 18.1"));
+        assert!(message.contains("from 7.0 to 17.x"));
+        assert!(message.contains("--allow-export-version"));
+
+        let too_old = check(serde_json::json!({"kind": "partial", "EVENT_SCHEMA_VERSION": [6, 9]}));
+        assert_eq!(too_old, Err(ExportCheckError::TooOld((6, 9))));
+        assert!(too_old
+            .unwrap_err()
+            .to_string()
+            .contains("--allow-export-version"));
+
+        assert_eq!(
+            check(serde_json::json!({"kind": "full", "EVENT_SCHEMA_VERSION": [15, 0]})),
+            Err(ExportCheckError::WrongKind("full".to_owned()))
+        );
+
+        for data in [
+            serde_json::json!({"EVENT_SCHEMA_VERSION": [15, 0]}),
+            serde_json::json!({"kind": "partial"}),
+            serde_json::json!({"kind": "partial", "EVENT_SCHEMA_VERSION": [15]}),
+            serde_json::json!({"kind": "partial", "EVENT_SCHEMA_VERSION": "15.0"}),
+        ] {
+            assert!(matches!(check(data), Err(ExportCheckError::Malformed(_))));
+        }
+
+        // Unsupported versions are accepted on request, but other problems are not
+        let data = serde_json::json!({"kind": "partial", "EVENT_SCHEMA_VERSION": [18, 1]});
+        assert_eq!(check_export_type_and_version(&data, true), Ok(()));
+        let data = serde_json::json!({"kind": "full", "EVENT_SCHEMA_VERSION": [18, 1]});
+        assert!(check_export_type_and_version(&data, true).is_err());
+    }
+
+    #[test]
+    fn test_allow_export_version() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+
+        // Modify JSON to set an unsupported export version
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+        json_data["EVENT_SCHEMA_VERSION"] =
+            serde_json::json!([super::MAXIMUM_EXPORT_VERSION.0 + 1, 0]);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+
+        let result = super::read(
+            &modified_data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                ..Default::default()
+            },
+        );
+        assert!(result.is_err());
+
+        let (participants, courses, _import_ambience) = super::read(
+            &modified_data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                allow_any_export_version: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        super::super::assert_data_consitency(&participants, &courses);
+        assert_eq!(courses.len(), 5);
+
+        // Other checks must still be enforced
+        json_data["kind"] = serde_json::json!("full");
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let result = super::read(
+            &modified_data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                allow_any_export_version: true,
+                ..Default::default()
+            },
+        );
+        assert!(result.is_err());
     }
 
     #[test]
     fn test_ignore_cancelled() {
         let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
-        let (participants, courses, _import_ambience) =
-            super::read(&data[..], Some(3), true, false, None, None).unwrap();
+        let (participants, courses, _import_ambience) = super::read(
+            &data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                ignore_inactive_courses: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
         super::super::assert_data_consitency(&participants, &courses);
 
         // Course 'γ. Kurz' (id=3) has not been offered and course 'ε. Backup' (id=5) is cancelled
@@ -1242,6 +2534,101 @@ mod tests {
         assert_eq!(participants.len(), 5);
     }
 
+    #[test]
+    fn test_dump_input_data_roundtrip() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+        let (participants, courses, _import_ambience) = super::read(
+            &data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                ignore_inactive_courses: true,
+                ignore_assigned: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let mut buffer = Vec::<u8>::new();
+        crate::io::simple::write_input_data(&mut buffer, &participants, &courses).unwrap();
+        let (dumped_participants, dumped_courses) =
+            crate::io::simple::read(&buffer[..], false, None).unwrap();
+        super::super::assert_data_consitency(&dumped_participants, &dumped_courses);
+
+        assert_eq!(dumped_participants.len(), participants.len());
+        for (dumped, original) in dumped_participants.iter().zip(participants.iter()) {
+            assert_eq!(dumped.index, original.index);
+            assert_eq!(dumped.dbid, original.dbid);
+            assert_eq!(dumped.name, original.name);
+            assert_eq!(dumped.choices, original.choices);
+            assert_eq!(dumped.current_course, original.current_course);
+            assert_eq!(dumped.forbidden_courses, original.forbidden_courses);
+            assert_eq!(dumped.max_acceptable_rank, original.max_acceptable_rank);
+        }
+        assert_eq!(dumped_courses.len(), courses.len());
+        for (dumped, original) in dumped_courses.iter().zip(courses.iter()) {
+            assert_eq!(dumped.index, original.index);
+            assert_eq!(dumped.dbid, original.dbid);
+            assert_eq!(dumped.name, original.name);
+            assert_eq!(dumped.num_min, original.num_min);
+            assert_eq!(dumped.num_max, original.num_max);
+            assert_eq!(dumped.instructors, original.instructors);
+            assert_eq!(dumped.room_factor, original.room_factor);
+            assert_eq!(dumped.room_offset, original.room_offset);
+            assert_eq!(dumped.fixed_course, original.fixed_course);
+            assert_eq!(dumped.required_attendees, original.required_attendees);
+            assert_eq!(
+                dumped.hidden_participant_names,
+                original.hidden_participant_names
+            );
+        }
+    }
+
+    #[test]
+    fn test_convert_to_simple_and_solve() {
+        let data = include_bytes!("test_ressources/TestAka_partial_export_event.json");
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+        for (course_id, factor) in [("1", 1.3), ("2", 2.5)] {
+            json_data["courses"][course_id]["fields"]["my_factor_field"] =
+                serde_json::json!(factor);
+        }
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+
+        // Assigned participants result in fixed courses and room offsets
+        let (participants, courses, _import_ambience) = super::read(
+            &modified_data[..],
+            &super::ReadOptions {
+                tracks: &[3],
+                ignore_assigned: true,
+                room_factor_field: Some("my_factor_field"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(courses.iter().any(|c| c.fixed_course));
+        assert!(courses.iter().any(|c| c.room_offset != 0.0));
+
+        let mut buffer = Vec::<u8>::new();
+        crate::io::simple::write_input_data(&mut buffer, &participants, &courses).unwrap();
+        let (converted_participants, converted_courses) =
+            crate::io::simple::read(&buffer[..], false, None).unwrap();
+
+        // Solving the converted problem must give the same result as solving the original one
+        let rooms = vec![10, 8, 4];
+        let solve = |participants: Vec<Participant>, courses: Vec<Course>| {
+            crate::caobab::solve(
+                std::sync::Arc::new(courses),
+                std::sync::Arc::new(participants),
+                Some(&rooms),
+                false,
+                1,
+            )
+            .0
+        };
+        let result = solve(participants, courses);
+        assert!(result.is_some());
+        assert_eq!(solve(converted_participants, converted_courses), result);
+    }
+
     #[test]
     fn test_write_result() {
         let courses = vec![
@@ -1252,10 +2639,7 @@ mod tests {
                 num_max: 10 - 1,
                 num_min: 3 - 1,
                 instructors: vec![2],
-                room_factor: 1.0,
-                room_offset: 0.0,
-                fixed_course: false,
-                hidden_participant_names: vec![],
+                ..Default::default()
             },
             Course {
                 index: 1,
@@ -1264,10 +2648,7 @@ mod tests {
                 num_max: 20,
                 num_min: 10,
                 instructors: vec![4],
-                room_factor: 1.0,
-                room_offset: 0.0,
-                fixed_course: false,
-                hidden_participant_names: vec![],
+                ..Default::default()
             },
             Course {
                 index: 2,
@@ -1276,10 +2657,7 @@ mod tests {
                 num_max: 25,
                 num_min: 0,
                 instructors: vec![2],
-                room_factor: 1.0,
-                room_offset: 0.0,
-                fixed_course: false,
-                hidden_participant_names: vec![],
+                ..Default::default()
             },
             Course {
                 index: 3,
@@ -1288,10 +2666,7 @@ mod tests {
                 num_max: 25,
                 num_min: 0,
                 instructors: vec![2],
-                room_factor: 1.0,
-                room_offset: 0.0,
-                fixed_course: false,
-                hidden_participant_names: vec![],
+                ..Default::default()
             },
         ];
         let participants = vec![
@@ -1300,36 +2675,43 @@ mod tests {
                 dbid: 1,
                 name: String::from("Anton Armin A. Administrator"),
                 choices: choices_from_list(&[0, 2]),
+                ..Default::default()
             },
             Participant {
                 index: 1,
                 dbid: 2,
                 name: String::from("Emilia E. Eventis"),
                 choices: choices_from_list(&[2, 1]),
+                ..Default::default()
             },
             Participant {
                 index: 2,
                 dbid: 3,
                 name: String::from("Garcia G. Generalis"),
                 choices: choices_from_list(&[1, 2]),
+                ..Default::default()
             },
             Participant {
                 index: 3,
                 dbid: 4,
                 name: String::from("Inga Iota"),
                 choices: choices_from_list(&[0, 1]),
+                ..Default::default()
             },
             Participant {
                 index: 4,
                 dbid: 5,
                 name: String::from("Backup course instructor"),
                 choices: vec![],
+                ..Default::default()
             },
         ];
         super::super::assert_data_consitency(&participants, &courses);
         let ambience_data = super::ImportAmbienceData {
             event_id: 1,
-            track_id: 3,
+            track_ids: vec![3],
+            course_track_ids: courses.iter().map(|c| (c.dbid, vec![3])).collect(),
+            registration_track_ids: participants.iter().map(|p| (p.dbid, vec![3])).collect(),
             external_assignment_quality_info: None,
             export_timestamp: chrono::DateTime::from_naive_utc_and_offset(
                 chrono::NaiveDateTime::new(
@@ -1346,9 +2728,13 @@ mod tests {
         let quality_info = crate::caobab::solution_score::QualityInfo {
             solution_score: 299_999,
             theoretical_max_score: 300_000,
+            theoretical_min_score: 299_990,
             solution_quality: 0.25,
             theoretical_max_quality: 0.0,
+            theoretical_min_quality: 1.0,
             overall_quality: None,
+            penalty_statistics: None,
+            normalized_quality: None,
         };
 
         let mut buffer = Vec::<u8>::new();
@@ -1357,10 +2743,11 @@ mod tests {
             &assignment,
             &participants,
             &courses,
-            ambience_data,
+            &ambience_data,
             &quality_info,
             None,
             None,
+            false,
         );
         assert!(result.is_ok());
 
@@ -1410,6 +2797,29 @@ mod tests {
             "not found in {}",
             summary_comment
         );
+
+        // Write again, including the instructors of running courses
+        let mut buffer = Vec::<u8>::new();
+        super::write(
+            &mut buffer,
+            &assignment,
+            &participants,
+            &courses,
+            &ambience_data,
+            &quality_info,
+            None,
+            None,
+            true,
+        )
+        .unwrap();
+        let data: serde_json::Value = serde_json::from_reader(&buffer[..]).unwrap();
+        // Course 4 takes place with its instructor (registration 3)
+        assert_eq!(data["courses"]["4"]["instructors"], serde_json::json!([3]));
+        // Course 1 takes place, but its instructor is assigned to course 4
+        assert_eq!(data["courses"]["1"]["instructors"], serde_json::json!([]));
+        // Cancelled courses have no instructor list
+        assert!(data["courses"]["2"].get("instructors").is_none());
+        assert!(data["courses"]["5"].get("instructors").is_none());
     }
 
     fn find_course_by_id(courses: &[Course], dbid: usize) -> Option<&Course> {
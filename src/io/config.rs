@@ -0,0 +1,253 @@
+//! IO functionality for reading a config file, which bundles the parameters of the assignment
+//! algorithm (weighting, thresholds, instructor scoring, …) for reproducible runs.
+
+use serde::Deserialize;
+
+use crate::caobab::{RandomRestarts, SolveConfig, SolveObjective};
+
+/// Parameters of the assignment algorithm, as read from a JSON config file by [read_parameters].
+///
+/// All entries are optional. Missing entries keep the default of the respective [SolveConfig]
+/// option. The entries are named like the corresponding command line options of `cdecao solve`.
+#[derive(Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SolveParameters {
+    /// Allow participants to stay unassigned (see [SolveConfig::require_full_assignment])
+    pub allow_unassigned: Option<bool>,
+    /// Minimum number of participants getting their first choice (see
+    /// [SolveConfig::min_first_choice_count])
+    pub min_first_choices: Option<usize>,
+    /// Penalty per missing room place (see [SolveConfig::soft_room_penalty])
+    pub soft_rooms: Option<u32>,
+    /// See [SolveConfig::max_scored_instructors]
+    pub max_scored_instructors: Option<usize>,
+    /// If false, instructors are not counted in the score (see
+    /// [SolveConfig::count_instructors_in_score])
+    pub instructor_score: Option<bool>,
+    /// Penalty per place of group imbalance (see [SolveConfig::group_balance_penalty])
+    pub group_balance: Option<u32>,
+    /// See [SolveConfig::even_fill_tie_break]
+    pub even_fill: Option<bool>,
+    /// See [SolveConfig::max_penalty_tie_break]
+    pub minimize_max_penalty: Option<bool>,
+    /// Penalty per unit of cancel cost of the cancelled courses (see
+    /// [SolveConfig::cancel_cost_penalty])
+    pub cancel_costs: Option<u32>,
+    /// See [SolveConfig::prefer_flexible_cancellation]
+    pub prefer_flexible_cancellation: Option<bool>,
+    /// Use [SolveObjective::MaximizeCoursesRunning] instead of the default objective
+    pub maximize_courses: Option<bool>,
+    /// Use [SolveObjective::MinimizeWorstChoice] instead of the default objective. Takes precedence
+    /// over `maximize_courses`.
+    pub minimize_worst_choice: Option<bool>,
+    /// See [SolveConfig::num_threads]
+    pub num_threads: Option<u32>,
+    /// Timeout in seconds (see [SolveConfig::timeout])
+    pub timeout: Option<u64>,
+    /// Number of runs with random branch orderings (see [SolveConfig::random_restarts])
+    pub random_restart: Option<u32>,
+    /// Seed for the random branch orderings. Defaults to 0.
+    pub seed: Option<u64>,
+    /// See [SolveConfig::stop_at_first_feasible]
+    pub first_feasible: Option<bool>,
+}
+
+impl SolveParameters {
+    /// Combine these parameters with `other` parameters of higher precedence (e.g. the command line
+    /// options), i.e. each entry given in `other` replaces the respective entry of `self`.
+    ///
+    /// The objective entries (`maximize_courses` and `minimize_worst_choice`) are replaced
+    /// together: If `other` selects an objective, the objective of `self` is discarded.
+    pub fn override_with(self, other: SolveParameters) -> SolveParameters {
+        let (maximize_courses, minimize_worst_choice) =
+            if other.maximize_courses.is_some() || other.minimize_worst_choice.is_some() {
+                (other.maximize_courses, other.minimize_worst_choice)
+            } else {
+                (self.maximize_courses, self.minimize_worst_choice)
+            };
+        SolveParameters {
+            allow_unassigned: other.allow_unassigned.or(self.allow_unassigned),
+            min_first_choices: other.min_first_choices.or(self.min_first_choices),
+            soft_rooms: other.soft_rooms.or(self.soft_rooms),
+            max_scored_instructors: other.max_scored_instructors.or(self.max_scored_instructors),
+            instructor_score: other.instructor_score.or(self.instructor_score),
+            group_balance: other.group_balance.or(self.group_balance),
+            even_fill: other.even_fill.or(self.even_fill),
+            minimize_max_penalty: other.minimize_max_penalty.or(self.minimize_max_penalty),
+            cancel_costs: other.cancel_costs.or(self.cancel_costs),
+            prefer_flexible_cancellation: other
+                .prefer_flexible_cancellation
+                .or(self.prefer_flexible_cancellation),
+            maximize_courses,
+            minimize_worst_choice,
+            num_threads: other.num_threads.or(self.num_threads),
+            timeout: other.timeout.or(self.timeout),
+            random_restart: other.random_restart.or(self.random_restart),
+            seed: other.seed.or(self.seed),
+            first_feasible: other.first_feasible.or(self.first_feasible),
+        }
+    }
+
+    /// Check if all participants must be assigned to a course, i.e. `allow_unassigned` is not set
+    pub fn require_full_assignment(&self) -> bool {
+        !self.allow_unassigned.unwrap_or(false)
+    }
+
+    /// Apply the given parameters to the `config`, keeping its options for all missing entries
+    pub fn apply(&self, mut config: SolveConfig) -> SolveConfig {
+        if let Some(allow_unassigned) = self.allow_unassigned {
+            config = config.require_full_assignment(!allow_unassigned);
+        }
+        if let Some(count) = self.min_first_choices {
+            config = config.min_first_choice_count(count);
+        }
+        if let Some(penalty) = self.soft_rooms {
+            config = config.soft_room_penalty(penalty);
+        }
+        if let Some(num) = self.max_scored_instructors {
+            config = config.max_scored_instructors(num);
+        }
+        if let Some(instructor_score) = self.instructor_score {
+            config = config.count_instructors_in_score(instructor_score);
+        }
+        if let Some(penalty) = self.group_balance {
+            config = config.group_balance_penalty(penalty);
+        }
+        if let Some(even_fill) = self.even_fill {
+            config = config.even_fill_tie_break(even_fill);
+        }
+        if let Some(minimize_max_penalty) = self.minimize_max_penalty {
+            config = config.max_penalty_tie_break(minimize_max_penalty);
+        }
+        if let Some(penalty) = self.cancel_costs {
+            config = config.cancel_cost_penalty(penalty);
+        }
+        if let Some(prefer_flexible) = self.prefer_flexible_cancellation {
+            config = config.prefer_flexible_cancellation(prefer_flexible);
+        }
+        if let Some(maximize_courses) = self.maximize_courses {
+            config = config.objective(if maximize_courses {
+                SolveObjective::MaximizeCoursesRunning
+            } else {
+                SolveObjective::ChoiceQuality
+            });
+        }
+        if self.minimize_worst_choice == Some(true) {
+            config = config.objective(SolveObjective::MinimizeWorstChoice);
+        }
+        if let Some(num_threads) = self.num_threads {
+            config = config.num_threads(num_threads);
+        }
+        if let Some(timeout) = self.timeout {
+            config = config.timeout(std::time::Duration::from_secs(timeout));
+        }
+        if let Some(num_runs) = self.random_restart {
+            config = config.random_restarts(RandomRestarts {
+                num_runs,
+                seed: self.seed.unwrap_or(0),
+            });
+        }
+        if let Some(first_feasible) = self.first_feasible {
+            config = config.stop_at_first_feasible(first_feasible);
+        }
+        config
+    }
+}
+
+/// Read the parameters of the assignment algorithm from a JSON config file.
+///
+/// # Errors
+///
+/// Fails with a string error message, if the data is not valid JSON, contains unknown entries or
+/// entries of the wrong type.
+pub fn read_parameters<R: std::io::Read>(reader: R) -> Result<SolveParameters, String> {
+    serde_json::from_reader(reader).map_err(|e| format!("{}", e))
+}
+
+/// Read a JSON config file (see [read_parameters]) and create a [SolveConfig] from it, using the
+/// default options for all missing entries.
+pub fn read<R: std::io::Read>(reader: R) -> Result<SolveConfig, String> {
+    Ok(read_parameters(reader)?.apply(SolveConfig::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_read_parameters() {
+        let data = br#"{"allow_unassigned": true, "soft_rooms": 3, "instructor_score": false,
+            "random_restart": 5, "timeout": 60}"#;
+        let parameters = super::read_parameters(&data[..]).unwrap();
+        assert_eq!(
+            parameters,
+            super::SolveParameters {
+                allow_unassigned: Some(true),
+                soft_rooms: Some(3),
+                instructor_score: Some(false),
+                random_restart: Some(5),
+                timeout: Some(60),
+                ..Default::default()
+            }
+        );
+        assert!(!parameters.require_full_assignment());
+        assert!(super::read(&data[..]).is_ok());
+
+        // Unknown entries (e.g. typos) and wrong types are rejected
+        assert!(super::read_parameters(&br#"{"soft_room": 3}"#[..]).is_err());
+        assert!(super::read_parameters(&br#"{"soft_rooms": "3"}"#[..]).is_err());
+        assert_eq!(
+            super::read_parameters(&b"{}"[..]).unwrap(),
+            super::SolveParameters::default()
+        );
+    }
+
+    #[test]
+    fn test_override_parameters() {
+        let file_parameters = super::SolveParameters {
+            soft_rooms: Some(3),
+            group_balance: Some(2),
+            even_fill: Some(true),
+            ..Default::default()
+        };
+        let cli_parameters = super::SolveParameters {
+            soft_rooms: Some(5),
+            num_threads: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            file_parameters.override_with(cli_parameters),
+            super::SolveParameters {
+                soft_rooms: Some(5),
+                group_balance: Some(2),
+                even_fill: Some(true),
+                num_threads: Some(2),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_override_objective() {
+        let file_parameters = super::SolveParameters {
+            minimize_worst_choice: Some(true),
+            ..Default::default()
+        };
+        let cli_parameters = super::SolveParameters {
+            maximize_courses: Some(true),
+            ..Default::default()
+        };
+        // The objective of the command line replaces the objective of the config file
+        assert_eq!(
+            file_parameters
+                .clone()
+                .override_with(cli_parameters.clone()),
+            cli_parameters
+        );
+        // Without an objective on the command line, the config file's objective is kept
+        assert_eq!(
+            file_parameters
+                .clone()
+                .override_with(super::SolveParameters::default()),
+            file_parameters
+        );
+    }
+}
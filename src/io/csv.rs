@@ -0,0 +1,89 @@
+//! IO functionality for writing the calculated course assignment as a simple CSV roster
+
+use crate::{Assignment, Course, Participant};
+
+/// Write the calculated course assignment as a CSV roster to a Writer (e.g. an output file).
+///
+/// The CSV file has one row per participant (in the order of the participants list) with the
+/// columns `participant`, `course` and `role`. The role is either `attendee` or `instructor`. For
+/// participants without an assigned course, the course and role columns are empty.
+pub fn write<W: std::io::Write>(
+    mut writer: W,
+    assignment: &Assignment,
+    participants: &[Participant],
+    courses: &[Course],
+) -> Result<(), String> {
+    let mut data = String::from("participant,course,role\n");
+    for (p, course) in participants.iter().zip(assignment.iter()) {
+        let (course_name, role) = match course {
+            Some(c) => (
+                courses[*c].name.as_str(),
+                if courses[*c].instructors.contains(&p.index) {
+                    "instructor"
+                } else {
+                    "attendee"
+                },
+            ),
+            None => ("", ""),
+        };
+        data.push_str(&format!(
+            "{},{},{}\n",
+            escape_field(&p.name),
+            escape_field(course_name),
+            role
+        ));
+    }
+    writer
+        .write_all(data.as_bytes())
+        .map_err(|e| format!("{}", e))
+}
+
+/// Quote a CSV field, if required, according to RFC 4180
+fn escape_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{choices_from_list, Course, Participant};
+
+    #[test]
+    fn write_csv_file() {
+        let participants: Vec<Participant> = ["Anton", "Berta \"B\" Beispiel", "Charly, Clown"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Participant {
+                index: i,
+                dbid: i,
+                name: name.to_string(),
+                choices: choices_from_list(&[0]),
+                ..Default::default()
+            })
+            .collect();
+        let courses = vec![Course {
+            index: 0,
+            dbid: 0,
+            name: "1. Example Course".to_owned(),
+            num_max: 10,
+            num_min: 0,
+            instructors: vec![0],
+            ..Default::default()
+        }];
+        let assignment = vec![Some(0), Some(0), None];
+
+        let mut buffer = Vec::<u8>::new();
+        super::write(&mut buffer, &assignment, &participants, &courses).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "participant,course,role
+Anton,1. Example Course,instructor
+\"Berta \"\"B\"\" Beispiel\",1. Example Course,attendee
+\"Charly, Clown\",,
+"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! IO functionality for writing the calculated course assignment as a "long format" JSON array of
+//! records (one object per participant), which can be loaded directly into a data frame for
+//! analysis, e.g. with `pandas.read_json()`.
+
+use crate::caobab::solution_score::{satisfaction_report, AssignedRank};
+use crate::{Assignment, Course, Participant};
+use serde::{Deserialize, Serialize};
+
+/// A single participant's record, as written by [write()]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct AssignmentRecord {
+    /// Name of the participant
+    pub participant: String,
+    /// Name of the assigned course, or None, if the participant is not assigned to any course
+    pub course: Option<String>,
+    /// Rank of the assigned course within the participant's choices (1 for the best choice), or
+    /// None for instructors, unassigned participants and participants in a course they did not
+    /// choose
+    pub rank: Option<usize>,
+    /// True, if the participant is assigned as instructor of their course
+    pub instructor: bool,
+}
+
+/// Write the calculated course assignment as JSON array of [AssignmentRecord]s to a Writer (e.g.
+/// an output file). The array has one record per participant (in the order of the participants
+/// list).
+pub fn write<W: std::io::Write>(
+    writer: W,
+    assignment: &Assignment,
+    participants: &[Participant],
+    courses: &[Course],
+) -> Result<(), String> {
+    let records: Vec<AssignmentRecord> = satisfaction_report(assignment, participants, courses)
+        .into_iter()
+        .map(|satisfaction| AssignmentRecord {
+            participant: participants[satisfaction.participant].name.clone(),
+            course: satisfaction
+                .assigned_course
+                .map(|c| courses[c].name.clone()),
+            rank: match satisfaction.assigned_rank {
+                AssignedRank::Rank(rank) => Some(rank + 1),
+                _ => None,
+            },
+            instructor: satisfaction.assigned_rank == AssignedRank::Instructor,
+        })
+        .collect();
+    serde_json::to_writer(writer, &records).map_err(|e| format!("{}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{choices_from_list, Course, Participant};
+
+    #[test]
+    fn write_records() {
+        let participants: Vec<Participant> = [&[0, 1][..], &[0, 1], &[], &[1]]
+            .iter()
+            .enumerate()
+            .map(|(i, choices)| Participant {
+                index: i,
+                dbid: i,
+                name: format!("Participant {}", i),
+                choices: choices_from_list(choices),
+                ..Default::default()
+            })
+            .collect();
+        let courses: Vec<Course> = (0..2)
+            .map(|i| Course {
+                index: i,
+                dbid: i,
+                name: format!("Course {}", i),
+                num_max: 10,
+                num_min: 0,
+                instructors: if i == 1 { vec![2] } else { vec![] },
+                ..Default::default()
+            })
+            .collect();
+        let assignment = vec![Some(0), Some(1), Some(1), None];
+
+        let mut buffer = Vec::<u8>::new();
+        super::write(&mut buffer, &assignment, &participants, &courses).unwrap();
+        let records: Vec<super::AssignmentRecord> = serde_json::from_slice(&buffer).unwrap();
+
+        assert_eq!(
+            records,
+            vec![
+                super::AssignmentRecord {
+                    participant: "Participant 0".to_owned(),
+                    course: Some("Course 0".to_owned()),
+                    rank: Some(1),
+                    instructor: false,
+                },
+                super::AssignmentRecord {
+                    participant: "Participant 1".to_owned(),
+                    course: Some("Course 1".to_owned()),
+                    rank: Some(2),
+                    instructor: false,
+                },
+                super::AssignmentRecord {
+                    participant: "Participant 2".to_owned(),
+                    course: Some("Course 1".to_owned()),
+                    rank: None,
+                    instructor: true,
+                },
+                super::AssignmentRecord {
+                    participant: "Participant 3".to_owned(),
+                    course: None,
+                    rank: None,
+                    instructor: false,
+                },
+            ]
+        );
+    }
+}
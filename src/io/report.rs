@@ -0,0 +1,215 @@
+//! IO functionality for writing a combined JSON report of the calculated course assignment, e.g.
+//! for dashboards: The assignment, the resulting course sizes and rooms and the full quality info
+//! in a single document.
+
+use crate::caobab::solution_score::{satisfaction_report, AssignedRank, QualityInfo};
+use crate::{Assignment, Course, Participant};
+use serde::{Deserialize, Serialize};
+
+/// Format identifier of the combined report format
+const REPORT_FORMAT: &str = "X-courseassignment-report";
+/// Version of the combined report format, which is written by `write_full()`
+const REPORT_FORMAT_VERSION: &str = "1.0";
+
+/// Combined report of a calculated course assignment, as written by [write_full]
+#[derive(Serialize, Deserialize)]
+pub struct FullReport {
+    /// Format identifier, always "X-courseassignment-report"
+    pub format: String,
+    /// Version of the report format
+    pub version: String,
+    /// The course assignment (course index for each participant or null)
+    pub assignment: Assignment,
+    /// Resulting size and room of each course (in the order of the courses list)
+    pub courses: Vec<CourseReport>,
+    /// Quality info of the assignment
+    pub quality: QualityInfo,
+    /// Histogram of the assigned choice ranks: The number of participants, who are assigned to
+    /// their 1st, 2nd, … choice (see [crate::caobab::solution_score::satisfaction_report])
+    pub rank_histogram: Vec<usize>,
+    /// Number of participants, who are assigned as instructor of their course
+    pub num_instructors: usize,
+    /// Number of participants, who are not assigned to any course
+    pub num_unassigned: usize,
+    /// Number of participants, who are assigned to a course, they did not choose
+    pub num_wrong: usize,
+}
+
+/// Assignment result of a single course within a [FullReport]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct CourseReport {
+    pub index: usize,
+    pub dbid: usize,
+    pub name: String,
+    /// True, if at least one participant is assigned to the course
+    pub takes_place: bool,
+    /// Number of assigned attendees (excl. instructors)
+    pub num_attendees: usize,
+    /// Number of assigned instructors
+    pub num_instructors: usize,
+    /// The designated room (or possible rooms) of the course, if rooms are given
+    pub room: Option<String>,
+}
+
+/// Write a combined JSON report of the calculated course assignment to a Writer (e.g. an output
+/// file). See [FullReport] for the contents of the report.
+///
+/// `rooms` is the list of designated/possible rooms of each course (in the order of the courses
+/// list), e.g. from `rooms::get_course_room_size_list()` or `rooms::get_course_room_kind_names()`.
+pub fn write_full<W: std::io::Write>(
+    writer: W,
+    assignment: &Assignment,
+    participants: &[Participant],
+    courses: &[Course],
+    quality_info: &QualityInfo,
+    rooms: Option<&[String]>,
+) -> Result<(), String> {
+    let course_reports: Vec<CourseReport> =
+        crate::caobab::iter_course_assignments(assignment, courses, participants)
+            .map(|(course, attendees, instructors)| CourseReport {
+                index: course.index,
+                dbid: course.dbid,
+                name: course.name.clone(),
+                takes_place: !attendees.is_empty() || !instructors.is_empty(),
+                num_attendees: attendees.len(),
+                num_instructors: instructors.len(),
+                room: rooms.map(|rooms| rooms[course.index].clone()),
+            })
+            .collect();
+
+    let mut rank_histogram = Vec::new();
+    let (mut num_instructors, mut num_unassigned, mut num_wrong) = (0, 0, 0);
+    for satisfaction in satisfaction_report(assignment, participants, courses) {
+        match satisfaction.assigned_rank {
+            AssignedRank::Rank(rank) => {
+                if rank >= rank_histogram.len() {
+                    rank_histogram.resize(rank + 1, 0);
+                }
+                rank_histogram[rank] += 1;
+            }
+            AssignedRank::Instructor => num_instructors += 1,
+            AssignedRank::Unassigned => num_unassigned += 1,
+            AssignedRank::Wrong => num_wrong += 1,
+        }
+    }
+
+    let data = serde_json::json!({
+        "format": REPORT_FORMAT,
+        "version": REPORT_FORMAT_VERSION,
+        "assignment": assignment,
+        "courses": course_reports,
+        "quality": quality_info,
+        "rank_histogram": rank_histogram,
+        "num_instructors": num_instructors,
+        "num_unassigned": num_unassigned,
+        "num_wrong": num_wrong,
+    });
+    serde_json::to_writer(writer, &data).map_err(|e| format!("{}", e))
+}
+
+/// Read a combined report, as written by [write_full], e.g. for further processing.
+///
+/// # Errors
+///
+/// Fails with a string error message, if the data is not valid JSON, does not have the report
+/// format identifier or does not match the structure of a [FullReport].
+pub fn read_full<R: std::io::Read>(reader: R) -> Result<FullReport, String> {
+    let data: serde_json::Value = serde_json::from_reader(reader).map_err(|e| e.to_string())?;
+    let format = data.get("format").ok_or("No 'format' found in data.")?;
+    if format.as_str() != Some(REPORT_FORMAT) {
+        return Err(format!(
+            "The given file has format {}, but {} is expected.",
+            format, REPORT_FORMAT
+        ));
+    }
+    serde_json::from_value(data).map_err(|e| format!("{}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::caobab::solution_score::QualityInfo;
+    use crate::{choices_from_list, Course, Participant};
+
+    #[test]
+    fn write_and_read_full_report() {
+        let participants: Vec<Participant> = [&[0, 1][..], &[1, 0], &[1, 0], &[]]
+            .iter()
+            .enumerate()
+            .map(|(i, choices)| Participant {
+                index: i,
+                dbid: 10 + i,
+                name: format!("Participant {}", i),
+                choices: choices_from_list(choices),
+                ..Default::default()
+            })
+            .collect();
+        let courses: Vec<Course> = (0..3)
+            .map(|i| Course {
+                index: i,
+                dbid: 20 + i,
+                name: format!("Course {}", i),
+                num_max: 10,
+                num_min: 0,
+                instructors: if i == 0 { vec![3] } else { vec![] },
+                ..Default::default()
+            })
+            .collect();
+        let assignment = vec![Some(1), Some(1), Some(0), Some(0)];
+        let quality_info = QualityInfo::calculate(
+            crate::caobab::score_assignment(
+                &assignment,
+                &participants,
+                &courses,
+                &crate::caobab::SolveConfig::new(),
+            )
+            .unwrap(),
+            &participants,
+            &courses,
+            &assignment,
+            None,
+        );
+        let rooms = vec!["10".to_owned(), "20".to_owned(), "".to_owned()];
+
+        let mut buffer = Vec::<u8>::new();
+        super::write_full(
+            &mut buffer,
+            &assignment,
+            &participants,
+            &courses,
+            &quality_info,
+            Some(&rooms),
+        )
+        .unwrap();
+        let report = super::read_full(&buffer[..]).unwrap();
+
+        assert_eq!(report.version, "1.0");
+        assert_eq!(report.assignment, assignment);
+        assert_eq!(report.courses.len(), 3);
+        assert_eq!(
+            report.courses[0],
+            super::CourseReport {
+                index: 0,
+                dbid: 20,
+                name: "Course 0".to_owned(),
+                takes_place: true,
+                num_attendees: 1,
+                num_instructors: 1,
+                room: Some("10".to_owned()),
+            }
+        );
+        assert_eq!(report.courses[1].num_attendees, 2);
+        assert!(!report.courses[2].takes_place);
+        assert_eq!(report.quality.solution_score, quality_info.solution_score);
+        assert_eq!(
+            report.quality.penalty_statistics,
+            quality_info.penalty_statistics
+        );
+        assert_eq!(report.rank_histogram, vec![1, 2]);
+        assert_eq!(report.num_instructors, 1);
+        assert_eq!(report.num_unassigned, 0);
+        assert_eq!(report.num_wrong, 0);
+
+        // Other formats are rejected
+        assert!(super::read_full(&br#"{"format": "X-courseassignment-simple"}"#[..]).is_err());
+    }
+}
@@ -15,34 +15,196 @@ pub struct CourseRoomKind {
     capacity: usize,
     /// Number of available rooms of this kind
     quantity: usize,
+    /// If true, each room of this kind may host multiple shareable courses, as long as their
+    /// combined effective sizes fit the room's capacity. See [assign_rooms].
+    #[serde(default)]
+    shareable: bool,
+    /// Names of the time slots, in which the rooms of this kind are available (see
+    /// [build_schedule]). If empty (default), the rooms are available in every time slot.
+    #[serde(default)]
+    slots: Vec<String>,
 }
 
+/// A single course room, assigned to a course by [assign_rooms]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AssignedRoom {
+    /// Index of the room's kind in the list of course room kinds
+    pub kind: usize,
+    /// Number of the room within its kind (starting with 0)
+    pub number: usize,
+}
+
+/// Usage state of a single room during [assign_rooms]
+#[derive(Clone, Copy)]
+enum RoomUsage {
+    Free,
+    Exclusive,
+    /// Used by shareable courses, with the given remaining capacity
+    Shared(usize),
+}
+
+/// Maximum number of available rooms per course. More rooms than courses are useless for the
+/// assignment, but a huge number of (small) rooms slows down the generation of room constraints.
+/// See [check_room_count].
+pub const MAX_ROOMS_PER_COURSE: usize = 10;
+
 /// Read the available course rooms from a JSON-serialized list of course room kinds
-pub fn read<R: std::io::Read>(reader: R) -> Result<(Vec<usize>, Vec<CourseRoomKind>), String> {
+///
+/// Returns the list of room sizes and the list of room kinds, both sorted by descending capacity.
+/// Room kinds of equal capacity are sorted by name (see `cmp_room_kinds()`), so the order does not
+/// depend on the order of the file.
+///
+/// The total number of rooms is checked against `max_rooms` (see [max_room_count]) before the
+/// list of room sizes is built, so a huge quantity in the file cannot exhaust the memory.
+///
+/// # Errors
+///
+/// Fails with a string error message, if the data cannot be parsed, any room kind has a capacity
+/// of 0 or the room kinds contain more than `max_rooms` rooms in total.
+pub fn read<R: std::io::Read>(
+    reader: R,
+    max_rooms: usize,
+) -> Result<(Vec<usize>, Vec<CourseRoomKind>), String> {
     let mut room_kinds =
         serde_json::from_reader::<_, Vec<CourseRoomKind>>(reader).map_err(|err| err.to_string())?;
+    if let Some(room_kind) = room_kinds.iter().find(|room_kind| room_kind.capacity == 0) {
+        return Err(format!(
+            "Room kind '{}' has a capacity of 0.",
+            room_kind.name
+        ));
+    }
 
-    room_kinds.sort_by_key(|room_kind| room_kind.capacity);
-    room_kinds.reverse();
+    room_kinds.sort_by(cmp_room_kinds);
+    let rooms = expand_room_kinds(&room_kinds, max_rooms)?;
 
-    let rooms = room_kinds
-        .iter()
-        .flat_map(|room_kind| std::iter::repeat(room_kind.capacity).take(room_kind.quantity))
-        .collect();
+    Ok((rooms, room_kinds))
+}
 
+/// Parse a comma-separated list of available course room sizes, e.g. "15,10,10,8".
+///
+/// # Errors
+///
+/// Fails with a string error message, if any entry is not a non-negative integer or any room size
+/// is 0.
+pub fn parse_room_list(rooms_list: &str) -> Result<Vec<usize>, String> {
+    rooms_list
+        .split(',')
+        .enumerate()
+        .map(|(i, room)| {
+            let size = room
+                .trim()
+                .parse::<usize>()
+                .map_err(|e| format!("Invalid size '{}' of room {}: {}", room, i + 1, e))?;
+            if size == 0 {
+                return Err(format!("Room {} has a size of 0.", i + 1));
+            }
+            Ok(size)
+        })
+        .collect()
+}
+
+/// Merge additional course room sizes (e.g. from [parse_room_list]) into the course room kinds
+/// from a rooms file (see [read]).
+///
+/// For each distinct size of the additional rooms, an anonymous, non-shareable room kind with the
+/// name "Room (N places)" is added. Returns the merged list of room sizes and the merged room kinds,
+/// both sorted by descending capacity, like the result of [read].
+///
+/// # Errors
+///
+/// Fails with a string error message, if the merged room kinds contain more than `max_rooms` rooms
+/// in total.
+pub fn merge_room_list(
+    mut room_kinds: Vec<CourseRoomKind>,
+    additional_rooms: &[usize],
+    max_rooms: usize,
+) -> Result<(Vec<usize>, Vec<CourseRoomKind>), String> {
+    let mut sizes = additional_rooms.to_vec();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes.dedup();
+    for size in sizes {
+        room_kinds.push(CourseRoomKind {
+            name: format!("Room ({} places)", size),
+            capacity: size,
+            quantity: additional_rooms.iter().filter(|r| **r == size).count(),
+            shareable: false,
+            slots: vec![],
+        });
+    }
+
+    room_kinds.sort_by(cmp_room_kinds);
+    let rooms = expand_room_kinds(&room_kinds, max_rooms)?;
     Ok((rooms, room_kinds))
 }
 
+/// Build the list of room sizes from the (sorted) course room kinds, with one entry per room.
+///
+/// Fails with a string error message, if the room kinds contain more than `max_rooms` rooms in
+/// total. This is checked before allocating the list.
+fn expand_room_kinds(
+    room_kinds: &[CourseRoomKind],
+    max_rooms: usize,
+) -> Result<Vec<usize>, String> {
+    let num_rooms = room_kinds
+        .iter()
+        .try_fold(0usize, |sum, room_kind| sum.checked_add(room_kind.quantity))
+        .filter(|num_rooms| *num_rooms <= max_rooms)
+        .ok_or_else(|| format!("More than {} rooms are given.", max_rooms))?;
+    let mut rooms = Vec::with_capacity(num_rooms);
+    for room_kind in room_kinds {
+        rooms.extend(std::iter::repeat_n(room_kind.capacity, room_kind.quantity));
+    }
+    Ok(rooms)
+}
+
+/// Deterministic order of course room kinds: by descending capacity and, for equal capacities, by
+/// name (and by the remaining attributes, for room kinds of equal name).
+fn cmp_room_kinds(a: &CourseRoomKind, b: &CourseRoomKind) -> std::cmp::Ordering {
+    b.capacity
+        .cmp(&a.capacity)
+        .then_with(|| a.name.cmp(&b.name))
+        .then_with(|| a.quantity.cmp(&b.quantity))
+        .then_with(|| a.shareable.cmp(&b.shareable))
+        .then_with(|| a.slots.cmp(&b.slots))
+}
+
+/// Check that the number of available rooms does not exceed the number of courses by more than
+/// the factor [MAX_ROOMS_PER_COURSE].
+///
+/// # Errors
+///
+/// Fails with a string error message, if there are too many rooms.
+pub fn check_room_count(rooms: &[usize], num_courses: usize) -> Result<(), String> {
+    if rooms.len() > max_room_count(num_courses) {
+        return Err(format!(
+            "{} rooms are given for {} courses. At most {} rooms per course are allowed.",
+            rooms.len(),
+            num_courses,
+            MAX_ROOMS_PER_COURSE
+        ));
+    }
+    Ok(())
+}
+
+/// Maximum number of available rooms for the given number of courses, according to
+/// [MAX_ROOMS_PER_COURSE]. Used as limit for [read] and [merge_room_list].
+pub fn max_room_count(num_courses: usize) -> usize {
+    std::cmp::max(num_courses, 1).saturating_mul(MAX_ROOMS_PER_COURSE)
+}
+
 /// Returns a human-readable list of possible course room kind names in the form
-/// "room kind 1, room kind 2" for each course
+/// "room kind 1, room kind 2" for each course. The room kinds are listed by descending capacity
+/// and, for equal capacities, by name, independent of the order of `room_kinds`.
 pub fn get_course_room_kind_names(
     assignment: &Assignment,
     courses: &[Course],
     room_kinds: &[CourseRoomKind],
 ) -> Vec<String> {
+    let mut room_kinds: Vec<&CourseRoomKind> = room_kinds.iter().collect();
+    room_kinds.sort_by(|a, b| cmp_room_kinds(a, b));
     let rooms: Vec<usize> = room_kinds
         .iter()
-        .flat_map(|room_kind| std::iter::repeat(room_kind.capacity).take(room_kind.quantity))
+        .flat_map(|room_kind| std::iter::repeat_n(room_kind.capacity, room_kind.quantity))
         .collect();
 
     let course_rooms = calculate_possible_course_room_sizes(assignment, courses, rooms);
@@ -83,9 +245,348 @@ pub fn get_course_room_size_list(
         .collect()
 }
 
+/// Calculate the rooms, which are required for the given assignment, i.e. the room-effective size
+/// (see `caobab::room_effective_course_sizes()`) of each course taking place, sorted in descending
+/// order. This is independent of any list of available rooms, e.g. to find out which rooms need to
+/// be rented for the assignment. A course takes place, if any participant is assigned to it or if
+/// it is fixed.
+pub fn required_rooms(assignment: &Assignment, courses: &[Course]) -> Vec<usize> {
+    let mut is_running: Vec<bool> = courses.iter().map(|c| c.fixed_course).collect();
+    for c in assignment.iter().flatten() {
+        is_running[*c] = true;
+    }
+    let mut sizes: Vec<usize> = crate::caobab::room_effective_course_sizes(assignment, courses)
+        .into_iter()
+        .filter(|(c, _size)| is_running[c.index])
+        .map(|(_c, size)| size)
+        .collect();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes
+}
+
+/// Group the required room sizes (e.g. from [required_rooms]) into buckets of room sizes, e.g. the
+/// sizes of the rooms, which can be rented. Each required room is counted for the smallest bucket
+/// size, which is large enough for it.
+///
+/// Returns the number of required rooms for each of the given `bucket_sizes` (in the same order).
+///
+/// # Errors
+///
+/// Fails with a string error message, if any required room is larger than the largest bucket.
+pub fn bucket_required_rooms(
+    required_rooms: &[usize],
+    bucket_sizes: &[usize],
+) -> Result<Vec<usize>, String> {
+    let mut counts = vec![0; bucket_sizes.len()];
+    for room in required_rooms.iter() {
+        let bucket = bucket_sizes
+            .iter()
+            .enumerate()
+            .filter(|(_i, size)| **size >= *room)
+            .min_by_key(|(_i, size)| **size)
+            .ok_or(format!(
+                "A room of size {} is required, but the largest bucket has size {}.",
+                room,
+                bucket_sizes.iter().max().unwrap_or(&0)
+            ))?
+            .0;
+        counts[bucket] += 1;
+    }
+    Ok(counts)
+}
+
+/// Check, if the courses taking place in the given assignment fit into the available `rooms`
+/// (without room sharing), e.g. for an existing assignment and a changed list of rooms.
+///
+/// The courses are matched to the rooms in descending order of their room-effective size, which
+/// is an optimal matching. Returns a human-readable description of each course, which does not
+/// get a sufficient room. The list is empty, if all courses fit.
+pub fn find_room_conflicts(
+    assignment: &Assignment,
+    courses: &[Course],
+    rooms: &[usize],
+) -> Vec<String> {
+    let mut takes_place: Vec<bool> = courses.iter().map(|c| c.fixed_course).collect();
+    for c in assignment.iter().flatten() {
+        takes_place[*c] = true;
+    }
+    let mut course_sizes: Vec<(&Course, usize)> =
+        crate::caobab::room_effective_course_sizes(assignment, courses)
+            .into_iter()
+            .filter(|(c, _size)| takes_place[c.index])
+            .collect();
+    course_sizes.sort_by_key(|(_c, size)| std::cmp::Reverse(*size));
+    let mut rooms = rooms.to_vec();
+    rooms.sort_unstable_by(|a, b| b.cmp(a));
+
+    course_sizes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (course, size))| match rooms.get(i) {
+            None => Some(format!(
+                "{} (room-effective size {}): No room left ({} rooms for {} courses).",
+                course.name,
+                size,
+                rooms.len(),
+                course_sizes.len()
+            )),
+            Some(room) if room < size => Some(format!(
+                "{} (room-effective size {}): Only a room with {} places is left.",
+                course.name, size, room
+            )),
+            Some(_) => None,
+        })
+        .collect()
+}
+
+/// Assign a single course room to each course, which takes place in the given assignment.
+///
+/// The courses are processed in descending order of their room-effective size. Each course is put
+/// into the smallest free room with sufficient capacity. Courses marked as `shareable` may instead
+/// be put into a shareable room, which is already used by other shareable courses, if the
+/// remaining capacity of the room is sufficient. Thus, rooms are only shared, if this is enabled
+/// for the room kind as well as for the courses.
+///
+/// This is a greedy heuristic for the underlying bin-packing problem, so it may fail to find a room
+/// plan, even if one exists. Note that the assignment algorithm itself does not consider room
+/// sharing, so the assignment's courses always fit into the rooms without sharing.
+///
+/// Returns the room of each course (or None for cancelled courses) or None, if no room plan has
+/// been found.
+pub fn assign_rooms(
+    assignment: &Assignment,
+    courses: &[Course],
+    room_kinds: &[CourseRoomKind],
+) -> Option<Vec<Option<AssignedRoom>>> {
+    assign_rooms_filtered(assignment, courses, room_kinds, |_| true, |_| true, true)
+}
+
+/// Implementation of [assign_rooms] for a subset of the courses and rooms: Only courses, which take
+/// place and match the `course_filter`, get a room. Only rooms of kinds matching the
+/// `room_kind_filter` are used. If `allow_sharing` is false, each room is used by at most one
+/// course, regardless of the courses' and rooms' `shareable` flags.
+fn assign_rooms_filtered(
+    assignment: &Assignment,
+    courses: &[Course],
+    room_kinds: &[CourseRoomKind],
+    course_filter: impl Fn(&Course) -> bool,
+    room_kind_filter: impl Fn(&CourseRoomKind) -> bool,
+    allow_sharing: bool,
+) -> Option<Vec<Option<AssignedRoom>>> {
+    let course_sizes = crate::caobab::room_effective_course_sizes(assignment, courses);
+    let mut takes_place: Vec<bool> = courses.iter().map(|c| c.fixed_course).collect();
+    for c in assignment.iter().flatten() {
+        takes_place[*c] = true;
+    }
+    let mut course_order: Vec<usize> = (0..courses.len())
+        .filter(|c| takes_place[*c] && course_filter(&courses[*c]))
+        .collect();
+    course_order.sort_by_key(|c| std::cmp::Reverse(course_sizes[*c].1));
+
+    let rooms: Vec<(AssignedRoom, &CourseRoomKind)> = room_kinds
+        .iter()
+        .enumerate()
+        .filter(|(_kind, room_kind)| room_kind_filter(room_kind))
+        .flat_map(|(kind, room_kind)| {
+            (0..room_kind.quantity).map(move |number| (AssignedRoom { kind, number }, room_kind))
+        })
+        .collect();
+    let mut usage = vec![RoomUsage::Free; rooms.len()];
+
+    let mut result = vec![None; courses.len()];
+    for c in course_order {
+        let size = course_sizes[c].1;
+        let shareable = allow_sharing && courses[c].shareable;
+
+        // Try to share an already used room with the least remaining capacity
+        let shared_room = (0..rooms.len())
+            .filter_map(|r| match usage[r] {
+                RoomUsage::Shared(remaining) if shareable && remaining >= size => {
+                    Some((r, remaining))
+                }
+                _ => None,
+            })
+            .min_by_key(|(_r, remaining)| *remaining);
+        if let Some((r, remaining)) = shared_room {
+            usage[r] = RoomUsage::Shared(remaining - size);
+            result[c] = Some(rooms[r].0);
+            continue;
+        }
+
+        // Otherwise, take the smallest free room, which is large enough
+        let (r, room_kind) = rooms
+            .iter()
+            .enumerate()
+            .filter(|(r, (_room, room_kind))| {
+                matches!(usage[*r], RoomUsage::Free) && room_kind.capacity >= size
+            })
+            .min_by_key(|(_r, (_room, room_kind))| room_kind.capacity)
+            .map(|(r, (_room, room_kind))| (r, room_kind))?;
+        usage[r] = if shareable && room_kind.shareable {
+            RoomUsage::Shared(room_kind.capacity - size)
+        } else {
+            RoomUsage::Exclusive
+        };
+        result[c] = Some(rooms[r].0);
+    }
+    Some(result)
+}
+
+/// A single entry of a room schedule, as created by [build_schedule]: The course, which takes place
+/// in a room during a time slot
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ScheduleEntry {
+    /// Name of the time slot, or None for the default slot
+    pub slot: Option<String>,
+    /// The room, hosting the course during the time slot
+    pub room: AssignedRoom,
+    /// Index of the course
+    pub course: usize,
+}
+
+/// Build a room schedule for the given assignment, if the courses take place in different time
+/// slots (see `Course::slot`) and the rooms are only available in some of the time slots (see
+/// `CourseRoomKind::slots`): Each course, which takes place, gets a room, which is available in the
+/// course's time slot. A room may host different courses in different time slots, but never two
+/// courses in the same time slot (i.e. rooms are not shared in the schedule). Courses without time
+/// slot are in the default slot, in which only the rooms without time slot declarations are
+/// available.
+///
+/// For each time slot, the rooms are assigned like by [assign_rooms]. Note that the assignment
+/// algorithm itself does not consider time slots, i.e. it assumes that each room can only host a
+/// single course.
+///
+/// Returns the schedule entries, sorted by time slot (the default slot first), room kind and room
+/// number.
+///
+/// # Errors
+///
+/// Fails with a string error message, naming the first time slot, for which the courses cannot be
+/// fitted into the available rooms.
+pub fn build_schedule(
+    assignment: &Assignment,
+    courses: &[Course],
+    room_kinds: &[CourseRoomKind],
+) -> Result<Vec<ScheduleEntry>, String> {
+    let mut slots: Vec<&Option<String>> = courses.iter().map(|c| &c.slot).collect();
+    slots.sort();
+    slots.dedup();
+
+    let mut schedule = Vec::new();
+    for slot in slots {
+        let room_assignment = assign_rooms_filtered(
+            assignment,
+            courses,
+            room_kinds,
+            |course| course.slot == *slot,
+            |room_kind| {
+                room_kind.slots.is_empty()
+                    || slot
+                        .as_ref()
+                        .is_some_and(|slot| room_kind.slots.contains(slot))
+            },
+            false,
+        )
+        .ok_or_else(|| match slot {
+            Some(slot) => format!(
+                "The courses of time slot '{}' do not fit into its rooms.",
+                slot
+            ),
+            None => "The courses without time slot do not fit into the rooms.".to_owned(),
+        })?;
+        let mut entries: Vec<ScheduleEntry> = room_assignment
+            .into_iter()
+            .enumerate()
+            .filter_map(|(course, room)| {
+                room.map(|room| ScheduleEntry {
+                    slot: slot.clone(),
+                    room,
+                    course,
+                })
+            })
+            .collect();
+        entries.sort_by_key(|entry| (entry.room.kind, entry.room.number));
+        schedule.extend(entries);
+    }
+    Ok(schedule)
+}
+
+/// Check, if any course or room kind declares a time slot, i.e. if a room schedule (see
+/// [build_schedule]) is to be used instead of a simple room plan (see [assign_rooms])
+pub fn uses_time_slots(courses: &[Course], room_kinds: &[CourseRoomKind]) -> bool {
+    courses.iter().any(|c| c.slot.is_some()) || room_kinds.iter().any(|rk| !rk.slots.is_empty())
+}
+
+/// Returns a human-readable room schedule, based on the schedule from [build_schedule], with one
+/// line in the form "slot: room kind #2: course name" for each schedule entry
+pub fn format_schedule(
+    schedule: &[ScheduleEntry],
+    courses: &[Course],
+    room_kinds: &[CourseRoomKind],
+) -> String {
+    schedule
+        .iter()
+        .map(|entry| {
+            format!(
+                "{}: {} #{}: {}\n",
+                entry.slot.as_deref().unwrap_or("(default slot)"),
+                room_kinds[entry.room.kind].name,
+                entry.room.number + 1,
+                courses[entry.course].name
+            )
+        })
+        .collect()
+}
+
+/// Returns a human-readable room plan, based on the room assignment from [assign_rooms], with one
+/// line in the form "course name: room kind #2" for each course, which takes place.
+pub fn format_room_plan(
+    room_assignment: &[Option<AssignedRoom>],
+    courses: &[Course],
+    room_kinds: &[CourseRoomKind],
+) -> String {
+    room_assignment
+        .iter()
+        .zip(courses.iter())
+        .filter_map(|(room, course)| {
+            room.map(|r| {
+                format!(
+                    "{}: {} #{}\n",
+                    course.name,
+                    room_kinds[r.kind].name,
+                    r.number + 1
+                )
+            })
+        })
+        .collect()
+}
+
+/// Returns a human-readable list of the possible rooms (e.g. from [get_course_room_size_list]) with
+/// one line in the form "course name: 15, 12" for each course, which takes place in the given
+/// assignment.
+pub fn format_course_rooms(
+    course_rooms: &[String],
+    assignment: &Assignment,
+    courses: &[Course],
+) -> String {
+    let mut takes_place: Vec<bool> = courses.iter().map(|c| c.fixed_course).collect();
+    for c in assignment.iter().flatten() {
+        takes_place[*c] = true;
+    }
+    courses
+        .iter()
+        .zip(course_rooms.iter())
+        .filter(|(course, _rooms)| takes_place[course.index])
+        .map(|(course, rooms)| format!("{}: {}\n", course.name, rooms))
+        .collect()
+}
+
 /// Helper function for get_course_room_size_list() and get_course_room_kind_names():
 /// Returns a list of possible course room sizes for each course (in descending order)
 ///
+/// Courses of equal room-effective size are processed in the order of the courses list, such that
+/// the result is deterministic.
+///
 /// It is assumed, that `assignment` is a valid course assignment, such that for each course,
 /// a matching room can be found.
 fn calculate_possible_course_room_sizes(
@@ -94,7 +595,7 @@ fn calculate_possible_course_room_sizes(
     mut rooms: Vec<usize>,
 ) -> Vec<Vec<usize>> {
     let mut course_sizes = crate::caobab::room_effective_course_sizes(assignment, courses);
-    course_sizes.sort_unstable_by_key(|(_c, s)| std::cmp::Reverse(*s));
+    course_sizes.sort_by_key(|(c, s)| (std::cmp::Reverse(*s), c.index));
     let num = courses.len();
     rooms.sort_unstable_by_key(|v| std::cmp::Reverse(*v));
     let mut result: Vec<(&Course, Vec<usize>)> = course_sizes
@@ -144,8 +645,7 @@ mod tests {
                 instructors: vec![],
                 room_factor: *room_factor,
                 room_offset: *room_offset,
-                fixed_course: false,
-                hidden_participant_names: vec![],
+                ..Default::default()
             })
             .collect()
     }
@@ -205,26 +705,36 @@ mod tests {
                 name: "Seminar Room".into(),
                 capacity: 15,
                 quantity: 1,
+                shareable: false,
+                slots: vec![],
             },
             CourseRoomKind {
                 name: "Meeting Room".into(),
                 capacity: 6,
                 quantity: 2,
+                shareable: false,
+                slots: vec![],
             },
             CourseRoomKind {
                 name: "Seating Area".into(),
                 capacity: 6,
                 quantity: 1,
+                shareable: false,
+                slots: vec![],
             },
             CourseRoomKind {
                 name: "Normal Room".into(),
                 capacity: 3,
                 quantity: 1,
+                shareable: false,
+                slots: vec![],
             },
             CourseRoomKind {
                 name: "Office".into(),
                 capacity: 1,
                 quantity: 1,
+                shareable: false,
+                slots: vec![],
             },
         ];
 
@@ -239,10 +749,160 @@ mod tests {
         assert_eq!(assigned_course_rooms, expected_course_rooms);
     }
 
+    #[test]
+    fn test_assign_rooms_sharing() {
+        let mut courses = create_courses_with_room_offset_factor(&[
+            (0.0, 1.0),
+            (0.0, 1.0),
+            (0.0, 1.0),
+            (0.0, 1.0),
+        ]);
+        let assignment = [0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 3, 3, 3, 3, 3, 3]
+            .iter()
+            .map(|v| Some(*v))
+            .collect();
+        // effective room sizes:
+        // course 0: 5
+        // course 1: 4
+        // course 2: 3
+        // course 3: 6
+        let room_kinds = vec![
+            CourseRoomKind {
+                name: "Hall".into(),
+                capacity: 10,
+                quantity: 1,
+                shareable: true,
+                slots: vec![],
+            },
+            CourseRoomKind {
+                name: "Meeting Room".into(),
+                capacity: 6,
+                quantity: 1,
+                shareable: false,
+                slots: vec![],
+            },
+            CourseRoomKind {
+                name: "Office".into(),
+                capacity: 3,
+                quantity: 1,
+                shareable: true,
+                slots: vec![],
+            },
+        ];
+
+        // Without shareable courses, there are not enough rooms
+        assert_eq!(
+            super::assign_rooms(&assignment, &courses, &room_kinds),
+            None
+        );
+
+        // Courses 0 and 1 share the hall, course 2 does not fit in anymore, so it gets the office
+        for c in courses.iter_mut().take(3) {
+            c.shareable = true;
+        }
+        let room_assignment = super::assign_rooms(&assignment, &courses, &room_kinds).unwrap();
+        let hall = Some(super::AssignedRoom { kind: 0, number: 0 });
+        assert_eq!(
+            room_assignment,
+            vec![
+                hall,
+                hall,
+                Some(super::AssignedRoom { kind: 2, number: 0 }),
+                Some(super::AssignedRoom { kind: 1, number: 0 }),
+            ]
+        );
+        assert_eq!(
+            super::format_room_plan(&room_assignment, &courses, &room_kinds),
+            "Course 0: Hall #1\nCourse 1: Hall #1\nCourse 2: Office #1\nCourse 3: Meeting Room #1\n"
+        );
+
+        // Rooms, which are not shareable, are never shared
+        let mut room_kinds = room_kinds;
+        room_kinds[0].shareable = false;
+        assert_eq!(
+            super::assign_rooms(&assignment, &courses, &room_kinds),
+            None
+        );
+    }
+
+    #[test]
+    fn test_build_schedule() {
+        let mut courses =
+            create_courses_with_room_offset_factor(&[(0.0, 1.0), (0.0, 1.0), (0.0, 1.0)]);
+        courses[0].slot = Some("morning".into());
+        courses[1].slot = Some("afternoon".into());
+        courses[2].slot = Some("morning".into());
+        // effective room sizes:
+        // course 0: 4
+        // course 1: 4
+        // course 2: 8
+        let mut assignment: crate::Assignment =
+            [0, 0, 0, 0, 1, 1, 1, 1].iter().map(|v| Some(*v)).collect();
+        assignment.extend([Some(2); 8]);
+        let room_kinds = vec![
+            CourseRoomKind {
+                name: "Hall".into(),
+                capacity: 10,
+                quantity: 1,
+                shareable: true,
+                slots: vec![],
+            },
+            CourseRoomKind {
+                name: "Studio".into(),
+                capacity: 5,
+                quantity: 1,
+                shareable: false,
+                slots: vec!["morning".into()],
+            },
+        ];
+
+        // The hall hosts course 2 in the morning and course 1 in the afternoon, when the studio is
+        // not available
+        let hall = super::AssignedRoom { kind: 0, number: 0 };
+        let studio = super::AssignedRoom { kind: 1, number: 0 };
+        let schedule = super::build_schedule(&assignment, &courses, &room_kinds).unwrap();
+        assert_eq!(
+            schedule,
+            vec![
+                super::ScheduleEntry {
+                    slot: Some("afternoon".into()),
+                    room: hall,
+                    course: 1,
+                },
+                super::ScheduleEntry {
+                    slot: Some("morning".into()),
+                    room: hall,
+                    course: 2,
+                },
+                super::ScheduleEntry {
+                    slot: Some("morning".into()),
+                    room: studio,
+                    course: 0,
+                },
+            ]
+        );
+        assert_eq!(
+            super::format_schedule(&schedule, &courses, &room_kinds),
+            "afternoon: Hall #1: Course 1\nmorning: Hall #1: Course 2\nmorning: Studio #1: Course 0\n"
+        );
+
+        // Without time slots, the rooms are not sufficient. In the schedule, the shareable hall is
+        // never shared within a time slot.
+        for c in courses.iter_mut() {
+            c.slot = None;
+            c.shareable = true;
+        }
+        assert!(super::build_schedule(&assignment, &courses, &room_kinds).is_err());
+        courses[1].slot = Some("afternoon".into());
+        courses[2].slot = Some("afternoon".into());
+        let error = super::build_schedule(&assignment, &courses, &room_kinds).unwrap_err();
+        assert!(error.contains("'afternoon'"));
+    }
+
     #[test]
     fn test_read() {
         let data = include_bytes!("test_ressources/rooms_example.json");
-        let (rooms, room_kinds) = super::read(&data[..]).unwrap();
+        let (rooms, room_kinds) = super::read(&data[..], 100).unwrap();
 
         let expected_rooms = [15, 6, 6, 1];
         let expected_room_kinds = vec![
@@ -250,19 +910,222 @@ mod tests {
                 name: "Seminar Room".into(),
                 capacity: 15,
                 quantity: 1,
+                shareable: false,
+                slots: vec![],
             },
             CourseRoomKind {
                 name: "Meeting Room".into(),
                 capacity: 6,
                 quantity: 2,
+                shareable: false,
+                slots: vec![],
             },
             CourseRoomKind {
                 name: "Office".into(),
                 capacity: 1,
                 quantity: 1,
+                shareable: false,
+                slots: vec![],
             },
         ];
         assert_eq!(room_kinds, expected_room_kinds);
         assert_eq!(rooms, expected_rooms);
+
+        // Rooms without capacity are rejected
+        let data = r#"[{"name": "Broom Closet", "capacity": 0, "quantity": 1}]"#;
+        match super::read(data.as_bytes(), 100) {
+            Ok(_) => panic!("Expected room kind with capacity 0 to be rejected"),
+            Err(e) => assert!(e.contains("Broom Closet")),
+        }
+
+        // Too many rooms are rejected before building the list of room sizes
+        let data = include_bytes!("test_ressources/rooms_example.json");
+        assert!(super::read(&data[..], 4).is_ok());
+        assert!(super::read(&data[..], 3).is_err());
+        let data = r#"[{"name": "Cell", "capacity": 1, "quantity": 1000000000000000000}]"#;
+        assert!(super::read(data.as_bytes(), 100).is_err());
+        let data = format!(
+            r#"[{{"name": "A", "capacity": 1, "quantity": {0}}},
+                {{"name": "B", "capacity": 1, "quantity": {0}}}]"#,
+            usize::MAX
+        );
+        assert!(super::read(data.as_bytes(), usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_room_kind_order() {
+        // Room kinds of equal capacity are sorted by name, independent of the order in the file
+        let data = br#"[{"name": "Zeta Room", "capacity": 10, "quantity": 1},
+                        {"name": "Hall", "capacity": 20, "quantity": 1},
+                        {"name": "Beta Room", "capacity": 10, "quantity": 2},
+                        {"name": "Alpha Room", "capacity": 10, "quantity": 1}]"#;
+        let (rooms, mut room_kinds) = super::read(&data[..], 100).unwrap();
+        assert_eq!(rooms, vec![20, 10, 10, 10, 10]);
+        let names: Vec<&str> = room_kinds.iter().map(|rk| rk.name.as_str()).collect();
+        assert_eq!(names, vec!["Hall", "Alpha Room", "Beta Room", "Zeta Room"]);
+
+        // The possible room kinds of each course are listed in the same order, even if the room
+        // kinds are given in a different order. The two courses of equal size get the same rooms.
+        let courses = create_courses_with_room_offset_factor(&[(0.0, 1.0), (0.0, 1.0)]);
+        let assignment = vec![Some(0), Some(0), Some(1), Some(1)];
+        let expected: Vec<String> = vec![
+            "Hall, Alpha Room, Beta Room, Zeta Room".into(),
+            "Hall, Alpha Room, Beta Room, Zeta Room".into(),
+        ];
+        assert_eq!(
+            super::get_course_room_kind_names(&assignment, &courses, &room_kinds),
+            expected
+        );
+        room_kinds.reverse();
+        assert_eq!(
+            super::get_course_room_kind_names(&assignment, &courses, &room_kinds),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_room_list() {
+        assert_eq!(
+            super::parse_room_list("15,10, 10,8"),
+            Ok(vec![15, 10, 10, 8])
+        );
+        assert_eq!(super::parse_room_list("7"), Ok(vec![7]));
+
+        // Zero-size rooms are rejected
+        assert_eq!(
+            super::parse_room_list("15,0,8"),
+            Err("Room 2 has a size of 0.".to_owned())
+        );
+        // Malformed entries are rejected
+        for malformed in ["15,-3", "15,,8", "15;8", "", "ten"] {
+            assert!(
+                super::parse_room_list(malformed).is_err(),
+                "Expected '{}' to be rejected",
+                malformed
+            );
+        }
+    }
+
+    #[test]
+    fn test_required_rooms() {
+        let mut courses = create_courses_with_room_offset_factor(&[
+            (0.0, 2.0),
+            (10.0, 1.0),
+            (0.0, 1.5),
+            (0.0, 1.0),
+            (4.0, 1.0),
+        ]);
+        courses[4].fixed_course = true;
+        let assignment = [0, 0, 0, 1, 1, 2, 2, 2].iter().map(|v| Some(*v)).collect();
+        // effective room sizes:
+        // course 0:    3*2   =  6
+        // course 1: 10+2     = 12
+        // course 2:    3*1.5 =  5
+        // course 3: cancelled
+        // course 4: 4+0      =  4 (fixed)
+        let required = super::required_rooms(&assignment, &courses);
+        assert_eq!(required, vec![12, 6, 5, 4]);
+
+        assert_eq!(
+            super::bucket_required_rooms(&required, &[20, 5, 10]),
+            Ok(vec![1, 2, 1])
+        );
+        assert!(super::bucket_required_rooms(&required, &[10, 5]).is_err());
+        assert_eq!(super::bucket_required_rooms(&[], &[]), Ok(vec![]));
+
+        // Without any assigned participant, only the fixed course requires a room
+        assert_eq!(super::required_rooms(&vec![None; 8], &courses), vec![4]);
+    }
+
+    #[test]
+    fn test_find_room_conflicts() {
+        let mut courses =
+            create_courses_with_room_offset_factor(&[(0.0, 2.0), (10.0, 1.0), (0.0, 1.5)]);
+        courses[2].fixed_course = true;
+        let assignment = [0, 0, 0, 1, 1].iter().map(|v| Some(*v)).collect();
+        // effective room sizes:
+        // course 0:    3*2   =  6
+        // course 1: 10+2     = 12
+        // course 2:    0     =  0 (fixed)
+        assert!(super::find_room_conflicts(&assignment, &courses, &[12, 6, 1]).is_empty());
+        assert!(super::find_room_conflicts(&assignment, &courses, &[6, 20, 1, 1]).is_empty());
+
+        let conflicts = super::find_room_conflicts(&assignment, &courses, &[12, 5, 1]);
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].starts_with("Course 0 "));
+
+        let conflicts = super::find_room_conflicts(&assignment, &courses, &[20, 10]);
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].starts_with("Course 2 "));
+        assert!(conflicts[0].contains("No room left"));
+
+        let course_rooms = super::get_course_room_size_list(&assignment, &courses, &[12, 6, 1]);
+        assert_eq!(
+            super::format_course_rooms(&course_rooms, &assignment, &courses),
+            "Course 0: 6\nCourse 1: 12\nCourse 2: 1\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_room_list() {
+        let (_rooms, room_kinds) = super::read(
+            &br#"[{"name": "Hall", "capacity": 20, "quantity": 1},
+                  {"name": "Office", "capacity": 4, "quantity": 2}]"#[..],
+            100,
+        )
+        .unwrap();
+        let (rooms, room_kinds) = super::merge_room_list(room_kinds, &[8, 25, 8], 100).unwrap();
+        assert_eq!(rooms, vec![25, 20, 8, 8, 4, 4]);
+        assert_eq!(
+            room_kinds,
+            vec![
+                CourseRoomKind {
+                    name: "Room (25 places)".into(),
+                    capacity: 25,
+                    quantity: 1,
+                    shareable: false,
+                    slots: vec![],
+                },
+                CourseRoomKind {
+                    name: "Hall".into(),
+                    capacity: 20,
+                    quantity: 1,
+                    shareable: false,
+                    slots: vec![],
+                },
+                CourseRoomKind {
+                    name: "Room (8 places)".into(),
+                    capacity: 8,
+                    quantity: 2,
+                    shareable: false,
+                    slots: vec![],
+                },
+                CourseRoomKind {
+                    name: "Office".into(),
+                    capacity: 4,
+                    quantity: 2,
+                    shareable: false,
+                    slots: vec![],
+                },
+            ]
+        );
+
+        // Without additional rooms, the room kinds are kept
+        let (rooms, room_kinds) = super::merge_room_list(room_kinds, &[], 100).unwrap();
+        assert_eq!(rooms, vec![25, 20, 8, 8, 4, 4]);
+        assert_eq!(room_kinds.len(), 4);
+
+        // The merged rooms are checked against the limit
+        assert!(super::merge_room_list(room_kinds, &[10], 6).is_err());
+    }
+
+    #[test]
+    fn test_check_room_count() {
+        assert!(super::check_room_count(&[10; 30], 3).is_ok());
+        assert!(super::check_room_count(&[10; 31], 3).is_err());
+        assert!(super::check_room_count(&[10; 10], 0).is_ok());
+        assert!(super::check_room_count(&[], 0).is_ok());
+        assert_eq!(super::max_room_count(3), 30);
+        assert_eq!(super::max_room_count(usize::MAX), usize::MAX);
     }
 }
@@ -9,14 +9,74 @@
 // "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the License for the
 // specific language governing permissions and limitations under the License.
 
-use crate::{caobab::solution_score::QualityInfo, Assignment, Course, Participant};
+use crate::caobab::solution_score::{ParticipantSatisfaction, QualityInfo};
+use crate::{Assignment, Course, Participant};
+use log::warn;
 use serde_json::json;
+use std::collections::HashMap;
+
+/// Format identifier of the simple input data format
+const INPUT_FORMAT: &str = "X-coursedata-simple";
+/// Format identifier of the simple result data format
+const RESULT_FORMAT: &str = "X-courseassignment-simple";
+/// Latest version of the simple input data format, which is written by `write_input_data()`. Files
+/// with a newer version are rejected by `read()`.
+const INPUT_FORMAT_VERSION: (u64, u64) = (1, 0);
 
 /// Read the list of participants and courses from the simple JSON representation (canonical
 /// serde_json serialization of `Participant` and `Course` objects).
-pub fn read<R: std::io::Read>(reader: R) -> Result<(Vec<Participant>, Vec<Course>), String> {
+///
+/// The optional "format" and "version" entries of the data are checked (see
+/// `check_format_and_version()`).
+///
+/// As an alternative to the participant indexes in "instructors", the course instructors may be
+/// given by their names in an "instructor_names" list. See `resolve_instructor_names()`.
+///
+/// As an alternative to the list of choice objects, the "choices" of a participant may be given as
+/// an object, mapping course indexes to the rank of the choice. See `resolve_choice_maps()`. Or
+/// they may be given as "alternatives", a list of sets of equally preferred courses. See
+/// `resolve_choice_alternatives()`.
+///
+/// The optional "dbid" entries of participants and courses (e.g. the CdE Datenbank ids of data,
+/// converted from a CdE Datenbank export) default to the index in the respective list.
+///
+/// Courses with the `sizes_include_instructors` flag are converted to the default convention of
+/// course sizes excl. instructors, such that both conventions can be mixed in one course list.
+///
+/// If the data contains a "num_choices" (or "expected_num_choices") entry, the number of course
+/// choices of each participant (with any choices) is checked against it, to catch data-entry
+/// mistakes. See `check_num_choices()`. Mismatches are logged as warnings or, if `strict` is true,
+/// rejected as an error.
+///
+/// If `max_input_bytes` is given, larger inputs are rejected with an error, before reading them
+/// completely (see `io::SizeLimitedReader`). Too deeply nested data is always rejected.
+pub fn read<R: std::io::Read>(
+    reader: R,
+    strict: bool,
+    max_input_bytes: Option<u64>,
+) -> Result<(Vec<Participant>, Vec<Course>), String> {
+    read_with_num_choices(reader, strict, max_input_bytes)
+        .map(|(participants, courses, _num_choices)| (participants, courses))
+}
+
+/// Like `read()`, but additionally return the number of course choices per participant, given by
+/// the data's "num_choices" (or "expected_num_choices") entry, if any. It can be used to calculate
+/// quality metrics consistently with the event's configuration, see
+/// `solution_score::default_unfulfilled_choices_penalty()`.
+#[allow(clippy::type_complexity)]
+pub fn read_with_num_choices<R: std::io::Read>(
+    reader: R,
+    strict: bool,
+    max_input_bytes: Option<u64>,
+) -> Result<(Vec<Participant>, Vec<Course>, Option<usize>), String> {
     let mut data: serde_json::Value =
-        serde_json::from_reader(reader).map_err(|err| err.to_string())?;
+        serde_json::from_reader(super::SizeLimitedReader::new(reader, max_input_bytes))
+            .map_err(|err| err.to_string())?;
+    check_format_and_version(&data)?;
+    resolve_instructor_names(&mut data)?;
+    resolve_choice_maps(&mut data)?;
+    resolve_choice_alternatives(&mut data)?;
+    fill_default_dbids(&mut data);
 
     let participants_data = data
         .get_mut("participants")
@@ -29,34 +89,396 @@ pub fn read<R: std::io::Read>(reader: R) -> Result<(Vec<Participant>, Vec<Course
     let courses_data = data
         .get_mut("courses")
         .ok_or("No 'courses' found in data.")?;
+    // Remember, which room parameters are given explicitly, as missing ones are replaced by their
+    // defaults during deserialization
+    let given_room_parameters: Vec<(bool, bool)> = courses_data
+        .as_array()
+        .map(|courses| {
+            courses
+                .iter()
+                .map(|c| {
+                    (
+                        c.get("room_factor").is_some(),
+                        c.get("room_offset").is_some(),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
     let mut courses: Vec<Course> =
         serde_json::from_value(courses_data.take()).map_err(|e| format!("{}", e))?;
     for (i, c) in courses.iter_mut().enumerate() {
         c.index = i;
+        c.resolve_sizes_incl_instructors();
+        (c.room_factor_given, c.room_offset_given) = given_room_parameters[i];
+    }
+
+    let num_choices = read_num_choices(&data)?;
+    if let Some(expected) = num_choices {
+        let problems = check_num_choices(&participants, expected);
+        if strict && !problems.is_empty() {
+            return Err(problems.join("\n"));
+        }
+        for problem in problems {
+            warn!("{}", problem);
+        }
+    }
+
+    Ok((participants, courses, num_choices))
+}
+
+/// Get the number of course choices per participant from the "num_choices" entry of the data or,
+/// alternatively, its older name "expected_num_choices". Only one of them may be given.
+fn read_num_choices(data: &serde_json::Value) -> Result<Option<usize>, String> {
+    let (key, value) = match (data.get("num_choices"), data.get("expected_num_choices")) {
+        (Some(_), Some(_)) => {
+            return Err(
+                "Only one of 'num_choices' and 'expected_num_choices' may be given.".to_owned(),
+            )
+        }
+        (Some(value), None) => ("num_choices", value),
+        (None, Some(value)) => ("expected_num_choices", value),
+        (None, None) => return Ok(None),
+    };
+    value
+        .as_u64()
+        .map(|n| Some(n as usize))
+        .ok_or(format!("'{}' is not a non-negative integer.", key))
+}
+
+/// Check that each participant has the expected number of course choices. Participants without
+/// any choices (e.g. instructor-only participants) are not considered.
+///
+/// Returns a list of human readable problem descriptions (one per mismatching participant).
+fn check_num_choices(participants: &[Participant], expected: usize) -> Vec<String> {
+    participants
+        .iter()
+        .filter(|p| !p.choices.is_empty() && p.choices.len() != expected)
+        .map(|p| {
+            format!(
+                "Participant {} has {} course choices, but {} are expected.",
+                p.name,
+                p.choices.len(),
+                expected
+            )
+        })
+        .collect()
+}
+
+/// Check the "format" and "version" entries of simple input data.
+///
+/// Both entries are optional, as the format has been written by hand before they were introduced.
+/// Data without version is considered to be of version 1.0. All changes of the format within
+/// version 1.0 only introduced new optional fields with default values, so no migration of older
+/// data is required. When changing the format incompatibly, INPUT_FORMAT_VERSION must be
+/// incremented and older data must be migrated after this check.
+///
+/// # Errors
+///
+/// Fails with a string error message, if the format is not the simple input data format (e.g. an
+/// assignment output file), the version is malformed or it is not a 1.x version up to
+/// INPUT_FORMAT_VERSION.
+fn check_format_and_version(data: &serde_json::Value) -> Result<(), String> {
+    if let Some(format) = data.get("format") {
+        if format.as_str() != Some(INPUT_FORMAT) {
+            return Err(format!(
+                "The given file has format {}, but {} is expected.",
+                format, INPUT_FORMAT
+            ));
+        }
+    }
+    let version = match data.get("version") {
+        None => return Ok(()),
+        Some(v) => v.as_str().ok_or("'version' is not a string.")?,
+    };
+    let parse_error = || format!("Invalid format version '{}'.", version);
+    let (major, minor) = version.split_once('.').ok_or_else(parse_error)?;
+    let version = (
+        major.parse::<u64>().map_err(|_| parse_error())?,
+        minor.parse::<u64>().map_err(|_| parse_error())?,
+    );
+    if version > INPUT_FORMAT_VERSION {
+        return Err(format!(
+            "The given file has format version {}.{}, but only versions up to {}.{} are \
+            supported by this version of cdecao.",
+            version.0, version.1, INPUT_FORMAT_VERSION.0, INPUT_FORMAT_VERSION.1
+        ));
+    }
+    if version.0 < 1 {
+        return Err(format!(
+            "Unknown format version {}.{}.",
+            version.0, version.1
+        ));
+    }
+    Ok(())
+}
+
+/// Resolve the optional "instructor_names" entries of the courses in simple input data to
+/// participant indexes and add them to the courses' "instructors" lists (which are created, if
+/// missing). This allows to write input files by hand without counting participant indexes.
+///
+/// # Errors
+///
+/// Fails with a string error message, if a name does not match any participant's name or matches
+/// the names of multiple participants.
+fn resolve_instructor_names(data: &mut serde_json::Value) -> Result<(), String> {
+    let mut participant_indexes_by_name: HashMap<String, Vec<usize>> = HashMap::new();
+    if let Some(participants_data) = data.get("participants").and_then(|v| v.as_array()) {
+        for (i, p) in participants_data.iter().enumerate() {
+            if let Some(name) = p.get("name").and_then(|v| v.as_str()) {
+                participant_indexes_by_name
+                    .entry(name.to_owned())
+                    .or_default()
+                    .push(i);
+            }
+        }
+    }
+
+    let courses_data = match data.get_mut("courses").and_then(|v| v.as_array_mut()) {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+    for course in courses_data.iter_mut() {
+        let course = match course.as_object_mut() {
+            Some(c) => c,
+            None => continue,
+        };
+        let names = match course.remove("instructor_names") {
+            Some(names) => names,
+            None => continue,
+        };
+        let course_name = course
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_owned();
+        let names = names.as_array().ok_or(format!(
+            "'instructor_names' of course {} is not a list.",
+            course_name
+        ))?;
+        let instructors = course
+            .entry("instructors")
+            .or_insert_with(|| json!([]))
+            .as_array_mut()
+            .ok_or(format!(
+                "'instructors' of course {} is not a list.",
+                course_name
+            ))?;
+        for name in names {
+            let name = name.as_str().ok_or(format!(
+                "Instructor name {} of course {} is not a string.",
+                name, course_name
+            ))?;
+            let index = match participant_indexes_by_name.get(name).map(|v| &v[..]) {
+                Some([index]) => *index,
+                Some(_) => {
+                    return Err(format!(
+                        "Instructor name '{}' of course {} is ambiguous: There are multiple \
+                         participants with this name.",
+                        name, course_name
+                    ))
+                }
+                None => {
+                    return Err(format!(
+                        "Instructor '{}' of course {} does not match any participant's name.",
+                        name, course_name
+                    ))
+                }
+            };
+            if !instructors.iter().any(|i| i.as_u64() == Some(index as u64)) {
+                instructors.push(json!(index));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Add the index of each participant and course in simple input data as its "dbid", if the entry is
+/// missing.
+fn fill_default_dbids(data: &mut serde_json::Value) {
+    for list in ["participants", "courses"] {
+        if let Some(entries) = data.get_mut(list).and_then(|v| v.as_array_mut()) {
+            for (i, entry) in entries.iter_mut().enumerate() {
+                if let Some(entry) = entry.as_object_mut() {
+                    entry.entry("dbid").or_insert_with(|| json!(i));
+                }
+            }
+        }
     }
+}
+
+/// Convert the "choices" entries of participants in simple input data, which are given as an object
+/// mapping course indexes to choice ranks (e.g. `{"3": 0, "5": 1}`), into the regular list of
+/// choice objects, using the rank as penalty. The resulting list is ordered by rank (and course
+/// index). A warning is logged, if the ranks of a participant have gaps (e.g. only ranks 0 and 2).
+///
+/// # Errors
+///
+/// Fails with a string error message, if a key is not a course index or a rank is not a
+/// non-negative integer.
+fn resolve_choice_maps(data: &mut serde_json::Value) -> Result<(), String> {
+    let participants_data = match data.get_mut("participants").and_then(|v| v.as_array_mut()) {
+        Some(p) => p,
+        None => return Ok(()),
+    };
+    for participant in participants_data.iter_mut() {
+        let participant_name = participant
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_owned();
+        let choice_map = match participant.get("choices").and_then(|v| v.as_object()) {
+            Some(m) => m,
+            None => continue,
+        };
+        let mut choices = Vec::new();
+        for (course, rank) in choice_map.iter() {
+            let course = course.parse::<usize>().map_err(|_| {
+                format!(
+                    "Course choice '{}' of participant {} is not a course index.",
+                    course, participant_name
+                )
+            })?;
+            let rank = rank.as_u64().ok_or(format!(
+                "Rank {} of course choice {} of participant {} is not a non-negative integer.",
+                rank, course, participant_name
+            ))?;
+            choices.push((rank, course));
+        }
+        choices.sort_unstable();
+
+        let mut ranks: Vec<u64> = choices.iter().map(|(rank, _)| *rank).collect();
+        ranks.dedup();
+        if ranks.iter().enumerate().any(|(i, rank)| *rank != i as u64) {
+            warn!(
+                "The ranks of participant {}'s course choices have gaps: {:?}",
+                participant_name, ranks
+            );
+        }
+
+        participant["choices"] = choices
+            .iter()
+            .map(|(rank, course)| json!({"course": course, "penalty": rank}))
+            .collect();
+    }
+    Ok(())
+}
 
-    Ok((participants, courses))
+/// Convert the "alternatives" entries of participants in simple input data into the regular list of
+/// choice objects. The alternatives are given as a list of ranks, each of which is a list of
+/// equally preferred course indexes (e.g. `[[3, 5], [1]]` for either course 3 or 5 as first choice
+/// and course 1 as second choice). All courses of one rank get the same penalty (the index of the
+/// rank), i.e. the penalty is not incremented within a set of alternatives.
+///
+/// # Errors
+///
+/// Fails with a string error message, if a participant has both "alternatives" and "choices" or
+/// the alternatives are not a list of lists of course indexes.
+fn resolve_choice_alternatives(data: &mut serde_json::Value) -> Result<(), String> {
+    let participants_data = match data.get_mut("participants").and_then(|v| v.as_array_mut()) {
+        Some(p) => p,
+        None => return Ok(()),
+    };
+    for participant in participants_data.iter_mut() {
+        let participant_obj = match participant.as_object_mut() {
+            Some(p) => p,
+            None => continue,
+        };
+        let alternatives = match participant_obj.remove("alternatives") {
+            Some(a) => a,
+            None => continue,
+        };
+        let participant_name = participant_obj
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_owned();
+        if participant_obj.contains_key("choices") {
+            return Err(format!(
+                "Participant {} has both 'choices' and 'alternatives'.",
+                participant_name
+            ));
+        }
+        let ranks = alternatives.as_array().ok_or(format!(
+            "'alternatives' of participant {} is not a list.",
+            participant_name
+        ))?;
+        let mut choices = Vec::new();
+        for (rank, courses) in ranks.iter().enumerate() {
+            let courses = courses.as_array().ok_or(format!(
+                "Alternative {} of participant {} is not a list of courses.",
+                rank + 1,
+                participant_name
+            ))?;
+            for course in courses.iter() {
+                let course = course.as_u64().ok_or(format!(
+                    "Course {} in alternative {} of participant {} is not a course index.",
+                    course,
+                    rank + 1,
+                    participant_name
+                ))?;
+                choices.push(json!({"course": course, "penalty": rank}));
+            }
+        }
+        participant_obj.insert("choices".to_owned(), choices.into());
+    }
+    Ok(())
 }
 
 /// Write the calculated course assignment as simple JSON representation (canonical
 /// serde_json serialization of `Assignmet` objects) to a Writer (e.g. an output file).
+///
+/// If a `satisfaction` report is given, it is added to the output data as an additional
+/// "satisfaction" entry.
 pub fn write<W: std::io::Write>(
     writer: W,
     assignment: &Assignment,
     quality_info: &QualityInfo,
+    satisfaction: Option<&[ParticipantSatisfaction]>,
 ) -> Result<(), String> {
     let a: serde_json::Value = serde_json::to_value(assignment).map_err(|e| format!("{}", e))?;
-    let data = json!({
-        "format": "X-courseassignment-simple",
+    let mut data = json!({
+        "format": RESULT_FORMAT,
         "version": "1.1",
         "assignment": a,
         "quality": quality_info,
     });
+    if let Some(satisfaction) = satisfaction {
+        data["satisfaction"] = serde_json::to_value(satisfaction).map_err(|e| format!("{}", e))?;
+    }
     serde_json::to_writer(writer, &data).map_err(|e| format!("{}", e))?;
 
     Ok(())
 }
 
+/// Read a calculated course assignment and its quality info from the simple JSON representation,
+/// as written by `write()`, e.g. to compare the results of two solver runs.
+pub fn read_result<R: std::io::Read>(reader: R) -> Result<(Assignment, QualityInfo), String> {
+    let mut data: serde_json::Value =
+        serde_json::from_reader(reader).map_err(|err| err.to_string())?;
+    let format = data.get("format").ok_or("No 'format' found in data.")?;
+    if format.as_str() != Some(RESULT_FORMAT) {
+        return Err(format!(
+            "The given file has format {}, but {} is expected.",
+            format, RESULT_FORMAT
+        ));
+    }
+    let assignment: Assignment = serde_json::from_value(
+        data.get_mut("assignment")
+            .ok_or("No 'assignment' found in data.")?
+            .take(),
+    )
+    .map_err(|e| format!("{}", e))?;
+    let quality_info: QualityInfo = serde_json::from_value(
+        data.get_mut("quality")
+            .ok_or("No 'quality' found in data.")?
+            .take(),
+    )
+    .map_err(|e| format!("{}", e))?;
+
+    Ok((assignment, quality_info))
+}
+
 /// Write the list of participants and courses to the simple JSON representation (canonical
 /// serde_json serialization of `Participant` and `Course` objects).
 pub fn write_input_data<W: std::io::Write>(
@@ -67,8 +489,8 @@ pub fn write_input_data<W: std::io::Write>(
     let p: serde_json::Value = serde_json::to_value(participants).map_err(|e| format!("{}", e))?;
     let c: serde_json::Value = serde_json::to_value(courses).map_err(|e| format!("{}", e))?;
     let data = json!({
-        "format": "X-coursedata-simple",
-        "version": "1.0",
+        "format": INPUT_FORMAT,
+        "version": format!("{}.{}", INPUT_FORMAT_VERSION.0, INPUT_FORMAT_VERSION.1),
         "participants": p,
         "courses": c,
     });
@@ -82,7 +504,7 @@ mod tests {
     #[test]
     fn parse_simple_file() {
         let data = include_bytes!("test_ressources/simple_input.json");
-        let (participants, courses) = super::read(&data[..]).unwrap();
+        let (participants, courses) = super::read(&data[..], false, None).unwrap();
 
         super::super::assert_data_consitency(&participants, &courses);
         assert_eq!(participants.len(), 6);
@@ -93,18 +515,27 @@ mod tests {
             vec![
                 crate::Choice {
                     course_index: 2,
-                    penalty: 0
+                    penalty: 0,
+                    tier: crate::ChoiceTier::Preferred,
                 },
                 crate::Choice {
                     course_index: 0,
-                    penalty: 1
+                    penalty: 1,
+                    tier: crate::ChoiceTier::Preferred,
                 },
                 crate::Choice {
                     course_index: 1,
-                    penalty: 42
+                    penalty: 42,
+                    tier: crate::ChoiceTier::Acceptable,
                 },
             ]
         );
+        assert_eq!(participants[3].forbidden_courses, vec![1]);
+        assert!(participants[2].forbidden_courses.is_empty());
+        assert_eq!(participants[3].available_courses, Some(vec![0, 2, 3]));
+        assert_eq!(participants[2].available_courses, None);
+        assert_eq!(participants[3].max_acceptable_rank, Some(2));
+        assert_eq!(participants[2].max_acceptable_rank, None);
         assert_eq!(courses[2].name, "3. The Third Course");
         assert_eq!(courses[2].num_min, 3);
         assert_eq!(courses[2].num_max, 20);
@@ -112,9 +543,372 @@ mod tests {
         assert_eq!(courses[2].room_offset, 12.0);
         assert!(!courses[2].fixed_course);
         assert_eq!(courses[0].room_offset, 0.0);
+        assert!(courses[2].room_offset_given);
+        assert!(!courses[0].room_offset_given);
+        assert!(!courses[2].room_factor_given);
         assert!(courses[1].fixed_course);
     }
 
+    #[test]
+    fn parse_simple_file_versions() {
+        let data = include_bytes!("test_ressources/simple_input.json");
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+
+        // Explicit version 1.0
+        json_data["format"] = serde_json::json!("X-coursedata-simple");
+        json_data["version"] = serde_json::json!("1.0");
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let (participants, courses) = super::read(&modified_data[..], false, None).unwrap();
+        assert_eq!(participants.len(), 6);
+        assert_eq!(courses.len(), 4);
+
+        // Hypothetical future version 2.0 with new fields
+        json_data["version"] = serde_json::json!("2.0");
+        json_data["participants"][0]["groups"] = serde_json::json!([1, 2]);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        match super::read(&modified_data[..], false, None) {
+            Ok(_) => panic!("Expected version 2.0 to be rejected"),
+            Err(e) => assert!(e.contains("2.0")),
+        }
+
+        // Unknown older version
+        json_data["version"] = serde_json::json!("0.9");
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        assert!(super::read(&modified_data[..], false, None).is_err());
+
+        // Malformed version
+        json_data["version"] = serde_json::json!("1");
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        assert!(super::read(&modified_data[..], false, None).is_err());
+
+        // Other file formats (e.g. an assignment output file) are rejected
+        json_data["format"] = serde_json::json!("X-courseassignment-simple");
+        json_data["version"] = serde_json::json!("1.0");
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        assert!(super::read(&modified_data[..], false, None).is_err());
+    }
+
+    #[test]
+    fn parse_simple_file_size_limit() {
+        let data = include_bytes!("test_ressources/simple_input.json");
+        let size = data.len() as u64;
+        assert!(super::read(&data[..], false, Some(size)).is_ok());
+        let error = super::read(&data[..], false, Some(size - 1)).err().unwrap();
+        assert!(
+            error.contains(&format!(
+                "Input exceeds the maximum size of {} bytes",
+                size - 1
+            )),
+            "{}",
+            error
+        );
+
+        // Deeply nested data is rejected, regardless of the size limit
+        let nested = format!("{}{}", "[".repeat(10_000), "]".repeat(10_000));
+        let error = super::read(nested.as_bytes(), false, None).err().unwrap();
+        assert!(error.contains("recursion limit exceeded"), "{}", error);
+    }
+
+    #[test]
+    fn parse_simple_file_dbids() {
+        let data = include_bytes!("test_ressources/simple_input.json");
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+        json_data["participants"][1]["dbid"] = serde_json::json!(1234);
+        json_data["courses"][3]["dbid"] = serde_json::json!(42);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let (participants, courses) = super::read(&modified_data[..], false, None).unwrap();
+
+        // Missing ids default to the index
+        let participant_dbids: Vec<usize> = participants.iter().map(|p| p.dbid).collect();
+        assert_eq!(participant_dbids, vec![0, 1234, 2, 3, 4, 5]);
+        let course_dbids: Vec<usize> = courses.iter().map(|c| c.dbid).collect();
+        assert_eq!(course_dbids, vec![0, 1, 2, 42]);
+
+        // The ids survive writing the input data
+        let mut buffer = Vec::<u8>::new();
+        super::write_input_data(&mut buffer, &participants, &courses).unwrap();
+        let (parsed_participants, parsed_courses) = super::read(&buffer[..], false, None).unwrap();
+        assert_eq!(parsed_participants[1].dbid, 1234);
+        assert_eq!(parsed_courses[3].dbid, 42);
+    }
+
+    #[test]
+    fn parse_simple_file_sizes_include_instructors() {
+        let data = include_bytes!("test_ressources/simple_input.json");
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+
+        // Course 0 (with 2 instructors) and course 2 (with 1 instructor, given by name) count their
+        // instructors, while course 1 (with 1 instructor) uses the default convention
+        json_data["courses"][0]["sizes_include_instructors"] = serde_json::json!(true);
+        json_data["courses"][1]["sizes_include_instructors"] = serde_json::json!(false);
+        json_data["courses"][2]["sizes_include_instructors"] = serde_json::json!(true);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let (participants, courses) = super::read(&modified_data[..], false, None).unwrap();
+        super::super::assert_data_consitency(&participants, &courses);
+        let sizes: Vec<(usize, usize)> = courses.iter().map(|c| (c.num_min, c.num_max)).collect();
+        assert_eq!(sizes, vec![(3, 13), (6, 10), (2, 19), (0, 5)]);
+        assert!(courses.iter().all(|c| !c.sizes_include_instructors));
+
+        // Instructors given by name are added before converting the sizes
+        json_data["courses"][1]["sizes_include_instructors"] = serde_json::json!(true);
+        json_data["courses"][1]["instructor_names"] = serde_json::json!(["Anton Administrator"]);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let (_participants, courses) = super::read(&modified_data[..], false, None).unwrap();
+        assert_eq!((courses[1].num_min, courses[1].num_max), (4, 8));
+    }
+
+    #[test]
+    fn parse_simple_file_instructor_names() {
+        let data = include_bytes!("test_ressources/simple_input.json");
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+
+        // Names and indexes may be mixed
+        json_data["courses"][0]["instructor_names"] =
+            serde_json::json!(["Anton Administrator", "Charly Clown"]);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let (participants, courses) = super::read(&modified_data[..], false, None).unwrap();
+        super::super::assert_data_consitency(&participants, &courses);
+        assert_eq!(courses[0].instructors, vec![0, 1, 2]);
+
+        // Unknown names are rejected
+        json_data["courses"][0]["instructor_names"] = serde_json::json!(["Zacharias Zauberer"]);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        match super::read(&modified_data[..], false, None) {
+            Ok(_) => panic!("Expected unknown instructor name to be rejected"),
+            Err(e) => assert!(e.contains("Zacharias Zauberer")),
+        }
+
+        // Ambiguous names are rejected
+        json_data["participants"][3]["name"] = serde_json::json!("Charly Clown");
+        json_data["courses"][0]["instructor_names"] = serde_json::json!(["Charly Clown"]);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        match super::read(&modified_data[..], false, None) {
+            Ok(_) => panic!("Expected ambiguous instructor name to be rejected"),
+            Err(e) => assert!(e.contains("ambiguous")),
+        }
+    }
+
+    #[test]
+    fn parse_simple_file_expected_num_choices() {
+        let data = include_bytes!("test_ressources/simple_input.json");
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+        // Charly Clown and Daniel Dino have 3 choices, all others have 2
+        let participants = super::read(&data[..], false, None).unwrap().0;
+        assert_eq!(super::check_num_choices(&participants, 2).len(), 2);
+        assert_eq!(super::check_num_choices(&participants, 3).len(), 4);
+
+        // Matching counts
+        for p in json_data["participants"].as_array_mut().unwrap() {
+            p["choices"].as_array_mut().unwrap().truncate(2);
+        }
+        json_data["expected_num_choices"] = serde_json::json!(2);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let (participants, _courses) = super::read(&modified_data[..], true, None).unwrap();
+        assert!(super::check_num_choices(&participants, 2).is_empty());
+
+        // Participants without choices are not considered
+        json_data["participants"][0]["choices"] = serde_json::json!([]);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        assert!(super::read(&modified_data[..], true, None).is_ok());
+
+        // Mismatching counts are only rejected in strict mode
+        json_data["expected_num_choices"] = serde_json::json!(3);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        assert!(super::read(&modified_data[..], false, None).is_ok());
+        match super::read(&modified_data[..], true, None) {
+            Ok(_) => panic!("Expected mismatching number of choices to be rejected"),
+            Err(e) => assert!(e.contains("Bertalottå Beispiel has 2 course choices, but 3")),
+        }
+
+        json_data["expected_num_choices"] = serde_json::json!("3");
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        assert!(super::read(&modified_data[..], false, None).is_err());
+    }
+
+    #[test]
+    fn parse_simple_file_num_choices() {
+        let data = include_bytes!("test_ressources/simple_input.json");
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+        let (participants, _courses, num_choices) =
+            super::read_with_num_choices(&data[..], false, None).unwrap();
+        assert_eq!(num_choices, None);
+        // Without the header value, the largest number of choices (Charly Clown's and Daniel
+        // Dino's 3 choices) is used as penalty
+        assert_eq!(
+            crate::caobab::solution_score::default_unfulfilled_choices_penalty(
+                &participants,
+                num_choices
+            ),
+            3
+        );
+
+        // With all participants' choices truncated to 2, the header value is accepted in strict
+        // mode ...
+        for p in json_data["participants"].as_array_mut().unwrap() {
+            p["choices"].as_array_mut().unwrap().truncate(2);
+        }
+        json_data["num_choices"] = serde_json::json!(2);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let (participants, courses, num_choices) =
+            super::read_with_num_choices(&modified_data[..], true, None).unwrap();
+        assert_eq!(num_choices, Some(2));
+
+        // ... and drives the penalty for participants assigned to a course they did not choose
+        let penalty = crate::caobab::solution_score::default_unfulfilled_choices_penalty(
+            &participants,
+            num_choices,
+        );
+        assert_eq!(penalty, 2);
+        let unchosen_course = (0..courses.len())
+            .find(|c| {
+                participants[0]
+                    .choices
+                    .iter()
+                    .all(|ch| ch.course_index != *c)
+                    && !courses[*c].instructors.contains(&0)
+            })
+            .unwrap();
+        let assignment: crate::Assignment = participants
+            .iter()
+            .map(|p| {
+                if p.index == 0 {
+                    Some(unchosen_course)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        let num_scored = participants
+            .iter()
+            .filter(|p| !p.is_instructor_only())
+            .count();
+        assert_eq!(
+            crate::caobab::solution_score::assignment_quality(
+                &participants,
+                &courses,
+                &assignment,
+                0,
+                penalty
+            ),
+            2.0 / num_scored as f32
+        );
+
+        // Only one of the entries may be given
+        json_data["expected_num_choices"] = serde_json::json!(2);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        assert!(super::read(&modified_data[..], false, None).is_err());
+    }
+
+    #[test]
+    fn write_and_parse_input_data() {
+        let data = include_bytes!("test_ressources/simple_input.json");
+        let (participants, courses) = super::read(&data[..], false, None).unwrap();
+
+        let mut buffer = Vec::<u8>::new();
+        super::write_input_data(&mut buffer, &participants, &courses).unwrap();
+        let written_data: serde_json::Value = serde_json::from_reader(&buffer[..]).unwrap();
+        assert_eq!(written_data["version"], "1.0");
+
+        let (parsed_participants, parsed_courses) = super::read(&buffer[..], false, None).unwrap();
+        assert_eq!(parsed_participants.len(), participants.len());
+        assert_eq!(parsed_courses.len(), courses.len());
+        assert_eq!(parsed_participants[2].choices, participants[2].choices);
+        assert_eq!(parsed_courses[2].instructors, courses[2].instructors);
+    }
+
+    #[test]
+    fn parse_simple_file_choice_maps() {
+        let data = include_bytes!("test_ressources/simple_input.json");
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+        let (array_participants, _courses) = super::read(&data[..], false, None).unwrap();
+
+        // The map form is equivalent to the array form (regardless of the order of the entries)
+        json_data["participants"][0]["choices"] = serde_json::json!({"3": 1, "1": 0});
+        json_data["participants"][1]["choices"] = serde_json::json!({"3": 0, "1": 1});
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let (map_participants, courses) = super::read(&modified_data[..], false, None).unwrap();
+        super::super::assert_data_consitency(&map_participants, &courses);
+        for i in 0..2 {
+            assert_eq!(map_participants[i].choices, array_participants[i].choices);
+        }
+
+        // Both forms round-trip through the array form of written input data
+        let mut buffer = Vec::<u8>::new();
+        super::write_input_data(&mut buffer, &map_participants, &courses).unwrap();
+        let (parsed_participants, _courses) = super::read(&buffer[..], false, None).unwrap();
+        for (parsed, array) in parsed_participants.iter().zip(array_participants.iter()) {
+            assert_eq!(parsed.choices, array.choices);
+        }
+
+        // Gaps in the ranks are accepted (with a warning)
+        json_data["participants"][0]["choices"] = serde_json::json!({"3": 2, "1": 0});
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let (participants, _courses) = super::read(&modified_data[..], false, None).unwrap();
+        assert_eq!(
+            participants[0].choices,
+            vec![
+                crate::Choice {
+                    course_index: 1,
+                    penalty: 0,
+                    tier: crate::ChoiceTier::Preferred
+                },
+                crate::Choice {
+                    course_index: 3,
+                    penalty: 2,
+                    tier: crate::ChoiceTier::Preferred
+                }
+            ]
+        );
+
+        // Negative ranks and invalid course indexes are rejected
+        for invalid in [
+            serde_json::json!({"3": -1, "1": 0}),
+            serde_json::json!({"3": 0.5}),
+            serde_json::json!({"three": 0}),
+        ] {
+            json_data["participants"][0]["choices"] = invalid;
+            let modified_data = serde_json::to_vec(&json_data).unwrap();
+            assert!(super::read(&modified_data[..], false, None).is_err());
+        }
+    }
+
+    #[test]
+    fn parse_simple_file_choice_alternatives() {
+        let data = include_bytes!("test_ressources/simple_input.json");
+        let mut json_data = serde_json::from_reader::<&[u8], serde_json::Value>(&data[..]).unwrap();
+        let participant = json_data["participants"][0].as_object_mut().unwrap();
+        participant.remove("choices");
+        participant.insert("alternatives".to_owned(), serde_json::json!([[3, 1], [2]]));
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        let (participants, courses) = super::read(&modified_data[..], false, None).unwrap();
+        super::super::assert_data_consitency(&participants, &courses);
+
+        // Both alternatives are treated as the same (first) rank
+        let choice = |course_index, penalty| crate::Choice {
+            course_index,
+            penalty,
+            tier: crate::ChoiceTier::Preferred,
+        };
+        assert_eq!(
+            participants[0].choices,
+            vec![choice(3, 0), choice(1, 0), choice(2, 1)]
+        );
+
+        // Giving both forms or malformed alternatives is rejected
+        json_data["participants"][1]["alternatives"] = serde_json::json!([[3]]);
+        let modified_data = serde_json::to_vec(&json_data).unwrap();
+        assert!(super::read(&modified_data[..], false, None).is_err());
+        json_data["participants"][1]
+            .as_object_mut()
+            .unwrap()
+            .remove("alternatives");
+        for invalid in [serde_json::json!([3, 1]), serde_json::json!([["three"]])] {
+            json_data["participants"][0]["alternatives"] = invalid;
+            let modified_data = serde_json::to_vec(&json_data).unwrap();
+            assert!(super::read(&modified_data[..], false, None).is_err());
+        }
+    }
+
     #[test]
     fn write_simple_file() {
         let assignment: crate::Assignment =
@@ -123,12 +917,16 @@ mod tests {
         let quality_info = crate::caobab::solution_score::QualityInfo {
             solution_score: 299_999,
             theoretical_max_score: 300_000,
+            theoretical_min_score: 299_990,
             solution_quality: 0.25,
             theoretical_max_quality: 0.0,
+            theoretical_min_quality: 1.0,
             overall_quality: None,
+            penalty_statistics: None,
+            normalized_quality: None,
         };
         let mut buffer = Vec::<u8>::new();
-        let result = super::write(&mut buffer, &assignment, &quality_info);
+        let result = super::write(&mut buffer, &assignment, &quality_info, None);
         assert!(result.is_ok());
 
         // Parse buffer as JSON file
@@ -151,5 +949,62 @@ mod tests {
                 "There sould be a float 'solution_quality' in the serialized quality data."
             )
         );
+        assert!(data.get("satisfaction").is_none());
+
+        // Write again, including a satisfaction report
+        let satisfaction = vec![crate::caobab::solution_score::ParticipantSatisfaction {
+            participant: 0,
+            assigned_course: Some(0),
+            assigned_rank: crate::caobab::solution_score::AssignedRank::Rank(1),
+            best_achievable_rank: Some(0),
+        }];
+        let mut buffer = Vec::<u8>::new();
+        super::write(&mut buffer, &assignment, &quality_info, Some(&satisfaction)).unwrap();
+        let data: serde_json::Value = serde_json::from_reader(&buffer[..]).unwrap();
+        assert_eq!(
+            data["satisfaction"],
+            serde_json::json!([{
+                "participant": 0,
+                "assigned_course": 0,
+                "assigned_rank": {"rank": 1},
+                "best_achievable_rank": 0,
+            }])
+        );
+    }
+
+    #[test]
+    fn write_and_read_result() {
+        let assignment: crate::Assignment = vec![Some(1), None, Some(0)];
+        let quality_info = crate::caobab::solution_score::QualityInfo {
+            solution_score: 99_999,
+            theoretical_max_score: 100_000,
+            theoretical_min_score: 99_990,
+            solution_quality: 0.5,
+            theoretical_max_quality: 0.0,
+            theoretical_min_quality: 1.0,
+            overall_quality: None,
+            penalty_statistics: Some(crate::caobab::solution_score::PenaltyStatistics {
+                min: 0,
+                max: 1,
+                mean: 0.5,
+                stddev: 0.5,
+            }),
+            normalized_quality: None,
+        };
+        let mut buffer = Vec::<u8>::new();
+        super::write(&mut buffer, &assignment, &quality_info, None).unwrap();
+
+        let (parsed_assignment, parsed_quality_info) = super::read_result(&buffer[..]).unwrap();
+        assert_eq!(parsed_assignment, assignment);
+        assert_eq!(parsed_quality_info.solution_score, 99_999);
+        assert_eq!(parsed_quality_info.solution_quality, 0.5);
+        assert_eq!(
+            parsed_quality_info.penalty_statistics,
+            quality_info.penalty_statistics
+        );
+
+        // Input data files are rejected
+        let data = include_bytes!("test_ressources/simple_input.json");
+        assert!(super::read_result(&data[..]).is_err());
     }
 }
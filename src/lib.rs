@@ -19,18 +19,43 @@ pub mod io;
 use serde::{Deserialize, Serialize};
 
 /// Representation of an event participant's data
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, Default)]
 pub struct Participant {
     /// id/index of the Participant in the list of participants
     #[serde(skip)]
     index: usize,
-    /// Participant's registration id in the CdE Datebank
-    #[serde(skip)]
+    /// Participant's registration id in the CdE Datebank. In the simple data format, it is
+    /// optional and defaults to the participant's index.
+    #[serde(default)]
     dbid: usize,
     /// Participant's name. Mainly used for info/debug output
     name: String,
     /// Course choices
     pub choices: Vec<Choice>,
+    /// Index of the course, the participant is currently assigned to (if any). Keeping participants
+    /// in their current course is used as a tie-break between solutions of equal score, to avoid
+    /// needless changes of existing assignments.
+    #[serde(default)]
+    current_course: Option<usize>,
+    /// Indexes of courses, the participant must never be assigned to (e.g. "anything but course
+    /// X"), even if this is the only way to fill the course
+    #[serde(default)]
+    forbidden_courses: Vec<usize>,
+    /// Indexes of the courses, the participant may be assigned to at all (e.g. because they only
+    /// attend part of the event), or None, if all courses are available. Unlike choices, this is a
+    /// hard restriction: The participant is never assigned to any other course.
+    #[serde(default)]
+    available_courses: Option<Vec<usize>>,
+    /// Worst rank of a course choice (1 for the first choice), the participant accepts to be
+    /// assigned to, if any. Choices with a worse rank are ignored and the participant is left
+    /// unassigned rather than being assigned to one of them. See [Participant::is_acceptable_choice].
+    #[serde(default)]
+    max_acceptable_rank: Option<u32>,
+    /// Id of the participant's group (e.g. their age group or home town), if any. If balancing of
+    /// groups is enabled, the solver tries to assign participants of each group proportionally to
+    /// all courses. See `caobab::SolveConfig::group_balance_penalty`.
+    #[serde(default)]
+    group: Option<u32>,
 }
 
 impl Participant {
@@ -39,17 +64,91 @@ impl Participant {
     pub fn is_instructor_only(&self) -> bool {
         self.choices.is_empty()
     }
+
+    /// Check if this participant must not be assigned to the given course as an attendee. See
+    /// `Participant::forbidden_courses`.
+    pub fn is_forbidden_course(&self, course_index: usize) -> bool {
+        self.forbidden_courses.contains(&course_index)
+    }
+
+    /// Check if this participant may be assigned to the given course at all. See
+    /// `Participant::available_courses`.
+    pub fn is_available_course(&self, course_index: usize) -> bool {
+        self.available_courses
+            .as_ref()
+            .is_none_or(|courses| courses.contains(&course_index))
+    }
+
+    /// Check if this participant may be left without any course (instead of being assigned to a
+    /// course choice with a rank worse than their `Participant::max_acceptable_rank`)
+    pub fn may_stay_unassigned(&self) -> bool {
+        self.max_acceptable_rank.is_some()
+    }
+
+    /// Check if the given course choice of this participant is within their
+    /// `Participant::max_acceptable_rank`. The rank of a choice is one more than the number of
+    /// the participant's choices with a strictly lower effective penalty, so choices with equal
+    /// penalty share the same rank.
+    pub fn is_acceptable_choice(&self, choice: &Choice) -> bool {
+        match self.max_acceptable_rank {
+            None => true,
+            Some(max_rank) => {
+                let rank = 1 + self
+                    .choices
+                    .iter()
+                    .filter(|c| c.effective_penalty() < choice.effective_penalty())
+                    .count();
+                rank <= max_rank as usize
+            }
+        }
+    }
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Eq, Debug)]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
 pub struct Choice {
     /// Index of the chosen choice in the list of courses
     #[serde(rename = "course")]
     course_index: usize,
     /// Negative weight of this course choice, e.g. 0 for first choice, 1 for second choice, etc.
     penalty: u32,
+    /// Tier of this course choice. See [ChoiceTier].
+    #[serde(default)]
+    tier: ChoiceTier,
+}
+
+impl Choice {
+    /// The effective penalty of this course choice, combining its tier and its `penalty`.
+    ///
+    /// Each tier forms a separate band of penalties: The penalty of every choice in the
+    /// `Acceptable` tier is increased by [ACCEPTABLE_TIER_PENALTY]. Thus, as long as all choice
+    /// penalties are smaller than this offset, any `Preferred` choice (e.g. the third choice) is
+    /// better than any `Acceptable` choice (e.g. the first choice). Within one tier, choices are
+    /// ordered by their `penalty`.
+    pub fn effective_penalty(&self) -> u32 {
+        match self.tier {
+            ChoiceTier::Preferred => self.penalty,
+            ChoiceTier::Acceptable => self.penalty + ACCEPTABLE_TIER_PENALTY,
+        }
+    }
 }
 
+/// Tier of a course choice, to distinguish courses a participant would love to attend from courses
+/// they are okay with.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ChoiceTier {
+    #[default]
+    Preferred,
+    Acceptable,
+}
+
+/// Penalty offset for course choices in the [ChoiceTier::Acceptable] tier. See
+/// [Choice::effective_penalty].
+///
+/// The offset must be small enough to keep the range of edge weights within the limits described
+/// at `hungarian::EdgeWeight`.
+pub const ACCEPTABLE_TIER_PENALTY: u32 = 100;
+
 pub fn choices_from_list(choices: &[usize]) -> Vec<Choice> {
     choices
         .iter()
@@ -57,18 +156,34 @@ pub fn choices_from_list(choices: &[usize]) -> Vec<Choice> {
         .map(|(i, c)| Choice {
             course_index: *c,
             penalty: i as u32,
+            tier: ChoiceTier::Preferred,
+        })
+        .collect()
+}
+
+/// Create a list of course choices with individual tiers from an ordered list of course indexes
+/// and tiers. Like in [choices_from_list], the penalty of each choice is its position in the list.
+pub fn choices_from_tiered_list(choices: &[(usize, ChoiceTier)]) -> Vec<Choice> {
+    choices
+        .iter()
+        .enumerate()
+        .map(|(i, (c, tier))| Choice {
+            course_index: *c,
+            penalty: i as u32,
+            tier: *tier,
         })
         .collect()
 }
 
 /// Representation of an event course's data
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Course {
     /// id/index of the Course in the list of courses
     #[serde(skip)]
     index: usize,
-    /// Course's id in the CdE Datebank
-    #[serde(skip)]
+    /// Course's id in the CdE Datebank. In the simple data format, it is optional and defaults to
+    /// the course's index.
+    #[serde(default)]
     dbid: usize,
     /// Course's name. Mainly used for info/debug output
     name: String,
@@ -76,30 +191,167 @@ pub struct Course {
     num_max: usize,
     /// Minimum number of attendees (excl. course instructors)
     num_min: usize,
+    /// If true, `num_max` and `num_min` have been given incl. the course instructors (in contrast to
+    /// the default convention). This is resolved during import by subtracting the number of
+    /// instructors from both sizes (see [Course::resolve_sizes_incl_instructors]), such that the
+    /// flag is always false for the assignment algorithm.
+    #[serde(default)]
+    sizes_include_instructors: bool,
     /// Indexes of course instructor's indexes in the list of participants
     instructors: Vec<usize>,
-    /// Scaling factor for room size check: The room of this course must have
-    /// >= room_offset + room_factor * num_participants (incl. instructors) places. E.g. for dancing
-    /// courses this might be somewhere around 2.5
+    /// Indexes of participants, who are qualified to instruct this course (in addition to the fixed
+    /// `instructors`). The assignment algorithm selects exactly `min_instructors` of them as
+    /// additional instructors. The others are assigned to one of their course choices, but never to
+    /// this course. See `caobab::apply_instructor_selection()` for retrieving the selection.
+    #[serde(default)]
+    potential_instructors: Vec<usize>,
+    /// Number of instructors to select from the `potential_instructors`
+    #[serde(default)]
+    min_instructors: usize,
+    /// Scaling factor for room size check: The room of this course must have at least
+    /// room_offset + room_factor * num_participants (incl. instructors, unless
+    /// `instructors_need_space` is false) places. E.g. for dancing courses this might be somewhere
+    /// around 2.5
     #[serde(default = "default_room_factor")]
     room_factor: f32,
     /// Offset to add to the number of assigned participants to check if the course fits a room of a
     /// specific size
     #[serde(default)]
     room_offset: f32,
+    /// True, if `room_factor` has been given explicitly in the input data (in contrast to the
+    /// default value). Only courses without explicit value are affected by
+    /// `io::apply_default_room_parameters()`.
+    #[serde(skip)]
+    room_factor_given: bool,
+    /// True, if `room_offset` has been given explicitly in the input data (see
+    /// [Course::room_factor_given])
+    #[serde(skip)]
+    room_offset_given: bool,
     /// If true, the course may *not* be cancelled by the assignment algorithm. This may be the
     /// case, if the course has fixed participants.
     #[serde(default)]
     fixed_course: bool,
+    /// If true, the course should take place if possible: Among solutions of equal score, the ones
+    /// with this course taking place are preferred (tie-break). In contrast to `fixed_course`, the
+    /// course may still be cancelled, e.g. if it is undersized. This is useful for courses of
+    /// instructor-only participants, who are not considered in the score.
+    #[serde(default)]
+    keep_if_possible: bool,
+    /// Cost of cancelling this course, e.g. to express that cancelling an advanced course is worse
+    /// than cancelling a beginner course. The costs of all cancelled courses (courses without any
+    /// assigned participant) are used as a tie-break between solutions of equal score or, if
+    /// configured (see `caobab::SolveConfig::cancel_cost_penalty()`), reduce the score.
+    #[serde(default)]
+    cancel_cost: f32,
+    /// If true, the course must take place with at least `num_min` attendees: The assignment
+    /// algorithm never considers cancelling it, but optimizes its attendees as usual. In contrast
+    /// to `fixed_course`, the course does not take place without assigned participants (e.g. with
+    /// `num_min = 0` and no instructors), as it is not assumed to have fixed participants
+    /// outside of the assignment.
+    #[serde(default)]
+    must_run: bool,
+    /// Preferred number of attendees (excl. course instructors), if any. Deviations of the final
+    /// number of attendees from this number are used as a tie-break between solutions of equal
+    /// score, i.e. they are weighted well below the choice quality.
+    #[serde(default)]
+    preferred_size: Option<usize>,
+    /// Maximum number of participants on the course's waitlist (see
+    /// `caobab::solution_score::build_waitlists()`). None means an unlimited waitlist.
+    #[serde(default)]
+    waitlist_capacity: Option<usize>,
+    /// If true, the course may be split into two instances, if its demand exceeds a given threshold
+    /// (see `io::split_oversized_courses()`)
+    #[serde(default)]
+    splittable: bool,
+    /// Indexes of participants, who must attend this course (in addition to the instructors), if
+    /// the course takes place. In contrast to instructors, they occupy a regular course place. If
+    /// they cannot be assigned to the course, the course must be cancelled.
+    #[serde(default)]
+    required_attendees: Vec<usize>,
+    /// If false, the course instructors are not considered for the room-effective size of the
+    /// course, e.g. because they rotate and do not occupy additional space in the room.
+    #[serde(default = "default_instructors_need_space")]
+    instructors_need_space: bool,
+    /// If true, the course may share its room with other shareable courses, if the room is marked
+    /// as shareable in the rooms file and the combined room-effective sizes of the courses fit the
+    /// room's capacity. See `io::rooms::assign_rooms()`.
+    #[serde(default)]
+    shareable: bool,
+    /// Name of the time slot, in which the course takes place, if the rooms are scheduled in
+    /// multiple time slots (see `io::rooms::build_schedule()`). None means the default slot.
+    #[serde(default)]
+    slot: Option<String>,
     /// Additional participant names to be included in the printed result output
     #[serde(default)]
     hidden_participant_names: Vec<String>,
 }
 
+impl Default for Course {
+    /// An empty course without instructors and size, with the same default values for all further
+    /// fields as in the simple data format, e.g. for building test data with struct update syntax
+    fn default() -> Self {
+        Course {
+            index: 0,
+            dbid: 0,
+            name: String::new(),
+            num_max: 0,
+            num_min: 0,
+            sizes_include_instructors: false,
+            instructors: Vec::new(),
+            potential_instructors: Vec::new(),
+            min_instructors: 0,
+            room_factor: default_room_factor(),
+            room_offset: 0.0,
+            room_factor_given: false,
+            room_offset_given: false,
+            fixed_course: false,
+            keep_if_possible: false,
+            cancel_cost: 0.0,
+            must_run: false,
+            preferred_size: None,
+            waitlist_capacity: None,
+            splittable: false,
+            required_attendees: Vec::new(),
+            instructors_need_space: default_instructors_need_space(),
+            shareable: false,
+            slot: None,
+            hidden_participant_names: Vec::new(),
+        }
+    }
+}
+
 fn default_room_factor() -> f32 {
     1.0
 }
 
+fn default_instructors_need_space() -> bool {
+    true
+}
+
+impl Course {
+    /// Convert `num_max` and `num_min` of the course to the default convention (excl. course
+    /// instructors), if they have been given incl. instructors (see
+    /// [Course::sizes_include_instructors]). This must be done after the course's instructors list
+    /// is complete and before any further adjustment of the course sizes.
+    fn resolve_sizes_incl_instructors(&mut self) {
+        if self.sizes_include_instructors {
+            self.num_max = self.num_max.saturating_sub(self.instructors.len());
+            self.num_min = self.num_min.saturating_sub(self.instructors.len());
+            self.sizes_include_instructors = false;
+        }
+    }
+
+    /// Number of course instructors to be considered for the room-effective size of the course.
+    /// See [Course::instructors_need_space].
+    fn num_room_relevant_instructors(&self) -> usize {
+        if self.instructors_need_space {
+            self.instructors.len()
+        } else {
+            0
+        }
+    }
+}
+
 /// A course assignment as result of the overall algorithm. It maps the participant index to the course index, such that
 /// the course of participants\[i\] is courses\[assignment\[i\]\].
 pub type Assignment = Vec<Option<usize>>;
@@ -9,8 +9,13 @@
 // "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the License for the
 // specific language governing permissions and limitations under the License.
 
+use cdecao::io::cdedb::ImportAmbienceData;
+use cdecao::io::config::SolveParameters;
 use cdecao::io::rooms::{get_course_room_kind_names, get_course_room_size_list};
 use cdecao::{caobab, io::rooms::CourseRoomKind};
+use cdecao::{Assignment, Course, Participant};
+use std::ffi::OsString;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::{fs::File, ops::Deref};
 
@@ -23,11 +28,97 @@ fn main() {
         "This is the CdE Course Assignment Optimizer (cdecao), version {}",
         option_env!("CARGO_PKG_VERSION").unwrap_or("unknown")
     );
-    let args = parse_cli_args();
+    let args = parse_cli_args(std::env::args_os()).unwrap_or_else(|e| e.exit());
+    JSON_ERRORS.store(args.get_flag("json_errors"), Ordering::Relaxed);
 
-    if args.get_one::<String>("OUTPUT").is_none() && !args.get_flag("print") {
+    match args.subcommand() {
+        Some(("solve", args)) => solve(args),
+        Some(("validate", args)) => validate(args),
+        Some(("stats", args)) => stats(args),
+        Some(("convert", args)) => convert(args),
+        Some(("compare", args)) => compare(args),
+        _ => unreachable!("clap ensures that a known subcommand is given"),
+    }
+}
+
+/// `solve` subcommand: Calculate the optimal course assignment for the input data and write it to
+/// the output files
+fn solve(args: &clap::ArgMatches) {
+    // Collect output files and formats
+    let default_format = if args.get_flag("cde") {
+        OutputFormat::Cde
+    } else {
+        OutputFormat::Simple
+    };
+    let mut emit_targets: Vec<(OutputFormat, String)> = args
+        .get_one::<String>("OUTPUT")
+        .map(|outpath| (default_format, outpath.clone()))
+        .into_iter()
+        .collect();
+    for emit in args.get_many::<String>("emit").unwrap_or_default() {
+        emit_targets.push(parse_emit_target(emit).unwrap_or_else(|e| {
+            exit_with_error(
+                &format!("Invalid --emit option '{}': {}", emit, e),
+                exitcode::USAGE,
+            )
+        }));
+    }
+    if emit_targets
+        .iter()
+        .any(|(format, _)| *format == OutputFormat::Cde)
+        && !args.get_flag("cde")
+    {
+        exit_with_error(
+            "The cde output format is only available with the --cde input data format.",
+            exitcode::USAGE,
+        )
+    }
+
+    if args.contains_id("split_courses")
+        && emit_targets
+            .iter()
+            .any(|(format, _)| *format == OutputFormat::Cde)
+    {
+        exit_with_error(
+            "The --split-courses option is not supported for the cde output format, since the \
+             CdE Datenbank does not know the additional course instances.",
+            exitcode::USAGE,
+        )
+    }
+
+    if emit_targets.is_empty()
+        && !args.get_flag("print")
+        && !args.get_flag("info")
+        && !args.get_flag("stats_only")
+    {
         warn!(
-            "No OUTPUT file and no --print option given. Assignment will not be exported anywhere."
+            "No OUTPUT file, no --emit and no --print option given. Assignment will not be \
+             exported anywhere."
+        );
+    }
+    if args.get_flag("satisfaction_report")
+        && !emit_targets
+            .iter()
+            .any(|(format, _)| *format == OutputFormat::Simple)
+    {
+        warn!("The --satisfaction-report option is only supported for the simple output format.");
+    }
+
+    let solve_parameters = read_solve_parameters(args);
+
+    let (participants, mut courses, import_ambience) = read_input(args);
+    if args.get_flag("print") {
+        print!(
+            "Number of course choices per course (1st / 2nd / …):\n{}\n",
+            cdecao::io::format_choice_statistics(&participants, &courses)
+        );
+    }
+    if let Some(result_path) = args.get_one::<String>("lock_previous") {
+        lock_previous(
+            result_path,
+            args.get_one::<String>("previous_input").unwrap(),
+            &participants,
+            &mut courses,
         );
     }
 
@@ -35,42 +126,669 @@ fn main() {
     let (rooms, room_kinds) = parse_rooms(
         args.get_one::<String>("rooms").map(|x| x.deref()),
         args.get_one::<String>("rooms_file").map(|x| x.deref()),
+        args.get_flag("merge_rooms"),
+        cdecao::io::rooms::max_room_count(courses.len()),
+    );
+    if let Some(ref rooms) = rooms {
+        cdecao::io::rooms::check_room_count(rooms, courses.len()).unwrap_or_else(|e| {
+            exit_with_error(&format!("Invalid rooms: {}", e), exitcode::DATAERR)
+        });
+    }
+    if let Some(result_path) = args.get_one::<String>("assign_rooms_only") {
+        assign_rooms_only(
+            result_path,
+            &participants,
+            &courses,
+            rooms.as_deref(),
+            room_kinds.as_deref(),
+        );
+        return;
+    }
+    if args.get_flag("max_course_size_from_rooms") {
+        match rooms {
+            Some(ref rooms) => cdecao::io::cap_course_sizes_to_rooms(&mut courses, rooms)
+                .unwrap_or_else(|e| {
+                    exit_with_error(
+                        &format!("Could not cap course sizes to the rooms: {}", e),
+                        exitcode::DATAERR,
+                    )
+                }),
+            None => warn!(
+                "The --max-course-size-from-rooms option is only useful with --rooms or \
+                 --rooms-file."
+            ),
+        }
+    }
+
+    if let Some(dumppath) = args.get_one::<String>("dump_input") {
+        debug!("Writing input dump file {} ...", dumppath);
+        cdecao::io::write_file_atomically(dumppath, |file| {
+            cdecao::io::simple::write_input_data(file, &participants, &courses)
+        })
+        .unwrap_or_else(|e| {
+            exit_with_error(
+                &format!("Could not write input dump file {}: {}", dumppath, e),
+                exitcode::CANTCREAT,
+            )
+        });
+        info!("Effective input data written to {}.", dumppath);
+    }
+
+    if participants.is_empty() {
+        exit_with_error(
+            "Calculating course assignments is only possible with 1 or more participants.",
+            exitcode::DATAERR,
+        );
+    }
+    if courses.is_empty() {
+        exit_with_error(
+            "Calculating course assignments is only possible with 1 or more courses.",
+            exitcode::DATAERR,
+        )
+    }
+
+    if args.get_flag("info") {
+        print!(
+            "{}",
+            caobab::problem_size(
+                &courses,
+                &participants,
+                solve_parameters.require_full_assignment()
+            )
+        );
+        return;
+    }
+    if args.get_flag("stats_only") {
+        print!(
+            "{}",
+            cdecao::io::format_demand_report(&participants, &courses)
+        );
+        return;
+    }
+
+    // Execute assignment algorithm
+    let courses = Arc::new(courses);
+    let participants = Arc::new(participants);
+    let output_settings = Arc::new(OutputSettings {
+        import_ambience,
+        rooms,
+        room_kinds,
+        possible_rooms_field: args.get_one::<String>("possible_rooms_field").cloned(),
+        satisfaction_report: args.get_flag("satisfaction_report"),
+        instructor_lists: args.get_flag("instructor_lists"),
+        normalized_quality: args.get_flag("normalized_quality"),
+    });
+    let checkpoint_callback = args
+        .get_one::<String>("checkpoint_output")
+        .map(|checkpoint_path| {
+            let checkpoint_path = checkpoint_path.clone();
+            let output_settings = output_settings.clone();
+            let courses = courses.clone();
+            let participants = participants.clone();
+            Box::new(move |assignment: &Assignment, score: u32| {
+                let mut courses = (*courses).clone();
+                caobab::apply_instructor_selection(&mut courses, assignment);
+                let quality_info =
+                    output_settings.quality_info(score, &participants, &courses, assignment);
+                let possible_rooms = output_settings.possible_rooms(assignment, &courses);
+                match output_settings.write_assignment(
+                    default_format,
+                    &checkpoint_path,
+                    assignment,
+                    &participants,
+                    &courses,
+                    &quality_info,
+                    possible_rooms.as_deref(),
+                ) {
+                    Ok(_) => info!("Current best assignment written to {}.", checkpoint_path),
+                    Err(e) => error!("Could not write checkpoint output: {}.", e),
+                }
+            }) as caobab::NewBestCallback
+        });
+    let trace_writer = args.get_one::<String>("trace_file").map(|tracepath| {
+        debug!("Opening trace file {} ...", tracepath);
+        match File::create(tracepath) {
+            Ok(file) => Box::new(std::io::BufWriter::new(file)) as caobab::TraceWriter,
+            Err(e) => exit_with_error(
+                &format!("Could not open trace file {}: {}.", tracepath, e),
+                exitcode::CANTCREAT,
+            ),
+        }
+    });
+    let mut solve_config = solve_parameters.apply(
+        caobab::SolveConfig::new()
+            .report_no_solution(args.get_flag("report_no_solution"))
+            .collect_thread_statistics(args.get_flag("thread_statistics")),
     );
+    if let Some(rooms) = output_settings.rooms.as_ref() {
+        solve_config = solve_config.rooms(rooms.clone());
+    }
+    if let Some(callback) = checkpoint_callback {
+        solve_config = solve_config.new_best_callback(callback);
+    }
+    if let Some(writer) = trace_writer {
+        solve_config = solve_config.trace_writer(writer);
+    }
+    let (result, statistics) =
+        caobab::solve_with_config(courses.clone(), participants.clone(), solve_config);
+    info!("Finished solving course assignment. {}", statistics);
+
+    if let Some((assignment, score)) = result {
+        info!("Solution found.");
+        // Add the instructors, selected from the courses' potential instructors, to the courses
+        let mut courses = (*courses).clone();
+        caobab::apply_instructor_selection(&mut courses, &assignment);
+        if statistics.aborted_by_timeout {
+            warn!(
+                "The search has been stopped by the timeout. The assignment is not necessarily \
+                 optimal."
+            );
+        }
+        if solve_parameters.first_feasible == Some(true) {
+            info!(
+                "First feasible assignment (score {}) found after {} subproblems. The assignment \
+                 is not necessarily optimal.",
+                score, statistics.num_executed_subproblems
+            );
+        }
+        let quality_info =
+            output_settings.quality_info(score, &participants, &courses, &assignment);
+        info!("Solution quality info:\n{}", quality_info);
+        if caobab::verify_instructor_assignment(&assignment, &courses).is_err()
+            && args.get_flag("verify_instructors")
+        {
+            exit_with_error(
+                "Course instructors are not assigned to their courses.",
+                exitcode::SOFTWARE,
+            );
+        }
+        for wrong in cdecao::io::list_wrongly_assigned(&assignment, &participants, &courses) {
+            warn!("{}", wrong.describe(&participants, &courses));
+        }
+        if args.get_flag("explain_cancellation") {
+            let explanations = cdecao::io::explain_cancellations(
+                &assignment,
+                &participants,
+                &courses,
+                output_settings.rooms.as_deref(),
+            );
+            if !explanations.is_empty() {
+                info!("Cancelled courses:\n{}", explanations.join("\n"));
+            }
+        }
+        if args.get_flag("print_fingerprint") {
+            info!(
+                "Assignment fingerprint: {:016x}",
+                caobab::assignment_fingerprint(&assignment)
+            );
+        }
+
+        let possible_rooms = output_settings.possible_rooms(&assignment, &courses);
+        if let Some(ref room_kinds) = output_settings.room_kinds {
+            if cdecao::io::rooms::uses_time_slots(&courses, room_kinds) {
+                match cdecao::io::rooms::build_schedule(&assignment, &courses, room_kinds) {
+                    Ok(schedule) => info!(
+                        "Room schedule:\n{}",
+                        cdecao::io::rooms::format_schedule(&schedule, &courses, room_kinds)
+                    ),
+                    Err(e) => warn!("Could not find a room schedule for the assignment: {}", e),
+                }
+            } else {
+                match cdecao::io::rooms::assign_rooms(&assignment, &courses, room_kinds) {
+                    Some(room_assignment) => info!(
+                        "Room plan:\n{}",
+                        cdecao::io::rooms::format_room_plan(&room_assignment, &courses, room_kinds)
+                    ),
+                    None => warn!("Could not find a room plan for the assignment."),
+                }
+            }
+        }
+
+        for (format, outpath) in emit_targets.iter() {
+            match output_settings.write_assignment(
+                *format,
+                outpath,
+                &assignment,
+                &participants,
+                &courses,
+                &quality_info,
+                possible_rooms.as_deref(),
+            ) {
+                Ok(_) => debug!("Assignment written to {}.", outpath),
+                Err(e) => error!("Could not write assignment to {}: {}.", outpath, e),
+            }
+        }
 
+        if args.get_flag("print") {
+            print!(
+                "The assignment is:\n{}",
+                cdecao::io::format_assignment(
+                    &assignment,
+                    &courses,
+                    &participants,
+                    possible_rooms.as_deref(),
+                )
+            );
+        }
+        if args.get_flag("waitlists") {
+            print!(
+                "Course waitlists:\n{}",
+                cdecao::io::format_waitlists(&assignment, &participants, &courses)
+            );
+        }
+    } else {
+        if statistics.aborted_by_timeout {
+            exit_with_error(
+                "No feasible solution found before the timeout.",
+                exitcode::TEMPFAIL,
+            );
+        }
+        exit_with_error("No feasible solution found.", 1);
+    }
+}
+
+/// Collect the parameters of the assignment algorithm from the command line options of the `solve`
+/// subcommand. Flags, which are not given, are left empty (instead of false), such that they do
+/// not override the values from a config file.
+fn cli_solve_parameters(args: &clap::ArgMatches) -> SolveParameters {
+    let flag = |name: &str| args.get_flag(name).then_some(true);
+    SolveParameters {
+        allow_unassigned: flag("allow_unassigned"),
+        min_first_choices: args.get_one::<usize>("min_first_choices").copied(),
+        soft_rooms: args.get_one::<u32>("soft_rooms").copied(),
+        max_scored_instructors: args.get_one::<usize>("max_scored_instructors").copied(),
+        instructor_score: args.get_flag("no_instructor_score").then_some(false),
+        group_balance: args.get_one::<u32>("group_balance").copied(),
+        even_fill: flag("even_fill"),
+        minimize_max_penalty: flag("minimize_max_penalty"),
+        cancel_costs: args.get_one::<u32>("cancel_costs").copied(),
+        prefer_flexible_cancellation: flag("prefer_flexible_cancellation"),
+        maximize_courses: flag("maximize_courses"),
+        minimize_worst_choice: flag("minimize_worst_choice"),
+        num_threads: args.get_one::<u32>("num_threads").copied(),
+        timeout: args.get_one::<u64>("timeout").copied(),
+        random_restart: args.get_one::<u32>("random_restart").copied(),
+        seed: args.get_one::<u64>("seed").copied(),
+        first_feasible: flag("first_feasible"),
+    }
+}
+
+/// Read the parameters of the assignment algorithm from the config file (if given with `--config`)
+/// and override them with the command line options
+fn read_solve_parameters(args: &clap::ArgMatches) -> SolveParameters {
+    let cli_parameters = cli_solve_parameters(args);
+    let config_path = match args.get_one::<String>("config") {
+        Some(path) => path,
+        None => return cli_parameters,
+    };
+    debug!("Opening config file {} ...", config_path);
+    let file = File::open(config_path).unwrap_or_else(|e| {
+        exit_with_error(
+            &format!("Could not open config file {}: {}", config_path, e),
+            exitcode::NOINPUT,
+        )
+    });
+    let file_parameters = cdecao::io::config::read_parameters(file).unwrap_or_else(|e| {
+        exit_with_error(
+            &format!("Could not read config file: {}", e),
+            exitcode::CONFIG,
+        )
+    });
+    file_parameters.override_with(cli_parameters)
+}
+
+/// `validate` subcommand: Check the input data for obvious reasons of unsolvability, without
+/// solving the course assignment problem
+fn validate(args: &clap::ArgMatches) {
+    let (participants, courses, _) = read_input(args);
+    let problems = caobab::check_obvious_infeasibility(
+        &courses,
+        &participants,
+        !args.get_flag("allow_unassigned"),
+    );
+    if !problems.is_empty() {
+        exit_with_error(&problems.join("\n"), exitcode::DATAERR);
+    }
+    info!("No obvious problems found. This does not guarantee that a feasible assignment exists.");
+}
+
+/// `stats` subcommand: Print the distribution of the participants' course choices to stdout
+fn stats(args: &clap::ArgMatches) {
+    let (participants, courses, _) = read_input(args);
+    print!(
+        "Number of course choices per course (1st / 2nd / …):\n{}",
+        cdecao::io::format_choice_statistics(&participants, &courses)
+    );
+}
+
+/// `convert` subcommand: Write the input data to the OUTPUT file without solving the course
+/// assignment problem. By default, the simple input data format is used. With `--format`, a roster
+/// of the placements known from the input data (i.e. the course instructors) is written in one of
+/// the output data formats instead.
+fn convert(args: &clap::ArgMatches) {
+    let format = match args.get_one::<String>("format") {
+        Some(format) => format.parse::<OutputFormat>().unwrap_or_else(|e| {
+            exit_with_error(&format!("Invalid --format option: {}", e), exitcode::USAGE)
+        }),
+        None => OutputFormat::Simple,
+    };
+    if matches!(format, OutputFormat::Cde | OutputFormat::Report) {
+        exit_with_error(
+            "The cde and report formats require an assignment and are not supported by convert.",
+            exitcode::USAGE,
+        )
+    }
+    let (participants, courses, _) = read_input(args);
+    let outpath: &String = args.get_one("OUTPUT").unwrap();
+    let assignment = cdecao::io::instructor_assignment(&participants, &courses);
+    debug!("Writing output file {} ...", outpath);
+    cdecao::io::write_file_atomically(outpath, |mut file| match format {
+        OutputFormat::Csv => cdecao::io::csv::write(file, &assignment, &participants, &courses),
+        OutputFormat::Records => {
+            cdecao::io::json_long::write(file, &assignment, &participants, &courses)
+        }
+        OutputFormat::Schedule => std::io::Write::write_all(
+            &mut file,
+            cdecao::io::format_participant_schedule(&assignment, &participants, &courses)
+                .as_bytes(),
+        )
+        .map_err(|e| format!("{}", e)),
+        OutputFormat::Simple => cdecao::io::simple::write_input_data(file, &participants, &courses),
+        OutputFormat::Cde | OutputFormat::Report => unreachable!(),
+    })
+    .unwrap_or_else(|e| {
+        exit_with_error(
+            &format!("Could not write input data to {}: {}", outpath, e),
+            exitcode::CANTCREAT,
+        )
+    });
+    info!("Input data written to {}.", outpath);
+}
+
+/// `compare` subcommand: Print a side-by-side comparison of two result files (in the simple output
+/// data format) to stdout
+fn compare(args: &clap::ArgMatches) {
+    let read_result = |name: &str| {
+        let path: &String = args.get_one(name).unwrap();
+        debug!("Opening result file {} ...", path);
+        let file = File::open(path).unwrap_or_else(|e| {
+            exit_with_error(
+                &format!("Could not open result file {}: {}", path, e),
+                exitcode::NOINPUT,
+            )
+        });
+        cdecao::io::simple::read_result(file).unwrap_or_else(|e| {
+            exit_with_error(
+                &format!("Could not read result file {}: {}", path, e),
+                exitcode::DATAERR,
+            )
+        })
+    };
+    let (first_assignment, first_quality) = read_result("FIRST");
+    let (second_assignment, second_quality) = read_result("SECOND");
+    match cdecao::io::format_result_comparison(
+        (&first_assignment, &first_quality),
+        (&second_assignment, &second_quality),
+    ) {
+        Ok(comparison) => print!("{}", comparison),
+        Err(e) => exit_with_error(
+            &format!("Could not compare the results: {}", e),
+            exitcode::DATAERR,
+        ),
+    }
+}
+
+/// Read a previous result file and the input file (in the simple data format), it has been
+/// calculated from, and lock the previous course placements of the participants in the given
+/// courses. Exits the program if the files cannot be read or matched.
+fn lock_previous(
+    result_path: &str,
+    input_path: &str,
+    participants: &[Participant],
+    courses: &mut [Course],
+) {
+    debug!("Opening previous input file {} ...", input_path);
+    let file = File::open(input_path).unwrap_or_else(|e| {
+        exit_with_error(
+            &format!("Could not open previous input file {}: {}", input_path, e),
+            exitcode::NOINPUT,
+        )
+    });
+    let (previous_participants, previous_courses) = cdecao::io::simple::read(file, false, None)
+        .unwrap_or_else(|e| {
+            exit_with_error(
+                &format!("Could not read previous input file {}: {}", input_path, e),
+                exitcode::DATAERR,
+            )
+        });
+    debug!("Opening previous result file {} ...", result_path);
+    let file = File::open(result_path).unwrap_or_else(|e| {
+        exit_with_error(
+            &format!("Could not open previous result file {}: {}", result_path, e),
+            exitcode::NOINPUT,
+        )
+    });
+    let (previous_assignment, _quality_info) = cdecao::io::simple::read_result(file)
+        .unwrap_or_else(|e| {
+            exit_with_error(
+                &format!("Could not read previous result file {}: {}", result_path, e),
+                exitcode::DATAERR,
+            )
+        });
+    match cdecao::io::lock_previous_assignment(
+        &previous_assignment,
+        &previous_participants,
+        &previous_courses,
+        participants,
+        courses,
+        false,
+    ) {
+        Ok(num_locked) => info!(
+            "Locked the previous course placement of {} participants.",
+            num_locked
+        ),
+        Err(e) => exit_with_error(
+            &format!("Could not lock the previous course placements: {}", e),
+            exitcode::DATAERR,
+        ),
+    }
+}
+
+/// Read an existing result file (in the simple output format) and only recalculate the course
+/// rooms for its assignment, without solving the course assignment problem. Prints the room plan
+/// (or the possible rooms of each course) to stdout. Exits the program if the result file cannot
+/// be read or the assignment's courses do not fit into the rooms.
+fn assign_rooms_only(
+    result_path: &str,
+    participants: &[Participant],
+    courses: &[Course],
+    rooms: Option<&[usize]>,
+    room_kinds: Option<&[CourseRoomKind]>,
+) {
+    let rooms = rooms.unwrap_or_else(|| {
+        exit_with_error(
+            "The --assign-rooms-only option requires --rooms or --rooms-file.",
+            exitcode::USAGE,
+        )
+    });
+    debug!("Opening result file {} ...", result_path);
+    let file = File::open(result_path).unwrap_or_else(|e| {
+        exit_with_error(
+            &format!("Could not open result file {}: {}", result_path, e),
+            exitcode::NOINPUT,
+        )
+    });
+    let (assignment, _quality_info) = cdecao::io::simple::read_result(file).unwrap_or_else(|e| {
+        exit_with_error(
+            &format!("Could not read result file {}: {}", result_path, e),
+            exitcode::DATAERR,
+        )
+    });
+    if assignment.len() != participants.len()
+        || assignment.iter().flatten().any(|c| *c >= courses.len())
+    {
+        exit_with_error(
+            &format!(
+                "The assignment in {} does not match the input data ({} participants, {} courses).",
+                result_path,
+                participants.len(),
+                courses.len()
+            ),
+            exitcode::DATAERR,
+        );
+    }
+
+    let conflicts = cdecao::io::rooms::find_room_conflicts(&assignment, courses, rooms);
+    if !conflicts.is_empty() {
+        exit_with_error(
+            &format!(
+                "The courses of the assignment do not fit into the rooms:\n{}",
+                conflicts.join("\n")
+            ),
+            exitcode::DATAERR,
+        );
+    }
+    match room_kinds {
+        Some(room_kinds) if cdecao::io::rooms::uses_time_slots(courses, room_kinds) => {
+            match cdecao::io::rooms::build_schedule(&assignment, courses, room_kinds) {
+                Ok(schedule) => print!(
+                    "Room schedule:\n{}",
+                    cdecao::io::rooms::format_schedule(&schedule, courses, room_kinds)
+                ),
+                Err(e) => exit_with_error(
+                    &format!("Could not find a room schedule for the assignment: {}", e),
+                    exitcode::DATAERR,
+                ),
+            }
+        }
+        Some(room_kinds) => {
+            match cdecao::io::rooms::assign_rooms(&assignment, courses, room_kinds) {
+                Some(room_assignment) => print!(
+                    "Room plan:\n{}",
+                    cdecao::io::rooms::format_room_plan(&room_assignment, courses, room_kinds)
+                ),
+                None => exit_with_error(
+                    "Could not find a room plan for the assignment.",
+                    exitcode::DATAERR,
+                ),
+            }
+        }
+        None => print!(
+            "Possible rooms:\n{}",
+            cdecao::io::rooms::format_course_rooms(
+                &get_course_room_size_list(&assignment, courses, rooms),
+                &assignment,
+                courses
+            )
+        ),
+    }
+}
+
+/// Read the INPUT file (in the data format selected by the command line options) and apply the
+/// global course size overrides. Exits the program if the input data cannot be read.
+fn read_input(
+    args: &clap::ArgMatches,
+) -> (Vec<Participant>, Vec<Course>, Option<ImportAmbienceData>) {
     // Open input file
     let inpath: &String = args.get_one("INPUT").unwrap();
     debug!("Opening input file {} ...", inpath);
     let file = std::fs::File::open(inpath).unwrap_or_else(|e| {
-        error!("Could not open input file {}: {}", inpath, e);
-        std::process::exit(exitcode::NOINPUT)
+        exit_with_error(
+            &format!("Could not open input file {}: {}", inpath, e),
+            exitcode::NOINPUT,
+        )
     });
     // Read input file
-    let (participants, courses, import_ambience) = if args.get_flag("cde") {
+    let (mut participants, mut courses, import_ambience) = if args.get_flag("cde") {
         // --cde file format
-        let track_id: Option<u64> = args.get_one("track").map(|t: &String| {
-            t.parse().unwrap_or_else(|e| {
-                error!("Could not parse track id: {}", e);
-                std::process::exit(exitcode::DATAERR)
-            })
-        });
+        let track_ids: Vec<u64> = args
+            .get_many::<u64>("track")
+            .unwrap_or_default()
+            .copied()
+            .collect();
         cdecao::io::cdedb::read(
             file,
-            track_id,
-            args.get_flag("ignore_cancelled"),
-            args.get_flag("ignore_assigned"),
-            args.get_one::<String>("room_factor_field").map(|x| &**x),
-            args.get_one::<String>("room_offset_field").map(|x| &**x),
+            &cdecao::io::cdedb::ReadOptions {
+                tracks: &track_ids,
+                ignore_inactive_courses: args.get_flag("ignore_cancelled"),
+                ignore_assigned: args.get_flag("ignore_assigned"),
+                room_factor_field: args.get_one::<String>("room_factor_field").map(|x| &**x),
+                room_offset_field: args.get_one::<String>("room_offset_field").map(|x| &**x),
+                forbidden_courses_field: args
+                    .get_one::<String>("forbidden_courses_field")
+                    .map(|x| &**x),
+                available_courses_field: args
+                    .get_one::<String>("available_courses_field")
+                    .map(|x| &**x),
+                max_rank_field: args.get_one::<String>("max_rank_field").map(|x| &**x),
+                choice_weight_field: args.get_one::<String>("choice_weight_field").map(|x| &**x),
+                allow_any_export_version: args.get_flag("allow_export_version"),
+                unranked_choices: args.get_flag("unranked"),
+                max_input_bytes: args.get_one::<u64>("max_input_bytes").copied(),
+            },
         )
         .map(|(p, c, a)| (p, c, Some(a)))
     } else {
         // simple file format
-        cdecao::io::simple::read(file).map(|(p, c)| (p, c, None))
+        cdecao::io::simple::read(
+            file,
+            args.get_flag("strict"),
+            args.get_one::<u64>("max_input_bytes").copied(),
+        )
+        .map(|(p, c)| (p, c, None))
     }
     .unwrap_or_else(|e| {
-        error!("Could not read input file: {}", e);
-        std::process::exit(exitcode::DATAERR)
+        exit_with_error(
+            &format!("Could not read input file: {}", e),
+            exitcode::DATAERR,
+        )
     });
 
+    // Apply global course size overrides
+    cdecao::io::apply_course_size_overrides(
+        &mut courses,
+        args.get_one::<usize>("cap_max").copied(),
+        args.get_one::<usize>("floor_min").copied(),
+    )
+    .unwrap_or_else(|e| {
+        exit_with_error(
+            &format!("Could not apply course size overrides: {}", e),
+            exitcode::DATAERR,
+        )
+    });
+    if let Some(ratio) = args.get_one::<f64>("min_fill_ratio") {
+        if !(*ratio > 0.0 && *ratio <= 1.0) {
+            exit_with_error(
+                &format!(
+                    "Invalid --min-fill-ratio {}: Expected a number in (0, 1]",
+                    ratio
+                ),
+                exitcode::USAGE,
+            )
+        }
+        cdecao::io::apply_min_fill_ratio(&mut courses, *ratio);
+    }
+    if let Some(percent) = args.get_one::<u32>("max_fill_percent") {
+        cdecao::io::apply_max_fill_percent(&mut courses, *percent);
+    }
+    cdecao::io::apply_default_room_parameters(
+        &mut courses,
+        args.get_one::<f32>("default_room_factor").copied(),
+        args.get_one::<f32>("default_room_offset").copied(),
+    );
+    if let Some(course_ids) = args.get_many::<usize>("must_run") {
+        let course_ids: Vec<usize> = course_ids.copied().collect();
+        cdecao::io::apply_must_run(&mut courses, &course_ids).unwrap_or_else(|e| {
+            exit_with_error(
+                &format!("Invalid --must-run option: {}", e),
+                exitcode::DATAERR,
+            )
+        });
+    }
+    if let Some(max_demand) = args.get_one::<usize>("split_courses") {
+        cdecao::io::split_oversized_courses(&mut courses, &mut participants, *max_demand);
+    }
+
     // In debug build: Check consistency of imported data
     if cfg!(debug_assertions) {
         cdecao::io::assert_data_consitency(&participants, &courses);
@@ -84,262 +802,1285 @@ fn main() {
 
     debug!("Courses:\n{}", cdecao::io::debug_list_of_courses(&courses));
 
-    if participants.is_empty() {
-        error!("Calculating course assignments is only possible with 1 or more participants.");
-        std::process::exit(exitcode::DATAERR);
+    (participants, courses, import_ambience)
+}
+
+/// Data formats for writing the calculated assignment to output files
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum OutputFormat {
+    /// The simple JSON format, see `cdecao::io::simple`
+    Simple,
+    /// The CdE Datenbank partial import format, see `cdecao::io::cdedb`. Only available with
+    /// `--cde` input data.
+    Cde,
+    /// A CSV roster, see `cdecao::io::csv`
+    Csv,
+    /// A long-format JSON array with one record per participant, see `cdecao::io::json_long`
+    Records,
+    /// A human readable list of participants with their assigned course, see
+    /// `cdecao::io::format_participant_schedule`
+    Schedule,
+    /// A combined JSON report with the assignment, course sizes, rooms and quality info, see
+    /// `cdecao::io::report`
+    Report,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "simple" | "json" => Ok(OutputFormat::Simple),
+            "cde" => Ok(OutputFormat::Cde),
+            "csv" => Ok(OutputFormat::Csv),
+            "records" => Ok(OutputFormat::Records),
+            "schedule" => Ok(OutputFormat::Schedule),
+            "report" => Ok(OutputFormat::Report),
+            _ => Err(format!(
+                "Unknown output format '{}'. Available formats: simple (or json), cde, csv, \
+                 records, schedule, report",
+                s
+            )),
+        }
     }
+}
 
-    // Execute assignment algorithm
-    let courses = Arc::new(courses);
-    let participants = Arc::new(participants);
-    let (result, statistics) = caobab::solve(
-        courses.clone(),
-        participants.clone(),
-        rooms.as_ref(),
-        args.get_flag("report_no_solution"),
-        *args
-            .get_one("num_threads")
-            .unwrap_or(&(num_cpus::get() as u32)),
-    );
-    info!("Finished solving course assignment. {}", statistics);
+/// Parse the value of an `--emit` option in the form `FORMAT=PATH`
+fn parse_emit_target(value: &str) -> Result<(OutputFormat, String), String> {
+    let (format, path) = value
+        .split_once('=')
+        .ok_or("Expected the form FORMAT=PATH")?;
+    if path.is_empty() {
+        return Err("No output file path given".to_owned());
+    }
+    Ok((format.parse()?, path.to_owned()))
+}
 
-    if let Some((assignment, score)) = result {
-        info!("Solution found.");
+/// Settings and additional data for writing the calculated assignment to output files. They are
+/// shared between the final output and checkpoint outputs during solving.
+struct OutputSettings {
+    import_ambience: Option<ImportAmbienceData>,
+    rooms: Option<Vec<usize>>,
+    room_kinds: Option<Vec<CourseRoomKind>>,
+    possible_rooms_field: Option<String>,
+    /// If true, add the participants' satisfaction report to the output (simple format only)
+    satisfaction_report: bool,
+    /// If true, restate the instructors of each running course in the output (cde format only)
+    instructor_lists: bool,
+    /// If true, add the normalized quality lack to the quality info
+    normalized_quality: bool,
+}
+
+impl OutputSettings {
+    fn quality_info(
+        &self,
+        score: u32,
+        participants: &[Participant],
+        courses: &[Course],
+        assignment: &Assignment,
+    ) -> caobab::solution_score::QualityInfo {
         let quality_info = caobab::solution_score::QualityInfo::calculate(
             score,
-            &participants,
-            &courses,
-            import_ambience
+            participants,
+            courses,
+            assignment,
+            self.import_ambience
                 .as_ref()
                 .and_then(|a| a.external_assignment_quality_info.as_ref()),
         );
-        info!("Solution quality info:\n{}", quality_info);
+        if self.normalized_quality {
+            quality_info.with_normalized_quality(participants, courses, assignment)
+        } else {
+            quality_info
+        }
+    }
 
+    /// Calculate the list of possible course rooms for each course, if rooms are given
+    fn possible_rooms(&self, assignment: &Assignment, courses: &[Course]) -> Option<Vec<String>> {
         #[allow(clippy::manual_map)] // allow writing an if-let-else-if-let ladder
-        let possible_rooms = if let Some(rk) = room_kinds {
-            Some(get_course_room_kind_names(&assignment, &courses, &rk))
-        } else if let Some(rs) = rooms {
-            Some(get_course_room_size_list(&assignment, &courses, &rs))
+        if let Some(ref rk) = self.room_kinds {
+            Some(get_course_room_kind_names(assignment, courses, rk))
+        } else if let Some(ref rs) = self.rooms {
+            Some(get_course_room_size_list(assignment, courses, rs))
         } else {
             None
-        };
-
-        if let Some(outpath) = args.get_one::<String>("OUTPUT") {
-            debug!("Opening output file {} ...", outpath);
-            match File::create(outpath) {
-                Err(e) => error!("Could not open output file {}: {}.", outpath, e),
-                Ok(file) => {
-                    let res = if args.get_flag("cde") {
-                        cdecao::io::cdedb::write(
-                            file,
-                            &assignment,
-                            &participants,
-                            &courses,
-                            import_ambience.unwrap(),
-                            &quality_info,
-                            args.get_one::<String>("possible_rooms_field")
-                                .map(|x| x.deref()),
-                            possible_rooms.as_deref(),
-                        )
-                    } else {
-                        cdecao::io::simple::write(file, &assignment, &quality_info)
-                    };
-                    match res {
-                        Ok(_) => debug!("Assignment written to {}.", outpath),
-                        Err(e) => error!("Could not write assignment to {}: {}.", outpath, e),
-                    }
-                }
-            }
         }
+    }
 
-        if args.get_flag("print") {
-            print!(
-                "The assignment is:\n{}",
-                cdecao::io::format_assignment(
-                    &assignment,
-                    &courses,
-                    &participants,
-                    possible_rooms.as_deref(),
-                )
-            );
-        }
+    /// Write the assignment to the file at `outpath` in the given data format
+    #[allow(clippy::too_many_arguments)]
+    fn write_assignment(
+        &self,
+        format: OutputFormat,
+        outpath: &str,
+        assignment: &Assignment,
+        participants: &[Participant],
+        courses: &[Course],
+        quality_info: &caobab::solution_score::QualityInfo,
+        possible_rooms: Option<&[String]>,
+    ) -> Result<(), String> {
+        debug!("Writing output file {} ...", outpath);
+        cdecao::io::write_file_atomically(outpath, |mut file| match format {
+            OutputFormat::Cde => cdecao::io::cdedb::write(
+                file,
+                assignment,
+                participants,
+                courses,
+                self.import_ambience.as_ref().unwrap(),
+                quality_info,
+                self.possible_rooms_field.as_deref(),
+                possible_rooms,
+                self.instructor_lists,
+            ),
+            OutputFormat::Simple => {
+                let satisfaction = self.satisfaction_report.then(|| {
+                    caobab::solution_score::satisfaction_report(assignment, participants, courses)
+                });
+                cdecao::io::simple::write(file, assignment, quality_info, satisfaction.as_deref())
+            }
+            OutputFormat::Csv => cdecao::io::csv::write(file, assignment, participants, courses),
+            OutputFormat::Records => {
+                cdecao::io::json_long::write(file, assignment, participants, courses)
+            }
+            OutputFormat::Schedule => std::io::Write::write_all(
+                &mut file,
+                cdecao::io::format_participant_schedule(assignment, participants, courses)
+                    .as_bytes(),
+            )
+            .map_err(|e| format!("{}", e)),
+            OutputFormat::Report => cdecao::io::report::write_full(
+                file,
+                assignment,
+                participants,
+                courses,
+                quality_info,
+                possible_rooms,
+            ),
+        })
+    }
+}
+
+/// If set (by the `--json-errors` option), fatal errors are written to stderr as a single-line JSON
+/// object instead of being logged. See [exit_with_error].
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Report a fatal error and exit the program with the given exit code.
+///
+/// With `--json-errors`, the error is written to stderr as a single-line JSON object (see
+/// [format_json_error]), which is easier to parse for programs wrapping cdecao. Otherwise, it is
+/// logged as usual.
+fn exit_with_error(message: &str, code: i32) -> ! {
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        eprintln!("{}", format_json_error(message, code));
     } else {
-        warn!("No feasible solution found.");
-        std::process::exit(1);
+        error!("{}", message);
     }
+    std::process::exit(code)
 }
 
-/// Helper function to construct and execute parser for command line options
-fn parse_cli_args() -> clap::ArgMatches {
+/// Format an error as a single-line JSON object `{"error": "...", "code": N}`
+fn format_json_error(message: &str, code: i32) -> String {
+    serde_json::json!({
+        "error": message,
+        "code": code,
+    })
+    .to_string()
+}
+
+/// Helper function to construct and execute parser for command line options. For backwards
+/// compatibility, the `solve` subcommand is assumed, if no subcommand is given.
+fn parse_cli_args<I, T>(args: I) -> Result<clap::ArgMatches, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
+    let command = build_cli();
+    let mut args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    let has_subcommand = args.get(1).is_some_and(|arg| {
+        let arg = arg.to_string_lossy();
+        ["help", "-h", "--help", "-V", "--version"].contains(&arg.as_ref())
+            || command.get_subcommands().any(|s| s.get_name() == arg)
+    });
+    if !has_subcommand {
+        args.insert(args.len().min(1), "solve".into());
+    }
+    command.try_get_matches_from(args)
+}
+
+/// Construct the parser for command line options with all subcommands
+fn build_cli() -> clap::Command {
     clap::command!()
+        .subcommand_required(true)
         .arg(
-            clap::Arg::new("cde")
-                .short('c')
-                .long("cde")
-                .help("Use CdE Datenbank format for input and output files")
-                .action(clap::ArgAction::SetTrue),
-        )
-        .arg(
-            clap::Arg::new("track")
-                .short('t')
-                .long("track")
+            clap::Arg::new("json_errors")
+                .long("json-errors")
+                .global(true)
+                .action(clap::ArgAction::SetTrue)
                 .help(
-                    "Specify CdE-Datenbank id of the course track to assign courses in. Only \
-                     useful in combination with --cde input data format.",
+                    "Write fatal errors to stderr as a single-line JSON object \
+                     {\"error\": \"...\", \"code\": N} (with the exit code N of the program) \
+                     instead of log messages",
+                ),
+        )
+        .subcommand(
+            clap::Command::new("solve")
+                .about("Calculate the optimal course assignment (default subcommand)")
+                .args(input_args())
+                .args(solve_args()),
+        )
+        .subcommand(
+            clap::Command::new("validate")
+                .about("Check the input data for obvious reasons of unsolvability, without solving")
+                .args(input_args())
+                .arg(allow_unassigned_arg()),
+        )
+        .subcommand(
+            clap::Command::new("stats")
+                .about("Print the distribution of course choices in the input data")
+                .args(input_args()),
+        )
+        .subcommand(
+            clap::Command::new("convert")
+                .about("Write the input data to the OUTPUT file without solving")
+                .args(input_args())
+                .arg(
+                    clap::Arg::new("OUTPUT")
+                        .help("Sets the output file to use")
+                        .required(true)
+                        .index(2),
                 )
-                .value_name("TRACK_ID"),
+                .arg(
+                    clap::Arg::new("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .help(
+                            "Output data format: simple (default) for the input data format, or \
+                             csv, records or schedule for a roster of the instructors' placements",
+                        ),
+                ),
         )
-        .arg(
-            clap::Arg::new("ignore_cancelled")
-                .short('i')
-                .long("ignore-cancelled")
-                .help(
-                    "Ignore already cancelled courses. Otherwise, they are considered for \
-                     assignment and might be un-cancelled. Only possible with --cde data \
-                     format.",
+        .subcommand(
+            clap::Command::new("compare")
+                .about(
+                    "Compare the quality and the assignments of two result files in the simple \
+                     output data format",
                 )
-                .action(clap::ArgAction::SetTrue),
+                .arg(
+                    clap::Arg::new("FIRST")
+                        .help("The first result file")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    clap::Arg::new("SECOND")
+                        .help("The second result file")
+                        .required(true)
+                        .index(2),
+                ),
         )
-        .arg(
-            clap::Arg::new("ignore_assigned")
-                .short('j')
-                .long("ignore-assigned")
-                .help(
-                    "Ignore already assigned participants. Otherwise all participants are \
+}
+
+/// Command line options for reading the input data, shared by all subcommands
+fn input_args() -> Vec<clap::Arg> {
+    vec![
+        clap::Arg::new("cde")
+            .short('c')
+            .long("cde")
+            .help("Use CdE Datenbank format for input and output files")
+            .action(clap::ArgAction::SetTrue),
+        clap::Arg::new("track")
+            .short('t')
+            .long("track")
+            .help(
+                "Specify CdE-Datenbank id of the course track to assign courses in. Multiple \
+                 (comma-separated) track ids may be given to merge the tracks' courses and \
+                 participants into a single assignment problem. Only useful in combination \
+                 with --cde input data format.",
+            )
+            .value_name("TRACK_ID")
+            .value_parser(clap::value_parser!(u64))
+            .value_delimiter(',')
+            .action(clap::ArgAction::Append),
+        clap::Arg::new("ignore_cancelled")
+            .short('i')
+            .long("ignore-cancelled")
+            .help(
+                "Ignore already cancelled courses. Otherwise, they are considered for \
+                     assignment and might be un-cancelled. Only possible with --cde data \
+                     format.",
+            )
+            .action(clap::ArgAction::SetTrue),
+        clap::Arg::new("ignore_assigned")
+            .short('j')
+            .long("ignore-assigned")
+            .help(
+                "Ignore already assigned participants. Otherwise all participants are \
                      considered for re-assigned and course assignments are overwritten. Only \
                      possible with --cde data format. If present, courses with assigned \
                      participants will not be cancelled. Attention: This might impair the \
                      solution's quality or even make the problem unsolvable.",
-                )
-                .action(clap::ArgAction::SetTrue),
-        )
-        .arg(
-            clap::Arg::new("room_factor_field")
-                .long("room-factor-field")
-                .value_name("FIELD_NAME")
-                .help(
-                    "The name of a course-associated data field from the CdE Datenbank, which \
+            )
+            .action(clap::ArgAction::SetTrue),
+        clap::Arg::new("room_factor_field")
+            .long("room-factor-field")
+            .value_name("FIELD_NAME")
+            .help(
+                "The name of a course-associated data field from the CdE Datenbank, which \
                      stores a fixed offset to be added to the course size when comparing the \
                      course size with the awailable rooms. Only useful for the --cde data format \
                      and with --rooms or --rooms-file given. If not present, the default offset of \
                      0 is used for all courses.",
-                ),
-        )
-        .arg(
-            clap::Arg::new("room_offset_field")
-                .long("room-offset-field")
-                .value_name("FIELD_NAME")
-                .help(
-                    "The name of a course-associated data field from the CdE Datenbank, which \
+            ),
+        clap::Arg::new("room_offset_field")
+            .long("room-offset-field")
+            .value_name("FIELD_NAME")
+            .help(
+                "The name of a course-associated data field from the CdE Datenbank, which \
                      stores a scaling factor to be multiplied with the course size (before adding \
                      the offset) when comparing the course size with the awailable rooms. Only \
                      useful for the --cde data format and with --rooms or --rooms-file given. If \
                      not present, the default factor of 1.0 is used for all courses.",
-                ),
-        )
-        .arg(
-            clap::Arg::new("possible_rooms_field")
-                .long("possible-rooms-field")
-                .value_name("FIELD_NAME")
-                .help(
-                    "The name of a course-associated data field in the CdE Datenbank, which \
+            ),
+        clap::Arg::new("allow_export_version")
+            .long("allow-export-version")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Accept CdE Datenbank export files with any export schema version, even if it \
+                     is not within the range of versions, supported by this version of cdecao. \
+                     Only use this, if you know, what you are doing: The data might not be \
+                     interpreted correctly. Only useful for the --cde data format.",
+            ),
+        clap::Arg::new("strict")
+            .long("strict")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Reject input data with suspicious entries instead of only logging a warning, \
+                     e.g. participants with a different number of course choices than the \
+                     'num_choices' of the input file (only for the simple data \
+                     format).",
+            ),
+        clap::Arg::new("unranked")
+            .long("unranked")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Interpret the course choices as an unranked set of preferences: All chosen \
+                     courses are considered equally good, regardless of their position in the \
+                     list of choices. Only useful for the --cde data format.",
+            ),
+        clap::Arg::new("forbidden_courses_field")
+            .long("forbidden-courses-field")
+            .value_name("FIELD_NAME")
+            .help(
+                "The name of a registration-associated data field from the CdE Datenbank, \
+                     which stores a comma-separated list of course ids, the participant must never \
+                     be assigned to. Only useful for the --cde data format.",
+            ),
+        clap::Arg::new("available_courses_field")
+            .long("available-courses-field")
+            .value_name("FIELD_NAME")
+            .help(
+                "The name of a registration-associated data field from the CdE Datenbank, \
+                     which stores a comma-separated list of course ids, the participant may be \
+                     assigned to at all. If the field is empty, all courses are available. Only \
+                     useful for the --cde data format.",
+            ),
+        clap::Arg::new("max_rank_field")
+            .long("max-rank-field")
+            .value_name("FIELD_NAME")
+            .help(
+                "The name of a registration-associated data field from the CdE Datenbank, \
+                     which stores the worst course choice rank (1 for the first choice), the \
+                     participant accepts to be assigned to. Participants are left unassigned \
+                     rather than being assigned to a worse choice. Only useful for the --cde \
+                     data format.",
+            ),
+        clap::Arg::new("choice_weight_field")
+            .long("choice-weight-field")
+            .value_name("FIELD_NAME")
+            .help(
+                "The name of a registration-associated data field from the CdE Datenbank, \
+                     which stores an explicit penalty for each of the participant's course \
+                     choices as a comma-separated list of non-negative integers (lower is \
+                     better, e.g. \"0,2,5\"). It is used instead of the choices' positions. \
+                     Only useful for the --cde data format.",
+            ),
+        clap::Arg::new("max_input_bytes")
+            .long("max-input-bytes")
+            .help(
+                "Reject input files larger than the given number of bytes, e.g. to protect a \
+                     server against huge inputs.",
+            )
+            .value_name("BYTES")
+            .value_parser(clap::value_parser!(u64)),
+        clap::Arg::new("cap_max")
+            .long("cap-max")
+            .help(
+                "Reduce the maximum number of attendees (excl. instructors) of every course \
+                     to at most the given number.",
+            )
+            .value_name("NUM")
+            .value_parser(clap::value_parser!(usize)),
+        clap::Arg::new("floor_min")
+            .long("floor-min")
+            .help(
+                "Raise the minimum number of attendees (excl. instructors) of every course \
+                     to at least the given number.",
+            )
+            .value_name("NUM")
+            .value_parser(clap::value_parser!(usize)),
+        clap::Arg::new("min_fill_ratio")
+            .long("min-fill-ratio")
+            .help(
+                "Cancel every course, which does not reach the given fraction (e.g. 0.6) of its \
+                     maximum number of attendees, even if it reaches its minimum size. This \
+                     raises the minimum size of every course to the fraction of its maximum size \
+                     (rounded up). Thus, the solver treats every running course below the ratio \
+                     like a course below its minimum size and branches into cancelling or \
+                     enforcing it.",
+            )
+            .value_name("R")
+            .value_parser(clap::value_parser!(f64)),
+        clap::Arg::new("max_fill_percent")
+            .long("max-fill-percent")
+            .help(
+                "Reduce the maximum number of attendees (excl. instructors) of every course \
+                     to the given percentage of its maximum size (rounded down), but not below \
+                     its minimum size.",
+            )
+            .value_name("P")
+            .value_parser(clap::value_parser!(u32).range(1..=100)),
+        clap::Arg::new("default_room_factor")
+            .long("default-room-factor")
+            .help(
+                "Room size factor (space per participant) of all courses, which do not specify \
+                     their own room factor in the input data. Defaults to 1.0.",
+            )
+            .value_name("F")
+            .value_parser(clap::value_parser!(f32)),
+        clap::Arg::new("default_room_offset")
+            .long("default-room-offset")
+            .help(
+                "Room size offset (additional space) of all courses, which do not specify their \
+                     own room offset in the input data. Defaults to 0.0.",
+            )
+            .value_name("O")
+            .value_parser(clap::value_parser!(f32)),
+        clap::Arg::new("split_courses")
+            .long("split-courses")
+            .help(
+                "Split each course, which is marked as splittable in the input data and which \
+                     is the first choice of more than MAX_DEMAND participants, into two course \
+                     instances, between which its choosers are distributed. Not supported for \
+                     the cde output format.",
+            )
+            .value_name("MAX_DEMAND")
+            .value_parser(clap::value_parser!(usize)),
+        clap::Arg::new("must_run")
+            .long("must-run")
+            .help(
+                "Comma-separated list of course ids (CdE-Datenbank ids with --cde, course \
+                     indexes or dbids otherwise) of courses, which must take place with at least \
+                     their minimum size. In contrast to fixed courses, they are not assumed to \
+                     have participants outside of the assignment.",
+            )
+            .value_name("COURSE_IDS")
+            .value_parser(clap::value_parser!(usize))
+            .value_delimiter(',')
+            .action(clap::ArgAction::Append),
+        clap::Arg::new("INPUT")
+            .help("Sets the input file to use")
+            .required(true)
+            .index(1),
+    ]
+}
+
+/// Command line options for solving the course assignment problem and writing the result
+fn solve_args() -> Vec<clap::Arg> {
+    vec![
+        clap::Arg::new("possible_rooms_field")
+            .long("possible-rooms-field")
+            .value_name("FIELD_NAME")
+            .help(
+                "The name of a course-associated data field in the CdE Datenbank, which \
                      will be used to provide the possible rooms for the course in the output file.
                      Only useful for the --cde data format and with --rooms or --rooms-file given.
                      If present, the generated CdEDB import file will set this field to a \
                      comma-separated list of possible course room kinds (from --room-file) resp. \
                      room sizes (from --rooms) for the respective course.",
-                ),
-        )
-        .arg(
-            clap::Arg::new("report_no_solution")
-                .long("report-no-solution")
-                .help(
-                    "Log some unsolvable Branch-and-Bound nodes with INFO log level. This will \
+            ),
+        clap::Arg::new("config")
+            .long("config")
+            .value_name("FILE")
+            .help(
+                "Read the parameters of the assignment algorithm (e.g. soft_rooms, \
+                     group_balance, timeout) from the given JSON config file. Command line \
+                     options take precedence over the values from the file.",
+            ),
+        clap::Arg::new("instructor_lists")
+            .long("instructor-lists")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Restate the instructors (registration ids) of each course taking place in the \
+                     output file. Only useful for the --cde data format.",
+            ),
+        clap::Arg::new("report_no_solution")
+            .long("report-no-solution")
+            .help(
+                "Log some unsolvable Branch-and-Bound nodes with INFO log level. This will \
                     be a great help with debugging unsolvable course assignement problems.",
-                )
-                .action(clap::ArgAction::SetTrue),
-        )
-        .arg(
-            clap::Arg::new("rooms")
-                .short('r')
-                .long("rooms")
-                .help(
-                    "Comma-separated list of available course room sizes, e.g. 15,10,10,8. \
-                       Cannot be used together with --rooms-file.",
-                )
-                .value_name("ROOMS"),
-        )
-        .arg(
-            clap::Arg::new("rooms_file")
-                .long("rooms-file")
-                .help(
-                    "Path of a JSON file, specifying the available course rooms. Cannot be used \
-                       together with --rooms.",
-                )
-                .value_name("ROOM_FILE"),
-        )
-        .arg(
-            clap::Arg::new("num_threads")
-                .long("num-threads")
-                .help(
-                    "Number of worker threads to spawn. Defaults to number of detected CPU cores.",
-                )
-                .value_name("THREADS")
-                .value_parser(clap::value_parser!(u32)),
-        )
-        .arg(
-            clap::Arg::new("print")
-                .short('p')
-                .long("print")
-                .help("Print the caluclated course assignment to stdout in a human readable format")
-                .action(clap::ArgAction::SetTrue),
-        )
-        .arg(
-            clap::Arg::new("INPUT")
-                .help("Sets the input file to use")
-                .required(true)
-                .index(1),
-        )
-        .arg(
-            clap::Arg::new("OUTPUT")
-                .help("Sets the output file to use")
-                .index(2),
+            )
+            .action(clap::ArgAction::SetTrue),
+        clap::Arg::new("min_first_choices")
+            .long("min-first-choices")
+            .help(
+                "Only accept course assignments, which assign at least the given number of \
+                     participants (excl. course instructors) to their first course choice. This \
+                     is a best-effort search, which only tries cancelling further courses: If no \
+                     such assignment is found, no assignment is calculated at all, even if one \
+                     exists.",
+            )
+            .value_name("NUM")
+            .value_parser(clap::value_parser!(usize)),
+        clap::Arg::new("maximize_courses")
+            .long("maximize-courses")
+            .help(
+                "Maximize the number of courses taking place first and optimize the quality of \
+                     the assigned course choices only among those assignments. This favours the \
+                     variety of courses over the satisfaction of individual participants.",
+            )
+            .action(clap::ArgAction::SetTrue),
+        clap::Arg::new("minimize_worst_choice")
+            .long("minimize-worst-choice")
+            .help(
+                "Minimize the worst assigned course choice of all participants first and \
+                     optimize the overall quality of the assigned course choices only among those \
+                     assignments. This favours the worst-off participant over the satisfaction of \
+                     the majority.",
+            )
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with("maximize_courses"),
+        clap::Arg::new("prefer_flexible_cancellation")
+            .long("prefer-flexible-cancellation")
+            .help(
+                "When multiple courses lack the same number of participants, first try to \
+                     cancel the course with the fewest participants who chose no other (still \
+                     available) course. This may find good solutions earlier.",
+            )
+            .action(clap::ArgAction::SetTrue),
+        clap::Arg::new("rooms")
+            .short('r')
+            .long("rooms")
+            .help(
+                "Comma-separated list of available course room sizes, e.g. 15,10,10,8. \
+                       Cannot be used together with --rooms-file, unless --merge-rooms is \
+                       given.",
+            )
+            .value_name("ROOMS"),
+        clap::Arg::new("rooms_file")
+            .long("rooms-file")
+            .help(
+                "Path of a JSON file, specifying the available course rooms. Cannot be used \
+                       together with --rooms, unless --merge-rooms is given.",
+            )
+            .value_name("ROOM_FILE"),
+        clap::Arg::new("merge_rooms")
+            .long("merge-rooms")
+            .help(
+                "Allow combining --rooms and --rooms-file: The rooms given with --rooms are \
+                     added to the rooms from the rooms file (as anonymous room kinds).",
+            )
+            .action(clap::ArgAction::SetTrue)
+            .requires("rooms")
+            .requires("rooms_file"),
+        clap::Arg::new("max_course_size_from_rooms")
+            .long("max-course-size-from-rooms")
+            .help(
+                "Before solving, lower the maximum size of each course to the number of \
+                     attendees fitting into the largest available room (considering the course's \
+                     room factor, room offset and instructors). Fails, if not even the minimum \
+                     size of a course fits. Only useful with --rooms or --rooms-file.",
+            )
+            .action(clap::ArgAction::SetTrue),
+        clap::Arg::new("soft_rooms")
+            .long("soft-rooms")
+            .help(
+                "Use soft room constraints: Instead of shrinking or cancelling courses to fit \
+                     the rooms, reduce the score by PENALTY for each place exceeding a course's \
+                     room size. Only useful with --rooms or --rooms-file.",
+            )
+            .value_name("PENALTY")
+            .value_parser(clap::value_parser!(u32)),
+        clap::Arg::new("max_scored_instructors")
+            .long("max-scored-instructors")
+            .help(
+                "Only count the first NUM instructors of each course in the assignment's score, \
+                     such that courses with many instructors are not preferred too strongly.",
+            )
+            .value_name("NUM")
+            .value_parser(clap::value_parser!(usize)),
+        clap::Arg::new("no_instructor_score")
+            .long("no-instructor-score")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Don't count course instructors in the assignment's score at all, such that \
+                     courses are not preferred to take place because of their instructors.",
+            ),
+        clap::Arg::new("group_balance")
+            .long("group-balance")
+            .help(
+                "Balance the participants' groups across the courses: Reduce the score by PENALTY \
+                     for each place, by which a course deviates from the overall distribution of \
+                     the groups.",
+            )
+            .value_name("PENALTY")
+            .value_parser(clap::value_parser!(u32)),
+        clap::Arg::new("even_fill")
+            .long("even-fill")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Among assignments of equal score, prefer the one with the most evenly filled \
+                     courses (lowest variance of the courses' fill ratios).",
+            ),
+        clap::Arg::new("minimize_max_penalty")
+            .long("minimize-max-penalty")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Among assignments of equal score, prefer the one with the lowest maximum penalty \
+                     of all assigned course choices (best result for the worst-off participant).",
+            ),
+        clap::Arg::new("cancel_costs")
+            .long("cancel-costs")
+            .help(
+                "Reduce the score by PENALTY for each unit of the cancel costs of the cancelled \
+                     courses. Without this option, the courses' cancel costs are only used as a \
+                     tie-break between assignments of equal score.",
+            )
+            .value_name("PENALTY")
+            .value_parser(clap::value_parser!(u32)),
+        clap::Arg::new("timeout")
+            .long("timeout")
+            .value_name("SECONDS")
+            .help(
+                "Stop the search after the given number of seconds and use the best assignment \
+                     found so far, which is not necessarily optimal. If no assignment has been \
+                     found until then, the program exits with code 75 (instead of 1 for a proven \
+                     infeasible problem).",
+            )
+            .value_parser(clap::value_parser!(u64)),
+        clap::Arg::new("first_feasible")
+            .long("first-feasible")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Stop the search at the first feasible assignment (without optimizing it) and \
+                     report the number of subproblems solved to get there, e.g. as a quick probe \
+                     of the problem's hardness.",
+            ),
+        clap::Arg::new("num_threads")
+            .long("num-threads")
+            .help("Number of worker threads to spawn. Defaults to number of detected CPU cores.")
+            .value_name("THREADS")
+            .value_parser(clap::value_parser!(u32)),
+        clap::Arg::new("dump_input")
+            .long("dump-input")
+            .help(
+                "Write the effective input data (after applying all import options, like \
+                     --ignore-cancelled and --ignore-assigned) to the given file in the simple \
+                     data format, before solving the problem. This allows to reproduce and \
+                     inspect the problem that was actually solved.",
+            )
+            .value_name("FILE"),
+        clap::Arg::new("checkpoint_output")
+            .long("checkpoint-output")
+            .help(
+                "Write each new best assignment, found during solving, to the given file \
+                     (in the selected data format), such that the progress is not lost when \
+                     interrupting a long-running optimization.",
+            )
+            .value_name("FILE"),
+        clap::Arg::new("lock_previous")
+            .long("lock-previous")
+            .help(
+                "Path of a result file (in the simple output format) of a previous run. All \
+                     participants, who have been assigned to a course as attendees in this \
+                     result, are forced into the same course again (matched by course and \
+                     participant name). Requires --previous-input.",
+            )
+            .value_name("RESULT_FILE")
+            .requires("previous_input"),
+        clap::Arg::new("assign_rooms_only")
+            .long("assign-rooms-only")
+            .help(
+                "Path of a result file (in the simple output format). Instead of solving the \
+                     course assignment problem, only the course rooms are recalculated for the \
+                     existing assignment and printed to stdout (or the conflicting courses are \
+                     reported). Requires --rooms or --rooms-file.",
+            )
+            .value_name("RESULT_FILE"),
+        clap::Arg::new("previous_input")
+            .long("previous-input")
+            .help(
+                "Path of the input file (in the simple data format), the --lock-previous \
+                     result has been calculated from.",
+            )
+            .value_name("INPUT_FILE")
+            .requires("lock_previous"),
+        allow_unassigned_arg(),
+        clap::Arg::new("random_restart")
+            .long("random-restart")
+            .help(
+                "Run the solver N times with randomized orderings of the branch and bound \
+                     subproblems and keep the best result (or the one found with the fewest \
+                     subproblems among equally good results).",
+            )
+            .value_name("N")
+            .value_parser(clap::value_parser!(u32).range(1..)),
+        clap::Arg::new("seed")
+            .long("seed")
+            .help(
+                "Seed for the randomized orderings of --random-restart. The same seed \
+                     reproduces the same orderings. Defaults to 0.",
+            )
+            .value_name("S")
+            .requires("random_restart")
+            .value_parser(clap::value_parser!(u64)),
+        clap::Arg::new("thread_statistics")
+            .long("thread-statistics")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Collect and print statistics of each worker thread (number of solved \
+                     subproblems and busy time), e.g. to diagnose an imbalanced distribution of \
+                     the work or to tune the number of threads.",
+            ),
+        clap::Arg::new("trace_file")
+            .long("trace-file")
+            .help(
+                "Write a trace of the branch and bound execution to the given file for \
+                     performance debugging: One JSON object per line for each solved \
+                     subproblem, containing the subproblem's parameters and the type and score \
+                     of its result.",
+            )
+            .value_name("FILE"),
+        clap::Arg::new("emit")
+            .long("emit")
+            .help(
+                "Additionally write the assignment to the file PATH in the given FORMAT. \
+                     Available formats: simple (or json), cde (only with --cde input data), \
+                     csv (a simple roster), records (a JSON array with one record per \
+                     participant), schedule (a list of participants with their course) and \
+                     report (a combined JSON report with course sizes, rooms and quality info). \
+                     Can be given multiple times.",
+            )
+            .value_name("FORMAT=PATH")
+            .action(clap::ArgAction::Append),
+        clap::Arg::new("satisfaction_report")
+            .long("satisfaction-report")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Add a report of each participant's assigned choice rank and best achievable \
+                     rank (given the cancelled courses) to the output file. Only supported for \
+                     the simple output format.",
+            ),
+        clap::Arg::new("normalized_quality")
+            .long("normalized-quality")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Add a quality lack from 0 to 1 to the quality info, which is normalized by the \
+                     number of each participant's course choices, such that events with different \
+                     numbers of choices are comparable.",
+            ),
+        clap::Arg::new("waitlists")
+            .long("waitlists")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Print the waitlist of each course: the participants, who chose the course and \
+                     would rather attend it than their assigned course, ordered by their choice \
+                     rank.",
+            ),
+        clap::Arg::new("verify_instructors")
+            .long("verify-instructors")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Fail instead of writing the assignment, if the calculated assignment does not \
+                     assign all instructors of the courses taking place to their course. Without \
+                     this option, such violations are only logged as errors.",
+            ),
+        clap::Arg::new("explain_cancellation")
+            .long("explain-cancellation")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Log the reason for each cancelled course of the calculated assignment: too few \
+                     participants chose the course, no room is left for it or the overall \
+                     assignment is better without it.",
+            ),
+        clap::Arg::new("print_fingerprint")
+            .long("print-fingerprint")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Log a stable fingerprint (hash) of the calculated assignment, e.g. to check \
+                     in regression tests that the result has not changed.",
+            ),
+        clap::Arg::new("info")
+            .long("info")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Print the size of the problem (number of participants, courses, course places and \
+                     the dimensions of the adjacency matrix) to stdout and exit without solving",
+            ),
+        clap::Arg::new("stats_only")
+            .long("stats-only")
+            .action(clap::ArgAction::SetTrue)
+            .help(
+                "Print the course demand (number of 1st, 2nd, … choices, over- and \
+                     undersubscription of each course and courses at risk of cancellation) to \
+                     stdout and exit without solving",
+            ),
+        clap::Arg::new("print")
+            .short('p')
+            .long("print")
+            .help("Print the caluclated course assignment to stdout in a human readable format")
+            .action(clap::ArgAction::SetTrue),
+        clap::Arg::new("OUTPUT")
+            .help("Sets the output file to use")
+            .index(2),
+    ]
+}
+
+/// Command line option for allowing unassigned participants, shared by `solve` and `validate`
+fn allow_unassigned_arg() -> clap::Arg {
+    clap::Arg::new("allow_unassigned")
+        .long("allow-unassigned")
+        .action(clap::ArgAction::SetTrue)
+        .help(
+            "Allow leaving participants without a course, e.g. for optional courses. \
+                 Assigning participants to one of their choices is still preferred. By \
+                 default, every participant must be assigned to a course (unless they \
+                 have a maximum acceptable choice rank).",
         )
-        .get_matches()
 }
 
+/// Parse the available course rooms from the --rooms list and/or the --rooms-file. Both can only
+/// be combined with `merge_rooms`, in which case the listed rooms are added to the rooms file's
+/// room kinds. Rooms files with more than `max_rooms` rooms in total are rejected. Exits the
+/// program if the rooms cannot be read.
 fn parse_rooms(
     rooms_list: Option<&str>,
     rooms_file_path: Option<&str>,
+    merge_rooms: bool,
+    max_rooms: usize,
 ) -> (Option<Vec<usize>>, Option<Vec<CourseRoomKind>>) {
+    let parse_rooms_list = |rooms_raw: &str| {
+        cdecao::io::rooms::parse_room_list(rooms_raw).unwrap_or_else(|e| {
+            exit_with_error(
+                &format!("Could not parse room sizes: {}", e),
+                exitcode::DATAERR,
+            )
+        })
+    };
     match (rooms_list, rooms_file_path) {
-        (Some(rooms_raw), None) => {
-            let rooms = rooms_raw
-                .split(',')
-                .map(|r| r.parse::<usize>())
-                .collect::<Result<Vec<usize>, std::num::ParseIntError>>()
-                .unwrap_or_else(|e| {
-                    error!("Could not parse room sizes: {}", e);
-                    std::process::exit(exitcode::DATAERR);
-                });
-            (Some(rooms), None)
+        (Some(rooms_raw), None) => (Some(parse_rooms_list(rooms_raw)), None),
+        (Some(rooms_raw), Some(file_path)) if merge_rooms => {
+            let (_rooms, room_kinds) = parse_rooms(None, Some(file_path), false, max_rooms);
+            let (rooms, room_kinds) = cdecao::io::rooms::merge_room_list(
+                room_kinds.unwrap(),
+                &parse_rooms_list(rooms_raw),
+                max_rooms,
+            )
+            .unwrap_or_else(|e| {
+                exit_with_error(&format!("Invalid rooms: {}", e), exitcode::DATAERR)
+            });
+            (Some(rooms), Some(room_kinds))
         }
         (None, Some(file_path)) => {
             debug!("Opening rooms file {} ...", file_path);
             let file = std::fs::File::open(file_path).unwrap_or_else(|e| {
-                error!("Could not open rooms file {}: {}", file_path, e);
-                std::process::exit(exitcode::NOINPUT)
-            });
-            let (rooms, room_kinds) = cdecao::io::rooms::read(file).unwrap_or_else(|e| {
-                error!("Could not read rooms file: {}", e);
-                std::process::exit(exitcode::DATAERR);
+                exit_with_error(
+                    &format!("Could not open rooms file {}: {}", file_path, e),
+                    exitcode::NOINPUT,
+                )
             });
+            let (rooms, room_kinds) =
+                cdecao::io::rooms::read(file, max_rooms).unwrap_or_else(|e| {
+                    exit_with_error(
+                        &format!("Could not read rooms file: {}", e),
+                        exitcode::DATAERR,
+                    )
+                });
             (Some(rooms), Some(room_kinds))
         }
-        (Some(_), Some(_)) => {
-            error!("Either --rooms or --rooms-file can be used, not both.");
-            std::process::exit(exitcode::USAGE);
-        }
+        (Some(_), Some(_)) => exit_with_error(
+            "Either --rooms or --rooms-file can be used, not both (unless --merge-rooms is given).",
+            exitcode::USAGE,
+        ),
         (None, None) => (None, None),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_cli_args;
+
+    #[test]
+    fn test_parse_default_subcommand() {
+        for args in [
+            vec!["cdecao", "input.json", "output.json", "--cde", "-t", "3"],
+            vec![
+                "cdecao",
+                "solve",
+                "input.json",
+                "output.json",
+                "--cde",
+                "-t",
+                "3",
+            ],
+        ] {
+            let matches = parse_cli_args(args).unwrap();
+            let (name, args) = matches.subcommand().unwrap();
+            assert_eq!(name, "solve");
+            assert_eq!(args.get_one::<String>("INPUT").unwrap(), "input.json");
+            assert_eq!(args.get_one::<String>("OUTPUT").unwrap(), "output.json");
+            assert!(args.get_flag("cde"));
+            assert_eq!(
+                args.get_many::<u64>("track").unwrap().collect::<Vec<_>>(),
+                vec![&3]
+            );
+        }
+
+        assert!(parse_cli_args(["cdecao"]).is_err());
+        assert!(parse_cli_args(["cdecao", "input.json", "--unknown-option"]).is_err());
+    }
+
+    /// Parse the given options of the (default) solve subcommand with an input file
+    fn parse_solve_args(options: &[&str]) -> clap::ArgMatches {
+        let matches = parse_cli_args(["cdecao", "input.json"].iter().chain(options)).unwrap();
+        matches.subcommand_matches("solve").unwrap().clone()
+    }
+
+    /// Check if parsing the given options of the solve subcommand fails
+    fn solve_args_fail(options: &[&str]) -> bool {
+        parse_cli_args(["cdecao", "input.json"].iter().chain(options)).is_err()
+    }
+
+    #[test]
+    fn test_parse_solve_subcommand() {
+        let args = parse_solve_args(&["--num-threads=2"]);
+        assert_eq!(args.get_one::<u32>("num_threads"), Some(&2));
+        assert_eq!(args.get_one::<String>("OUTPUT"), None);
+    }
+
+    #[test]
+    fn test_parse_emit() {
+        let args = parse_solve_args(&["--emit=csv=roster.csv"]);
+        assert_eq!(
+            args.get_many::<String>("emit").unwrap().collect::<Vec<_>>(),
+            vec!["csv=roster.csv"]
+        );
+    }
+
+    #[test]
+    fn test_parse_allow_unassigned() {
+        assert!(parse_solve_args(&["--allow-unassigned"]).get_flag("allow_unassigned"));
+    }
+
+    #[test]
+    fn test_parse_course_size_overrides() {
+        let args = parse_solve_args(&[
+            "--cap-max=10",
+            "--max-fill-percent=90",
+            "--min-fill-ratio=0.6",
+            "--max-course-size-from-rooms",
+        ]);
+        assert_eq!(args.get_one::<usize>("cap_max"), Some(&10));
+        assert_eq!(args.get_one::<u32>("max_fill_percent"), Some(&90));
+        assert_eq!(args.get_one::<f64>("min_fill_ratio"), Some(&0.6));
+        assert!(args.get_flag("max_course_size_from_rooms"));
+        assert!(solve_args_fail(&["--max-fill-percent=120"]));
+    }
+
+    #[test]
+    fn test_parse_default_room_parameters() {
+        let args = parse_solve_args(&["--default-room-factor=2.5", "--default-room-offset=1"]);
+        assert_eq!(args.get_one::<f32>("default_room_factor"), Some(&2.5));
+        assert_eq!(args.get_one::<f32>("default_room_offset"), Some(&1.0));
+    }
+
+    #[test]
+    fn test_parse_random_restart() {
+        let args = parse_solve_args(&["--random-restart=3", "--seed=42"]);
+        assert_eq!(args.get_one::<u32>("random_restart"), Some(&3));
+        assert_eq!(args.get_one::<u64>("seed"), Some(&42));
+
+        // --seed is only allowed together with --random-restart
+        assert!(solve_args_fail(&["--seed=42"]));
+    }
+
+    #[test]
+    fn test_parse_print_fingerprint() {
+        assert!(parse_solve_args(&["--print-fingerprint"]).get_flag("print_fingerprint"));
+    }
+
+    #[test]
+    fn test_parse_score_options() {
+        let args = parse_solve_args(&[
+            "--soft-rooms=3",
+            "--max-scored-instructors=2",
+            "--no-instructor-score",
+            "--group-balance=5",
+            "--cancel-costs=2",
+        ]);
+        assert_eq!(args.get_one::<u32>("soft_rooms"), Some(&3));
+        assert_eq!(args.get_one::<usize>("max_scored_instructors"), Some(&2));
+        assert!(args.get_flag("no_instructor_score"));
+        assert_eq!(args.get_one::<u32>("group_balance"), Some(&5));
+        assert_eq!(args.get_one::<u32>("cancel_costs"), Some(&2));
+    }
+
+    #[test]
+    fn test_parse_tie_break_options() {
+        let args = parse_solve_args(&["--even-fill", "--minimize-max-penalty"]);
+        assert!(args.get_flag("even_fill"));
+        assert!(args.get_flag("minimize_max_penalty"));
+    }
+
+    #[test]
+    fn test_parse_search_options() {
+        let args = parse_solve_args(&[
+            "--first-feasible",
+            "--prefer-flexible-cancellation",
+            "--timeout=60",
+        ]);
+        assert!(args.get_flag("first_feasible"));
+        assert!(args.get_flag("prefer_flexible_cancellation"));
+        assert_eq!(args.get_one::<u64>("timeout"), Some(&60));
+    }
+
+    #[test]
+    fn test_parse_objectives() {
+        assert!(parse_solve_args(&["--maximize-courses"]).get_flag("maximize_courses"));
+        assert!(parse_solve_args(&["--minimize-worst-choice"]).get_flag("minimize_worst_choice"));
+
+        // --minimize-worst-choice and --maximize-courses are mutually exclusive objectives
+        assert!(solve_args_fail(&[
+            "--minimize-worst-choice",
+            "--maximize-courses"
+        ]));
+    }
+
+    #[test]
+    fn test_parse_input_checks() {
+        let args = parse_solve_args(&[
+            "--strict",
+            "--verify-instructors",
+            "--max-input-bytes=1000000",
+        ]);
+        assert!(args.get_flag("strict"));
+        assert!(args.get_flag("verify_instructors"));
+        assert_eq!(args.get_one::<u64>("max_input_bytes"), Some(&1_000_000));
+    }
+
+    #[test]
+    fn test_parse_report_options() {
+        let args = parse_solve_args(&[
+            "--explain-cancellation",
+            "--waitlists",
+            "--normalized-quality",
+            "--instructor-lists",
+            "--info",
+        ]);
+        assert!(args.get_flag("explain_cancellation"));
+        assert!(args.get_flag("waitlists"));
+        assert!(args.get_flag("normalized_quality"));
+        assert!(args.get_flag("instructor_lists"));
+        assert!(args.get_flag("info"));
+    }
+
+    #[test]
+    fn test_parse_course_options() {
+        let args = parse_solve_args(&["--must-run=3,5", "--split-courses=20"]);
+        assert_eq!(
+            args.get_many::<usize>("must_run")
+                .unwrap()
+                .collect::<Vec<_>>(),
+            vec![&3, &5]
+        );
+        assert_eq!(args.get_one::<usize>("split_courses"), Some(&20));
+    }
+
+    #[test]
+    fn test_parse_assign_rooms_only() {
+        let args = parse_solve_args(&["--assign-rooms-only=result.json"]);
+        assert_eq!(
+            args.get_one::<String>("assign_rooms_only")
+                .map(|x| x.as_str()),
+            Some("result.json")
+        );
+    }
+
+    #[test]
+    fn test_parse_merge_rooms() {
+        let args = parse_solve_args(&["--rooms=20,10", "--rooms-file=rooms.json", "--merge-rooms"]);
+        assert!(args.get_flag("merge_rooms"));
+
+        // --merge-rooms requires a rooms file
+        assert!(solve_args_fail(&["--rooms=20", "--merge-rooms"]));
+    }
+
+    #[test]
+    fn test_parse_lock_previous() {
+        let args = parse_solve_args(&[
+            "--lock-previous=result.json",
+            "--previous-input=previous.json",
+        ]);
+        assert_eq!(
+            args.get_one::<String>("lock_previous").map(|x| x.as_str()),
+            Some("result.json")
+        );
+
+        // --lock-previous and --previous-input are only allowed together
+        assert!(solve_args_fail(&["--lock-previous=result.json"]));
+    }
+
+    #[test]
+    fn test_format_json_error() {
+        let error: serde_json::Value = serde_json::from_str(&super::format_json_error(
+            "Could not open input file input.json: No such file",
+            exitcode::NOINPUT,
+        ))
+        .unwrap();
+        assert_eq!(
+            error,
+            serde_json::json!({
+                "error": "Could not open input file input.json: No such file",
+                "code": 66,
+            })
+        );
+
+        // Multi-line messages (e.g. from validate) and quotes are escaped into a single line
+        let error = super::format_json_error("Problem \"A\"\nProblem B", exitcode::DATAERR);
+        assert!(!error.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&error).unwrap(),
+            serde_json::json!({"error": "Problem \"A\"\nProblem B", "code": 65})
+        );
+    }
+
+    #[test]
+    fn test_config_override_precedence() {
+        let file_parameters = cdecao::io::config::read_parameters(
+            &br#"{"allow_unassigned": true, "soft_rooms": 3, "group_balance": 2,
+                "instructor_score": true, "num_threads": 4}"#[..],
+        )
+        .unwrap();
+        let matches = parse_cli_args([
+            "cdecao",
+            "input.json",
+            "--config=config.json",
+            "--soft-rooms=5",
+            "--no-instructor-score",
+        ])
+        .unwrap();
+        let args = matches.subcommand_matches("solve").unwrap();
+        assert_eq!(args.get_one::<String>("config").unwrap(), "config.json");
+
+        let parameters = file_parameters.override_with(super::cli_solve_parameters(args));
+        // Given command line options take precedence ...
+        assert_eq!(parameters.soft_rooms, Some(5));
+        assert_eq!(parameters.instructor_score, Some(false));
+        // ... but missing options and flags do not override the config file
+        assert_eq!(parameters.allow_unassigned, Some(true));
+        assert!(!parameters.require_full_assignment());
+        assert_eq!(parameters.group_balance, Some(2));
+        assert_eq!(parameters.num_threads, Some(4));
+        assert_eq!(parameters.timeout, None);
+    }
+
+    #[test]
+    fn test_config_objective_precedence() {
+        let file_parameters =
+            cdecao::io::config::read_parameters(&br#"{"minimize_worst_choice": true}"#[..])
+                .unwrap();
+        let args = parse_solve_args(&["--config=config.json", "--maximize-courses"]);
+        let parameters = file_parameters.override_with(super::cli_solve_parameters(&args));
+        assert_eq!(parameters.maximize_courses, Some(true));
+        assert_eq!(parameters.minimize_worst_choice, None);
+    }
+
+    #[test]
+    fn test_parse_json_errors() {
+        for args in [
+            vec!["cdecao", "input.json", "--json-errors"],
+            vec!["cdecao", "validate", "--json-errors", "input.json"],
+        ] {
+            let matches = parse_cli_args(args).unwrap();
+            assert!(matches.get_flag("json_errors"));
+        }
+        assert!(!parse_cli_args(["cdecao", "input.json"])
+            .unwrap()
+            .get_flag("json_errors"));
+    }
+
+    #[test]
+    fn test_parse_validate_subcommand() {
+        let matches =
+            parse_cli_args(["cdecao", "validate", "input.json", "--allow-unassigned"]).unwrap();
+        let args = matches.subcommand_matches("validate").unwrap();
+        assert_eq!(args.get_one::<String>("INPUT").unwrap(), "input.json");
+        assert!(args.get_flag("allow_unassigned"));
+
+        // Options for solving and output files are not accepted
+        assert!(parse_cli_args(["cdecao", "validate", "input.json", "--num-threads=2"]).is_err());
+        assert!(parse_cli_args(["cdecao", "validate", "input.json", "output.json"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_stats_subcommand() {
+        let matches =
+            parse_cli_args(["cdecao", "stats", "--cde", "-t", "1,3", "input.json"]).unwrap();
+        let args = matches.subcommand_matches("stats").unwrap();
+        assert_eq!(args.get_one::<String>("INPUT").unwrap(), "input.json");
+        assert!(args.get_flag("cde"));
+        assert_eq!(
+            args.get_many::<u64>("track").unwrap().collect::<Vec<_>>(),
+            vec![&1, &3]
+        );
+        assert!(parse_cli_args(["cdecao", "stats", "--cde", "-t", "x", "input.json"]).is_err());
+
+        assert!(parse_cli_args(["cdecao", "stats"]).is_err());
+        assert!(parse_cli_args(["cdecao", "stats", "input.json", "--print"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_convert_subcommand() {
+        let matches = parse_cli_args([
+            "cdecao",
+            "convert",
+            "--cde",
+            "--ignore-cancelled",
+            "export.json",
+            "input.json",
+        ])
+        .unwrap();
+        let args = matches.subcommand_matches("convert").unwrap();
+        assert_eq!(args.get_one::<String>("INPUT").unwrap(), "export.json");
+        assert_eq!(args.get_one::<String>("OUTPUT").unwrap(), "input.json");
+        assert!(args.get_flag("ignore_cancelled"));
+        assert!(args.get_one::<String>("format").is_none());
+
+        let matches =
+            parse_cli_args(["cdecao", "convert", "in.json", "out.csv", "--format=csv"]).unwrap();
+        let args = matches.subcommand_matches("convert").unwrap();
+        assert_eq!(args.get_one::<String>("format").unwrap(), "csv");
+
+        // The OUTPUT file is required
+        assert!(parse_cli_args(["cdecao", "convert", "export.json"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_compare_subcommand() {
+        let matches = parse_cli_args(["cdecao", "compare", "first.json", "second.json"]).unwrap();
+        let args = matches.subcommand_matches("compare").unwrap();
+        assert_eq!(args.get_one::<String>("FIRST").unwrap(), "first.json");
+        assert_eq!(args.get_one::<String>("SECOND").unwrap(), "second.json");
+
+        assert!(parse_cli_args(["cdecao", "compare", "first.json"]).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use unicode_width::UnicodeWidthStr;
+
 pub trait IterSelections<'a, T> {
     /// Iterate all possible selections with k elements from the elements of this collection
     ///
@@ -16,6 +18,7 @@ impl<'a, T> IterSelections<'a, T> for [T] {
             k,
             data: self,
             index: None,
+            num_selections: binom_checked(self.len(), k),
         }
     }
 }
@@ -24,6 +27,9 @@ pub struct KSelectionIterator<'a, T> {
     k: usize,
     data: &'a [T],
     index: Option<Vec<usize>>,
+    /// Total number of selections (n choose k), cached for `size_hint()`. None, if it overflows
+    /// usize.
+    num_selections: Option<usize>,
 }
 
 impl<'a, T> Iterator for KSelectionIterator<'a, T> {
@@ -72,23 +78,44 @@ impl<'a, T> Iterator for KSelectionIterator<'a, T> {
         )
     }
 
+    /// Returns the exact number of remaining selections or `(usize::MAX, None)` if this number
+    /// cannot be calculated within usize.
     fn size_hint(&self) -> (usize, Option<usize>) {
+        const UNKNOWN: (usize, Option<usize>) = (usize::MAX, None);
+        let num = match self.num_selections {
+            Some(num) => num,
+            None => return UNKNOWN,
+        };
         if let Some(ref index) = self.index {
-            let mut rank = 0;
+            // The rank of the current selection is smaller than `num`, so its terms should fit
+            // into usize. Still, we use checked arithmetic to never panic.
+            let mut rank = 0usize;
             for (i, index_entry) in index.iter().enumerate() {
-                rank += binom(*index_entry, i + 1);
+                let term = match binom_checked(*index_entry, i + 1) {
+                    Some(term) => term,
+                    None => return UNKNOWN,
+                };
+                rank = match rank.checked_add(term) {
+                    Some(rank) => rank,
+                    None => return UNKNOWN,
+                };
             }
-            let remaining = binom(self.data.len(), self.k) - rank - 1;
+            let remaining = match num.checked_sub(rank + 1) {
+                Some(remaining) => remaining,
+                None => return UNKNOWN,
+            };
 
             (remaining, Some(remaining))
         } else {
-            let num = binom(self.data.len(), self.k);
-
             (num, Some(num))
         }
     }
 }
 
+/// Calculate the binomial coefficient (n choose k).
+///
+/// This function does not check for arithmetic overflows. It should only be used for small n,
+/// where the result is known to fit into usize. Otherwise, use [binom_checked].
 pub fn binom(n: usize, k: usize) -> usize {
     if k > n {
         return 0;
@@ -101,10 +128,90 @@ pub fn binom(n: usize, k: usize) -> usize {
     res
 }
 
+/// Calculate the binomial coefficient (n choose k) with overflow-checked arithmetic.
+///
+/// Returns None, if the result does not fit into usize.
+pub fn binom_checked(n: usize, k: usize) -> Option<usize> {
+    if k > n {
+        return Some(0);
+    }
+    // (n choose k) == (n choose n-k), so we can reduce the number of steps and the size of the
+    // intermediate results
+    let k = std::cmp::min(k, n - k);
+    // After the i-th step, `res` is (n choose i+1), which is increasing for i < k <= n/2. So we only
+    // need to check the intermediate results for exceeding usize. The product of an intermediate
+    // result and `n - i` always fits into u128.
+    let mut res = 1u128;
+    for i in 0..k {
+        res = res * (n - i) as u128 / (i + 1) as u128;
+        if res > usize::MAX as u128 {
+            return None;
+        }
+    }
+
+    Some(res as usize)
+}
+
+/// A small, seedable pseudo random number generator (SplitMix64), e.g. for randomizing the order
+/// of branch and bound subproblems reproducibly. It is not suitable for cryptographic purposes.
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    /// Mix the given value into the generator's state, e.g. to derive a generator from a seed and
+    /// further input data. Equal inputs always result in equal generator states.
+    pub fn feed(&mut self, value: u64) {
+        self.state = self.next_u64() ^ value;
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Shuffle the given slice in place (Fisher-Yates shuffle)
+    pub fn shuffle<T>(&mut self, data: &mut [T]) {
+        for i in (1..data.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            data.swap(i, j);
+        }
+    }
+}
+
+/// Pad the given string with spaces on the left (i.e. right-align it) to the given width. In
+/// contrast to Rust's formatting width (e.g. `{:>10}`), the width is measured as display width in a
+/// terminal, such that wide characters (like emoji) and combining characters are considered
+/// correctly.
+pub fn pad_left(s: &str, width: usize) -> String {
+    format!("{}{}", " ".repeat(width.saturating_sub(s.width())), s)
+}
+
+/// Pad the given string with spaces on the right (i.e. left-align it) to the given display width.
+/// See [pad_left].
+pub fn pad_right(s: &str, width: usize) -> String {
+    format!("{}{}", s, " ".repeat(width.saturating_sub(s.width())))
+}
+
+/// Maximum display width of the given strings (see [pad_left]) or 0, if there are none
+pub fn max_display_width<'a, I: IntoIterator<Item = &'a str>>(strings: I) -> usize {
+    strings.into_iter().map(|s| s.width()).max().unwrap_or(0)
+}
+
 #[cfg(test)]
 mod test {
-    use super::binom;
     use super::IterSelections;
+    use super::SplitMix64;
+    use super::{binom, binom_checked};
+    use super::{max_display_width, pad_left, pad_right};
 
     #[test]
     fn simple_test() {
@@ -154,6 +261,43 @@ mod test {
         assert_eq!(binom(3, 4), 0);
     }
 
+    #[test]
+    fn binom_checked_test() {
+        for n in 0..30 {
+            for k in 0..32 {
+                assert_eq!(binom_checked(n, k), Some(binom(n, k)), "n={}, k={}", n, k);
+            }
+        }
+        assert_eq!(binom_checked(3, 4), Some(0));
+        assert_eq!(binom_checked(usize::MAX, 1), Some(usize::MAX));
+        assert_eq!(binom_checked(usize::MAX, usize::MAX - 1), Some(usize::MAX));
+        assert_eq!(binom_checked(usize::MAX, 2), None);
+        assert_eq!(binom_checked(200, 100), None);
+        // The unchecked calculation's intermediate results would overflow
+        assert_eq!(binom_checked(63, 60), Some(39711));
+        assert_eq!(binom_checked(66, 33), Some(7219428434016265740));
+        assert_eq!(binom_checked(68, 34), None);
+    }
+
+    #[test]
+    fn size_hint_overflow_test() {
+        let data = [0u8; 200];
+        let mut iterator = data[..].iter_selections(100);
+        assert_eq!(iterator.size_hint(), (usize::MAX, None));
+        iterator.next();
+        assert_eq!(iterator.size_hint(), (usize::MAX, None));
+
+        // The number of selections fits into usize, but the unchecked calculation of the current
+        // selection's rank would overflow
+        let data = [0u8; 64];
+        let mut iterator = data[..].iter_selections(60);
+        assert_eq!(iterator.size_hint(), (635376, Some(635376)));
+        for i in 0..100 {
+            iterator.next();
+            assert_eq!(iterator.size_hint(), (635376 - i - 1, Some(635376 - i - 1)));
+        }
+    }
+
     #[test]
     fn size_hint_test() {
         let data = [1, 2, 3, 4];
@@ -165,4 +309,42 @@ mod test {
             assert_eq!(iterator.size_hint().0, 6 - i - 1);
         }
     }
+
+    #[test]
+    fn split_mix_test() {
+        // Reference values of the SplitMix64 generator for seed 0
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+
+        let mut data: Vec<u32> = (0..20).collect();
+        SplitMix64::new(42).shuffle(&mut data);
+        let mut other_data: Vec<u32> = (0..20).collect();
+        SplitMix64::new(42).shuffle(&mut other_data);
+        assert_eq!(data, other_data);
+        assert_ne!(data, (0..20).collect::<Vec<u32>>());
+        data.sort();
+        assert_eq!(data, (0..20).collect::<Vec<u32>>());
+
+        let mut rng = SplitMix64::new(42);
+        rng.feed(1);
+        let mut other_rng = SplitMix64::new(42);
+        other_rng.feed(2);
+        assert_ne!(rng.next_u64(), other_rng.next_u64());
+    }
+
+    #[test]
+    fn pad_display_width_test() {
+        assert_eq!(pad_left("abc", 5), "  abc");
+        assert_eq!(pad_right("abc", 5), "abc  ");
+        assert_eq!(pad_left("abcdef", 5), "abcdef");
+        // 'γ' takes two bytes, but only one column
+        assert_eq!(pad_right("γ. Kurz", 9), "γ. Kurz  ");
+        // The combining diaeresis takes no column
+        assert_eq!(pad_left("a\u{0308}b", 3), " a\u{0308}b");
+        // Emoji take two columns
+        assert_eq!(pad_right("🎉", 3), "🎉 ");
+        assert_eq!(max_display_width(["γ", "🎉", "a\u{0308}"]), 2);
+        assert_eq!(max_display_width(Vec::<&str>::new()), 0);
+    }
 }
@@ -0,0 +1,562 @@
+//! Integration tests, running the cdecao executable with command line options
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Create a fresh temporary directory for the files of a single test
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cdecao_test_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+const INPUT_DATA: &str = r#"{
+  "courses": [
+    {"name": "Course A", "num_min": 1, "num_max": 2, "instructors": [0]},
+    {"name": "Course B", "num_min": 0, "num_max": 2, "instructors": []}
+  ],
+  "participants": [
+    {"name": "Anton", "choices": [{"course": 1, "penalty": 0}]},
+    {"name": "Berta", "choices": [{"course": 0, "penalty": 0}, {"course": 1, "penalty": 1}]},
+    {"name": "Charly", "choices": [{"course": 1, "penalty": 0}, {"course": 0, "penalty": 1}]}
+  ]
+}"#;
+
+#[test]
+fn test_emit_multiple_formats() {
+    let dir = temp_dir("emit");
+    let input_path = dir.join("input.json");
+    let json_path = dir.join("result.json");
+    let csv_path = dir.join("roster.csv");
+    let schedule_path = dir.join("schedule.txt");
+    let records_path = dir.join("records.json");
+    std::fs::write(&input_path, INPUT_DATA).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg("--num-threads=1")
+        .arg(format!("--emit=json={}", json_path.display()))
+        .arg(format!("--emit=csv={}", csv_path.display()))
+        .arg(format!("--emit=schedule={}", schedule_path.display()))
+        .arg(format!("--emit=records={}", records_path.display()))
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let data: serde_json::Value =
+        serde_json::from_reader(std::fs::File::open(&json_path).unwrap()).unwrap();
+    assert_eq!(data["format"], "X-courseassignment-simple");
+    assert_eq!(data["assignment"], serde_json::json!([0, 0, 1]));
+
+    let roster = std::fs::read_to_string(&csv_path).unwrap();
+    assert_eq!(
+        roster,
+        "participant,course,role
+Anton,Course A,instructor
+Berta,Course A,attendee
+Charly,Course B,attendee
+"
+    );
+
+    let schedule = std::fs::read_to_string(&schedule_path).unwrap();
+    assert_eq!(
+        schedule,
+        "Anton:  Course A (instr)
+Berta:  Course A (rank 1)
+Charly: Course B (rank 1)
+"
+    );
+
+    let records: serde_json::Value =
+        serde_json::from_reader(std::fs::File::open(&records_path).unwrap()).unwrap();
+    assert_eq!(
+        records[1],
+        serde_json::json!({"participant": "Berta", "course": "Course A", "rank": 1,
+            "instructor": false})
+    );
+    assert_eq!(records[0]["instructor"], true);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_emit_invalid_format() {
+    let dir = temp_dir("emit_invalid");
+    let input_path = dir.join("input.json");
+    std::fs::write(&input_path, INPUT_DATA).unwrap();
+
+    for emit in ["xml=out.xml", "csv", "cde=out.json"] {
+        let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+            .arg(&input_path)
+            .arg(format!("--emit={}", emit))
+            .stderr(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(!status.success(), "--emit={} should be rejected", emit);
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_dump_input_error() {
+    let dir = temp_dir("dump_input_error");
+    let input_path = dir.join("input.json");
+    let output_path = dir.join("result.json");
+    std::fs::write(&input_path, INPUT_DATA).unwrap();
+
+    // The dump file's directory does not exist, so the program exits without solving
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg(&output_path)
+        .arg(format!(
+            "--dump-input={}",
+            dir.join("missing").join("dump.json").display()
+        ))
+        .stderr(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(73));
+    assert!(!output_path.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_validate_and_stats() {
+    let dir = temp_dir("validate");
+    let input_path = dir.join("input.json");
+    std::fs::write(&input_path, INPUT_DATA).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg("validate")
+        .arg(&input_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // Only 1 course place for Berta and Charly
+    let small_input_path = dir.join("small_input.json");
+    std::fs::write(
+        &small_input_path,
+        INPUT_DATA
+            .replace(
+                r#""num_min": 1, "num_max": 2"#,
+                r#""num_min": 1, "num_max": 1"#,
+            )
+            .replace(
+                r#""num_min": 0, "num_max": 2"#,
+                r#""num_min": 0, "num_max": 0"#,
+            ),
+    )
+    .unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg("validate")
+        .arg(&small_input_path)
+        .stderr(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!status.success());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg("stats")
+        .arg(&input_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Number of course choices per course (1st / 2nd / …):
+00 Course A: 1 / 1 (size 1-2, 1 instructors)
+01 Course B: 2 / 1 (size 0-2, 0 instructors)
+"
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_convert_cde_to_simple() {
+    let dir = temp_dir("convert");
+    let export_path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/io/test_ressources/TestAka_partial_export_event.json"
+    );
+    let input_path = dir.join("input.json");
+    let converted_result_path = dir.join("converted_result.json");
+    let direct_result_path = dir.join("direct_result.json");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .args([
+            "convert",
+            "--cde",
+            "--track=3",
+            "--ignore-assigned",
+            export_path,
+        ])
+        .arg(&input_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // Solving the converted input must give the same assignment as solving the export directly
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg(&converted_result_path)
+        .arg("--num-threads=1")
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .args(["--cde", "--track=3", "--ignore-assigned", export_path])
+        .arg("--num-threads=1")
+        .arg(format!("--emit=json={}", direct_result_path.display()))
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let read_assignment = |path: &PathBuf| {
+        let data: serde_json::Value =
+            serde_json::from_reader(std::fs::File::open(path).unwrap()).unwrap();
+        data["assignment"].clone()
+    };
+    assert_eq!(
+        read_assignment(&converted_result_path),
+        read_assignment(&direct_result_path)
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_convert_to_csv() {
+    let dir = temp_dir("convert_csv");
+    let input_path = dir.join("input.json");
+    let csv_path = dir.join("roster.csv");
+    std::fs::write(&input_path, INPUT_DATA).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg("convert")
+        .arg(&input_path)
+        .arg(&csv_path)
+        .arg("--format=csv")
+        .status()
+        .unwrap();
+    assert!(status.success());
+    // Only the instructors' placements are known without solving
+    assert_eq!(
+        std::fs::read_to_string(&csv_path).unwrap(),
+        "participant,course,role\nAnton,Course A,instructor\nBerta,,\nCharly,,\n"
+    );
+
+    // Formats requiring an assignment are rejected
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg("convert")
+        .arg(&input_path)
+        .arg(&csv_path)
+        .arg("--format=report")
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(exitcode::USAGE));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_compare_results() {
+    let dir = temp_dir("compare");
+    let first_path = dir.join("first.json");
+    let second_path = dir.join("second.json");
+    let make_result = |assignment: &str, score: u32, quality: f32| {
+        format!(
+            r#"{{"format": "X-courseassignment-simple", "version": "1.1", "assignment": {},
+                "quality": {{"solution_score": {}, "theoretical_max_score": 150000,
+                "theoretical_min_score": 149990, "solution_quality": {},
+                "theoretical_max_quality": 0.0, "theoretical_min_quality": 1.0}}}}"#,
+            assignment, score, quality
+        )
+    };
+    std::fs::write(&first_path, make_result("[0, 0, 1]", 150_000, 0.0)).unwrap();
+    std::fs::write(&second_path, make_result("[0, 1, 1]", 149_999, 0.5)).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg("compare")
+        .arg(&first_path)
+        .arg(&second_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "                            first     second
+Solution score:            150000     149999
+Solution quality lack:   0.000000   0.500000
+Max. assigned penalty:          -          -
+Mean assigned penalty:          -          -
+1 participants are assigned differently:
+- participant 1: course 0 -> course 1
+"
+    );
+
+    // Input data files cannot be compared
+    let input_path = dir.join("input.json");
+    std::fs::write(&input_path, INPUT_DATA).unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg("compare")
+        .arg(&first_path)
+        .arg(&input_path)
+        .stderr(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!status.success());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_no_courses() {
+    let dir = temp_dir("no_courses");
+    let input_path = dir.join("input.json");
+    std::fs::write(
+        &input_path,
+        r#"{"courses": [], "participants": [{"name": "Anton", "choices": []}]}"#,
+    )
+    .unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg("--num-threads=1")
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(exitcode::DATAERR));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_problem_size_info() {
+    let dir = temp_dir("info");
+    let input_path = dir.join("input.json");
+    let output_path = dir.join("output.json");
+    std::fs::write(&input_path, INPUT_DATA).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg(&output_path)
+        .arg("--info")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let info = String::from_utf8(output.stdout).unwrap();
+    // 4 course places, 1 instructor
+    assert!(info.contains("5 x 4 (n x m)"), "{}", info);
+    assert!(info.contains("(0 / 1 / 2 / …): 0 / 1 / 2"), "{}", info);
+    // Solving is skipped
+    assert!(!output_path.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_json_errors() {
+    let dir = temp_dir("json_errors");
+    let input_path = dir.join("input.json");
+    std::fs::write(&input_path, INPUT_DATA).unwrap();
+
+    let last_stderr_line = |output: std::process::Output| -> serde_json::Value {
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        serde_json::from_str(stderr.lines().last().unwrap()).unwrap()
+    };
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(dir.join("missing.json"))
+        .arg("--json-errors")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(66));
+    let error = last_stderr_line(output);
+    assert_eq!(error["code"], 66);
+    assert!(error["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Could not open input file"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg("--emit=xml=out.xml")
+        .arg("--json-errors")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(64));
+    assert_eq!(last_stderr_line(output)["code"], 64);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_no_solution_exit_codes() {
+    let dir = temp_dir("no_solution");
+
+    // No course places for Berta and Charly: The problem is proven to be infeasible
+    let infeasible_input_path = dir.join("infeasible_input.json");
+    std::fs::write(
+        &infeasible_input_path,
+        INPUT_DATA
+            .replace(
+                r#""num_min": 1, "num_max": 2"#,
+                r#""num_min": 0, "num_max": 0"#,
+            )
+            .replace(
+                r#""num_min": 0, "num_max": 2"#,
+                r#""num_min": 0, "num_max": 0"#,
+            ),
+    )
+    .unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&infeasible_input_path)
+        .arg("--timeout=600")
+        .stderr(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(1));
+
+    // The search is stopped by the timeout before finding any solution
+    let input_path = dir.join("input.json");
+    std::fs::write(&input_path, INPUT_DATA).unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg("--timeout=0")
+        .stderr(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(75));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_config_file() {
+    let dir = temp_dir("config");
+    let input_path = dir.join("input.json");
+    let config_path = dir.join("config.json");
+    std::fs::write(&input_path, INPUT_DATA).unwrap();
+
+    // allow_unassigned from the config file adds columns for unassigned participants
+    std::fs::write(&config_path, r#"{"allow_unassigned": true}"#).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg("--info")
+        .arg("--config")
+        .arg(&config_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let info = String::from_utf8(output.stdout).unwrap();
+    assert!(info.contains("8 x 7 (n x m)"), "{}", info);
+
+    // Unknown entries are rejected
+    std::fs::write(&config_path, r#"{"allow_unasigned": true}"#).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg("--info")
+        .arg("--config")
+        .arg(&config_path)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(78));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_assign_rooms_only() {
+    let dir = temp_dir("assign_rooms_only");
+    let input_path = dir.join("input.json");
+    let result_path = dir.join("result.json");
+    let rooms_path = dir.join("rooms.json");
+    std::fs::write(&input_path, INPUT_DATA).unwrap();
+    std::fs::write(
+        &result_path,
+        r#"{"format": "X-courseassignment-simple", "version": "1.1", "assignment": [0, 0, 1],
+            "quality": {"solution_score": 150000, "theoretical_max_score": 150000,
+            "theoretical_min_score": 149990, "solution_quality": 0.0,
+            "theoretical_max_quality": 0.0, "theoretical_min_quality": 1.0}}"#,
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg("--rooms=3,1")
+        .arg("--assign-rooms-only")
+        .arg(&result_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Possible rooms:\nCourse A: 3\nCourse B: 1\n"
+    );
+
+    std::fs::write(
+        &rooms_path,
+        r#"[{"name": "Hall", "capacity": 5, "quantity": 1},
+            {"name": "Nook", "capacity": 1, "quantity": 2}]"#,
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg("--rooms-file")
+        .arg(&rooms_path)
+        .arg("--assign-rooms-only")
+        .arg(&result_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Room plan:\nCourse A: Hall #1\nCourse B: Nook #1\n"
+    );
+
+    // With --merge-rooms, additional rooms can be added to the rooms file's rooms
+    let output = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg("--rooms-file")
+        .arg(&rooms_path)
+        .arg("--rooms=3")
+        .arg("--merge-rooms")
+        .arg("--assign-rooms-only")
+        .arg(&result_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Room plan:\nCourse A: Room (3 places) #1\nCourse B: Nook #1\n"
+    );
+    let status = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg("--rooms-file")
+        .arg(&rooms_path)
+        .arg("--rooms=3")
+        .arg("--assign-rooms-only")
+        .arg(&result_path)
+        .stderr(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(64));
+
+    // Course A does not fit into any of the rooms
+    let output = Command::new(env!("CARGO_BIN_EXE_cdecao"))
+        .arg(&input_path)
+        .arg("--rooms=1,1")
+        .arg("--assign-rooms-only")
+        .arg(&result_path)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(65));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Course A (room-effective size 2)"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}